@@ -0,0 +1,92 @@
+//! Benchmarks of the generator across the parameter regimes seen in practice.
+//!
+//! Throughput targets on a current desktop CPU, checked before releases:
+//! - `partition` of a month (23 days) in under 10 µs in every regime.
+//! - `generate_times` of a month in under 10 µs in every regime.
+//! - `generate_timesheet` of a whole year in under 200 µs, so batch runs over many employees and
+//!   years are dominated by output rather than generation.
+
+use std::hint::black_box;
+use std::num::NonZeroU32;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use stundenzettel::calendar::{DayOfWeek, Month, State, Year};
+use stundenzettel::generate::{generate_times, DayCapacity, Parameters, Partitioner, TimeSpan};
+use stundenzettel::rng::seeded;
+use stundenzettel::timesheet::{generate_timesheet, SheetParameters};
+
+/// `(name, hours, days, max_per_day)`: few hours, half of the capacity which is the hardest case
+/// for rejection sampling, nearly full and very tight daily limits.
+const REGIMES: [(&str, u32, u32, u32); 4] = [
+    ("sparse", 10, 23, 8),
+    ("half", 92, 23, 8),
+    ("dense", 170, 23, 8),
+    ("tight", 23, 23, 2),
+];
+
+fn bench_partition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("partition");
+    for (name, hours, days, max_per_day) in REGIMES {
+        let mut partitioner = Partitioner::new();
+        let mut target = Vec::new();
+        let mut rng = seeded(1);
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                partitioner
+                    .partition_into(hours, days, max_per_day, &mut target, &mut rng)
+                    .unwrap();
+                black_box(&target);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_generate_times(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_times");
+    for (name, hours, days, max_per_day) in REGIMES {
+        let mut rng = seeded(1);
+        let days = (1..=days)
+            .map(|day| DayCapacity {
+                day: NonZeroU32::new(day).unwrap(),
+                day_of_week: DayOfWeek::Monday,
+                min: 0,
+                max: max_per_day,
+                window: TimeSpan::hours(8, 20),
+                blackouts: Vec::new(),
+            })
+            .collect();
+        let parameters = Parameters { hours, days };
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| black_box(generate_times(&parameters, &mut rng).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_year(c: &mut Criterion) {
+    let year = Year::new(2024);
+    let months: Vec<_> = (1..=12)
+        .map(|month| Month::new(NonZeroU32::new(month).unwrap(), &year).unwrap())
+        .collect();
+    let mut rng = seeded(1);
+    c.bench_function("generate_timesheet/year", |b| {
+        b.iter(|| {
+            for month in &months {
+                let parameters = SheetParameters {
+                    state: State::Bavaria,
+                    city: None,
+                    hours: 80,
+                    from: 8,
+                    to: 20,
+                    max_per_day: 8,
+                };
+                black_box(generate_timesheet(&year, month, parameters, &mut rng).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_partition, bench_generate_times, bench_year);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Turns `data/holidays.txt` into the tables `calendar` includes, see the header of the file.
+fn bundle_holidays() {
+    println!("cargo:rerun-if-changed=data/holidays.txt");
+    let input = fs::read_to_string("data/holidays.txt").expect("data/holidays.txt is readable");
+    let mut version = None;
+    let mut names = Vec::new();
+    let mut rows = String::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        let malformed = || -> ! { panic!("data/holidays.txt:{} is malformed", index + 1) };
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(number) = line.strip_prefix("version ") {
+            version = Some(number.parse::<u32>().unwrap_or_else(|_| malformed()));
+        } else if let Some(name) = line.strip_prefix("name ") {
+            names.push(name.to_string());
+        } else {
+            let mut columns = line.split(' ');
+            let (Some(year), Some(state)) = (columns.next(), columns.next()) else {
+                malformed()
+            };
+            let _ = write!(rows, "({}, {:?}, &[", year, state);
+            for holiday in columns {
+                let (date, name) = holiday.split_once(':').unwrap_or_else(|| malformed());
+                let (day, month) = date.split_once('.').unwrap_or_else(|| malformed());
+                let name: usize = name.parse().unwrap_or_else(|_| malformed());
+                if name >= names.len() {
+                    malformed();
+                }
+                let _ = write!(rows, "({}, {}, {}), ", day, month, name);
+            }
+            rows.push_str("]),\n");
+        }
+    }
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "pub const VERSION: u32 = {};",
+        version.expect("data/holidays.txt has a version")
+    );
+    let _ = writeln!(
+        output,
+        "pub static NAMES: [&str; {}] = {:?};",
+        names.len(),
+        names
+    );
+    // the year, the state and the day, month and name index of each holiday
+    let _ = writeln!(
+        output,
+        "pub type Row = (u32, &'static str, &'static [(u32, u32, usize)]);"
+    );
+    let _ = writeln!(output, "pub static HOLIDAYS: &[Row] = &[\n{}];", rows);
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("holidays.rs"), output).unwrap();
+}
+
+fn main() {
+    bundle_holidays();
+
+    // the header is generated from `src/ffi.rs` alone into `OUT_DIR`, a test of `ffi` compares it with
+    // the committed `include/stundenzettel.h`
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let config = cbindgen::Config::from_file("cbindgen.toml").expect("cbindgen.toml is valid");
+        let out_dir = env::var("OUT_DIR").unwrap();
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src("src/ffi.rs")
+            .generate()
+            .expect("Unable to generate C bindings")
+            .write_to_file(Path::new(&out_dir).join("stundenzettel.h"));
+    }
+}
@@ -0,0 +1,85 @@
+#ifndef STUNDENZETTEL_H
+#define STUNDENZETTEL_H
+
+/* Generated by cbindgen from src/ffi.rs, don't edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of a call.
+typedef enum SzStatus {
+  // The call succeeded.
+  SzOk = 0,
+  // A pointer was null, a string wasn't valid or a number was out of range.
+  SzInvalidArgument = 1,
+  // The hours can't be distributed with the given constraints.
+  SzInfeasible = 2,
+  // The buffer can't hold all results.
+  SzBufferTooSmall = 3,
+  // The call failed unexpectedly, this is a bug.
+  SzPanic = 4,
+} SzStatus;
+
+// A day worked, see [`crate::timesheet::Entry`].
+typedef struct SzEntry {
+  // The day of the month, starting at 1.
+  uint32_t day;
+  // The starting minute after midnight.
+  uint32_t from;
+  // The stopping minute after midnight.
+  uint32_t to;
+} SzEntry;
+
+// A public holiday, see [`crate::calendar::Holiday`].
+typedef struct SzHoliday {
+  // The day of the month, starting at 1.
+  uint32_t day;
+  // The month, starting at 1.
+  uint32_t month;
+} SzHoliday;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Generates the timesheet of `month` in `year` for the state with the abbreviation `state`.
+//
+// On success the days worked are written into `entries` and their number into `len`. If the
+// buffer is too small `len` receives the required capacity. An `earliest`, `latest` or
+// `max_per_day` above 24 is an invalid argument.
+//
+// # Safety
+// `state` has to be a valid nul terminated string, `entries` has to be valid for `capacity`
+// writes and `len` has to be valid for a write.
+enum SzStatus sz_generate(uint32_t month,
+                          uint32_t year,
+                          uint32_t hours,
+                          uint32_t max_per_day,
+                          uint32_t earliest,
+                          uint32_t latest,
+                          const char *state,
+                          uint64_t seed,
+                          struct SzEntry *entries,
+                          size_t capacity,
+                          size_t *len);
+
+// Writes the public holidays of `year` in the state with the abbreviation `state` into
+// `holidays` and their number into `len`.
+//
+// # Safety
+// `state` has to be a valid nul terminated string, `holidays` has to be valid for `capacity`
+// writes and `len` has to be valid for a write.
+enum SzStatus sz_holidays(uint32_t year,
+                          const char *state,
+                          struct SzHoliday *holidays,
+                          size_t capacity,
+                          size_t *len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* STUNDENZETTEL_H */
@@ -0,0 +1,198 @@
+//! Stores every generated timesheet as `<root>/<year>/<month>.json`.
+//!
+//! The files are written in the current [`FORMAT_VERSION`](crate::output::FORMAT_VERSION) of the
+//! JSON, files of earlier versions are migrated when they are loaded.
+
+use std::fs;
+use std::io;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use crate::output::{FileSink, Format};
+use crate::parser::parse_json;
+use crate::paths::Paths;
+use crate::timesheet::Timesheet;
+
+/// A directory of archived timesheets.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    root: PathBuf,
+}
+
+/// A timesheet in an [`Archive`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Archived {
+    /// The year of the timesheet.
+    pub year: u32,
+    /// The month of the timesheet.
+    pub month: NonZeroU32,
+    /// The file the timesheet is stored in.
+    pub path: PathBuf,
+}
+
+impl Archive {
+    /// An archive in `root`, which is created when the first timesheet is stored.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Archive { root: root.into() }
+    }
+
+    /// The archive in `~/.local/share/stundenzettel` or the data directory of [`crate::paths`] on
+    /// this platform, `None` if the home directory is unknown.
+    pub fn default_location() -> Option<Self> {
+        Some(Self::new(Paths::from_environment()?.data))
+    }
+
+    /// The directory of this archive.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The file the timesheet of `month` in `year` is stored in.
+    pub fn path(&self, year: u32, month: NonZeroU32) -> PathBuf {
+        self.root
+            .join(year.to_string())
+            .join(format!("{:02}.json", month))
+    }
+
+    /// Whether a timesheet of `month` in `year` is stored.
+    pub fn contains(&self, year: u32, month: NonZeroU32) -> bool {
+        self.path(year, month).is_file()
+    }
+
+    /// Stores `sheet`, replacing a previously stored timesheet of the same month.
+    pub fn store(&self, sheet: &Timesheet) -> io::Result<PathBuf> {
+        let path = self.path(sheet.year, sheet.month);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Format::Json.stream(sheet, None, &mut FileSink::create(&path)?)?;
+        Ok(path)
+    }
+
+    /// Loads the timesheet of `month` in `year`, `None` if none was stored.
+    pub fn load(&self, year: u32, month: NonZeroU32) -> io::Result<Option<Timesheet>> {
+        match fs::read_to_string(self.path(year, month)) {
+            Ok(input) => parse_json(&input)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// All stored timesheets, ordered by year and month.
+    pub fn list(&self) -> io::Result<Vec<Archived>> {
+        let mut archived = Vec::new();
+        let years = match fs::read_dir(&self.root) {
+            Ok(years) => years,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(archived),
+            Err(e) => return Err(e),
+        };
+        for year in years {
+            let year = year?;
+            let Some(year_number) = year.file_name().to_str().and_then(|s| s.parse().ok()) else {
+                continue;
+            };
+            for month in fs::read_dir(year.path())? {
+                let path = month?.path();
+                let month = path
+                    .extension()
+                    .filter(|extension| *extension == "json")
+                    .and(path.file_stem())
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse::<NonZeroU32>().ok())
+                    .filter(|month| month.get() <= 12);
+                if let Some(month) = month {
+                    archived.push(Archived {
+                        year: year_number,
+                        month,
+                        path,
+                    });
+                }
+            }
+        }
+        archived.sort_by_key(|archived| (archived.year, archived.month));
+        Ok(archived)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::num::NonZeroU32;
+
+    use crate::archive::Archive;
+    use crate::calendar::{Month, State, Year};
+    use crate::generate::TimeSpan;
+    use crate::output::FORMAT_VERSION;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_store_and_list() {
+        let root = tempfile::tempdir().unwrap();
+        let archive = Archive::new(root.path());
+        assert_eq!(archive.list().unwrap(), vec![]);
+
+        let mut sheets = Vec::new();
+        for (year, month) in [(2024, 3), (2023, 12), (2024, 11)] {
+            let year = Year::new(year);
+            let month = Month::new(NonZeroU32::new(month).unwrap(), &year).unwrap();
+            let parameters = SheetParameters {
+                state: State::Bavaria,
+                city: None,
+                hours: 20,
+                from: 8,
+                to: 20,
+                max_per_day: 8,
+            };
+            let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+            archive.store(&sheet).unwrap();
+            sheets.push(sheet);
+        }
+
+        let listed: Vec<_> = archive
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|archived| (archived.year, archived.month.get()))
+            .collect();
+        assert_eq!(listed, vec![(2023, 12), (2024, 3), (2024, 11)]);
+        let loaded = archive.load(2024, NonZeroU32::new(3).unwrap()).unwrap();
+        assert_eq!(loaded.as_ref(), Some(&sheets[0]));
+        assert!(archive.contains(2023, NonZeroU32::new(12).unwrap()));
+        assert!(!archive.contains(2024, NonZeroU32::new(4).unwrap()));
+        assert_eq!(
+            archive.load(2024, NonZeroU32::new(4).unwrap()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_load_unversioned() {
+        let root = tempfile::tempdir().unwrap();
+        let archive = Archive::new(root.path());
+        let path = archive.path(2023, NonZeroU32::new(5).unwrap());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let unversioned = r#"{
+            "year": 2023,
+            "month": 5,
+            "state": "BY",
+            "entries": [{ "day": 2, "day_of_week": "Tuesday", "time": { "from": 8, "to": 12 } }]
+        }"#;
+        fs::write(&path, unversioned).unwrap();
+        let sheet = archive
+            .load(2023, NonZeroU32::new(5).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(sheet.entries[0].time, TimeSpan::hours(8, 12));
+
+        archive.store(&sheet).unwrap();
+        let stored = fs::read_to_string(&path).unwrap();
+        assert!(stored.contains(&format!(r#""format_version": {}"#, FORMAT_VERSION)));
+        assert_eq!(
+            archive.load(2023, NonZeroU32::new(5).unwrap()).unwrap(),
+            Some(sheet)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Scores how suspicious a timesheet looks to someone reading it.
+//!
+//! Timesheets written by hand rarely repeat the same times over and over, work the maximum every
+//! day or add up to round totals every week. An [`Audit`] measures how often a timesheet does each
+//! of these and combines them into a score, so implausible timesheets can be generated again.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::calendar::{Month, Year};
+use crate::stats::week_of;
+use crate::timesheet::Timesheet;
+
+/// Weekly totals that are a multiple of this many minutes count as round.
+pub(crate) const ROUND_WEEK: u32 = 5 * 60;
+
+/// How often a [`Timesheet`] shows each suspicious pattern, all in percent.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct Audit {
+    /// The entries with the same times as an earlier entry.
+    pub identical_rows: u32,
+    /// The entries that are as long as allowed.
+    pub maximal_days: u32,
+    /// The worked weeks whose hours are a multiple of five.
+    pub round_weeks: u32,
+}
+
+/// `part` of `total` in percent, rounded, 0 if `total` is.
+fn percent(part: usize, total: usize) -> u32 {
+    (part * 100 + total / 2).checked_div(total).unwrap_or(0) as u32
+}
+
+impl Audit {
+    /// Audits `sheet`, which was generated with at most `max_per_day` hours per day.
+    pub fn new(sheet: &Timesheet, max_per_day: u32) -> Self {
+        let entries = sheet.entries.len();
+        let distinct: HashSet<_> = sheet
+            .entries
+            .iter()
+            .map(|entry| (entry.time.from, entry.time.to))
+            .collect();
+        let maximal = sheet
+            .entries
+            .iter()
+            .filter(|entry| entry.time.duration() >= max_per_day * 60)
+            .count();
+
+        let mut weeks = BTreeMap::new();
+        if let Ok(month) = Month::new(sheet.month, &Year::new(sheet.year)) {
+            for entry in &sheet.entries {
+                *weeks.entry(week_of(&month, entry.day.get())).or_insert(0) +=
+                    entry.time.duration();
+            }
+        }
+        let round = weeks
+            .values()
+            .filter(|&&minutes| minutes % ROUND_WEEK == 0)
+            .count();
+
+        Audit {
+            identical_rows: percent(entries - distinct.len(), entries),
+            maximal_days: percent(maximal, entries),
+            round_weeks: percent(round, weeks.len()),
+        }
+    }
+
+    /// The mean of all patterns, from 0 for a plausible to 100 for a very suspicious timesheet.
+    pub fn score(&self) -> u32 {
+        (self.identical_rows + self.maximal_days + self.round_weeks + 1) / 3
+    }
+}
+
+/// A line per pattern followed by the score.
+impl fmt::Display for Audit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Identical rows  {:>3}%", self.identical_rows)?;
+        writeln!(f, "Maximal days    {:>3}%", self.maximal_days)?;
+        writeln!(f, "Round weeks     {:>3}%", self.round_weeks)?;
+        writeln!(f, "Score           {:>3}", self.score())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::audit::Audit;
+    use crate::calendar::{DayOfWeek, Month, State, Year};
+    use crate::generate::TimeSpan;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, Entry, SheetParameters, Timesheet};
+
+    #[test]
+    fn test_audit() {
+        // Monday to Friday of the second week of May 2024, always from 8 to 16
+        let days = [
+            DayOfWeek::Monday,
+            DayOfWeek::Tuesday,
+            DayOfWeek::Wednesday,
+            DayOfWeek::Thursday,
+            DayOfWeek::Friday,
+        ];
+        let sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(5).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: (6..)
+                .zip(days)
+                .map(|(day, day_of_week)| Entry {
+                    day: NonZeroU32::new(day).unwrap(),
+                    day_of_week,
+                    time: TimeSpan::hours(8, 16),
+                    surcharge: None,
+                    break_minutes: 0,
+                    note: None,
+                })
+                .collect(),
+        };
+        let audit = Audit::new(&sheet, 8);
+        assert_eq!(
+            audit,
+            Audit {
+                identical_rows: 80,
+                maximal_days: 100,
+                round_weeks: 100,
+            }
+        );
+        assert_eq!(audit.score(), 93);
+        assert_eq!(Audit::new(&sheet, 10).maximal_days, 0);
+
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 60,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let generated = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+        assert!(Audit::new(&generated, 8).score() < 50);
+    }
+}
@@ -0,0 +1,377 @@
+//! Deducts the breaks that are due after working a number of hours.
+//!
+//! The times of an entry are the time present, [`deduct_breaks`] records the break due on it
+//! without moving the start or end. [`add_breaks`] lengthens the generated entries by the breaks
+//! first, so that the time worked still adds up to the requested hours. Once a timesheet has breaks, the formats of
+//! [`crate::output`] show the gross time, the break and the net time of every entry.
+//! [`crate::output::Layout::Pauses`] shows the break as a row of its own where [`pause`] places it.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::{Blackout, DateConstraint};
+use crate::error::{Error, Result};
+use crate::generate::TimeSpan;
+use crate::timesheet::{widen, Entry, SheetParameters, Timesheet, GRANULARITY};
+
+/// A break of `deduct` minutes that is due after working more than `after` minutes a day.
+///
+/// ```toml
+/// [[breaks]]
+/// after = 360
+/// deduct = 30
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BreakRule {
+    /// The minutes worked a day after which the break is due.
+    pub after: u32,
+    /// The minutes of the break.
+    pub deduct: u32,
+}
+
+impl BreakRule {
+    /// The breaks of the German working hours act, 30 minutes after six hours and 45 after nine.
+    pub const STATUTORY: [BreakRule; 2] = [
+        BreakRule {
+            after: 6 * 60,
+            deduct: 30,
+        },
+        BreakRule {
+            after: 9 * 60,
+            deduct: 45,
+        },
+    ];
+
+    /// The longest break of `rules` due after working `minutes`, zero if none is.
+    pub fn due(rules: &[BreakRule], minutes: u32) -> u32 {
+        rules
+            .iter()
+            .filter(|rule| minutes > rule.after)
+            .map(|rule| rule.deduct)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The fewest minutes present that leave `net` minutes of work once the break due on them is
+    /// deducted, `None` if no break of `rules` does.
+    pub fn gross(rules: &[BreakRule], net: u32) -> Option<u32> {
+        let mut breaks: Vec<_> = rules.iter().map(|rule| rule.deduct).collect();
+        breaks.push(0);
+        breaks.sort_unstable();
+        breaks
+            .into_iter()
+            .find(|&deduct| BreakRule::due(rules, net + deduct) == deduct)
+            .map(|deduct| net + deduct)
+    }
+}
+
+/// Records the longest break of `rules` due on each entry of `sheet`, replacing earlier ones.
+/// A break never exceeds the time of its entry.
+pub fn deduct_breaks(sheet: &mut Timesheet, rules: &[BreakRule]) {
+    let gross: Vec<_> = sheet
+        .entries
+        .iter()
+        .map(|entry| sheet.gross_minutes_of(entry))
+        .collect();
+    for (entry, gross) in sheet.entries.iter_mut().zip(gross) {
+        entry.break_minutes = BreakRule::due(rules, gross).min(gross);
+    }
+}
+
+/// Lengthens the entries of `sheet` by the break of `rules` that falls due on them and records it,
+/// so that the minutes worked still add up to the minutes generated.
+///
+/// The lengthened entries stay within the window of the `parameters` and avoid the `blackouts`,
+/// the maximum hours per day apply to the minutes worked. An entry that can't take its break is
+/// shortened until it can, the minutes it loses are added to the days without any of the
+/// `constraints` in steps of [`GRANULARITY`]. Fails if they fit nowhere.
+pub fn add_breaks(
+    sheet: &mut Timesheet,
+    rules: &[BreakRule],
+    parameters: &SheetParameters,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+) -> Result<()> {
+    let mut missing = 0;
+    let mut unfit = None;
+    for i in 0..sheet.entries.len() {
+        let entry = &sheet.entries[i];
+        let present = sheet.gross_minutes_of(entry);
+        let shorter = (0..present.div_ceil(GRANULARITY)).map(|k| present - k * GRANULARITY);
+        let Some((net, time)) = shorter
+            .filter_map(|net| {
+                Some((
+                    net,
+                    with_break(entry, present, net, rules, parameters, blackouts)?,
+                ))
+            })
+            .next()
+        else {
+            return Err(Error::BreakDoesNotFit {
+                day: entry.day,
+                minutes: BreakRule::due(rules, present),
+            });
+        };
+        if net < present {
+            unfit = unfit.or(Some(Error::BreakDoesNotFit {
+                day: entry.day,
+                minutes: BreakRule::due(rules, present),
+            }));
+            missing += present - net;
+        }
+        sheet.entries[i].time = time;
+    }
+
+    let constrained = |entry: &Entry| {
+        constraints
+            .iter()
+            .any(|c| c.year == sheet.year && c.month == sheet.month && c.day == entry.day)
+    };
+    while missing > 0 {
+        let before = missing;
+        for i in 0..sheet.entries.len() {
+            let entry = &sheet.entries[i];
+            let step = missing.min(GRANULARITY);
+            let present = sheet.gross_minutes_of(entry);
+            let net = present - BreakRule::due(rules, present);
+            if step == 0 || constrained(entry) || net + step > parameters.max_per_day * 60 {
+                continue;
+            }
+            if let Some(time) = with_break(entry, present, net + step, rules, parameters, blackouts)
+            {
+                sheet.entries[i].time = time;
+                missing -= step;
+            }
+        }
+        if missing == before {
+            return Err(unfit.expect("only shortened entries miss minutes"));
+        }
+    }
+    deduct_breaks(sheet, rules);
+    Ok(())
+}
+
+/// The span of `entry`, which lasts `present` minutes, changed to `net` minutes of work and the
+/// break of `rules` due on them. `None` if the span doesn't fit into the window of the
+/// `parameters` or overlaps the `blackouts`.
+fn with_break(
+    entry: &Entry,
+    present: u32,
+    net: u32,
+    rules: &[BreakRule],
+    parameters: &SheetParameters,
+    blackouts: &[Blackout],
+) -> Option<TimeSpan> {
+    let gross = BreakRule::gross(rules, net)?;
+    match gross.checked_sub(present) {
+        Some(minutes) => widen(entry, minutes, parameters, blackouts),
+        None => Some(TimeSpan {
+            from: entry.time.from,
+            to: entry.time.to - (present - gross),
+        }),
+    }
+}
+
+/// When the break of `entry` is taken, `None` without one. It starts at a quarter hour in the middle
+/// of the entry, so that the entry is worked for about as long before as after it.
+pub fn pause(entry: &Entry) -> Option<TimeSpan> {
+    let TimeSpan { from, to } = entry.time;
+    let length = entry.break_minutes.min(to - from);
+    if length == 0 {
+        return None;
+    }
+    let start = ((from + (to - from - length) / 2) / 15 * 15).max(from);
+    Some(TimeSpan {
+        from: start,
+        to: start + length,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::{add_breaks, deduct_breaks, pause, BreakRule};
+    use crate::calendar::{DayOfWeek, State};
+    use crate::error::Error;
+    use crate::generate::TimeSpan;
+    use crate::timesheet::{Entry, SheetParameters, Timesheet};
+
+    #[test]
+    fn test_deduct_breaks() {
+        assert_eq!(BreakRule::due(&BreakRule::STATUTORY, 6 * 60), 0);
+        assert_eq!(BreakRule::due(&BreakRule::STATUTORY, 6 * 60 + 15), 30);
+        assert_eq!(BreakRule::due(&BreakRule::STATUTORY, 10 * 60), 45);
+        assert_eq!(BreakRule::due(&[], 10 * 60), 0);
+
+        let entry = |day, from, to| Entry {
+            day: NonZeroU32::new(day).unwrap(),
+            day_of_week: DayOfWeek::Monday,
+            time: TimeSpan::hours(from, to),
+            surcharge: None,
+            break_minutes: 0,
+            note: None,
+        };
+        let mut sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(3).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: vec![entry(4, 8, 12), entry(11, 8, 15), entry(18, 7, 17)],
+        };
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        let breaks: Vec<_> = sheet.entries.iter().map(|e| e.break_minutes).collect();
+        assert_eq!(breaks, [0, 30, 45]);
+        assert_eq!(sheet.entries[2].net_minutes(), 9 * 60 + 15);
+        assert_eq!(sheet.net_minutes(), 4 * 60 + 6 * 60 + 30 + 9 * 60 + 15);
+        assert!(sheet.has_breaks());
+
+        let rules = [BreakRule {
+            after: 0,
+            deduct: 6 * 60,
+        }];
+        deduct_breaks(&mut sheet, &rules);
+        assert_eq!(sheet.entries[0].break_minutes, 4 * 60);
+        assert_eq!(sheet.entries[0].net_minutes(), 0);
+        deduct_breaks(&mut sheet, &[]);
+        assert!(!sheet.has_breaks());
+
+        // the night of 26./27.10.2024 lasts an hour longer in Berlin
+        let mut sheet = Timesheet {
+            month: NonZeroU32::new(10).unwrap(),
+            entries: vec![entry(27, 0, 6)],
+            ..sheet
+        };
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        assert!(!sheet.has_breaks());
+        sheet.timezone = Some("Europe/Berlin".parse().unwrap());
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        assert_eq!(sheet.entries[0].break_minutes, 30);
+        assert_eq!(sheet.net_minutes(), 6 * 60 + 30);
+        assert_eq!(sheet.night_minutes_of(&sheet.entries[0]), 7 * 60);
+    }
+
+    #[test]
+    fn test_add_breaks() {
+        assert_eq!(
+            BreakRule::gross(&BreakRule::STATUTORY, 6 * 60),
+            Some(6 * 60)
+        );
+        assert_eq!(
+            BreakRule::gross(&BreakRule::STATUTORY, 7 * 60),
+            Some(7 * 60 + 30)
+        );
+        assert_eq!(BreakRule::gross(&BreakRule::STATUTORY, 510), Some(540));
+        assert_eq!(BreakRule::gross(&BreakRule::STATUTORY, 520), Some(565));
+
+        let entry = |day, from, to| Entry {
+            day: NonZeroU32::new(day).unwrap(),
+            day_of_week: DayOfWeek::Monday,
+            time: TimeSpan::hours(from, to),
+            surcharge: None,
+            break_minutes: 0,
+            note: None,
+        };
+        let sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(3).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: vec![entry(4, 8, 15), entry(11, 9, 16), entry(18, 8, 16)],
+        };
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 22,
+            from: 8,
+            to: 16,
+            max_per_day: 8,
+        };
+        let mut lengthened = sheet.clone();
+        add_breaks(
+            &mut lengthened,
+            &BreakRule::STATUTORY,
+            &parameters,
+            &[],
+            &[],
+        )
+        .unwrap();
+        let times: Vec<_> = lengthened.entries.iter().map(|e| e.time).collect();
+        // the 18. only fits seven and a half hours with the break, the rest moves to the others
+        assert_eq!(
+            times,
+            [
+                TimeSpan {
+                    from: 8 * 60,
+                    to: 15 * 60 + 45
+                },
+                TimeSpan {
+                    from: 8 * 60 + 15,
+                    to: 16 * 60
+                },
+                TimeSpan::hours(8, 16)
+            ]
+        );
+        assert_eq!(lengthened.net_minutes(), sheet.minutes());
+
+        let mut full = Timesheet {
+            entries: vec![entry(18, 8, 16)],
+            ..sheet
+        };
+        assert_eq!(
+            add_breaks(&mut full, &BreakRule::STATUTORY, &parameters, &[], &[]),
+            Err(Error::BreakDoesNotFit {
+                day: NonZeroU32::new(18).unwrap(),
+                minutes: 30
+            })
+        );
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut entry = Entry {
+            day: NonZeroU32::new(4).unwrap(),
+            day_of_week: DayOfWeek::Monday,
+            time: TimeSpan {
+                from: 8 * 60,
+                to: 16 * 60 + 30,
+            },
+            surcharge: None,
+            break_minutes: 30,
+            note: None,
+        };
+        assert_eq!(
+            pause(&entry),
+            Some(TimeSpan {
+                from: 12 * 60,
+                to: 12 * 60 + 30
+            })
+        );
+        // the middle is moved back to a quarter hour, but not before the start
+        entry.time = TimeSpan {
+            from: 8 * 60 + 10,
+            to: 8 * 60 + 45,
+        };
+        assert_eq!(
+            pause(&entry),
+            Some(TimeSpan {
+                from: 8 * 60 + 10,
+                to: 8 * 60 + 40
+            })
+        );
+        entry.time = TimeSpan::hours(7, 17);
+        entry.break_minutes = 45;
+        assert_eq!(
+            pause(&entry),
+            Some(TimeSpan {
+                from: 11 * 60 + 30,
+                to: 12 * 60 + 15
+            })
+        );
+        entry.break_minutes = 0;
+        assert_eq!(pause(&entry), None);
+    }
+}
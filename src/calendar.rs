@@ -1,6 +1,27 @@
+//! Gregorian calendar arithmetic and the German public holidays.
+
+use std::borrow::Cow;
+use std::fmt;
 use std::num::NonZeroU32;
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+use crate::parser::{parse_error, parse_number};
+
+/// The tables generated from `data/holidays.txt` by the build script.
+mod bundle {
+    include!(concat!(env!("OUT_DIR"), "/holidays.rs"));
+}
+
+/// The version of the bundled holidays, increased whenever the data of past years changes.
+pub const HOLIDAY_DATA_VERSION: u32 = bundle::VERSION;
+
+/// A day of the week.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[allow(missing_docs)]
 pub enum DayOfWeek {
     Sunday,
     Monday,
@@ -12,7 +33,23 @@ pub enum DayOfWeek {
 }
 
 impl DayOfWeek {
-    fn is_weekend(&self) -> bool {
+    const ALL: [DayOfWeek; 7] = [
+        DayOfWeek::Sunday,
+        DayOfWeek::Monday,
+        DayOfWeek::Tuesday,
+        DayOfWeek::Wednesday,
+        DayOfWeek::Thursday,
+        DayOfWeek::Friday,
+        DayOfWeek::Saturday,
+    ];
+
+    /// The day of the week `value` days after a Sunday.
+    fn wrapping_from(value: u32) -> Self {
+        Self::ALL[(value % 7) as usize]
+    }
+
+    /// Whether this is a Saturday or a Sunday.
+    pub fn is_weekend(&self) -> bool {
         match self {
             DayOfWeek::Sunday => true,
             DayOfWeek::Monday => false,
@@ -24,7 +61,8 @@ impl DayOfWeek {
         }
     }
 
-    fn next(&self) -> Self {
+    /// The day following this one.
+    pub fn next(&self) -> Self {
         match self {
             DayOfWeek::Sunday => DayOfWeek::Monday,
             DayOfWeek::Monday => DayOfWeek::Tuesday,
@@ -37,32 +75,24 @@ impl DayOfWeek {
     }
 }
 
-#[derive(Debug)]
-pub struct NotADayOfWeek;
-
 impl TryFrom<u32> for DayOfWeek {
-    type Error = NotADayOfWeek;
+    type Error = Error;
 
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(DayOfWeek::Sunday),
-            1 => Ok(DayOfWeek::Monday),
-            2 => Ok(DayOfWeek::Tuesday),
-            3 => Ok(DayOfWeek::Wednesday),
-            4 => Ok(DayOfWeek::Thursday),
-            5 => Ok(DayOfWeek::Friday),
-            6 => Ok(DayOfWeek::Saturday),
-            _ => Err(NotADayOfWeek),
+    fn try_from(value: u32) -> Result<Self> {
+        if value < 7 {
+            Ok(DayOfWeek::wrapping_from(value))
+        } else {
+            Err(Error::InvalidDayOfWeek(value))
         }
     }
 }
 
 fn is_leap_year(year: u32) -> bool {
-    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
 }
 
 fn days_of_month(month: u32, leap_year: bool) -> u32 {
-    let days = match month {
+    match month {
         2 => {
             if leap_year {
                 29
@@ -72,8 +102,15 @@ fn days_of_month(month: u32, leap_year: bool) -> u32 {
         }
         4 | 6 | 9 | 11 => 30,
         _ => 31,
-    };
-    days
+    }
+}
+
+fn check_month(month: NonZeroU32) -> Result<NonZeroU32> {
+    if month.get() <= 12 {
+        Ok(month)
+    } else {
+        Err(Error::InvalidMonth(month.get()))
+    }
 }
 
 mod codes {
@@ -81,6 +118,7 @@ mod codes {
 
     use crate::calendar::DayOfWeek;
 
+    /// `month` has to be checked with [`super::check_month`].
     pub fn get_month(month: NonZeroU32) -> u32 {
         const MONTH_CODES: [u32; 12] = [0, 3, 3, 6, 1, 4, 6, 2, 5, 0, 3, 5];
         MONTH_CODES[month.get() as usize - 1]
@@ -96,21 +134,26 @@ mod codes {
     }
 
     pub fn day_of_week(day: u32, month_code: u32) -> DayOfWeek {
-        DayOfWeek::try_from((day + month_code) % 7).unwrap()
+        DayOfWeek::wrapping_from(day + month_code)
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// A date within an unspecified year.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DateOfYear {
+    /// The day of the month, starting at 1.
     pub day: NonZeroU32,
+    /// The month, starting at 1.
     pub month: NonZeroU32,
 }
 
 impl DateOfYear {
+    /// Creates a date from a day and a month.
     pub const fn new(day: NonZeroU32, month: NonZeroU32) -> Self {
         DateOfYear { day, month }
     }
 
+    /// Creates a date from a day and a month, returning `None` if either is zero.
     pub fn new_checked(day: u32, month: u32) -> Option<Self> {
         Some(DateOfYear {
             day: NonZeroU32::new(day)?,
@@ -118,26 +161,62 @@ impl DateOfYear {
         })
     }
 
-    pub fn add_days(&self, days: i32, leap_year: bool) -> Self {
-        const DAYS_TO_MONTH: [[u32; 12]; 2] = [
-            [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334],
-            [0, 31, 60, 91, 121, 152, 182, 213, 244, 274, 305, 335],
-        ];
+    /// Creates a date from a day and a month known to be valid at compile time.
+    const fn of(day: u32, month: u32) -> Self {
+        match (NonZeroU32::new(day), NonZeroU32::new(month)) {
+            (Some(day), Some(month)) => DateOfYear { day, month },
+            _ => panic!("day and month have to be non zero"),
+        }
+    }
+
+    /// Moves this date by `days`, which may be negative.
+    ///
+    /// Fails if the date is invalid or the result would leave the year.
+    pub fn add_days(&self, days: i32, leap_year: bool) -> Result<Self> {
         let days_to_month = DAYS_TO_MONTH[leap_year as usize];
-        let day_of_year: i32 = (days_to_month[self.month.get() as usize - 1] + self.day.get())
-            .try_into()
-            .unwrap();
-        let day_of_year = (day_of_year + days).try_into().unwrap();
+        let month = check_month(self.month).map_err(|_| Error::InvalidDate(*self))?;
+        if self.day.get() > days_of_month(month.get(), leap_year) {
+            return Err(Error::InvalidDate(*self));
+        }
+        let day_of_year = days_to_month[month.get() as usize - 1] + self.day.get();
+        let out_of_year = Error::DateOutOfYear { date: *self, days };
+        let day_of_year = day_of_year
+            .checked_add_signed(days)
+            .filter(|&day| day > 0 && day <= 365 + leap_year as u32)
+            .ok_or_else(|| out_of_year.clone())?;
         let month = match days_to_month.binary_search(&day_of_year) {
-            Ok(v) | Err(v) => v.checked_sub(1).unwrap(),
-        };
-        return Self {
-            day: NonZeroU32::new(day_of_year - days_to_month[month]).unwrap(),
-            month: NonZeroU32::new(month as u32 + 1).unwrap(),
+            Ok(v) | Err(v) => v - 1,
         };
+        Ok(Self {
+            day: NonZeroU32::new(day_of_year - days_to_month[month]).ok_or(out_of_year)?,
+            month: NonZeroU32::MIN.saturating_add(month as u32),
+        })
+    }
+}
+
+/// The days of a year before each month, without and with a leap day.
+const DAYS_TO_MONTH: [[u32; 12]; 2] = [
+    [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334],
+    [0, 31, 60, 91, 121, 152, 182, 213, 244, 274, 305, 335],
+];
+
+/// A week of ISO 8601, which starts on a Monday and belongs to the year of its Thursday.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct IsoWeek {
+    /// The year of the Thursday of the week.
+    pub year: u32,
+    /// The week of that year, starting at 1 with the week of its first Thursday.
+    pub week: u32,
+}
+
+/// Writes the week like `2024-W09`.
+impl fmt::Display for IsoWeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-W{:02}", self.year, self.week)
     }
 }
 
+/// A year together with the codes needed for day of week calculations.
 pub struct Year {
     year: u32,
     is_leap: bool,
@@ -145,11 +224,12 @@ pub struct Year {
 }
 
 impl Year {
+    /// Creates the year `year` of the Gregorian calendar.
     pub fn new(year: u32) -> Self {
         let year_code = codes::get_year(year);
         let century_code = codes::get_century(year);
         let is_leap = is_leap_year(year);
-        let combined_code = year_code + century_code - is_leap as u32;
+        let combined_code = year_code + century_code;
         Self {
             year,
             is_leap,
@@ -157,14 +237,59 @@ impl Year {
         }
     }
 
+    /// The number of this year.
     pub fn year(&self) -> u32 {
         self.year
     }
 
+    /// Whether this year has a 29th of February.
+    pub fn is_leap(&self) -> bool {
+        self.is_leap
+    }
+
+    /// The number of days of `month` in this year.
     pub fn days_of_month(&self, month: NonZeroU32) -> u32 {
         days_of_month(month.get(), self.is_leap)
     }
 
+    /// The number of ISO weeks of this year, 53 if it starts on a Thursday or is a leap year
+    /// starting on a Wednesday and 52 otherwise.
+    pub fn iso_weeks(&self) -> u32 {
+        let january = Month::new(NonZeroU32::MIN, self).expect("January is a month");
+        match january.day_of_week(1) {
+            DayOfWeek::Thursday => 53,
+            DayOfWeek::Wednesday if self.is_leap => 53,
+            _ => 52,
+        }
+    }
+
+    /// The ISO week of the `day`th day of `month` of this year.
+    pub fn iso_week(&self, month: &Month, day: u32) -> IsoWeek {
+        let day_of_year =
+            DAYS_TO_MONTH[self.is_leap as usize][month.month.get() as usize - 1] + day;
+        // Monday is 1 and Sunday 7
+        let weekday = (month.day_of_week(day) as u32 + 6) % 7 + 1;
+        let week = (day_of_year + 10 - weekday) / 7;
+        if week == 0 {
+            let year = self.year - 1;
+            IsoWeek {
+                year,
+                week: Year::new(year).iso_weeks(),
+            }
+        } else if week > self.iso_weeks() {
+            IsoWeek {
+                year: self.year + 1,
+                week: 1,
+            }
+        } else {
+            IsoWeek {
+                year: self.year,
+                week,
+            }
+        }
+    }
+
+    /// The date of Easter Sunday, computed with Gauss's algorithm.
     pub fn easter(&self) -> DateOfYear {
         let k = self.year as i32 / 100;
         let m = 15 + (3 * k + 3) / 4 - (8 * k + 13) / 25;
@@ -175,51 +300,539 @@ impl Year {
         let og = 21 + d - r;
         let sz = 7 - (self.year as i32 + self.year as i32 / 4 + s) % 7;
         let oe = 7 - (og - sz) % 7;
-        let os = og + oe;
-        return DateOfYear::new(NonZeroU32::new(1).unwrap(), NonZeroU32::new(3).unwrap())
-            .add_days((os - 1).try_into().unwrap(), self.is_leap);
+        // os is the day of March, it is at most 56 (the 25th of April)
+        let os = (og + oe) as u32;
+        if os <= 31 {
+            DateOfYear::of(os, 3)
+        } else {
+            DateOfYear::of(os - 31, 4)
+        }
+    }
+
+    /// The date of Buß- und Bettag, the last Wednesday before the 23rd of November.
+    pub fn penance_day(&self) -> DateOfYear {
+        let november = Month::new(NonZeroU32::new(11).expect("11 isn't zero"), self)
+            .expect("November is a month");
+        let day = (16..=22)
+            .find(|&day| november.day_of_week(day) == DayOfWeek::Wednesday)
+            .expect("every week has a Wednesday");
+        DateOfYear::of(day, 11)
+    }
+
+    /// Easter Sunday moved by `days`, all used offsets stay between March and June.
+    fn easter_relative(&self, easter: DateOfYear, days: i32) -> DateOfYear {
+        easter
+            .add_days(days, self.is_leap)
+            .expect("easter relative holidays are within the year")
+    }
+
+    /// The public holidays of this year in `state`, ordered by date.
+    ///
+    /// The years from 1990 until 2050 are taken from the bundled data, other years are computed
+    /// with the same rules the data was generated with.
+    pub fn holidays(&self, state: State) -> Vec<Holiday> {
+        let key = (self.year, state.abbreviation());
+        match bundle::HOLIDAYS.binary_search_by(|&(year, state, _)| (year, state).cmp(&key)) {
+            Ok(index) => bundle::HOLIDAYS[index]
+                .2
+                .iter()
+                .map(|&(day, month, name)| {
+                    Holiday::new(DateOfYear::of(day, month), bundle::NAMES[name])
+                })
+                .collect(),
+            Err(_) => self.computed_holidays(state),
+        }
     }
 
-    pub fn holidays(&self) -> [DateOfYear; 13] {
+    /// The public holidays of this year in `state` according to the rules, ordered by date.
+    fn computed_holidays(&self, state: State) -> Vec<Holiday> {
+        use State::*;
+
         let easter = self.easter();
-        let new_years_day = DateOfYear::new_checked(1, 1).unwrap();
-        let epiphany = DateOfYear::new_checked(6, 1).unwrap();
-        let good_friday = easter.add_days(-2, self.is_leap);
-        let easter_monday = easter.add_days(1, self.is_leap);
-        let labor_day = DateOfYear::new_checked(1, 5).unwrap();
-        let ascension_day = easter.add_days(39, self.is_leap);
-        let whit_monday = easter.add_days(50, self.is_leap);
-        let corpus_christi = easter.add_days(60, self.is_leap);
-        let assumption_day = DateOfYear::new_checked(15, 8).unwrap();
-        let german_unity_day = DateOfYear::new_checked(3, 10).unwrap();
-        let all_saints = DateOfYear::new_checked(1, 11).unwrap();
-        let christmas_day = DateOfYear::new_checked(25, 12).unwrap();
-        let boxing_day = DateOfYear::new_checked(26, 12).unwrap();
-
-        [
-            new_years_day,
-            epiphany,
-            good_friday,
-            easter_monday,
-            labor_day,
-            ascension_day,
-            whit_monday,
-            corpus_christi,
-            assumption_day,
-            german_unity_day,
-            all_saints,
-            christmas_day,
-            boxing_day,
-        ]
+        let year = self.year;
+        let mut holidays = vec![
+            Holiday::new(DateOfYear::of(1, 1), "Neujahr"),
+            Holiday::new(self.easter_relative(easter, -2), "Karfreitag"),
+            Holiday::new(self.easter_relative(easter, 1), "Ostermontag"),
+            Holiday::new(DateOfYear::of(1, 5), "Tag der Arbeit"),
+            Holiday::new(self.easter_relative(easter, 39), "Christi Himmelfahrt"),
+            Holiday::new(self.easter_relative(easter, 50), "Pfingstmontag"),
+            Holiday::new(DateOfYear::of(3, 10), "Tag der Deutschen Einheit"),
+            Holiday::new(DateOfYear::of(25, 12), "1. Weihnachtstag"),
+            Holiday::new(DateOfYear::of(26, 12), "2. Weihnachtstag"),
+        ];
+        let mut add = |condition: bool, date: DateOfYear, name: &'static str| {
+            if condition {
+                holidays.push(Holiday::new(date, name));
+            }
+        };
+        add(
+            matches!(state, BadenWuerttemberg | Bavaria | SaxonyAnhalt),
+            DateOfYear::of(6, 1),
+            "Heilige Drei Könige",
+        );
+        add(
+            (state == Berlin && year >= 2019) || (state == MecklenburgVorpommern && year >= 2023),
+            DateOfYear::of(8, 3),
+            "Internationaler Frauentag",
+        );
+        add(
+            matches!(
+                state,
+                BadenWuerttemberg
+                    | Bavaria
+                    | Hesse
+                    | NorthRhineWestphalia
+                    | RhinelandPalatinate
+                    | Saarland
+            ),
+            self.easter_relative(easter, 60),
+            "Fronleichnam",
+        );
+        add(
+            matches!(state, Bavaria | Saarland),
+            DateOfYear::of(15, 8),
+            "Mariä Himmelfahrt",
+        );
+        add(
+            state == Thuringia && year >= 2019,
+            DateOfYear::of(20, 9),
+            "Weltkindertag",
+        );
+        add(
+            matches!(
+                state,
+                Brandenburg | MecklenburgVorpommern | Saxony | SaxonyAnhalt | Thuringia
+            ) || (matches!(state, Bremen | Hamburg | LowerSaxony | SchleswigHolstein)
+                && year >= 2018)
+                || year == 2017,
+            DateOfYear::of(31, 10),
+            "Reformationstag",
+        );
+        add(
+            matches!(
+                state,
+                BadenWuerttemberg | Bavaria | NorthRhineWestphalia | RhinelandPalatinate | Saarland
+            ),
+            DateOfYear::of(1, 11),
+            "Allerheiligen",
+        );
+        // abolished everywhere but in Saxony in 1995 to fund the long-term care insurance
+        add(
+            state == Saxony || year <= 1994,
+            self.penance_day(),
+            "Buß- und Bettag",
+        );
+
+        holidays.sort_by_key(|holiday| (holiday.date.month, holiday.date.day));
+        holidays
+    }
+
+    /// The public holidays of this year in `region`, i.e. those of its state and its city, ordered
+    /// by date.
+    pub fn regional_holidays(&self, region: Region) -> Vec<Holiday> {
+        let mut holidays = self.holidays(region.state);
+        match region.city {
+            Some(City::Augsburg) => {
+                holidays.push(Holiday::new(
+                    DateOfYear::of(8, 8),
+                    "Augsburger Hohes Friedensfest",
+                ));
+            }
+            None => {}
+        }
+        holidays.sort_by_key(|holiday| (holiday.date.month, holiday.date.day));
+        holidays
+    }
+}
+
+/// A German federal state, the public holidays differ between them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[allow(missing_docs)]
+pub enum State {
+    BadenWuerttemberg,
+    #[default]
+    Bavaria,
+    Berlin,
+    Brandenburg,
+    Bremen,
+    Hamburg,
+    Hesse,
+    LowerSaxony,
+    MecklenburgVorpommern,
+    NorthRhineWestphalia,
+    RhinelandPalatinate,
+    Saarland,
+    Saxony,
+    SaxonyAnhalt,
+    SchleswigHolstein,
+    Thuringia,
+}
+
+impl State {
+    /// All states.
+    pub const ALL: [State; 16] = [
+        State::BadenWuerttemberg,
+        State::Bavaria,
+        State::Berlin,
+        State::Brandenburg,
+        State::Bremen,
+        State::Hamburg,
+        State::Hesse,
+        State::LowerSaxony,
+        State::MecklenburgVorpommern,
+        State::NorthRhineWestphalia,
+        State::RhinelandPalatinate,
+        State::Saarland,
+        State::Saxony,
+        State::SaxonyAnhalt,
+        State::SchleswigHolstein,
+        State::Thuringia,
+    ];
+
+    /// The official two letter abbreviation, e.g. `BY`.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            State::BadenWuerttemberg => "BW",
+            State::Bavaria => "BY",
+            State::Berlin => "BE",
+            State::Brandenburg => "BB",
+            State::Bremen => "HB",
+            State::Hamburg => "HH",
+            State::Hesse => "HE",
+            State::LowerSaxony => "NI",
+            State::MecklenburgVorpommern => "MV",
+            State::NorthRhineWestphalia => "NW",
+            State::RhinelandPalatinate => "RP",
+            State::Saarland => "SL",
+            State::Saxony => "SN",
+            State::SaxonyAnhalt => "ST",
+            State::SchleswigHolstein => "SH",
+            State::Thuringia => "TH",
+        }
+    }
+
+    /// The German name, e.g. `Bayern`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::BadenWuerttemberg => "Baden-Württemberg",
+            State::Bavaria => "Bayern",
+            State::Berlin => "Berlin",
+            State::Brandenburg => "Brandenburg",
+            State::Bremen => "Bremen",
+            State::Hamburg => "Hamburg",
+            State::Hesse => "Hessen",
+            State::LowerSaxony => "Niedersachsen",
+            State::MecklenburgVorpommern => "Mecklenburg-Vorpommern",
+            State::NorthRhineWestphalia => "Nordrhein-Westfalen",
+            State::RhinelandPalatinate => "Rheinland-Pfalz",
+            State::Saarland => "Saarland",
+            State::Saxony => "Sachsen",
+            State::SaxonyAnhalt => "Sachsen-Anhalt",
+            State::SchleswigHolstein => "Schleswig-Holstein",
+            State::Thuringia => "Thüringen",
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.abbreviation())
+    }
+}
+
+impl FromStr for State {
+    type Err = Error;
+
+    /// Parses the abbreviation of a state, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        State::ALL
+            .into_iter()
+            .find(|state| state.abbreviation().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::UnknownState(s.into()))
+    }
+}
+
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.abbreviation())
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// One of the abbreviations like `BY`.
+impl JsonSchema for State {
+    fn schema_name() -> Cow<'static, str> {
+        "State".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "The abbreviation of a German federal state.",
+            "enum": State::ALL.map(|state| state.abbreviation()),
+        })
+    }
+}
+
+/// A city with public holidays of its own.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[allow(missing_docs)]
+pub enum City {
+    Augsburg,
+}
+
+impl City {
+    /// All cities.
+    pub const ALL: [City; 1] = [City::Augsburg];
+
+    /// The German name, e.g. `Augsburg`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            City::Augsburg => "Augsburg",
+        }
+    }
+
+    /// The state the city is in.
+    pub fn state(&self) -> State {
+        match self {
+            City::Augsburg => State::Bavaria,
+        }
+    }
+}
+
+impl fmt::Display for City {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for City {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        City::ALL
+            .into_iter()
+            .find(|city| city.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::UnknownCity(s.to_string()))
+    }
+}
+
+impl Serialize for City {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for City {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// One of the names like `Augsburg`.
+impl JsonSchema for City {
+    fn schema_name() -> Cow<'static, str> {
+        "City".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A city with public holidays of its own.",
+            "enum": City::ALL.map(|city| city.name()),
+        })
+    }
+}
+
+/// The area whose public holidays apply, a state or a city within it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Region {
+    /// The state.
+    pub state: State,
+    /// The city within `state`, if it has holidays of its own.
+    pub city: Option<City>,
+}
+
+impl Region {
+    /// The region of `city` in `state`, failing if the city isn't in the state.
+    pub fn new(state: State, city: Option<City>) -> Result<Self> {
+        match city {
+            Some(city) if city.state() != state => Err(Error::CityOutsideState { city, state }),
+            _ => Ok(Region { state, city }),
+        }
+    }
+}
+
+impl From<State> for Region {
+    fn from(state: State) -> Self {
+        Region { state, city: None }
+    }
+}
+
+/// A public holiday.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Holiday {
+    /// The date of the holiday.
+    pub date: DateOfYear,
+    /// The German name of the holiday.
+    pub name: Cow<'static, str>,
+}
+
+impl Holiday {
+    /// Creates the holiday `name` on `date`.
+    pub const fn new(date: DateOfYear, name: &'static str) -> Self {
+        Holiday {
+            date,
+            name: Cow::Borrowed(name),
+        }
+    }
+}
+
+/// Where the public holidays of a year come from.
+///
+/// The built-in ones are a [`State`] and a [`Region`], [`Rules`] add holidays of other
+/// jurisdictions without changing this crate. Two sources combined as a tuple have the holidays of
+/// both.
+pub trait HolidaySource {
+    /// The holidays of `year`, ordered by date.
+    fn holidays(&self, year: &Year) -> Vec<Holiday>;
+}
+
+impl HolidaySource for State {
+    fn holidays(&self, year: &Year) -> Vec<Holiday> {
+        year.holidays(*self)
+    }
+}
+
+impl HolidaySource for Region {
+    fn holidays(&self, year: &Year) -> Vec<Holiday> {
+        year.regional_holidays(*self)
+    }
+}
+
+impl<A: HolidaySource, B: HolidaySource> HolidaySource for (A, B) {
+    fn holidays(&self, year: &Year) -> Vec<Holiday> {
+        let mut holidays = self.0.holidays(year);
+        holidays.extend(self.1.holidays(year));
+        holidays.sort_by_key(|holiday| (holiday.date.month, holiday.date.day));
+        holidays
+    }
+}
+
+/// When a holiday of [`Rules`] is.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RuleDate {
+    /// On the same date every year.
+    Fixed(DateOfYear),
+    /// This many days after Easter Sunday, before it if negative.
+    Easter(i32),
+}
+
+/// A holiday of [`Rules`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rule {
+    /// When the holiday is.
+    pub date: RuleDate,
+    /// The name of the holiday.
+    pub name: String,
+}
+
+/// Holidays given as rules, usually read from a file with [`Rules::parse`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Rules {
+    /// The rules, in any order.
+    pub rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// Reads one `day.month,name` or `easter+days,name` rule per line, empty lines and lines
+    /// starting with `#` are skipped.
+    ///
+    /// ```text
+    /// # Fronleichnam and Mariä Himmelfahrt
+    /// easter+60,Fronleichnam
+    /// 15.8,Mariä Himmelfahrt
+    /// ```
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (index, text) in input.lines().enumerate() {
+            let line = index + 1;
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let (date, name) = text
+                .split_once(',')
+                .ok_or_else(|| parse_error(line, "Expected `date,name`"))?;
+            let date = date.trim();
+            let date = if let Some(offset) = date.strip_prefix("easter") {
+                let offset = offset.strip_prefix('+').unwrap_or(offset);
+                if offset.is_empty() {
+                    RuleDate::Easter(0)
+                } else {
+                    RuleDate::Easter(parse_number(line, offset, "number of days")?)
+                }
+            } else {
+                let (day, month) = date
+                    .trim_end_matches('.')
+                    .split_once('.')
+                    .ok_or_else(|| parse_error(line, format!("`{}` isn't a date", date)))?;
+                let date = DateOfYear::new_checked(
+                    parse_number(line, day, "day")?,
+                    parse_number(line, month, "month")?,
+                )
+                .filter(|date| date.add_days(0, true).is_ok())
+                .ok_or_else(|| parse_error(line, format!("`{}` isn't a date", date)))?;
+                RuleDate::Fixed(date)
+            };
+            rules.push(Rule {
+                date,
+                name: name.trim().into(),
+            });
+        }
+        Ok(Rules { rules })
     }
 }
 
+/// Rules outside the year, like the 29th of February of other years, are skipped.
+impl HolidaySource for Rules {
+    fn holidays(&self, year: &Year) -> Vec<Holiday> {
+        let easter = year.easter();
+        let mut holidays: Vec<_> = self
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                let date = match rule.date {
+                    RuleDate::Fixed(date) => date.add_days(0, year.is_leap()),
+                    RuleDate::Easter(days) => easter.add_days(days, year.is_leap()),
+                };
+                Some(Holiday {
+                    date: date.ok()?,
+                    name: rule.name.clone().into(),
+                })
+            })
+            .collect();
+        holidays.sort_by_key(|holiday| (holiday.date.month, holiday.date.day));
+        holidays
+    }
+}
+
+/// A day within a [`Month`].
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct DayOfMonth {
+    /// The day of the week of this day.
     pub day_of_week: DayOfWeek,
+    /// The day of the month, starting at 1.
     pub day_of_month: NonZeroU32,
 }
 
+/// A month of a specific [`Year`].
 pub struct Month {
     month: NonZeroU32,
     combined_code: u32,
@@ -227,35 +840,48 @@ pub struct Month {
 }
 
 impl Month {
-    pub fn new(month: NonZeroU32, year: &Year) -> Self {
-        let combined_code = year.combined_code + codes::get_month(month);
+    /// Creates the month `month` of `year`, failing if `month` is greater than 12.
+    pub fn new(month: NonZeroU32, year: &Year) -> Result<Self> {
+        let month = check_month(month)?;
+        // the leap day only shifts January and February, codes are taken modulo 7 so adding 7
+        // keeps e.g. January 1984 from underflowing
+        let leap_correction = (year.is_leap && month.get() <= 2) as u32;
+        let combined_code = year.combined_code + codes::get_month(month) + 7 - leap_correction;
         let num_days = year.days_of_month(month);
-        Self {
+        Ok(Self {
             month,
             combined_code,
             num_days,
-        }
+        })
     }
 
+    /// The number of this month, starting at 1.
     pub fn month(&self) -> NonZeroU32 {
         self.month
     }
 
+    /// The number of days of this month.
+    pub fn num_days(&self) -> u32 {
+        self.num_days
+    }
+
+    /// The day of the week of the `day`th day of this month.
     pub fn day_of_week(&self, day: u32) -> DayOfWeek {
         codes::day_of_week(day, self.combined_code)
     }
 
+    /// All days of this month in order.
     pub fn days(&self) -> impl Iterator<Item = DayOfMonth> {
         let first_day = DayOfMonth {
             day_of_week: self.day_of_week(1),
-            day_of_month: NonZeroU32::new(1).unwrap(),
+            day_of_month: NonZeroU32::MIN,
         };
         let num_days = self.num_days;
         std::iter::successors(Some(first_day), move |day| {
-            if day.day_of_month.get() + 1 < num_days {
+            if day.day_of_month.get() < num_days {
                 Some(DayOfMonth {
                     day_of_week: day.day_of_week.next(),
-                    day_of_month: NonZeroU32::new(day.day_of_month.get() + 1).unwrap(),
+                    day_of_month: day.day_of_month.saturating_add(1),
                 })
             } else {
                 None
@@ -264,16 +890,69 @@ impl Month {
     }
 }
 
-pub fn non_holidays_of_month(month: &Month, year: &Year) -> Vec<DayOfMonth> {
-    let holidays = year.holidays();
+/// The working days of `month`, i.e. all days that are neither on a weekend nor a holiday of
+/// `source`.
+pub fn non_holidays_of_month(
+    month: &Month,
+    year: &Year,
+    source: &dyn HolidaySource,
+) -> Vec<DayOfMonth> {
+    let holidays = source.holidays(year);
     month
         .days()
         .filter(|day| {
-            !day.day_of_week.is_weekend()
-                && !holidays.contains(&DateOfYear {
-                    day: day.day_of_month,
-                    month: month.month,
-                })
+            let date = DateOfYear {
+                day: day.day_of_month,
+                month: month.month,
+            };
+            !day.day_of_week.is_weekend() && !holidays.iter().any(|holiday| holiday.date == date)
+        })
+        .collect()
+}
+
+/// How the holidays of a month affect its working days, see [`analyze_holidays`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct HolidayAnalysis {
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The number of days that are neither on a weekend nor a holiday.
+    pub working_days: u32,
+    /// The holidays on a Monday to Friday, ordered by date.
+    pub weekday_holidays: Vec<Holiday>,
+    /// The holidays on a Saturday or Sunday, which don't gain a free day, ordered by date.
+    pub weekend_holidays: Vec<Holiday>,
+    /// The number of weekdays that are holidays, holidays on the same date count once.
+    pub extra_free_days: u32,
+}
+
+/// Analyzes the holidays of every month of `year` in `region`, in order.
+pub fn analyze_holidays(year: &Year, region: impl Into<Region>) -> Vec<HolidayAnalysis> {
+    let holidays = year.regional_holidays(region.into());
+    (1..=12)
+        .map(|month| {
+            let month =
+                Month::new(NonZeroU32::new(month).unwrap(), year).expect("months up to 12 exist");
+            let (weekend_holidays, weekday_holidays): (Vec<Holiday>, Vec<Holiday>) = holidays
+                .iter()
+                .filter(|holiday| holiday.date.month == month.month)
+                .cloned()
+                .partition(|holiday| month.day_of_week(holiday.date.day.get()).is_weekend());
+            let mut dates: Vec<_> = weekday_holidays
+                .iter()
+                .map(|holiday| holiday.date.day)
+                .collect();
+            dates.dedup();
+            let weekdays = month
+                .days()
+                .filter(|day| !day.day_of_week.is_weekend())
+                .count();
+            HolidayAnalysis {
+                month: month.month,
+                working_days: (weekdays - dates.len()) as u32,
+                weekday_holidays,
+                weekend_holidays,
+                extra_free_days: dates.len() as u32,
+            }
         })
         .collect()
 }
@@ -282,18 +961,359 @@ pub fn non_holidays_of_month(month: &Month, year: &Year) -> Vec<DayOfMonth> {
 mod test {
     use std::num::NonZeroU32;
 
-    use crate::calendar::DateOfYear;
+    use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+    use crate::calendar::{
+        analyze_holidays, bundle, non_holidays_of_month, City, DateOfYear, DayOfWeek,
+        HolidaySource, IsoWeek, Month, Region, RuleDate, Rules, State, Year, HOLIDAY_DATA_VERSION,
+    };
+    use crate::error::Error;
+
+    /// Easter Sunday as the day of March for the years 1970 to 2099, a value above 31 is in April.
+    /// Taken from dateutil, which implements the algorithm of the Ecclesiastical calendar.
+    const EASTER_DAY_OF_MARCH: [u32; 130] = [
+        29, 42, 33, 53, 45, 30, 49, 41, 26, 46, 37, 50, 42, 34, 53, 38, 30, 50, 34, 26, 46, 31, 50,
+        42, 34, 47, 38, 30, 43, 35, 54, 46, 31, 51, 42, 27, 47, 39, 23, 43, 35, 55, 39, 31, 51, 36,
+        27, 47, 32, 52, 43, 35, 48, 40, 31, 51, 36, 28, 47, 32, 52, 44, 28, 48, 40, 25, 44, 36, 56,
+        41, 32, 52, 37, 29, 48, 40, 25, 45, 36, 49, 41, 33, 52, 37, 29, 49, 33, 53, 45, 30, 49, 41,
+        26, 46, 37, 29, 42, 34, 53, 45, 30, 50, 41, 26, 46, 38, 50, 42, 34, 54, 38, 30, 50, 35, 26,
+        46, 31, 51, 42, 34, 47, 39, 30, 43, 35, 55, 46, 31, 51, 43,
+    ];
+
+    fn reference_day_of_week(weekday: Weekday) -> DayOfWeek {
+        match weekday {
+            Weekday::Sun => DayOfWeek::Sunday,
+            Weekday::Mon => DayOfWeek::Monday,
+            Weekday::Tue => DayOfWeek::Tuesday,
+            Weekday::Wed => DayOfWeek::Wednesday,
+            Weekday::Thu => DayOfWeek::Thursday,
+            Weekday::Fri => DayOfWeek::Friday,
+            Weekday::Sat => DayOfWeek::Saturday,
+        }
+    }
+
+    fn reference_date(date: NaiveDate) -> DateOfYear {
+        DateOfYear::of(date.day(), date.month())
+    }
+
+    #[test]
+    fn test_days_match_reference() {
+        for year in 1970..2100 {
+            let calendar_year = Year::new(year);
+            for month in 1..=12 {
+                let calendar_month =
+                    Month::new(NonZeroU32::new(month).unwrap(), &calendar_year).unwrap();
+                let reference: Vec<_> = NaiveDate::from_ymd_opt(year as i32, month, 1)
+                    .unwrap()
+                    .iter_days()
+                    .take_while(|date| date.month() == month)
+                    .collect();
+                let days: Vec<_> = calendar_month.days().collect();
+                assert_eq!(days.len(), reference.len(), "{}-{}", year, month);
+                for (day, date) in days.iter().zip(&reference) {
+                    let expected = reference_day_of_week(date.weekday());
+                    assert_eq!(day.day_of_month.get(), date.day(), "{}", date);
+                    assert_eq!(day.day_of_week, expected, "{}", date);
+                    assert_eq!(calendar_month.day_of_week(date.day()), expected, "{}", date);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_iso_weeks_match_reference() {
+        for year in 1970..2100 {
+            let calendar_year = Year::new(year);
+            let first = NaiveDate::from_ymd_opt(year as i32, 1, 1).unwrap();
+            for date in first
+                .iter_days()
+                .take_while(|date| date.year() == year as i32)
+            {
+                let month = Month::new(NonZeroU32::new(date.month()).unwrap(), &calendar_year);
+                let expected = IsoWeek {
+                    year: date.iso_week().year() as u32,
+                    week: date.iso_week().week(),
+                };
+                let actual = calendar_year.iso_week(&month.unwrap(), date.day());
+                assert_eq!(actual, expected, "{}", date);
+            }
+        }
+        assert_eq!(Year::new(2020).iso_weeks(), 53);
+        let week = IsoWeek {
+            year: 2024,
+            week: 9,
+        };
+        assert_eq!(week.to_string(), "2024-W09");
+    }
+
+    #[test]
+    fn test_add_days_matches_reference() {
+        for year in [1970, 1984, 1999, 2000, 2023, 2024, 2099] {
+            let leap = Year::new(year).is_leap();
+            let first = NaiveDate::from_ymd_opt(year as i32, 1, 1).unwrap();
+            for date in first
+                .iter_days()
+                .take_while(|date| date.year() == year as i32)
+            {
+                for days in [-366i32, -60, -31, -1, 0, 1, 28, 29, 31, 60, 365] {
+                    let moved = if days < 0 {
+                        date.checked_sub_days(Days::new(days.unsigned_abs() as u64))
+                    } else {
+                        date.checked_add_days(Days::new(days as u64))
+                    }
+                    .unwrap();
+                    let actual = reference_date(date).add_days(days, leap);
+                    if moved.year() == date.year() {
+                        assert_eq!(actual, Ok(reference_date(moved)), "{} {}", date, days);
+                    } else {
+                        assert!(actual.is_err(), "{} {}", date, days);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_easter_matches_reference() {
+        for (year, day_of_march) in (1970..).zip(EASTER_DAY_OF_MARCH) {
+            let expected = if day_of_march <= 31 {
+                DateOfYear::of(day_of_march, 3)
+            } else {
+                DateOfYear::of(day_of_march - 31, 4)
+            };
+            assert_eq!(Year::new(year).easter(), expected, "{}", year);
+        }
+    }
+
+    #[test]
+    fn test_penance_day_matches_reference() {
+        for year in 2000..=2050 {
+            let mut expected = NaiveDate::from_ymd_opt(year as i32, 11, 22).unwrap();
+            while expected.weekday() != Weekday::Wed {
+                expected = expected - Days::new(1);
+            }
+            let expected = DateOfYear::of(expected.day(), 11);
+            assert_eq!(Year::new(year).penance_day(), expected, "{}", year);
+            assert!(Year::new(year)
+                .holidays(State::Saxony)
+                .iter()
+                .any(|holiday| holiday.date == expected && holiday.name == "Buß- und Bettag"));
+            assert!(Year::new(year)
+                .holidays(State::Bavaria)
+                .iter()
+                .all(|holiday| holiday.date != expected));
+        }
+    }
 
     #[test]
     fn test_add_days() {
         let first = DateOfYear::new(NonZeroU32::new(1).unwrap(), NonZeroU32::new(1).unwrap());
         assert_eq!(
             first.add_days(1, false),
-            DateOfYear::new(NonZeroU32::new(2).unwrap(), NonZeroU32::new(1).unwrap())
+            Ok(DateOfYear::new(
+                NonZeroU32::new(2).unwrap(),
+                NonZeroU32::new(1).unwrap()
+            ))
         );
         assert_eq!(
             first.add_days(31, false),
-            DateOfYear::new(NonZeroU32::new(1).unwrap(), NonZeroU32::new(2).unwrap())
+            Ok(DateOfYear::new(
+                NonZeroU32::new(1).unwrap(),
+                NonZeroU32::new(2).unwrap()
+            ))
+        );
+        assert_eq!(
+            first.add_days(-1, false),
+            Err(Error::DateOutOfYear {
+                date: first,
+                days: -1
+            })
+        );
+        let invalid = DateOfYear::new_checked(30, 2).unwrap();
+        assert_eq!(invalid.add_days(1, true), Err(Error::InvalidDate(invalid)));
+    }
+
+    #[test]
+    fn test_holidays() {
+        let names = |year: u32, state: State| {
+            Year::new(year)
+                .holidays(state)
+                .into_iter()
+                .map(|holiday| holiday.name.into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(2024, State::Bavaria).len(), 13);
+        assert_eq!(names(2024, State::Berlin).len(), 10);
+        assert!(names(2018, State::Berlin)
+            .iter()
+            .all(|name| name != "Internationaler Frauentag"));
+        assert!(names(2017, State::Bavaria).contains(&"Reformationstag".into()));
+        assert!(!names(2016, State::Hamburg).contains(&"Reformationstag".into()));
+        for state in State::ALL {
+            assert!(names(1994, state).contains(&"Buß- und Bettag".into()));
+            assert_eq!(
+                names(1995, state).contains(&"Buß- und Bettag".into()),
+                state == State::Saxony
+            );
+        }
+
+        let christmas = Year::new(2024).holidays(State::Saxony);
+        assert_eq!(christmas.last().unwrap().date, DateOfYear::of(26, 12));
+
+        let augsburg = Region::new(State::Bavaria, Some(City::Augsburg)).unwrap();
+        let holidays = Year::new(2024).regional_holidays(augsburg);
+        assert_eq!(holidays.len(), 14);
+        assert_eq!(holidays[8].date, DateOfYear::of(8, 8));
+        assert_eq!(holidays[8].name, "Augsburger Hohes Friedensfest");
+        assert_eq!(
+            Year::new(2024)
+                .regional_holidays(State::Bavaria.into())
+                .len(),
+            13
+        );
+        assert_eq!(
+            Region::new(State::Hesse, Some(City::Augsburg)),
+            Err(Error::CityOutsideState {
+                city: City::Augsburg,
+                state: State::Hesse
+            })
         );
+        assert_eq!("augsburg".parse(), Ok(City::Augsburg));
+    }
+
+    /// `data/holidays.txt` for the years 1990 until 2050.
+    fn render_bundle() -> String {
+        let mut names: Vec<String> = Vec::new();
+        let mut rows = String::new();
+        for year in 1990..=2050 {
+            let mut states = State::ALL;
+            states.sort_by_key(|state| state.abbreviation());
+            for state in states {
+                rows.push_str(&format!("{} {}", year, state.abbreviation()));
+                for holiday in Year::new(year).computed_holidays(state) {
+                    let name = holiday.name.into_owned();
+                    let index = match names.iter().position(|known| *known == name) {
+                        Some(index) => index,
+                        None => {
+                            names.push(name);
+                            names.len() - 1
+                        }
+                    };
+                    rows.push_str(&format!(
+                        " {}.{}:{}",
+                        holiday.date.day, holiday.date.month, index
+                    ));
+                }
+                rows.push('\n');
+            }
+        }
+        let mut bundle = String::from(
+            "# The public holidays of the German states from 1990 until 2050, generated from the\n\
+             # rules in src/calendar.rs with `UPDATE_HOLIDAYS=1 cargo test`.\n\
+             #\n\
+             # After the version and the names, every line lists the holidays of a year in a state as\n\
+             # `day.month:name`, where `name` is the index of a name line.\n",
+        );
+        bundle.push_str(&format!("version {}\n", HOLIDAY_DATA_VERSION));
+        for name in names {
+            bundle.push_str(&format!("name {}\n", name));
+        }
+        bundle + &rows
+    }
+
+    #[test]
+    fn test_bundled_holidays() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/holidays.txt");
+        let rendered = render_bundle();
+        if std::env::var_os("UPDATE_HOLIDAYS").is_some() {
+            std::fs::write(path, rendered).unwrap();
+            return;
+        }
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            rendered,
+            "data/holidays.txt is outdated"
+        );
+        for year in [1989, 1990, 2024, 2050, 2051] {
+            let year = Year::new(year);
+            for state in State::ALL {
+                assert_eq!(year.holidays(state), year.computed_holidays(state));
+            }
+        }
+        for (year, _, holidays) in bundle::HOLIDAYS {
+            let year = Year::new(*year);
+            let easter = year.easter();
+            let date = |name| {
+                holidays
+                    .iter()
+                    .find(|&&(_, _, index)| bundle::NAMES[index] == name)
+                    .map(|&(day, month, _)| DateOfYear::of(day, month))
+            };
+            assert_eq!(date("Karfreitag"), Some(year.easter_relative(easter, -2)));
+            assert_eq!(date("Ostermontag"), Some(year.easter_relative(easter, 1)));
+        }
+        assert_eq!(bundle::HOLIDAYS.len(), 61 * 16);
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::parse(
+            "# Corpus Christi\neaster+60, Fronleichnam\n\n15.8.,Mariä Himmelfahrt\n29.2,Schalttag",
+        )
+        .unwrap();
+        assert_eq!(rules.rules.len(), 3);
+        assert_eq!(rules.rules[0].date, RuleDate::Easter(60));
+        assert_eq!(rules.rules[0].name, "Fronleichnam");
+
+        let year = Year::new(2024);
+        let holidays = rules.holidays(&year);
+        assert_eq!(holidays[0].date, DateOfYear::of(29, 2));
+        assert_eq!(holidays[1].date, DateOfYear::of(30, 5));
+        assert_eq!(holidays[2].date, DateOfYear::of(15, 8));
+        assert_eq!(rules.holidays(&Year::new(2023)).len(), 2);
+
+        // Fronleichnam is the only holiday of May 2024 that Bavaria has and Berlin hasn't
+        let may = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        assert_eq!(non_holidays_of_month(&may, &year, &State::Berlin).len(), 20);
+        assert_eq!(
+            non_holidays_of_month(&may, &year, &(State::Berlin, rules)),
+            non_holidays_of_month(&may, &year, &State::Bavaria)
+        );
+
+        for invalid in ["15.8", "30.2,Nie", "easter+x,Nie", "1,Nie"] {
+            assert!(
+                matches!(Rules::parse(invalid), Err(Error::Parse { line: 1, .. })),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_holidays() {
+        let year = Year::new(2021);
+        let analysis = analyze_holidays(&year, State::Bavaria);
+        assert_eq!(analysis.len(), 12);
+        for (month, analysis) in analysis.iter().enumerate() {
+            let month = Month::new(NonZeroU32::new(month as u32 + 1).unwrap(), &year).unwrap();
+            let working_days = non_holidays_of_month(&month, &year, &State::Bavaria).len();
+            assert_eq!(analysis.working_days as usize, working_days);
+        }
+
+        // 6.1.2021 is a Wednesday, both Christmas days fall on the weekend
+        assert_eq!(analysis[0].extra_free_days, 2);
+        assert!(analysis[0].weekend_holidays.is_empty());
+        assert_eq!(analysis[11].extra_free_days, 0);
+        let names: Vec<_> = analysis[11]
+            .weekend_holidays
+            .iter()
+            .map(|holiday| &holiday.name)
+            .collect();
+        assert_eq!(names, ["1. Weihnachtstag", "2. Weihnachtstag"]);
+
+        // Christi Himmelfahrt and Tag der Arbeit are both on 1.5.2008
+        let may = &analyze_holidays(&Year::new(2008), State::Berlin)[4];
+        assert_eq!(may.weekday_holidays.len(), 3);
+        assert_eq!(may.extra_free_days, 2);
     }
 }
@@ -0,0 +1,1095 @@
+//! The user configuration in `~/.config/stundenzettel/config.toml`.
+//!
+//! ```toml
+//! employee = "Erika Mustermann"
+//! timezone = "Europe/Berlin"
+//! format = "csv"
+//!
+//! [contract]
+//! weekly_hours = 40
+//! state = "BY"
+//!
+//! [[jobs]]
+//! employer = "Lehrstuhl"
+//! weekly_hours = 10
+//! latest = 14
+//! rate = 15.5
+//!
+//! [[jobs]]
+//! employer = "Café"
+//! weekly_hours = 8
+//! earliest = 12
+//! rate = 13
+//!
+//! [[jobs]]
+//! employer = "Bäckerei"
+//! inherits = "Café"
+//! weekly_hours = 6
+//!
+//! [rounding]
+//! step = 15
+//! mode = "employee"
+//!
+//! [[breaks]]
+//! after = 360
+//! deduct = 30
+//!
+//! [realism.house]
+//! weights = [3, 3, 2, 2, 1]
+//! windows = ["mon=9-18", "fri=8-14"]
+//! cluster_starts = true
+//! avoid_round_weeks = true
+//! breaks = [{ after = 360, deduct = 30 }]
+//!
+//! [realism.summer]
+//! inherits = "house"
+//! windows = ["fri=8-12"]
+//!
+//! [warnings]
+//! silence = ["half_days"]
+//!
+//! [[activities]]
+//! text = "Development"
+//! weight = 3
+//!
+//! [[activities]]
+//! text = "Code review"
+//!
+//! [invoice]
+//! issuer = "Erika Mustermann\nHauptstraße 1\n12345 Musterstadt"
+//! recipient = "Muster GmbH\nAm Markt 2\n12345 Musterstadt"
+//! vat = 19
+//!
+//! [mail]
+//! host = "smtp.example.com"
+//! username = "me@example.com"
+//! password = "..."
+//! from = "Me <me@example.com>"
+//! subject = "Stundenzettel {month}/{year}"
+//!
+//! [webdav]
+//! username = "me"
+//! password = "..."
+//!
+//! [clockify]
+//! api_key = "..."
+//! workspace = "..."
+//!
+//! [toggl]
+//! api_token = "..."
+//! workspace = 1234567
+//!
+//! [gcal]
+//! client_id = "....apps.googleusercontent.com"
+//! client_secret = "..."
+//! calendar = "...@group.calendar.google.com"
+//!
+//! [signing]
+//! key = "..."
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::breaks::BreakRule;
+use crate::calendar::{City, State};
+use crate::constraints::WeekdayWindow;
+use crate::contract::{self, Contract};
+use crate::error::Error;
+use crate::notes::Activity;
+use crate::output::Format;
+use crate::paths::Paths;
+use crate::rounding::RoundingPolicy;
+use crate::timezone::Timezone;
+use crate::warnings::WarningKind;
+
+/// All settings, every section is optional.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The name heading the generated timesheets.
+    pub employee: Option<String>,
+    /// The zone of the local times like `Europe/Berlin` or a fixed offset to UTC like `+01:00`,
+    /// used when pushing entries to time tracking services. Defaults to UTC, also read from the
+    /// older `utc_offset`.
+    #[serde(alias = "utc_offset")]
+    pub timezone: Option<Timezone>,
+    /// The format `generate` writes if neither `--format` nor `--csv` is given.
+    pub format: Option<Format>,
+    /// The contract `generate` derives the parameters from if no hours are given, with the
+    /// settings it inherits filled in.
+    pub contract: Option<Contract>,
+    /// The `[[jobs]]` whose timesheets `generate --jobs` generates together without overlapping
+    /// times, with the settings they inherit filled in.
+    pub jobs: Vec<Contract>,
+    /// The rounding `generate` and `smooth` apply to the times before writing them.
+    pub rounding: Option<RoundingPolicy>,
+    /// The `[[breaks]]` `generate` deducts from the printed times and `smooth` from the days that
+    /// didn't take them, the latter defaults to [`BreakRule::STATUTORY`].
+    pub breaks: Vec<BreakRule>,
+    /// The `[realism.<name>]` profiles `generate --realism-profile <name>` applies, with the
+    /// settings they inherit filled in.
+    #[serde(deserialize_with = "profiles")]
+    #[schemars(with = "BTreeMap<String, PartialProfile>")]
+    pub realism: BTreeMap<String, RealismProfile>,
+    /// The warnings `generate` doesn't print.
+    pub warnings: WarningsConfig,
+    /// The `[[activities]]` `generate --activities` draws the notes of the days from.
+    pub activities: Vec<Activity>,
+    /// The addresses and the VAT of `invoice`.
+    pub invoice: Option<InvoiceConfig>,
+    /// The SMTP settings for `generate --mail-to`.
+    pub mail: Option<MailConfig>,
+    /// The credentials for `generate --upload`.
+    pub webdav: Option<WebdavConfig>,
+    /// The settings for `push clockify`.
+    pub clockify: Option<ClockifyConfig>,
+    /// The settings for `push toggl`.
+    pub toggl: Option<TogglConfig>,
+    /// The settings for `push gcal`.
+    pub gcal: Option<GcalConfig>,
+    /// The key `generate` signs the CSV and JSON it writes with.
+    pub signing: Option<SigningConfig>,
+}
+
+/// A `[realism.<name>]` profile, the house style of the generated times. Options given to
+/// `generate` take precedence over it.
+///
+/// A profile with `inherits = "<base>"` takes the settings it leaves out from the profile `base`,
+/// which can inherit from another one in turn.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RealismProfile {
+    /// The weights of the days from Monday to Friday or Sunday, like `--weights`. The hours are
+    /// distributed uniformly if empty.
+    pub weights: Vec<u32>,
+    /// The windows of single weekdays like `fri=8-14`, like `--window`.
+    pub windows: Vec<WeekdayWindow>,
+    /// Whether the days start around the same time.
+    pub cluster_starts: bool,
+    /// Whether the weekly totals are kept from being round, like `--avoid-round-weeks`.
+    pub avoid_round_weeks: bool,
+    /// The breaks deducted instead of the `[[breaks]]`, like `--break`.
+    pub breaks: Vec<BreakRule>,
+}
+
+/// A `[realism.<name>]` profile as written, `None` for the settings it leaves out.
+#[derive(Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+struct PartialProfile {
+    /// The profile the settings left out are taken from.
+    inherits: Option<String>,
+    /// The weights of the days from Monday to Friday or Sunday.
+    weights: Option<Vec<u32>>,
+    /// The windows of single weekdays.
+    windows: Option<Vec<WeekdayWindow>>,
+    /// Whether the days start around the same time.
+    cluster_starts: Option<bool>,
+    /// Whether the weekly totals are kept from being round.
+    avoid_round_weeks: Option<bool>,
+    /// The breaks deducted instead of the `[[breaks]]`.
+    breaks: Option<Vec<BreakRule>>,
+}
+
+/// Reads the profiles and fills in what each inherits, fails for a base that doesn't exist and
+/// for profiles inheriting from each other.
+fn profiles<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, RealismProfile>, D::Error> {
+    let partial = BTreeMap::<String, PartialProfile>::deserialize(deserializer)?;
+    partial
+        .keys()
+        .map(|name| {
+            let profile = inherit(&partial, name).map_err(serde::de::Error::custom)?;
+            Ok((name.clone(), profile))
+        })
+        .collect()
+}
+
+fn inherit(
+    partial: &BTreeMap<String, PartialProfile>,
+    name: &str,
+) -> Result<RealismProfile, String> {
+    let mut chain = vec![name];
+    let mut profile = &partial[name];
+    while let Some(base) = profile.inherits.as_deref() {
+        if chain.contains(&base) {
+            chain.push(base);
+            return Err(format!(
+                "The profiles inherit in a cycle: {}",
+                chain.join(" -> ")
+            ));
+        }
+        profile = partial.get(base).ok_or_else(|| {
+            let inheriting = chain.last().expect("the chain starts with the profile");
+            format!(
+                "The profile `{}` inherits from `{}`, which doesn't exist",
+                inheriting, base
+            )
+        })?;
+        chain.push(base);
+    }
+
+    let mut resolved = RealismProfile::default();
+    for name in chain.into_iter().rev() {
+        let PartialProfile {
+            inherits: _,
+            weights,
+            windows,
+            cluster_starts,
+            avoid_round_weeks,
+            breaks,
+        } = &partial[name];
+        if let Some(weights) = weights {
+            resolved.weights.clone_from(weights);
+        }
+        if let Some(windows) = windows {
+            resolved.windows.clone_from(windows);
+        }
+        if let Some(breaks) = breaks {
+            resolved.breaks.clone_from(breaks);
+        }
+        resolved.cluster_starts = cluster_starts.unwrap_or(resolved.cluster_starts);
+        resolved.avoid_round_weeks = avoid_round_weeks.unwrap_or(resolved.avoid_round_weeks);
+    }
+    Ok(resolved)
+}
+
+/// Fills in the settings the `[contract]` and the `[[jobs]]` with `inherits = "<employer>"` leave
+/// out from the entry of that employer, which can inherit from another one in turn. The employer
+/// itself isn't inherited. Returns whether any entry inherits, fails for an employer without an
+/// entry and for entries inheriting from each other.
+fn inherit_contracts(config: &mut toml::Table) -> Result<bool, String> {
+    let contract = config.get("contract").and_then(toml::Value::as_table);
+    let jobs = config.get("jobs").and_then(toml::Value::as_array);
+    let jobs = jobs.into_iter().flatten().filter_map(toml::Value::as_table);
+    let entries: Vec<_> = contract.into_iter().chain(jobs).cloned().collect();
+    let employer = |entry| text(entry, "employer");
+    let inherits = |entry| text(entry, "inherits");
+    if entries.iter().all(|entry| inherits(entry).is_none()) {
+        return Ok(false);
+    }
+
+    let first_job = usize::from(contract.is_some());
+    let label = |index: usize| match employer(&entries[index]) {
+        Some(employer) => format!("`{}`", employer),
+        None if index < first_job => "[contract]".into(),
+        None => format!("[[jobs]] {}", index - first_job + 1),
+    };
+    let mut resolved = Vec::with_capacity(entries.len());
+    for index in 0..entries.len() {
+        let mut chain = vec![index];
+        let mut current = index;
+        while let Some(name) = inherits(&entries[current]) {
+            let Some(base) = entries
+                .iter()
+                .position(|entry| employer(entry) == Some(name))
+            else {
+                return Err(format!(
+                    "The contract {} inherits from `{}`, which isn't the employer of a contract",
+                    label(current),
+                    name
+                ));
+            };
+            let cycle = chain.contains(&base);
+            chain.push(base);
+            if cycle {
+                let chain: Vec<_> = chain.into_iter().map(label).collect();
+                return Err(format!(
+                    "The contracts inherit in a cycle: {}",
+                    chain.join(" -> ")
+                ));
+            }
+            current = base;
+        }
+
+        let mut merged = toml::Table::new();
+        for &base in chain.iter().rev() {
+            for (key, value) in &entries[base] {
+                if key != "inherits" && (key != "employer" || base == index) {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        resolved.push(toml::Value::Table(merged));
+    }
+    let mut resolved = resolved.into_iter();
+    if let Some(contract) = config
+        .get_mut("contract")
+        .filter(|contract| contract.is_table())
+    {
+        *contract = resolved.next().expect("the contract is an entry");
+    }
+    if let Some(jobs) = config.get_mut("jobs").and_then(toml::Value::as_array_mut) {
+        for job in jobs.iter_mut().filter(|job| job.is_table()) {
+            *job = resolved.next().expect("every job is an entry");
+        }
+    }
+    Ok(true)
+}
+
+/// The string `key` of `entry`.
+fn text<'a>(entry: &'a toml::Table, key: &str) -> Option<&'a str> {
+    entry.get(key).and_then(toml::Value::as_str)
+}
+
+/// The `[warnings]` section.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct WarningsConfig {
+    /// The kinds of [`crate::warnings::Warning`] that are silenced.
+    pub silence: Vec<WarningKind>,
+}
+
+/// The `[invoice]` section.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct InvoiceConfig {
+    /// The name and address of the freelancer, one line each, defaults to `employee`.
+    pub issuer: Option<String>,
+    /// The name and address of the client, one line each.
+    pub recipient: Option<String>,
+    /// The VAT in percent, defaults to 19.
+    pub vat: Option<u32>,
+}
+
+/// How the connection to the SMTP server is encrypted.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    /// TLS from the start, port 465 by default.
+    #[default]
+    Tls,
+    /// Upgrade a plain connection with `STARTTLS`, port 587 by default.
+    Starttls,
+    /// No encryption, port 25 by default. Only useful for a local relay.
+    None,
+}
+
+/// The `[mail]` section.
+///
+/// `subject` and `body` are templates in which `{year}`, `{month}`, `{hours}` and `{state}` are
+/// replaced by the values of the sent timesheet.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MailConfig {
+    /// The host name of the SMTP server.
+    pub host: String,
+    /// The port of the SMTP server, defaults to the port of `security`.
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// The encryption of the connection.
+    #[serde(default)]
+    pub security: Security,
+    /// The user to log in as, no authentication is done if missing.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// The password of `username`.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// The sender address.
+    pub from: String,
+    /// The subject template.
+    #[serde(default = "default_subject")]
+    pub subject: String,
+    /// The body template.
+    #[serde(default = "default_body")]
+    pub body: String,
+}
+
+fn default_subject() -> String {
+    "Stundenzettel {month}/{year}".to_string()
+}
+
+fn default_body() -> String {
+    "Hallo,\n\nanbei mein Stundenzettel für {month}/{year} mit {hours} Stunden.\n".to_string()
+}
+
+/// The `[webdav]` section.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebdavConfig {
+    /// The user to log in as.
+    pub username: Option<String>,
+    /// The password of `username`, for Nextcloud preferably an app password.
+    pub password: Option<String>,
+}
+
+/// The `[clockify]` section.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClockifyConfig {
+    /// The API key from the profile settings.
+    pub api_key: String,
+    /// The ID of the workspace the entries are created in.
+    pub workspace: String,
+    /// The ID of the project of the entries.
+    #[serde(default)]
+    pub project: Option<String>,
+    /// The description of the entries without a note.
+    #[serde(default = "default_description")]
+    pub description: String,
+}
+
+/// The `[toggl]` section.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TogglConfig {
+    /// The API token from the profile settings.
+    pub api_token: String,
+    /// The ID of the workspace the entries are created in.
+    pub workspace: u64,
+    /// The ID of the project of the entries.
+    #[serde(default)]
+    pub project: Option<u64>,
+    /// The description of the entries without a note.
+    #[serde(default = "default_description")]
+    pub description: String,
+}
+
+/// The `[gcal]` section.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GcalConfig {
+    /// The ID of the OAuth client.
+    pub client_id: String,
+    /// The secret of the OAuth client.
+    pub client_secret: String,
+    /// The refresh token printed by `push gcal --authorize`.
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// The ID of the calendar the events are inserted into, `primary` for the main calendar.
+    pub calendar: String,
+    /// The title of the events.
+    #[serde(default = "default_description")]
+    pub description: String,
+}
+
+fn default_description() -> String {
+    "Arbeitszeit".to_string()
+}
+
+/// The `[signing]` section.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SigningConfig {
+    /// The private Ed25519 key in base64 as printed by `key generate`, keep it secret.
+    pub key: String,
+}
+
+/// The settings of the command line that take precedence over the configuration, like
+/// `--state`, `--contract-from` or `--realism-profile` of `generate`, see [`Config::apply`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overrides {
+    /// Replaces the state of the `[contract]` and with it its city.
+    pub state: Option<State>,
+    /// Replaces the city of the `[contract]`.
+    pub city: Option<City>,
+    /// Replaces the first day of the `[contract]`.
+    pub start: Option<contract::Date>,
+    /// Replaces the last day of the `[contract]`.
+    pub end: Option<contract::Date>,
+    /// Keeps only this `[realism.NAME]` profile.
+    pub realism_profile: Option<String>,
+    /// Replaces the output format.
+    pub format: Option<Format>,
+    /// Replaces the timezone.
+    pub timezone: Option<Timezone>,
+}
+
+impl Config {
+    /// Applies `overrides` the way `generate` does: a state without a city drops the city of the
+    /// contract. Fails if the profile isn't in the configuration.
+    pub fn apply(&mut self, overrides: &Overrides) -> Result<(), Error> {
+        if let Some(contract) = &mut self.contract {
+            match overrides.state {
+                Some(state) => {
+                    contract.state = state;
+                    contract.city = overrides.city;
+                }
+                None => contract.city = overrides.city.or(contract.city),
+            }
+            contract.start = overrides.start.or(contract.start);
+            contract.end = overrides.end.or(contract.end);
+        }
+        if let Some(name) = &overrides.realism_profile {
+            let profile = self
+                .realism
+                .remove(name)
+                .ok_or_else(|| Error::UnknownProfile(name.clone()))?;
+            self.realism = BTreeMap::from([(name.clone(), profile)]);
+        }
+        self.format = overrides.format.or(self.format);
+        self.timezone = overrides.timezone.or(self.timezone);
+        Ok(())
+    }
+
+    /// See [`Config::timezone`](#structfield.timezone).
+    pub fn timezone(&self) -> Timezone {
+        self.timezone.unwrap_or_default()
+    }
+
+    /// `~/.config/stundenzettel/config.toml` or where [`crate::paths`] puts it on this platform,
+    /// `None` if the home directory is unknown.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(Paths::from_environment()?.config)
+    }
+
+    /// Parses a configuration file and fills in what the realism profiles and the contracts
+    /// inherit.
+    pub fn parse(input: &str) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(input)?;
+        if !inherit_contracts(&mut table).map_err(serde::de::Error::custom)? {
+            // the errors of the input as written point at its lines
+            return toml::from_str(input);
+        }
+        toml::Value::Table(table).try_into()
+    }
+
+    /// The settings that contradict each other, like a contract ending before it starts or breaks
+    /// the rounding can't show. [`Config::parse`] already rejects what doesn't read.
+    pub fn check(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut problem = |section: String, error| problems.push(Problem { section, error });
+        let contracts = self.contract.iter().map(|c| ("[contract]".to_string(), c));
+        let jobs = (self.jobs.iter().zip(1..)).map(|(c, i)| (format!("[[jobs]] {}", i), c));
+        for (section, contract) in contracts.chain(jobs) {
+            let (from, to) = (contract.earliest, contract.latest);
+            if to < from {
+                problem(section.clone(), Error::InvalidWindow { from, to });
+            }
+            if let (Some(start), Some(end)) = (contract.start, contract.end) {
+                if end < start {
+                    problem(section, Error::EndBeforeStart);
+                }
+            }
+        }
+
+        let step = self.rounding.map(|rounding| rounding.step.max(1));
+        let breaks = self
+            .breaks
+            .iter()
+            .map(|rule| ("[[breaks]]".to_string(), rule));
+        let profiles = self.realism.iter().flat_map(|(name, profile)| {
+            let section = format!("[realism.{}]", name);
+            profile
+                .breaks
+                .iter()
+                .map(move |rule| (section.clone(), rule))
+        });
+        for (section, rule) in breaks.chain(profiles) {
+            if let Some(step) = step.filter(|step| rule.deduct % step != 0) {
+                let error = Error::BreakOffStep {
+                    deduct: rule.deduct,
+                    step,
+                };
+                problem(section, error);
+            }
+        }
+        for (name, profile) in &self.realism {
+            let count = profile.weights.len();
+            if count != 0 && count != 5 && count != 7 {
+                let error = Error::WeightsOfWeek {
+                    count: count as u32,
+                };
+                problem(format!("[realism.{}]", name), error);
+            }
+        }
+
+        #[cfg(feature = "signing")]
+        if let Some(signing) = &self.signing {
+            if let Err(error) = signing.key.parse::<crate::signing::SigningKey>() {
+                problem("[signing]".into(), error);
+            }
+        }
+        problems
+    }
+
+    /// The configuration with passwords, keys and tokens replaced by `...`, to be shown.
+    pub fn redacted(&self) -> Self {
+        let hide = |secret: &mut String| *secret = "...".into();
+        let mut config = self.clone();
+        if let Some(mail) = &mut config.mail {
+            mail.password.iter_mut().for_each(hide);
+        }
+        if let Some(webdav) = &mut config.webdav {
+            webdav.password.iter_mut().for_each(hide);
+        }
+        if let Some(clockify) = &mut config.clockify {
+            hide(&mut clockify.api_key);
+        }
+        if let Some(toggl) = &mut config.toggl {
+            hide(&mut toggl.api_token);
+        }
+        if let Some(gcal) = &mut config.gcal {
+            hide(&mut gcal.client_secret);
+            gcal.refresh_token.iter_mut().for_each(hide);
+        }
+        if let Some(signing) = &mut config.signing {
+            hide(&mut signing.key);
+        }
+        config
+    }
+
+    /// Loads the configuration in `path`, a missing file is an empty configuration.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(input) => Self::parse(&input).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A setting that reads fine but can't work, see [`Config::check`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Problem {
+    /// The section of the setting like `[contract]` or `[[jobs]] 2`.
+    pub section: String,
+    /// What is wrong with it.
+    pub error: Error,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.section, self.error)
+    }
+}
+
+/// The answers of `config init`, written as a commented configuration by [`Setup::to_toml`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Setup {
+    /// The name heading the generated timesheets.
+    pub employee: Option<String>,
+    /// The state whose holidays aren't worked on.
+    pub state: State,
+    /// The hours worked per week.
+    pub weekly_hours: u32,
+    /// The earliest starting hour.
+    pub earliest: u32,
+    /// The latest stopping hour.
+    pub latest: u32,
+    /// The format `generate` writes.
+    pub format: Format,
+}
+
+impl Setup {
+    /// The configuration with a comment above every setting and the common optional settings
+    /// commented out.
+    pub fn to_toml(&self) -> String {
+        let employee = match &self.employee {
+            Some(employee) => format!("employee = {}", toml::Value::from(employee.as_str())),
+            None => "# employee = \"Erika Mustermann\"".into(),
+        };
+        let formats: Vec<_> = Format::ALL.iter().map(Format::name).collect();
+        format!(
+            "\
+# The name heading the generated timesheets
+{employee}
+# The format `generate` writes without `--format`: {formats}
+format = \"{format}\"
+
+# The contract `generate` derives the hours of a month from if none are given
+[contract]
+# The hours worked per week
+weekly_hours = {weekly_hours}
+# The federal state whose holidays aren't worked on, {state_name}
+state = \"{state}\"
+# The earliest starting and the latest stopping hour
+earliest = {earliest}
+latest = {latest}
+# The most hours worked a day
+# max_per_day = 8
+# The first and the last day of the contract, the hours of the months they cut are pro-rated
+# start = 2024-10-01
+# end = 2025-09-30
+
+# Round the times to quarter hours in favor of the employee
+# [rounding]
+# step = 15
+# mode = \"employee\"
+",
+            formats = formats.join(", "),
+            format = self.format,
+            weekly_hours = self.weekly_hours,
+            state_name = self.state.name(),
+            state = self.state,
+            earliest = self.earliest,
+            latest = self.latest,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::BreakRule;
+    use crate::calendar::{City, State};
+    use crate::config::{Config, Overrides, Security, Setup};
+    use crate::contract::Date;
+    use crate::error::Error;
+    use crate::output::Format;
+    use crate::rounding::{Rounding, RoundingPolicy};
+    use crate::timezone::Timezone;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+
+        let config = Config::parse(
+            r#"
+            [mail]
+            host = "smtp.example.com"
+            security = "starttls"
+            from = "me@example.com"
+            "#,
+        )
+        .unwrap();
+        let mail = config.mail.unwrap();
+        assert_eq!(mail.host, "smtp.example.com");
+        assert_eq!(mail.security, Security::Starttls);
+        assert_eq!(mail.port, None);
+        assert!(mail.subject.contains("{month}"));
+
+        let config = Config::parse("[rounding]\nmode = \"daily\"").unwrap();
+        assert_eq!(
+            config.rounding,
+            Some(RoundingPolicy {
+                step: 15,
+                mode: Rounding::Daily
+            })
+        );
+
+        let config = Config::parse("[[breaks]]\nafter = 360\ndeduct = 30").unwrap();
+        assert_eq!(
+            config.breaks,
+            [BreakRule {
+                after: 360,
+                deduct: 30
+            }]
+        );
+
+        let config = Config::parse("utc_offset = \"+01:00\"").unwrap();
+        assert_eq!(config.timezone(), Timezone::Fixed(60));
+        let config = Config::parse("timezone = \"Europe/Berlin\"").unwrap();
+        assert_eq!(config.timezone().name(), Some("Europe/Berlin"));
+        assert!(Config::parse("timezone = \"Berlin\"").is_err());
+
+        let config = Config::parse(
+            r#"
+            [realism.house]
+            weights = [3, 3, 2, 2, 1]
+            windows = ["fri=8-14"]
+            cluster_starts = true
+            breaks = [{ after = 360, deduct = 30 }]
+            "#,
+        )
+        .unwrap();
+        let house = &config.realism["house"];
+        assert_eq!(house.weights, [3, 3, 2, 2, 1]);
+        assert_eq!(house.windows, ["fri=8-14".parse().unwrap()]);
+        assert!(house.cluster_starts && !house.avoid_round_weeks);
+        assert_eq!(house.breaks.len(), 1);
+        assert!(Config::parse("[realism.house]\nwindows = [\"fri=14-8\"]").is_err());
+
+        let config = Config::parse("[invoice]\nvat = 0").unwrap();
+        assert_eq!(config.invoice.unwrap().vat, Some(0));
+
+        assert!(Config::parse("[mail]\nhost = \"smtp.example.com\"").is_err());
+        assert!(Config::parse("[mails]").is_err());
+    }
+
+    #[test]
+    fn test_inherits() {
+        let config = Config::parse(
+            r#"
+            [realism.default]
+            weights = [3, 3, 2, 2, 1]
+            windows = ["fri=8-14"]
+            cluster_starts = true
+
+            [realism.short]
+            inherits = "default"
+            windows = []
+            breaks = [{ after = 360, deduct = 30 }]
+
+            [realism.shorter]
+            inherits = "short"
+            cluster_starts = false
+            "#,
+        )
+        .unwrap();
+        let default = &config.realism["default"];
+        let short = &config.realism["short"];
+        assert_eq!(short.weights, default.weights);
+        assert!(short.windows.is_empty());
+        assert!(short.cluster_starts);
+        assert_eq!(short.breaks.len(), 1);
+        let shorter = &config.realism["shorter"];
+        assert_eq!(shorter.weights, [3, 3, 2, 2, 1]);
+        assert_eq!(shorter.breaks, short.breaks);
+        assert!(!shorter.cluster_starts);
+
+        let error = Config::parse("[realism.short]\ninherits = \"house\"").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("The profile `short` inherits from `house`, which doesn't exist"));
+        let error = Config::parse(
+            "[realism.a]\ninherits = \"b\"\n[realism.b]\ninherits = \"c\"\n[realism.c]\ninherits = \"a\"",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("The profiles inherit in a cycle: a -> b -> c -> a"));
+    }
+
+    #[test]
+    fn test_inherits_contracts() {
+        let config = Config::parse(
+            r#"
+            [contract]
+            employer = "Lehrstuhl"
+            weekly_hours = 10
+            state = "NW"
+            latest = 14
+            start = 2024-04-01
+
+            [[jobs]]
+            employer = "Café"
+            inherits = "Lehrstuhl"
+            weekly_hours = 8
+
+            [[jobs]]
+            employer = "Bäckerei"
+            inherits = "Café"
+            earliest = 6
+            "#,
+        )
+        .unwrap();
+        let contract = config.contract.unwrap();
+        let [cafe, bakery] = &config.jobs[..] else {
+            panic!("two jobs")
+        };
+        assert_eq!(cafe.employer.as_deref(), Some("Café"));
+        assert_eq!(
+            (cafe.weekly_hours, cafe.state, cafe.latest),
+            (8, State::NorthRhineWestphalia, 14)
+        );
+        assert_eq!(cafe.start, contract.start);
+        assert_eq!(bakery.employer.as_deref(), Some("Bäckerei"));
+        assert_eq!(
+            (bakery.weekly_hours, bakery.earliest, bakery.latest),
+            (8, 6, 14)
+        );
+
+        // the employer isn't inherited
+        let config = Config::parse(
+            "[contract]\nemployer = \"A\"\nweekly_hours = 10\n\n[[jobs]]\ninherits = \"A\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.jobs[0].employer, None);
+        assert_eq!(config.jobs[0].weekly_hours, 10);
+
+        let error = Config::parse("[[jobs]]\ninherits = \"B\"\nweekly_hours = 1\n").unwrap_err();
+        assert!(error.to_string().contains(
+            "The contract [[jobs]] 1 inherits from `B`, which isn't the employer of a contract"
+        ));
+        let error = Config::parse(
+            "[contract]\nemployer = \"A\"\ninherits = \"B\"\n\n[[jobs]]\nemployer = \"B\"\ninherits = \"A\"\n",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("The contracts inherit in a cycle: `A` -> `B` -> `A`"));
+        // the settings left out everywhere are still missing
+        let error = Config::parse("[[jobs]]\nemployer = \"A\"\n\n[[jobs]]\ninherits = \"A\"\n")
+            .unwrap_err();
+        assert!(error.to_string().contains("weekly_hours"));
+    }
+
+    #[test]
+    fn test_apply() {
+        let parse = || {
+            Config::parse(
+                r#"
+                [contract]
+                weekly_hours = 10
+                state = "BY"
+                city = "Augsburg"
+                start = 2024-03-01
+
+                [realism.house]
+                cluster_starts = true
+
+                [realism.summer]
+                weights = [3, 3, 2, 2, 1]
+                "#,
+            )
+            .unwrap()
+        };
+        let mut config = parse();
+        config.apply(&Overrides::default()).unwrap();
+        assert_eq!(config, parse());
+
+        let end = Date {
+            year: 2024,
+            month: NonZeroU32::new(9).unwrap(),
+            day: NonZeroU32::new(30).unwrap(),
+        };
+        let overrides = Overrides {
+            end: Some(end),
+            realism_profile: Some("summer".into()),
+            format: Some(Format::Csv),
+            timezone: Some(Timezone::Fixed(60)),
+            ..Overrides::default()
+        };
+        config.apply(&overrides).unwrap();
+        let contract = config.contract.as_ref().unwrap();
+        assert_eq!(contract.city, Some(City::Augsburg));
+        assert_eq!(contract.start.unwrap().month.get(), 3);
+        assert_eq!(contract.end, Some(end));
+        assert_eq!(config.realism.keys().collect::<Vec<_>>(), ["summer"]);
+        assert_eq!(config.format, Some(Format::Csv));
+        assert_eq!(config.timezone(), Timezone::Fixed(60));
+
+        // another state doesn't keep the city of the contract
+        let state = Overrides {
+            state: Some(State::NorthRhineWestphalia),
+            ..Overrides::default()
+        };
+        config.apply(&state).unwrap();
+        let contract = config.contract.unwrap();
+        assert_eq!(
+            (contract.state, contract.city),
+            (State::NorthRhineWestphalia, None)
+        );
+
+        let unknown = Overrides {
+            realism_profile: Some("winter".into()),
+            ..Overrides::default()
+        };
+        assert_eq!(
+            parse().apply(&unknown),
+            Err(Error::UnknownProfile("winter".into()))
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let config = Config::parse(
+            r#"
+            [contract]
+            weekly_hours = 10
+            start = 2024-10-01
+            end = 2024-03-31
+
+            [[jobs]]
+            weekly_hours = 5
+            earliest = 14
+            latest = 12
+
+            [rounding]
+            step = 30
+
+            [[breaks]]
+            after = 360
+            deduct = 30
+
+            [realism.house]
+            weights = [3, 2, 1]
+            breaks = [{ after = 540, deduct = 45 }]
+            "#,
+        )
+        .unwrap();
+        let problems: Vec<_> = config.check().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            problems,
+            [
+                "[contract]: The contract ends before it starts",
+                "[[jobs]] 1: Earliest has to be before latest",
+                "[realism.house]: A break of 45 minutes can't be deducted from times rounded to 30 \
+                 minutes",
+                "[realism.house]: Five or seven weights are needed, one per day from Monday on, not 3",
+            ]
+        );
+        assert_eq!(
+            config.check()[1].error,
+            Error::InvalidWindow { from: 14, to: 12 }
+        );
+        assert!(Config::default().check().is_empty());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let input = r#"
+            employee = "Erika Mustermann"
+            format = "markdown"
+
+            [contract]
+            weekly_hours = 10
+            start = 2024-10-01
+            rate = 15.5
+
+            [realism.house]
+            windows = ["fri=8-14"]
+
+            [webdav]
+            username = "me"
+            password = "secret"
+
+            [signing]
+            key = "c2VjcmV0"
+            "#;
+        let config = Config::parse(input).unwrap();
+        let written = toml::to_string(&config).unwrap();
+        assert_eq!(Config::parse(&written).unwrap(), config);
+        assert!(written.contains("start = 2024-10-01\n"));
+        assert!(written.contains("rate = 15.5\n"));
+        assert!(written.contains("windows = [\"fri=8-14\"]\n"));
+
+        let redacted = config.redacted();
+        let webdav = redacted.webdav.as_ref().unwrap();
+        assert_eq!(webdav.username.as_deref(), Some("me"));
+        assert_eq!(webdav.password.as_deref(), Some("..."));
+        assert_eq!(redacted.signing.unwrap().key, "...");
+        assert_eq!(redacted.contract, config.contract);
+    }
+
+    #[test]
+    fn test_setup() {
+        let mut setup = Setup {
+            employee: Some("Erika \"Eri\" Mustermann".into()),
+            state: State::NorthRhineWestphalia,
+            weekly_hours: 10,
+            earliest: 9,
+            latest: 17,
+            format: Format::Csv,
+        };
+        let config = Config::parse(&setup.to_toml()).unwrap();
+        assert_eq!(config.employee.as_deref(), Some("Erika \"Eri\" Mustermann"));
+        assert_eq!(config.format, Some(Format::Csv));
+        let contract = config.contract.unwrap();
+        assert_eq!(contract.weekly_hours, 10);
+        assert_eq!(contract.state, State::NorthRhineWestphalia);
+        assert_eq!((contract.earliest, contract.latest), (9, 17));
+        assert_eq!(contract.max_per_day, 8);
+        assert!(config.rounding.is_none());
+
+        setup.employee = None;
+        assert!(setup.to_toml().contains("# employee = "));
+        assert_eq!(Config::parse(&setup.to_toml()).unwrap().employee, None);
+        assert!(Config::parse("format = \"pdf\"").is_err());
+    }
+}
@@ -0,0 +1,486 @@
+//! Per-day exceptions for [`generate_constrained_timesheet`](crate::timesheet::generate_constrained_timesheet).
+//!
+//! A constraints file has one `date,kind[,value...]` row per exception, dates are `day.month.year`.
+//! Empty rows and rows starting with `#` are skipped.
+//!
+//! ```text
+//! # worked on a Saturday
+//! 4.5.2024,pin
+//! 10.5.2024,exclude
+//! 13.5.2024,cap,3
+//! 14.5.2024,fill,9:00,13:00
+//! ```
+//!
+//! A date can be both pinned and capped, any other combination is rejected.
+//!
+//! A [`Blackout`] keeps the times of every day, or of every day of one weekday, free. A
+//! [`WeekdayWindow`] replaces the earliest and latest hour of one weekday. Days that are left with
+//! too short a window are skipped with [`exclude_short_days`].
+
+use std::borrow::Cow;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::calendar::{DayOfWeek, Month, Year};
+use crate::error::{Error, Result};
+use crate::generate::{longest_free_hours, parse_clock, TimeSpan};
+use crate::parser::{parse_date, parse_error, parse_hour, parse_number};
+
+/// What a row of a constraints file does to its date.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Constraint {
+    /// The date is worked at least an hour, even if it's on a weekend or a holiday.
+    Pin,
+    /// The date isn't worked.
+    Exclude,
+    /// The date is worked at most this many hours.
+    Cap(u32),
+    /// The date is worked at exactly this time, which counts towards the hours of the month.
+    Fill(TimeSpan),
+}
+
+/// A [`Constraint`] of a specific date.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DateConstraint {
+    /// The year of the date.
+    pub year: u32,
+    /// The month of the date, starting at 1.
+    pub month: NonZeroU32,
+    /// The day of the month, starting at 1.
+    pub day: NonZeroU32,
+    /// What applies to the date.
+    pub constraint: Constraint,
+}
+
+/// A row of a constraints file as written by [`DateConstraint`]'s `Display`.
+impl JsonSchema for DateConstraint {
+    fn schema_name() -> Cow<'static, str> {
+        "DateConstraint".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "An exception of a `day.month.year` date: `pin`, `exclude`, `cap,hours` or `fill,from,to` with full hours like `9:00`.",
+            "type": "string",
+            "pattern": r"^\s*\d{1,2}\.\d{1,2}\.\d{4}\s*,\s*(pin|exclude|cap\s*,\s*\d+|fill\s*,\s*\d{1,2}:00\s*,\s*\d{1,2}:00)\s*$",
+        })
+    }
+}
+
+/// The row of a constraints file, like `13.5.2024,cap,3`.
+impl fmt::Display for DateConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{},", self.day, self.month, self.year)?;
+        match self.constraint {
+            Constraint::Pin => write!(f, "pin"),
+            Constraint::Exclude => write!(f, "exclude"),
+            Constraint::Cap(hours) => write!(f, "cap,{}", hours),
+            Constraint::Fill(time) => write!(f, "fill,{}:00,{}:00", time.from / 60, time.to / 60),
+        }
+    }
+}
+
+/// A span of the day that is never worked, like a lecture or a standing meeting.
+///
+/// Written as `12:00-13:30` for every day or `tue=10:00-12:00` for one weekday. Generated times
+/// start and stop on full hours, so none of the hours the span touches are worked.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Blackout {
+    /// The weekday the blackout applies to, every day if `None`.
+    pub day_of_week: Option<DayOfWeek>,
+    /// The hours that aren't worked.
+    pub time: TimeSpan,
+}
+
+const WEEKDAYS: [(&str, DayOfWeek); 7] = [
+    ("mon", DayOfWeek::Monday),
+    ("tue", DayOfWeek::Tuesday),
+    ("wed", DayOfWeek::Wednesday),
+    ("thu", DayOfWeek::Thursday),
+    ("fri", DayOfWeek::Friday),
+    ("sat", DayOfWeek::Saturday),
+    ("sun", DayOfWeek::Sunday),
+];
+
+impl Blackout {
+    /// Whether the blackout applies to days on `day_of_week`.
+    pub fn applies_to(&self, day_of_week: DayOfWeek) -> bool {
+        self.day_of_week.is_none_or(|day| day == day_of_week)
+    }
+}
+
+impl FromStr for Blackout {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidBlackout(s.into());
+        let (day_of_week, span) = match s.split_once('=') {
+            Some((day, span)) => {
+                let (_, day) = WEEKDAYS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(day.trim()))
+                    .ok_or_else(invalid)?;
+                (Some(*day), span)
+            }
+            None => (None, s),
+        };
+        let (from, to) = span.split_once('-').ok_or_else(invalid)?;
+        let time = TimeSpan {
+            from: parse_clock(from).ok_or_else(invalid)?,
+            to: parse_clock(to).ok_or_else(invalid)?,
+        };
+        if time.to <= time.from {
+            return Err(invalid());
+        }
+        Ok(Blackout { day_of_week, time })
+    }
+}
+
+impl fmt::Display for Blackout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day_of_week {
+            let (name, _) = WEEKDAYS.iter().find(|(_, d)| *d == day).expect("all days");
+            write!(f, "{}=", name)?;
+        }
+        write!(f, "{}", self.time)
+    }
+}
+
+/// The earliest starting and latest stopping hour of one weekday, written as `fri=8-14`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WeekdayWindow {
+    /// The weekday.
+    pub day_of_week: DayOfWeek,
+    /// The earliest starting and the latest stopping hour.
+    pub time: TimeSpan,
+}
+
+impl fmt::Display for WeekdayWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = WEEKDAYS
+            .iter()
+            .find(|(_, d)| *d == self.day_of_week)
+            .expect("all days");
+        write!(f, "{}={}-{}", name, self.time.from / 60, self.time.to / 60)
+    }
+}
+
+/// Serializes into the written form like `fri=8-14`.
+impl Serialize for WeekdayWindow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from the written form like `fri=8-14`.
+impl<'de> Deserialize<'de> for WeekdayWindow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The written form like `fri=8-14`.
+impl JsonSchema for WeekdayWindow {
+    fn schema_name() -> Cow<'static, str> {
+        "WeekdayWindow".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "The window of a single weekday like `fri=8-14`.",
+            "type": "string",
+            "pattern": r"^\s*(mon|tue|wed|thu|fri|sat|sun)\s*=\s*\d{1,2}\s*-\s*\d{1,2}\s*$",
+        })
+    }
+}
+
+impl FromStr for WeekdayWindow {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidWeekdayWindow(s.into());
+        let (day, span) = s.split_once('=').ok_or_else(invalid)?;
+        let (_, day_of_week) = WEEKDAYS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(day.trim()))
+            .ok_or_else(invalid)?;
+        let (from, to) = span.split_once('-').ok_or_else(invalid)?;
+        let hour = |hour: &str| hour.trim().parse::<u32>().map_err(|_| invalid());
+        let (from, to) = (hour(from)?, hour(to)?);
+        if to <= from || to > 24 {
+            return Err(invalid());
+        }
+        Ok(WeekdayWindow {
+            day_of_week: *day_of_week,
+            time: TimeSpan::hours(from, to),
+        })
+    }
+}
+
+/// Expresses `windows` as blackouts of a window that covers all of them and `from` until `to`.
+///
+/// Returns the covering window and the blackouts that cut every day back to its own window, which
+/// is `from` until `to` for the weekdays without one. A weekday given multiple times is only worked
+/// in the intersection of its windows.
+pub fn window_blackouts(
+    from: u32,
+    to: u32,
+    windows: &[WeekdayWindow],
+) -> (TimeSpan, Vec<Blackout>) {
+    let outer = windows
+        .iter()
+        .fold(TimeSpan::hours(from, to), |outer, window| TimeSpan {
+            from: outer.from.min(window.time.from),
+            to: outer.to.max(window.time.to),
+        });
+    let mut blackouts = Vec::new();
+    for (_, day_of_week) in WEEKDAYS {
+        let own: Vec<_> = windows
+            .iter()
+            .filter(|window| window.day_of_week == day_of_week)
+            .map(|window| window.time)
+            .collect();
+        let own = if own.is_empty() {
+            vec![TimeSpan::hours(from, to)]
+        } else {
+            own
+        };
+        for time in own {
+            for blackout in [
+                TimeSpan {
+                    from: outer.from,
+                    to: time.from,
+                },
+                TimeSpan {
+                    from: time.to,
+                    to: outer.to,
+                },
+            ] {
+                if blackout.from < blackout.to {
+                    blackouts.push(Blackout {
+                        day_of_week: Some(day_of_week),
+                        time: blackout,
+                    });
+                }
+            }
+        }
+    }
+    (outer, blackouts)
+}
+
+/// Excludes the days of `month` whose window from `from` until `to` has less than `min_per_day`
+/// full hours in a row left by the `blackouts`, so they aren't given a short block.
+pub fn exclude_short_days(
+    year: &Year,
+    month: &Month,
+    from: u32,
+    to: u32,
+    blackouts: &[Blackout],
+    min_per_day: u32,
+) -> Vec<DateConstraint> {
+    (1..=month.num_days())
+        .filter_map(|day| {
+            let day_of_week = month.day_of_week(day);
+            let busy: Vec<_> = blackouts
+                .iter()
+                .filter(|blackout| blackout.applies_to(day_of_week))
+                .map(|blackout| blackout.time)
+                .collect();
+            (longest_free_hours(from, to, &busy) < min_per_day).then(|| DateConstraint {
+                year: year.year(),
+                month: month.month(),
+                day: NonZeroU32::new(day).expect("days start at 1"),
+                constraint: Constraint::Exclude,
+            })
+        })
+        .collect()
+}
+
+fn compatible(a: Constraint, b: Constraint) -> bool {
+    matches!(
+        (a, b),
+        (Constraint::Pin, Constraint::Cap(_)) | (Constraint::Cap(_), Constraint::Pin)
+    )
+}
+
+/// Reads a constraints file.
+pub fn parse_constraints(input: &str) -> Result<Vec<DateConstraint>> {
+    let mut constraints: Vec<DateConstraint> = Vec::new();
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let columns: Vec<_> = text.split(',').map(str::trim).collect();
+        let (day, month, year) = parse_date(line, columns[0])?;
+        let calendar_month =
+            Month::new(month, &Year::new(year)).map_err(|e| parse_error(line, e.to_string()))?;
+        if day.get() > calendar_month.num_days() {
+            return Err(parse_error(line, format!("`{}` isn't a date", columns[0])));
+        }
+        let constraint =
+            match columns[1..] {
+                ["pin"] => Constraint::Pin,
+                ["exclude"] => Constraint::Exclude,
+                ["cap", hours] => Constraint::Cap(parse_number(line, hours, "number of hours")?),
+                ["fill", from, to] => {
+                    let time = TimeSpan::hours(parse_hour(line, from)?, parse_hour(line, to)?);
+                    if time.to <= time.from || time.to > 24 * 60 {
+                        return Err(parse_error(
+                            line,
+                            format!("`{}-{}` isn't a time span", from, to),
+                        ));
+                    }
+                    Constraint::Fill(time)
+                }
+                _ => return Err(parse_error(
+                    line,
+                    "Expected `date,pin`, `date,exclude`, `date,cap,hours` or `date,fill,from,to`",
+                )),
+            };
+        let conflict = constraints.iter().any(|other| {
+            (other.year, other.month, other.day) == (year, month, day)
+                && !compatible(other.constraint, constraint)
+        });
+        if conflict {
+            return Err(parse_error(
+                line,
+                format!("`{}` conflicts with an earlier constraint", text),
+            ));
+        }
+        constraints.push(DateConstraint {
+            year,
+            month,
+            day,
+            constraint,
+        });
+    }
+    Ok(constraints)
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{DayOfWeek, Month, Year};
+    use crate::constraints::{
+        exclude_short_days, parse_constraints, window_blackouts, Blackout, Constraint,
+        DateConstraint, WeekdayWindow,
+    };
+    use crate::error::Error;
+    use crate::generate::TimeSpan;
+
+    #[test]
+    fn test_parse_constraints() {
+        let constraints = parse_constraints(
+            "# comment\n4.5.2024,pin\n\n4.5.2024, cap, 3\n14.5.2024,fill,9:00,13:00\n",
+        )
+        .unwrap();
+        let date = |day| DateConstraint {
+            year: 2024,
+            month: NonZeroU32::new(5).unwrap(),
+            day: NonZeroU32::new(day).unwrap(),
+            constraint: Constraint::Pin,
+        };
+        assert_eq!(
+            constraints,
+            vec![
+                date(4),
+                DateConstraint {
+                    constraint: Constraint::Cap(3),
+                    ..date(4)
+                },
+                DateConstraint {
+                    constraint: Constraint::Fill(TimeSpan::hours(9, 13)),
+                    ..date(14)
+                },
+            ]
+        );
+
+        for (input, line) in [
+            ("31.4.2024,pin", 1),
+            ("1.5.2024,skip", 1),
+            ("1.5.2024,cap", 1),
+            ("1.5.2024,fill,13:00,9:00", 1),
+            ("1.5.2024,pin\n1.5.2024,exclude", 2),
+        ] {
+            match parse_constraints(input) {
+                Err(Error::Parse { line: actual, .. }) => assert_eq!(actual, line, "{}", input),
+                result => panic!("{} parsed to {:?}", input, result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_blackout() {
+        assert_eq!(
+            "12:00-13:30".parse(),
+            Ok(Blackout {
+                day_of_week: None,
+                time: TimeSpan { from: 720, to: 810 },
+            })
+        );
+        let blackout: Blackout = "Tue=10:15-12:00".parse().unwrap();
+        assert_eq!(blackout.day_of_week, Some(DayOfWeek::Tuesday));
+        assert_eq!(blackout.time, TimeSpan { from: 615, to: 720 });
+        assert_eq!(blackout.to_string(), "tue=10:15-12:00");
+        assert!(blackout.applies_to(DayOfWeek::Tuesday));
+        assert!(!blackout.applies_to(DayOfWeek::Wednesday));
+        for invalid in [
+            "12:00",
+            "13:00-12:00",
+            "xyz=8:00-9:00",
+            "8:60-9:00",
+            "8:00-25:00",
+        ] {
+            assert_eq!(
+                invalid.parse::<Blackout>(),
+                Err(Error::InvalidBlackout(invalid.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_window_blackouts() {
+        let friday: WeekdayWindow = "fri=7-14".parse().unwrap();
+        assert_eq!(friday.time, TimeSpan::hours(7, 14));
+        assert!("fri=14-8".parse::<WeekdayWindow>().is_err());
+        assert!("8-14".parse::<WeekdayWindow>().is_err());
+
+        let (outer, blackouts) = window_blackouts(8, 20, &[friday]);
+        assert_eq!(outer, TimeSpan::hours(7, 20));
+        let of = |day_of_week| {
+            blackouts
+                .iter()
+                .filter(|blackout| blackout.day_of_week == Some(day_of_week))
+                .map(|blackout| blackout.time)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(of(DayOfWeek::Monday), [TimeSpan::hours(7, 8)]);
+        assert_eq!(of(DayOfWeek::Friday), [TimeSpan::hours(14, 20)]);
+        assert!(window_blackouts(8, 20, &[]).1.is_empty());
+    }
+
+    #[test]
+    fn test_exclude_short_days() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let blackouts: [Blackout; 2] = [
+            "tue=10:00-18:00".parse().unwrap(),
+            "12:00-13:00".parse().unwrap(),
+        ];
+        let excluded = exclude_short_days(&year, &month, 8, 20, &blackouts, 3);
+        assert_eq!(excluded.len(), 4);
+        assert!(excluded.iter().all(|c| c.constraint == Constraint::Exclude
+            && month.day_of_week(c.day.get()) == DayOfWeek::Tuesday));
+        assert!(exclude_short_days(&year, &month, 8, 20, &blackouts, 2).is_empty());
+        assert_eq!(exclude_short_days(&year, &month, 8, 20, &[], 13).len(), 31);
+    }
+}
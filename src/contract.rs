@@ -0,0 +1,556 @@
+//! The `[contract]` of the configuration, which `generate` derives its parameters from.
+//!
+//! ```toml
+//! [contract]
+//! weekly_hours = 20
+//! state = "NW"
+//! earliest = 9
+//! latest = 18
+//! start = 2024-03-15
+//! ```
+//!
+//! A month is worked `weekly_hours / 5` hours per working day, rounded to full hours. Working days
+//! before `start` or after `end` aren't worked and don't count, so a month the contract starts or
+//! ends in is pro-rated. The same [`Period`] can also be given without a contract. A contract
+//! following quarters or semesters is generated by [`Term`].
+
+use std::borrow::Cow;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use rand::Rng;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::calendar::{non_holidays_of_month, City, Month, Region, State, Year};
+use crate::constraints::{Constraint, DateConstraint};
+use crate::distribute::Partitioner;
+use crate::error::{Error, Result};
+use crate::request::{default_earliest, default_latest, default_max_per_day, GenerateRequest};
+
+/// A date of a [`Contract`], written as a TOML date like `2024-03-15`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Date {
+    /// The year.
+    pub year: u32,
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The day of the month, starting at 1.
+    pub day: NonZeroU32,
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let date = toml::value::Date {
+            year: self.year as u16,
+            month: self.month.get() as u8,
+            day: self.day.get() as u8,
+        };
+        toml::value::Datetime {
+            date: Some(date),
+            time: None,
+            offset: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let datetime = toml::value::Datetime::deserialize(deserializer)?;
+        let date = match datetime {
+            toml::value::Datetime {
+                date: Some(date),
+                time: None,
+                offset: None,
+            } => date,
+            _ => return Err(serde::de::Error::custom("expected a date like 2024-03-15")),
+        };
+        Ok(Date {
+            year: date.year.into(),
+            month: NonZeroU32::new(date.month.into()).expect("toml months start at 1"),
+            day: NonZeroU32::new(date.day.into()).expect("toml days start at 1"),
+        })
+    }
+}
+
+/// The employment period from `start` until `end`, both inclusive, a missing date leaves it open.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Period {
+    /// The first day.
+    pub start: Option<Date>,
+    /// The last day.
+    pub end: Option<Date>,
+}
+
+impl Period {
+    /// Whether `date` is neither before `start` nor after `end`.
+    pub fn covers(&self, date: Date) -> bool {
+        self.start.is_none_or(|start| start <= date) && self.end.is_none_or(|end| date <= end)
+    }
+
+    /// Excludes the days of `month` in `year` outside the period.
+    pub fn constraints(&self, year: u32, month: &Month) -> Vec<DateConstraint> {
+        month
+            .days()
+            .map(|day| Date {
+                year,
+                month: month.month(),
+                day: day.day_of_month,
+            })
+            .filter(|&date| !self.covers(date))
+            .map(|date| DateConstraint {
+                year,
+                month: date.month,
+                day: date.day,
+                constraint: Constraint::Exclude,
+            })
+            .collect()
+    }
+
+    /// The number of working days of `month` in `region` within the period, fails if there are
+    /// none.
+    pub fn working_days(&self, year: &Year, month: &Month, region: Region) -> Result<u32> {
+        let days = non_holidays_of_month(month, year, &region)
+            .into_iter()
+            .filter(|day| {
+                self.covers(Date {
+                    year: year.year(),
+                    month: month.month(),
+                    day: day.day_of_month,
+                })
+            })
+            .count() as u32;
+        if days == 0 {
+            return Err(Error::OutsideContract {
+                year: year.year(),
+                month: month.month(),
+            });
+        }
+        Ok(days)
+    }
+
+    /// Scales the `hours` of the whole `month` down to its working days within the period,
+    /// rounded to full hours.
+    pub fn prorate(&self, hours: u32, year: &Year, month: &Month, region: Region) -> Result<u32> {
+        let covered = self.working_days(year, month, region)?;
+        let all = non_holidays_of_month(month, year, &region).len() as u32;
+        Ok((2 * hours * covered + all) / (2 * all))
+    }
+}
+
+/// A quarter or a semester, whose months `generate --period` generates one after another.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Term {
+    /// The quarter `1..=4` of a year, written like `Q1-2024`.
+    Quarter {
+        /// The quarter.
+        quarter: u32,
+        /// The year.
+        year: u32,
+    },
+    /// April until September of a year, written like `SS25`.
+    SummerSemester {
+        /// The year.
+        year: u32,
+    },
+    /// October of a year until March of the next one, written like `WS24` or `WS24/25`.
+    WinterSemester {
+        /// The year the semester starts in.
+        year: u32,
+    },
+}
+
+impl Term {
+    /// The year and month the term starts in and its number of months.
+    fn span(&self) -> (u32, u32, u32) {
+        match *self {
+            Term::Quarter { quarter, year } => (year, 3 * quarter - 2, 3),
+            Term::SummerSemester { year } => (year, 4, 6),
+            Term::WinterSemester { year } => (year, 10, 6),
+        }
+    }
+
+    /// The years and months of the term, in order.
+    pub fn months(&self) -> impl Iterator<Item = (u32, NonZeroU32)> {
+        let (year, first, count) = self.span();
+        (first - 1..first - 1 + count).map(move |index| {
+            let month = NonZeroU32::new(index % 12 + 1).expect("months start at 1");
+            (year + index / 12, month)
+        })
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Quarter { quarter, year } => write!(f, "Q{}-{}", quarter, year),
+            Term::SummerSemester { year } => write!(f, "SS{}", year),
+            Term::WinterSemester { year } => write!(f, "WS{}/{:02}", year, (year + 1) % 100),
+        }
+    }
+}
+
+/// A year of two digits like `24` is one of this century.
+fn term_year(s: &str) -> Option<u32> {
+    if !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match s.len() {
+        2 => s.parse().ok().map(|year: u32| 2000 + year),
+        4 => s.parse().ok(),
+        _ => None,
+    }
+}
+
+impl FromStr for Term {
+    type Err = Error;
+
+    /// Parses `Q1-2024`, `SS25`, `WS24` or `WS24/25`, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidTerm(s.into());
+        let upper = s.to_ascii_uppercase();
+        if let Some(rest) = upper.strip_prefix('Q') {
+            let (quarter, year) = rest.split_once('-').ok_or_else(invalid)?;
+            let quarter = match quarter.parse() {
+                Ok(quarter @ 1..=4) => quarter,
+                _ => return Err(invalid()),
+            };
+            let year = term_year(year).ok_or_else(invalid)?;
+            Ok(Term::Quarter { quarter, year })
+        } else if let Some(year) = upper.strip_prefix("SS") {
+            let year = term_year(year).ok_or_else(invalid)?;
+            Ok(Term::SummerSemester { year })
+        } else if let Some(rest) = upper.strip_prefix("WS") {
+            let (year, next) = match rest.split_once('/') {
+                Some((year, next)) => (year, Some(next)),
+                None => (rest, None),
+            };
+            let year = term_year(year).ok_or_else(invalid)?;
+            if let Some(next) = next {
+                let next = term_year(next).ok_or_else(invalid)?;
+                if next % 100 != (year + 1) % 100 {
+                    return Err(invalid());
+                }
+            }
+            Ok(Term::WinterSemester { year })
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// The `[contract]` section or one of the `[[jobs]]`.
+///
+/// An entry with `inherits = "<employer>"` takes the settings it leaves out from the entry of that
+/// employer, see [`crate::config::Config::parse`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Contract {
+    /// The name of the employer, heads the timesheet of the job with `generate --jobs`.
+    #[serde(default)]
+    pub employer: Option<String>,
+    /// The hours worked per week.
+    pub weekly_hours: u32,
+    /// The state whose holidays aren't worked on.
+    #[serde(default)]
+    pub state: State,
+    /// The city within `state` whose holidays aren't worked on either.
+    #[serde(default)]
+    pub city: Option<City>,
+    /// Maximum hours per day.
+    #[serde(default = "default_max_per_day")]
+    pub max_per_day: u32,
+    /// Earliest starting hour.
+    #[serde(default = "default_earliest")]
+    pub earliest: u32,
+    /// Latest stopping hour.
+    #[serde(default = "default_latest")]
+    pub latest: u32,
+    /// The first day of the contract.
+    #[serde(default)]
+    pub start: Option<Date>,
+    /// The last day of the contract.
+    #[serde(default)]
+    pub end: Option<Date>,
+    /// The hourly rate in cents, written in euros like `15.5`. Splits the pay of
+    /// `generate --jobs` by job and is the default rate of `invoice`.
+    #[serde(default, serialize_with = "as_euros", deserialize_with = "euros")]
+    #[schemars(with = "Option<f64>", range(min = 0.01))]
+    pub rate: Option<u32>,
+}
+
+/// An amount of cents as euros like `15.5`.
+fn as_euros<S: Serializer>(
+    cents: &Option<u32>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    cents
+        .map(|cents| f64::from(cents) / 100.0)
+        .serialize(serializer)
+}
+
+/// An amount of euros like `15.5` in cents.
+fn euros<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<u32>, D::Error> {
+    let euros = f64::deserialize(deserializer)?;
+    let cents = (euros * 100.0).round();
+    if !(1.0..=f64::from(u32::MAX)).contains(&cents) {
+        return Err(serde::de::Error::custom(
+            "expected a positive amount of euros",
+        ));
+    }
+    Ok(Some(cents as u32))
+}
+
+/// A TOML date, which validators of TOML see as a string like `2024-03-15`.
+impl JsonSchema for Date {
+    fn schema_name() -> Cow<'static, str> {
+        "Date".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string", "format": "date" })
+    }
+}
+
+impl Contract {
+    /// The period from `start` until `end`.
+    pub fn period(&self) -> Period {
+        Period {
+            start: self.start,
+            end: self.end,
+        }
+    }
+
+    /// The hours of `month`, fails if the contract doesn't cover any of its working days.
+    pub fn hours(&self, year: &Year, month: &Month) -> Result<u32> {
+        let region = Region::new(self.state, self.city)?;
+        let days = self.period().working_days(year, month, region)?;
+        Ok((self.weekly_hours * days + 2) / 5)
+    }
+
+    /// The parameters of the timesheet of `month` in `year`, without a seed.
+    ///
+    /// The days outside the contract still have to be excluded with [`Period::constraints`].
+    pub fn request(&self, year: &Year, month: &Month) -> Result<GenerateRequest> {
+        Ok(GenerateRequest {
+            month: month.month(),
+            year: year.year(),
+            hours: self.hours(year, month)?,
+            max_per_day: self.max_per_day,
+            earliest: self.earliest,
+            latest: self.latest,
+            state: self.state,
+            city: self.city,
+            seed: None,
+        })
+    }
+
+    /// Splits the hours of all months of `term` unevenly onto them, the total stays the same.
+    ///
+    /// Every month gets at least half of its own hours and at most `cap` or the hours its working
+    /// days can hold with `max_per_day`. Months the contract doesn't cover get none. Every such
+    /// split is equally likely.
+    pub fn bank<R: Rng + ?Sized>(
+        &self,
+        term: Term,
+        cap: Option<u32>,
+        r: &mut R,
+    ) -> Result<Vec<BankedMonth>> {
+        let region = Region::new(self.state, self.city)?;
+        let mut months = Vec::new();
+        let mut caps = Vec::new();
+        for (year, month) in term.months() {
+            let calendar_year = Year::new(year);
+            let calendar_month = Month::new(month, &calendar_year)?;
+            let (contract, room) =
+                match self
+                    .period()
+                    .working_days(&calendar_year, &calendar_month, region)
+                {
+                    Ok(days) => (
+                        self.hours(&calendar_year, &calendar_month)?,
+                        days * self.max_per_day,
+                    ),
+                    Err(Error::OutsideContract { .. }) => (0, 0),
+                    Err(e) => return Err(e),
+                };
+            let room = cap.map_or(room, |cap| cap.min(room));
+            let floor = (contract / 2).min(room);
+            months.push(BankedMonth {
+                year,
+                month,
+                contract,
+                hours: floor,
+            });
+            caps.push(room - floor);
+        }
+        let total: u32 = months.iter().map(|month| month.contract).sum();
+        let floors: u32 = months.iter().map(|month| month.hours).sum();
+        let capacity = floors + caps.iter().sum::<u32>();
+        if capacity < total {
+            return Err(Error::BankOverCapacity {
+                hours: total,
+                capacity,
+            });
+        }
+        let mut parts = Vec::new();
+        Partitioner::new().partition_bounded_into(total - floors, &caps, &mut parts, r)?;
+        for (month, part) in months.iter_mut().zip(parts) {
+            month.hours += part;
+        }
+        Ok(months)
+    }
+}
+
+/// A month of [`Contract::bank`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BankedMonth {
+    /// The year of the month.
+    pub year: u32,
+    /// The month.
+    pub month: NonZeroU32,
+    /// The hours of the contract in this month.
+    pub contract: u32,
+    /// The hours worked in this month instead.
+    pub hours: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::config::Config;
+    use crate::constraints::Constraint;
+    use crate::contract::Term;
+    use crate::error::Error;
+    use crate::rng::seeded;
+
+    #[test]
+    fn test_contract() {
+        let config = Config::parse(
+            "[contract]\nweekly_hours = 20\nstate = \"NW\"\nstart = 2024-03-15\nend = 2024-12-31",
+        )
+        .unwrap();
+        let contract = config.contract.unwrap();
+        assert_eq!(contract.state, State::NorthRhineWestphalia);
+        assert_eq!(contract.max_per_day, 8);
+
+        let year = Year::new(2024);
+        let month = |month| Month::new(NonZeroU32::new(month).unwrap(), &year).unwrap();
+        // 20 working days, 20 hours a week are 4 hours a day
+        assert_eq!(contract.hours(&year, &month(6)), Ok(80));
+        // 15.3. until 28.3., Karfreitag is on 29.3.
+        assert_eq!(contract.hours(&year, &month(3)), Ok(40));
+        assert_eq!(
+            contract.hours(&year, &month(2)),
+            Err(Error::OutsideContract {
+                year: 2024,
+                month: NonZeroU32::new(2).unwrap()
+            })
+        );
+
+        let constraints = contract.period().constraints(2024, &month(3));
+        assert_eq!(constraints.len(), 14);
+        assert!(constraints
+            .iter()
+            .all(|c| c.day.get() < 15 && c.constraint == Constraint::Exclude));
+        assert!(contract.period().constraints(2024, &month(12)).is_empty());
+
+        // 160 hours for 20 days, 10 of which are covered
+        let region = State::NorthRhineWestphalia.into();
+        let period = contract.period();
+        assert_eq!(period.prorate(160, &year, &month(3), region), Ok(80));
+        assert_eq!(period.prorate(161, &year, &month(3), region), Ok(81));
+        assert_eq!(period.prorate(160, &year, &month(4), region), Ok(160));
+
+        assert!(
+            Config::parse("[contract]\nweekly_hours = 20\nstart = 2024-03-15T08:00:00").is_err()
+        );
+
+        assert_eq!(contract.rate, None);
+        let rate = |rate| {
+            Config::parse(&format!("[contract]\nweekly_hours = 20\nrate = {}", rate))
+                .map(|config| config.contract.unwrap().rate)
+        };
+        assert_eq!(rate("15.5"), Ok(Some(1550)));
+        assert_eq!(rate("40"), Ok(Some(4000)));
+        assert!(rate("-1").is_err());
+        assert!(rate("\"15\"").is_err());
+    }
+
+    #[test]
+    fn test_terms() {
+        let months = |term: &str| {
+            let term: Term = term.parse().unwrap();
+            term.months()
+                .map(|(year, month)| (year, month.get()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(months("Q1-2024"), [(2024, 1), (2024, 2), (2024, 3)]);
+        assert_eq!(months("q4-24"), [(2024, 10), (2024, 11), (2024, 12)]);
+        assert_eq!(
+            months("WS24"),
+            [
+                (2024, 10),
+                (2024, 11),
+                (2024, 12),
+                (2025, 1),
+                (2025, 2),
+                (2025, 3)
+            ]
+        );
+        assert_eq!(months("WS2024/25"), months("ws24"));
+        let summer: Vec<_> = (4..=9).map(|month| (2025, month)).collect();
+        assert_eq!(months("SS25"), summer);
+
+        assert_eq!("WS99/00".parse(), Ok(Term::WinterSemester { year: 2099 }));
+        for invalid in ["Q5-2024", "Q1", "WS24/26", "WS3", "SS2024x", "H1-2024"] {
+            assert_eq!(
+                invalid.parse::<Term>(),
+                Err(Error::InvalidTerm(invalid.into()))
+            );
+        }
+        for term in ["Q2-2024", "SS2025", "WS2024/25"] {
+            assert_eq!(term.parse::<Term>().unwrap().to_string(), term);
+        }
+    }
+
+    #[test]
+    fn test_bank() {
+        let config = Config::parse(
+            "[contract]\nweekly_hours = 10\nstate = \"NW\"\nstart = 2024-11-01\nend = 2025-02-28",
+        )
+        .unwrap();
+        let contract = config.contract.unwrap();
+        let term = "WS24".parse().unwrap();
+        let capped = |cap| {
+            (0..20)
+                .map(|seed| contract.bank(term, cap, &mut seeded(seed)))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        for months in capped(Some(60)).unwrap() {
+            let worked: Vec<_> = months.iter().map(|month| month.hours).collect();
+            assert_eq!(worked[0], 0);
+            assert_eq!(worked[5], 0);
+            for month in &months {
+                assert!(month.contract / 2 <= month.hours && month.hours <= 60);
+            }
+            let contract: u32 = months.iter().map(|month| month.contract).sum();
+            assert_eq!(worked.iter().sum::<u32>(), contract);
+        }
+        assert!(capped(None)
+            .unwrap()
+            .iter()
+            .any(|months| months.iter().any(|month| month.hours != month.contract)));
+        assert_eq!(
+            capped(Some(30)).unwrap_err(),
+            Error::BankOverCapacity {
+                hours: 164,
+                capacity: 120
+            }
+        );
+    }
+}
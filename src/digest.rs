@@ -0,0 +1,189 @@
+//! Digests identifying a generated timesheet.
+//!
+//! The calendar layout and the invoice carry the [`SheetDigest`] of their timesheet as a QR code. Scanning the
+//! code of a paper copy and checking it with the `digest` subcommand tells whether the times are
+//! still those that were generated. The digest is the SHA-256 of the timesheet as
+//! `generate --format json --no-header` writes it, so it changes with every time, break or note.
+
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::output::Format;
+use crate::timesheet::Timesheet;
+
+/// The prefix of the text of a [`SheetDigest`].
+const PREFIX: &str = "stundenzettel";
+
+/// The CSS of the figure of [`SheetDigest::html`].
+pub(crate) const STYLE: &str = ".digest { margin: 0; } \
+                                .digest svg { width: 3cm; height: 3cm; } \
+                                .digest figcaption { font-size: 0.6em; word-break: break-all; }";
+
+/// The month and the SHA-256 of a timesheet together with the seed it was generated with.
+///
+/// Written like `stundenzettel:2024-03:42:<64 hex digits>` with `-` instead of a missing seed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SheetDigest {
+    /// The year of the month.
+    pub year: u32,
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The seed the times were generated with, if they were seeded.
+    pub seed: Option<u64>,
+    /// The SHA-256 of the JSON of the timesheet.
+    pub sha256: [u8; 32],
+}
+
+impl SheetDigest {
+    /// The digest of `sheet`, which was generated with `seed`.
+    pub fn new(sheet: &Timesheet, seed: Option<u64>) -> Self {
+        SheetDigest {
+            year: sheet.year,
+            month: sheet.month,
+            seed,
+            sha256: Sha256::digest(Format::Json.formatter().render(sheet)).into(),
+        }
+    }
+
+    /// Whether `sheet` is the timesheet this is the digest of.
+    pub fn matches(&self, sheet: &Timesheet) -> bool {
+        SheetDigest::new(sheet, self.seed) == *self
+    }
+
+    /// The digest as the SVG of a QR code at the error correction level M, which restores up to
+    /// 15 % of damaged modules.
+    pub fn svg(&self) -> String {
+        let code = QrCode::with_error_correction_level(self.to_string(), EcLevel::M)
+            .expect("digests fit into a code");
+        let svg = code.render::<svg::Color>().module_dimensions(1, 1).build();
+        // the XML declaration has no place inside of HTML
+        match svg.split_once("?>") {
+            Some((_, svg)) => svg.into(),
+            None => svg,
+        }
+    }
+
+    /// The QR code with the digest as its caption, styled by [`STYLE`].
+    pub(crate) fn html(&self) -> String {
+        format!(
+            "<figure class=\"digest\">{}<figcaption>{}</figcaption></figure>",
+            self.svg(),
+            self
+        )
+    }
+}
+
+impl fmt::Display for SheetDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}-{:02}:", PREFIX, self.year, self.month)?;
+        match self.seed {
+            Some(seed) => write!(f, "{}:", seed)?,
+            None => write!(f, "-:")?,
+        }
+        for byte in self.sha256 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SheetDigest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDigest(s.into());
+        let mut parts = s.trim().split(':');
+        if parts.next() != Some(PREFIX) {
+            return Err(invalid());
+        }
+        let (year, month) = parts
+            .next()
+            .and_then(|month| month.split_once('-'))
+            .ok_or_else(invalid)?;
+        let seed = match parts.next().ok_or_else(invalid)? {
+            "-" => None,
+            seed => Some(seed.parse().map_err(|_| invalid())?),
+        };
+        let hex = parts
+            .next()
+            .filter(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()));
+        let (Some(hex), None) = (hex, parts.next()) else {
+            return Err(invalid());
+        };
+        let mut sha256 = [0; 32];
+        for (byte, digits) in sha256.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let digits = std::str::from_utf8(digits).expect("hex digits are ascii");
+            *byte = u8::from_str_radix(digits, 16).expect("two hex digits are a byte");
+        }
+        Ok(SheetDigest {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month
+                .parse()
+                .ok()
+                .filter(|month: &NonZeroU32| month.get() <= 12)
+                .ok_or_else(invalid)?,
+            seed,
+            sha256,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::digest::SheetDigest;
+    use crate::error::Error;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_digest() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let mut sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+        let digest = SheetDigest::new(&sheet, Some(1));
+        let text = digest.to_string();
+        assert!(text.starts_with("stundenzettel:2024-03:1:"));
+        assert_eq!(text.len(), "stundenzettel:2024-03:1:".len() + 64);
+        assert_eq!(text.parse(), Ok(digest));
+        assert!(digest.matches(&sheet));
+        let svg = digest.svg();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+
+        let unseeded = SheetDigest::new(&sheet, None);
+        assert_eq!(unseeded.to_string().parse(), Ok(unseeded));
+        assert!(unseeded.to_string().contains(":-:"));
+
+        sheet.entries[0].time.to += 60;
+        assert!(!digest.matches(&sheet));
+
+        for invalid in [
+            "stundenzettel:2024-13:1:00",
+            &text[..text.len() - 1],
+            &text.replace("stundenzettel", "timesheet"),
+            &format!("{}:", text),
+            &text.replace(":1:", ":one:"),
+        ] {
+            assert_eq!(
+                invalid.parse::<SheetDigest>(),
+                Err(Error::InvalidDigest(invalid.into()))
+            );
+        }
+    }
+}
@@ -0,0 +1,1018 @@
+//! Constrained random partitions of a total into buckets.
+//!
+//! [`distribute`] splits a total into buckets with a minimum and a maximum each and caps on groups
+//! of consecutive buckets, like the hours of a month onto its days with a limit per week. Every
+//! split that meets the [`Constraints`] is equally likely. [`partition`] and the [`Partitioner`]
+//! cover the common case of a single maximum for all buckets without allocating for every call.
+//!
+//! Implementations of [`Distribution`] choose other shapes than uniformly random splits, like
+//! [`Even`] or [`FrontLoaded`] ones, a [`Shape`] biases the uniform splits towards a part of the
+//! buckets.
+
+use std::ops::Range;
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::rng::index;
+
+/// The range of values of one bucket, see [`Constraints`]. [`crate::generate`] bounds the hours of
+/// a day with it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Bounds {
+    /// The minimum value.
+    pub min: u32,
+    /// The maximum value.
+    pub max: u32,
+}
+
+/// A cap on the sum of consecutive buckets, see [`Constraints`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Group {
+    /// The indices of the buckets.
+    pub buckets: Range<usize>,
+    /// The most the buckets may sum up to together.
+    pub cap: u32,
+}
+
+/// The constraints of [`distribute`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Constraints {
+    /// The bounds of every bucket in order, empty if the buckets are only bounded by the total.
+    pub bounds: Vec<Bounds>,
+    /// Caps on groups of buckets, the groups mustn't overlap.
+    pub groups: Vec<Group>,
+}
+
+/// Samples partitions with reusable buffers, see [`partition`].
+///
+/// The sampler counts the ways each remainder can be split into the remaining parts and draws every
+/// part weighted by these counts. This is exact and needs no rejection, regardless of how tight
+/// `max` is.
+#[derive(Debug, Default)]
+pub struct Partitioner {
+    /// `ways[i * (n + 1) + s]` is the number of ways to split `s` into `i` parts that are each at
+    /// most `max`. The counts exceed integers quickly but stay far below the range of `f64`.
+    ways: Vec<f64>,
+}
+
+impl Partitioner {
+    /// Creates a partitioner without any allocated buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn count(&mut self, n: u32, k: u32, max: u32) {
+        let width = n as usize + 1;
+        self.ways.clear();
+        self.ways.resize(width * (k as usize + 1), 0.0);
+        self.ways[0] = 1.0;
+        for i in 1..=k as usize {
+            let (previous, current) = self.ways.split_at_mut(i * width);
+            let previous = &previous[(i - 1) * width..];
+            let current = &mut current[..width];
+            // sliding window sum over previous[s - max..=s]
+            let mut window = 0.0;
+            for s in 0..width {
+                window += previous[s];
+                if s > max as usize {
+                    window -= previous[s - max as usize - 1];
+                }
+                current[s] = window;
+            }
+        }
+    }
+
+    fn count_bounded(&mut self, n: u32, caps: &[u32]) {
+        let width = n as usize + 1;
+        let k = caps.len();
+        self.ways.clear();
+        self.ways.resize(width * (k + 1), 0.0);
+        self.ways[k * width] = 1.0;
+        // unlike `count`, `ways[i * (n + 1) + s]` counts the splits into the parts `i..k`
+        for (i, &cap) in caps.iter().enumerate().rev() {
+            let (current, next) = self.ways.split_at_mut((i + 1) * width);
+            let current = &mut current[i * width..];
+            let next = &next[..width];
+            let mut window = 0.0;
+            for s in 0..width {
+                window += next[s];
+                if s > cap as usize {
+                    window -= next[s - cap as usize - 1];
+                }
+                current[s] = window;
+            }
+        }
+    }
+
+    /// Randomly splits `n` into `caps.len()` parts where the `i`th part is at most `caps[i]` and
+    /// writes them to `target`.
+    ///
+    /// Every such split is equally likely. Fails if the caps sum up to less than `n`.
+    pub fn partition_bounded_into<R: Rng + ?Sized>(
+        &mut self,
+        n: u32,
+        caps: &[u32],
+        target: &mut Vec<u32>,
+        r: &mut R,
+    ) -> Result<()> {
+        if caps.iter().map(|&cap| cap as u64).sum::<u64>() < n as u64 {
+            return Err(Error::Infeasible {
+                hours: n,
+                days: caps.len() as u32,
+                max_per_day: caps.iter().copied().max().unwrap_or_default(),
+            });
+        }
+        target.clear();
+        target.reserve(caps.len());
+        self.count_bounded(n, caps);
+
+        let width = n as usize + 1;
+        let mut remaining = n as usize;
+        for (i, &cap) in caps.iter().enumerate() {
+            let rest = &self.ways[(i + 1) * width..(i + 2) * width];
+            let mut pick = r.gen::<f64>() * self.ways[i * width + remaining];
+            let mut part = 0;
+            for candidate in 0..=remaining.min(cap as usize) {
+                let weight = rest[remaining - candidate];
+                if weight > 0.0 {
+                    part = candidate;
+                    pick -= weight;
+                    if pick < 0.0 {
+                        break;
+                    }
+                }
+            }
+            target.push(part as u32);
+            remaining -= part;
+        }
+        Ok(())
+    }
+
+    fn count_weighted(&mut self, n: u32, caps: &[u32], weights: &[f64]) {
+        let width = n as usize + 1;
+        let k = caps.len();
+        self.ways.clear();
+        self.ways.resize(width * (k + 1), 0.0);
+        self.ways[k * width] = 1.0;
+        // like `count_bounded`, but a part of `x` counts `weights[i]^x` times
+        for (i, (&cap, &weight)) in caps.iter().zip(weights).enumerate().rev() {
+            let (current, next) = self.ways.split_at_mut((i + 1) * width);
+            let current = &mut current[i * width..];
+            let next = &next[..width];
+            for s in 0..width {
+                let mut factor = 1.0;
+                for x in 0..=s.min(cap as usize) {
+                    current[s] += factor * next[s - x];
+                    factor *= weight;
+                }
+            }
+        }
+    }
+
+    /// Like [`Partitioner::partition_bounded_into`], but a split into the parts `p` is drawn with a
+    /// probability proportional to the product of `weights[i]^p[i]`.
+    ///
+    /// Weights above one favour larger parts, weights of one draw uniformly. Fails if the caps sum
+    /// up to less than `n` or a weight isn't positive.
+    pub fn partition_weighted_into<R: Rng + ?Sized>(
+        &mut self,
+        n: u32,
+        caps: &[u32],
+        weights: &[f64],
+        target: &mut Vec<u32>,
+        r: &mut R,
+    ) -> Result<()> {
+        if weights.len() != caps.len() || !weights.iter().all(|&w| w.is_finite() && w > 0.0) {
+            return Err(Error::InvalidDistribution(
+                "there has to be a positive weight for every part".into(),
+            ));
+        }
+        if caps.iter().map(|&cap| cap as u64).sum::<u64>() < n as u64 {
+            return Err(Error::Infeasible {
+                hours: n,
+                days: caps.len() as u32,
+                max_per_day: caps.iter().copied().max().unwrap_or_default(),
+            });
+        }
+        target.clear();
+        target.reserve(caps.len());
+        self.count_weighted(n, caps, weights);
+
+        let width = n as usize + 1;
+        let mut remaining = n as usize;
+        for (i, (&cap, &weight)) in caps.iter().zip(weights).enumerate() {
+            let rest = &self.ways[(i + 1) * width..(i + 2) * width];
+            let mut pick = r.gen::<f64>() * self.ways[i * width + remaining];
+            let mut part = 0;
+            let mut factor = 1.0;
+            for candidate in 0..=remaining.min(cap as usize) {
+                let ways = factor * rest[remaining - candidate];
+                factor *= weight;
+                if ways > 0.0 {
+                    part = candidate;
+                    pick -= ways;
+                    if pick < 0.0 {
+                        break;
+                    }
+                }
+            }
+            target.push(part as u32);
+            remaining -= part;
+        }
+        Ok(())
+    }
+
+    /// Randomly splits `n` into `k` parts that are each at most `max` and writes them to `target`.
+    ///
+    /// Every such split is equally likely. Fails if `k * max < n`.
+    pub fn partition_into<R: Rng + ?Sized>(
+        &mut self,
+        n: u32,
+        k: u32,
+        max: u32,
+        target: &mut Vec<u32>,
+        r: &mut R,
+    ) -> Result<()> {
+        if k.checked_mul(max).is_none_or(|total| total < n) {
+            return Err(Error::Infeasible {
+                hours: n,
+                days: k,
+                max_per_day: max,
+            });
+        }
+        target.clear();
+        target.reserve(k as usize);
+        self.count(n, k, max);
+
+        let width = n as usize + 1;
+        let mut remaining = n as usize;
+        for parts in (1..=k as usize).rev() {
+            let rest = &self.ways[(parts - 1) * width..parts * width];
+            let mut pick = r.gen::<f64>() * self.ways[parts * width + remaining];
+            // falls back to the last possible part if rounding errors of the weights leave a rest
+            let mut part = 0;
+            for candidate in 0..=remaining.min(max as usize) {
+                let weight = rest[remaining - candidate];
+                if weight > 0.0 {
+                    part = candidate;
+                    pick -= weight;
+                    if pick < 0.0 {
+                        break;
+                    }
+                }
+            }
+            target.push(part as u32);
+            remaining -= part;
+        }
+        Ok(())
+    }
+}
+
+/// Randomly splits `n` into `k` parts that are each at most `max`.
+///
+/// Every such split is equally likely. Fails if `k * max < n`. Use a [`Partitioner`] to reuse the
+/// buffers across many calls.
+pub fn partition<R: Rng + ?Sized>(n: u32, k: u32, max: u32, r: &mut R) -> Result<Vec<u32>> {
+    let mut result = Vec::new();
+    Partitioner::new().partition_into(n, k, max, &mut result, r)?;
+    Ok(result)
+}
+
+/// The number of ways to split each sum into the parts with the `caps`, up to `n`.
+fn ways(n: u32, caps: &[u32]) -> Vec<f64> {
+    let mut ways = vec![0.0; n as usize + 1];
+    ways[0] = 1.0;
+    for &cap in caps {
+        let mut window = 0.0;
+        let previous = std::mem::take(&mut ways);
+        ways = previous
+            .iter()
+            .enumerate()
+            .map(|(s, &count)| {
+                window += count;
+                if s > cap as usize {
+                    window -= previous[s - cap as usize - 1];
+                }
+                window
+            })
+            .collect();
+    }
+    ways
+}
+
+/// Randomly splits `total` into `buckets` parts that meet the `constraints`.
+///
+/// Every such split is equally likely. The buckets outside of all groups are only bounded by
+/// their [`Bounds`]. Fails with [`Error::InvalidDistribution`] if the constraints don't describe
+/// the buckets, e.g. groups overlap, and with [`Error::Infeasible`] if no split meets them.
+pub fn distribute<R: Rng + ?Sized>(
+    total: u32,
+    buckets: usize,
+    constraints: &Constraints,
+    r: &mut R,
+) -> Result<Vec<u32>> {
+    let invalid = |reason: &str| Error::InvalidDistribution(reason.into());
+    let bounds = match constraints.bounds.len() {
+        0 => vec![Bounds { min: 0, max: total }; buckets],
+        len if len == buckets => constraints.bounds.clone(),
+        _ => return Err(invalid("there have to be bounds for every bucket or none")),
+    };
+    if bounds.iter().any(|b| b.min > b.max) {
+        return Err(invalid("a minimum is above its maximum"));
+    }
+    let infeasible = Error::Infeasible {
+        hours: total,
+        days: buckets as u32,
+        max_per_day: bounds.iter().map(|b| b.max).max().unwrap_or_default(),
+    };
+    let minimum: u64 = bounds.iter().map(|b| u64::from(b.min)).sum();
+    let rest = u64::from(total)
+        .checked_sub(minimum)
+        .ok_or(infeasible.clone())? as u32;
+    let caps: Vec<u32> = bounds.iter().map(|b| (b.max - b.min).min(rest)).collect();
+
+    // consecutive segments that are either a group or a single bucket outside of all groups
+    let mut groups: Vec<&Group> = constraints.groups.iter().collect();
+    groups.sort_by_key(|group| group.buckets.start);
+    let mut segments = Vec::new();
+    let mut next = 0;
+    for group in groups {
+        let Range { start, end } = group.buckets;
+        if start < next || end > buckets || start >= end {
+            return Err(invalid(
+                "groups have to be non-empty, disjoint and within the buckets",
+            ));
+        }
+        segments.extend((next..start).map(|bucket| (bucket..bucket + 1, rest)));
+        let minimum: u64 = bounds[start..end].iter().map(|b| u64::from(b.min)).sum();
+        let cap = u64::from(group.cap)
+            .checked_sub(minimum)
+            .ok_or(infeasible.clone())?;
+        segments.push((start..end, cap.min(u64::from(rest)) as u32));
+        next = end;
+    }
+    segments.extend((next..buckets).map(|bucket| (bucket..bucket + 1, rest)));
+
+    let segment_ways: Vec<_> = segments
+        .iter()
+        .map(|(range, cap)| ways(*cap, &caps[range.clone()]))
+        .collect();
+    // `totals[g][t]` is the number of ways to split `t` into the segments `g..`
+    let width = rest as usize + 1;
+    let mut totals = vec![vec![0.0; width]; segments.len() + 1];
+    totals[segments.len()][0] = 1.0;
+    for g in (0..segments.len()).rev() {
+        let (current, later) = totals.split_at_mut(g + 1);
+        for t in 0..width {
+            current[g][t] = segment_ways[g]
+                .iter()
+                .take(t + 1)
+                .enumerate()
+                .map(|(s, &ways)| ways * later[0][t - s])
+                .sum();
+        }
+    }
+    if totals[0][rest as usize] == 0.0 {
+        return Err(infeasible);
+    }
+
+    let mut partitioner = Partitioner::new();
+    let mut parts = Vec::with_capacity(buckets);
+    let mut target = Vec::new();
+    let mut remaining = rest as usize;
+    for (g, (range, _)) in segments.iter().enumerate() {
+        let later = &totals[g + 1];
+        let mut pick = r.gen::<f64>() * totals[g][remaining];
+        // falls back to the last possible sum if rounding errors of the weights leave a rest
+        let mut sum = 0;
+        for (s, &ways) in segment_ways[g].iter().enumerate().take(remaining + 1) {
+            let weight = ways * later[remaining - s];
+            if weight > 0.0 {
+                sum = s;
+                pick -= weight;
+                if pick < 0.0 {
+                    break;
+                }
+            }
+        }
+        partitioner.partition_bounded_into(sum as u32, &caps[range.clone()], &mut target, r)?;
+        parts.extend_from_slice(&target);
+        remaining -= sum;
+    }
+    Ok(parts
+        .into_iter()
+        .zip(&bounds)
+        .map(|(part, b)| b.min + part)
+        .collect())
+}
+
+/// A way to split a total into buckets within their [`Bounds`], the shape of the hours of a month.
+pub trait Distribution {
+    /// Splits `total` into `bounds.len()` parts with the `i`th part within `bounds[i]`.
+    ///
+    /// Fails with [`Error::Infeasible`] if the bounds can't be met.
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>>;
+}
+
+/// Every split is equally likely.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Uniform;
+
+/// The parts are as equal as the bounds allow, the parts that get one more are random.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Even;
+
+/// Each unit goes to one of the buckets with room left with a probability proportional to its
+/// weight, buckets with a weight of zero only get their minimum.
+#[derive(Debug, Clone, Default)]
+pub struct Weighted {
+    /// The weight of every bucket in order.
+    pub weights: Vec<u32>,
+}
+
+/// Like [`Weighted`] with weights falling linearly from the first bucket to the last one.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FrontLoaded;
+
+/// Like [`Weighted`] with weights rising linearly from the first bucket to the last one.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct BackLoaded;
+
+/// What is left of `total` after the minimums of `bounds` and the room of each bucket above its
+/// minimum.
+fn rest_and_room(total: u32, bounds: &[Bounds]) -> Result<(u32, Vec<u32>)> {
+    let infeasible = || Error::Infeasible {
+        hours: total,
+        days: bounds.len() as u32,
+        max_per_day: bounds.iter().map(|b| b.max).max().unwrap_or_default(),
+    };
+    let room = bounds
+        .iter()
+        .map(|b| b.max.checked_sub(b.min))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(infeasible)?;
+    let minimum: u64 = bounds.iter().map(|b| u64::from(b.min)).sum();
+    let rest = u64::from(total)
+        .checked_sub(minimum)
+        .ok_or_else(infeasible)? as u32;
+    if room.iter().map(|&room| u64::from(room)).sum::<u64>() < u64::from(rest) {
+        return Err(infeasible());
+    }
+    Ok((rest, room))
+}
+
+fn add_minimums(parts: Vec<u32>, bounds: &[Bounds]) -> Vec<u32> {
+    parts
+        .into_iter()
+        .zip(bounds)
+        .map(|(part, b)| b.min + part)
+        .collect()
+}
+
+impl Distribution for Uniform {
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>> {
+        let (rest, room) = rest_and_room(total, bounds)?;
+        let max = room.iter().copied().max().unwrap_or_default();
+        let capacity = u64::from(max) * room.len() as u64;
+        let mut parts = Vec::new();
+        let mut partitioner = Partitioner::new();
+        // without minimums and equal maximums the free time is split if it is the smaller part
+        if bounds.iter().all(|b| b.min == 0 && b.max == max) && capacity / 2 <= u64::from(rest) {
+            let free = (capacity - u64::from(rest)) as u32;
+            partitioner.partition_into(free, room.len() as u32, max, &mut parts, r)?;
+            parts.iter_mut().for_each(|part| *part = max - *part);
+        } else {
+            partitioner.partition_bounded_into(rest, &room, &mut parts, r)?;
+        }
+        Ok(add_minimums(parts, bounds))
+    }
+}
+
+impl Distribution for Even {
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>> {
+        let (mut rest, _) = rest_and_room(total, bounds)?;
+        let mut parts: Vec<_> = bounds.iter().map(|b| b.min).collect();
+        // raises the lowest parts with room left one at a time
+        while rest > 0 {
+            let open = (0..parts.len()).filter(|&i| parts[i] < bounds[i].max);
+            let lowest = open.clone().map(|i| parts[i]).min();
+            let lowest = lowest.expect("the rest fits into the room");
+            let mut lowest: Vec<_> = open.filter(|&i| parts[i] == lowest).collect();
+            if (rest as usize) < lowest.len() {
+                for _ in 0..rest {
+                    parts[lowest.swap_remove(index(r, lowest.len()))] += 1;
+                }
+                break;
+            }
+            for i in lowest {
+                parts[i] += 1;
+                rest -= 1;
+            }
+        }
+        Ok(parts)
+    }
+}
+
+/// Gives the `rest` to the buckets one unit at a time with probabilities proportional to the
+/// `weights` of the buckets with room left.
+fn distribute_weighted(
+    total: u32,
+    bounds: &[Bounds],
+    weights: impl Fn(usize) -> u32,
+    r: &mut dyn RngCore,
+) -> Result<Vec<u32>> {
+    let (rest, room) = rest_and_room(total, bounds)?;
+    let weighted: u64 = (0..room.len())
+        .filter(|&i| weights(i) > 0)
+        .map(|i| u64::from(room[i]))
+        .sum();
+    if weighted < u64::from(rest) {
+        return Err(Error::Infeasible {
+            hours: total,
+            days: bounds.len() as u32,
+            max_per_day: bounds.iter().map(|b| b.max).max().unwrap_or_default(),
+        });
+    }
+    let mut parts = vec![0; bounds.len()];
+    for _ in 0..rest {
+        let weight = |i: usize| {
+            if parts[i] < room[i] {
+                u64::from(weights(i))
+            } else {
+                0
+            }
+        };
+        let sum: u64 = (0..parts.len()).map(weight).sum();
+        let mut pick = r.gen_range(0..sum);
+        let bucket = (0..parts.len())
+            .find(|&i| {
+                let found = pick < weight(i);
+                pick = pick.saturating_sub(weight(i));
+                found
+            })
+            .expect("the pick is below the sum of the weights");
+        parts[bucket] += 1;
+    }
+    Ok(add_minimums(parts, bounds))
+}
+
+impl Distribution for Weighted {
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>> {
+        if self.weights.len() != bounds.len() {
+            return Err(Error::InvalidDistribution(
+                "there has to be a weight for every bucket".into(),
+            ));
+        }
+        distribute_weighted(total, bounds, |i| self.weights[i], r)
+    }
+}
+
+impl Distribution for FrontLoaded {
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>> {
+        distribute_weighted(total, bounds, |i| (bounds.len() - i) as u32, r)
+    }
+}
+
+impl Distribution for BackLoaded {
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>> {
+        distribute_weighted(total, bounds, |i| i as u32 + 1, r)
+    }
+}
+
+/// How strongly a [`Shape`] biases the splits, the weight of the most favoured bucket is this many
+/// times the weight of the least favoured one.
+const SHAPE_BIAS: f64 = 2.0;
+
+/// Where the units are biased towards, like the hours of a deadline crunch.
+///
+/// Unlike [`FrontLoaded`] and [`BackLoaded`] the splits are drawn by the [`Partitioner`] with
+/// weights, so every bucket stays as random as with [`Uniform`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shape {
+    /// The first buckets.
+    Front,
+    /// The last buckets.
+    Back,
+    /// The buckets in the middle.
+    Mid,
+}
+
+impl Shape {
+    /// The weights of `buckets` buckets for [`Partitioner::partition_weighted_into`], between one
+    /// and [`SHAPE_BIAS`].
+    pub fn weights(&self, buckets: usize) -> Vec<f64> {
+        (0..buckets)
+            .map(|i| {
+                // the position of the bucket from 0 at the start to 1 at the end
+                let position = if buckets > 1 {
+                    i as f64 / (buckets - 1) as f64
+                } else {
+                    0.5
+                };
+                let favour = match self {
+                    Shape::Front => 1.0 - position,
+                    Shape::Back => position,
+                    Shape::Mid => 1.0 - (2.0 * position - 1.0).abs(),
+                };
+                SHAPE_BIAS.powf(favour)
+            })
+            .collect()
+    }
+}
+
+impl Distribution for Shape {
+    fn distribute(&self, total: u32, bounds: &[Bounds], r: &mut dyn RngCore) -> Result<Vec<u32>> {
+        let (rest, room) = rest_and_room(total, bounds)?;
+        let mut parts = Vec::new();
+        Partitioner::new().partition_weighted_into(
+            rest,
+            &room,
+            &self.weights(bounds.len()),
+            &mut parts,
+            r,
+        )?;
+        Ok(add_minimums(parts, bounds))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::distribute::{
+        distribute, BackLoaded, Bounds, Constraints, Distribution, Even, FrontLoaded, Group,
+        Partitioner, Shape, Uniform, Weighted,
+    };
+    use crate::error::Error;
+    use crate::rng::seeded;
+
+    /// All splits of `n` into parts where the `i`th part is at most `caps[i]`.
+    fn bounded_compositions(n: u32, caps: &[u32]) -> Vec<Vec<u32>> {
+        let Some((&cap, caps)) = caps.split_first() else {
+            return if n == 0 { vec![vec![]] } else { vec![] };
+        };
+        (0..=n.min(cap))
+            .flat_map(|first| {
+                bounded_compositions(n - first, caps)
+                    .into_iter()
+                    .map(move |mut rest| {
+                        rest.insert(0, first);
+                        rest
+                    })
+            })
+            .collect()
+    }
+
+    /// All splits of `n` into `k` parts that are each at most `max`.
+    fn compositions(n: u32, k: u32, max: u32) -> Vec<Vec<u32>> {
+        bounded_compositions(n, &vec![max; k as usize])
+    }
+
+    /// Upper quantile of the chi-squared distribution for `p = 0.001` (Wilson–Hilferty).
+    fn chi_squared_critical(degrees_of_freedom: f64) -> f64 {
+        const Z: f64 = 3.0902;
+        let a = 2.0 / (9.0 * degrees_of_freedom);
+        degrees_of_freedom * (1.0 - a + Z * a.sqrt()).powi(3)
+    }
+
+    /// Asserts that `sample` draws each of `expected` equally likely.
+    fn assert_uniform(name: &str, expected: &[Vec<u32>], mut sample: impl FnMut() -> Vec<u32>) {
+        const SAMPLES_PER_COMPOSITION: usize = 2000;
+        let samples = expected.len() * SAMPLES_PER_COMPOSITION;
+        let mut counts: HashMap<Vec<u32>, usize> = HashMap::new();
+        for _ in 0..samples {
+            *counts.entry(sample()).or_default() += 1;
+        }
+        assert!(counts
+            .keys()
+            .all(|composition| expected.contains(composition)));
+
+        let mean = SAMPLES_PER_COMPOSITION as f64;
+        let chi_squared: f64 = expected
+            .iter()
+            .map(|composition| {
+                let observed = counts.get(composition).copied().unwrap_or_default() as f64;
+                (observed - mean).powi(2) / mean
+            })
+            .sum();
+        let critical = chi_squared_critical((expected.len() - 1) as f64);
+        assert!(
+            chi_squared < critical,
+            "{} isn't uniform: chi squared {} >= {}",
+            name,
+            chi_squared,
+            critical
+        );
+    }
+
+    #[test]
+    fn test_partition_is_uniform() {
+        let mut partitioner = Partitioner::new();
+        let mut target = Vec::new();
+        for (seed, (n, k, max)) in [(4, 3, 2), (6, 4, 3), (3, 5, 1), (8, 3, 8), (10, 4, 4)]
+            .into_iter()
+            .enumerate()
+        {
+            let mut rng = seeded(seed as u64);
+            assert_uniform(
+                &format!("partition({}, {}, {})", n, k, max),
+                &compositions(n, k, max),
+                || {
+                    partitioner
+                        .partition_into(n, k, max, &mut target, &mut rng)
+                        .unwrap();
+                    target.clone()
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_partition_bounded_is_uniform() {
+        let mut partitioner = Partitioner::new();
+        let mut target = Vec::new();
+        let cases: [(u32, &[u32]); 4] = [
+            (3, &[1, 3, 2]),
+            (5, &[2, 0, 3, 1]),
+            (4, &[4, 2]),
+            (6, &[3, 1, 3, 2, 1]),
+        ];
+        for (seed, (n, caps)) in cases.into_iter().enumerate() {
+            let mut rng = seeded(seed as u64);
+            assert_uniform(
+                &format!("partition_bounded({}, {:?})", n, caps),
+                &bounded_compositions(n, caps),
+                || {
+                    partitioner
+                        .partition_bounded_into(n, caps, &mut target, &mut rng)
+                        .unwrap();
+                    target.clone()
+                },
+            );
+        }
+        assert!(partitioner
+            .partition_bounded_into(7, &[3, 3], &mut target, &mut seeded(0))
+            .is_err());
+    }
+
+    /// All splits of `total` with `bounds` whose `groups` stay below their caps.
+    fn constrained_compositions(total: u32, bounds: &[Bounds], groups: &[Group]) -> Vec<Vec<u32>> {
+        let caps: Vec<_> = bounds.iter().map(|b| b.max).collect();
+        bounded_compositions(total, &caps)
+            .into_iter()
+            .filter(|split| split.iter().zip(bounds).all(|(&part, b)| part >= b.min))
+            .filter(|split| {
+                groups
+                    .iter()
+                    .all(|group| split[group.buckets.clone()].iter().sum::<u32>() <= group.cap)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_distribute_is_uniform() {
+        let bounds = |bounds: &[(u32, u32)]| {
+            bounds
+                .iter()
+                .map(|&(min, max)| Bounds { min, max })
+                .collect::<Vec<_>>()
+        };
+        let cases = [
+            (
+                5,
+                bounds(&[(0, 3), (1, 2), (0, 3), (0, 1)]),
+                vec![Group {
+                    buckets: 0..2,
+                    cap: 3,
+                }],
+            ),
+            (
+                6,
+                bounds(&[(0, 2), (0, 2), (0, 2), (1, 2), (0, 2)]),
+                vec![
+                    Group {
+                        buckets: 0..2,
+                        cap: 2,
+                    },
+                    Group {
+                        buckets: 3..5,
+                        cap: 3,
+                    },
+                ],
+            ),
+            (4, bounds(&[(0, 4), (0, 4), (0, 4)]), vec![]),
+        ];
+        for (seed, (total, bounds, groups)) in cases.into_iter().enumerate() {
+            let constraints = Constraints {
+                bounds: bounds.clone(),
+                groups: groups.clone(),
+            };
+            let mut rng = seeded(seed as u64);
+            assert_uniform(
+                &format!("distribute({}, {:?})", total, constraints),
+                &constrained_compositions(total, &bounds, &groups),
+                || distribute(total, bounds.len(), &constraints, &mut rng).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_distribute() {
+        let mut rng = seeded(0);
+        // a month of 20 days with at most 8 hours a day and 30 hours a week
+        let constraints = Constraints {
+            bounds: vec![Bounds { min: 0, max: 8 }; 20],
+            groups: (0..4)
+                .map(|week| Group {
+                    buckets: 5 * week..5 * week + 5,
+                    cap: 30,
+                })
+                .collect(),
+        };
+        for _ in 0..100 {
+            let parts = distribute(100, 20, &constraints, &mut rng).unwrap();
+            assert_eq!(parts.iter().sum::<u32>(), 100);
+            assert!(parts.chunks(5).all(|week| week.iter().sum::<u32>() <= 30));
+        }
+        assert_eq!(
+            distribute(0, 0, &Constraints::default(), &mut rng),
+            Ok(vec![])
+        );
+        assert_eq!(
+            distribute(121, 20, &constraints, &mut rng),
+            Err(Error::Infeasible {
+                hours: 121,
+                days: 20,
+                max_per_day: 8
+            })
+        );
+
+        let overlapping = Constraints {
+            groups: vec![
+                Group {
+                    buckets: 0..3,
+                    cap: 5,
+                },
+                Group {
+                    buckets: 2..4,
+                    cap: 5,
+                },
+            ],
+            ..Constraints::default()
+        };
+        assert!(matches!(
+            distribute(5, 4, &overlapping, &mut rng),
+            Err(Error::InvalidDistribution(_))
+        ));
+        let unbounded = Constraints {
+            bounds: vec![Bounds { min: 0, max: 1 }],
+            ..Constraints::default()
+        };
+        assert!(matches!(
+            distribute(1, 2, &unbounded, &mut rng),
+            Err(Error::InvalidDistribution(_))
+        ));
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = seeded(0);
+        let bounds = [
+            Bounds { min: 0, max: 8 },
+            Bounds { min: 2, max: 4 },
+            Bounds { min: 0, max: 8 },
+            Bounds { min: 1, max: 8 },
+        ];
+        let distributions: [&dyn Distribution; 5] = [
+            &Uniform,
+            &Even,
+            &Weighted {
+                weights: vec![1, 2, 0, 3],
+            },
+            &FrontLoaded,
+            &BackLoaded,
+        ];
+        for distribution in distributions {
+            for total in 3..=20 {
+                let parts = distribution.distribute(total, &bounds, &mut rng).unwrap();
+                assert_eq!(parts.iter().sum::<u32>(), total);
+                assert!(parts
+                    .iter()
+                    .zip(&bounds)
+                    .all(|(&part, b)| b.min <= part && part <= b.max));
+            }
+            assert!(matches!(
+                distribution.distribute(2, &bounds, &mut rng),
+                Err(Error::Infeasible { .. })
+            ));
+        }
+
+        assert_eq!(Even.distribute(12, &bounds, &mut rng), Ok(vec![3, 3, 3, 3]));
+        assert_eq!(Even.distribute(22, &bounds, &mut rng), Ok(vec![6, 4, 6, 6]));
+        let weighted = Weighted {
+            weights: vec![1, 2, 0, 3],
+        };
+        assert_eq!(
+            weighted.distribute(20, &bounds, &mut rng),
+            Ok(vec![8, 4, 0, 8])
+        );
+        assert!(weighted.distribute(21, &bounds, &mut rng).is_err());
+
+        let days = [Bounds { min: 0, max: 8 }; 20];
+        let loaded: [(&dyn Distribution, bool); 2] = [(&FrontLoaded, true), (&BackLoaded, false)];
+        for (distribution, front) in loaded {
+            let parts = distribution.distribute(80, &days, &mut rng).unwrap();
+            let first: u32 = parts[..10].iter().sum();
+            assert_eq!(first > 40, front, "{} of 80 hours in the first half", first);
+        }
+    }
+
+    #[test]
+    fn test_partition_weighted_follows_weights() {
+        let mut partitioner = Partitioner::new();
+        let mut target = Vec::new();
+        let mut rng = seeded(0);
+        let (n, caps, weights) = (5, [3, 2, 3], [2.0f64, 1.0, 0.5]);
+        let expected = bounded_compositions(n, &caps);
+        let weight = |split: &Vec<u32>| -> f64 {
+            split
+                .iter()
+                .zip(weights)
+                .map(|(&part, weight)| weight.powi(part as i32))
+                .product()
+        };
+        let total: f64 = expected.iter().map(weight).sum();
+        const SAMPLES: usize = 20000;
+        let mut counts: HashMap<Vec<u32>, usize> = HashMap::new();
+        for _ in 0..SAMPLES {
+            partitioner
+                .partition_weighted_into(n, &caps, &weights, &mut target, &mut rng)
+                .unwrap();
+            *counts.entry(target.clone()).or_default() += 1;
+        }
+        let chi_squared: f64 = expected
+            .iter()
+            .map(|split| {
+                let mean = SAMPLES as f64 * weight(split) / total;
+                let observed = counts.get(split).copied().unwrap_or_default() as f64;
+                (observed - mean).powi(2) / mean
+            })
+            .sum();
+        let critical = chi_squared_critical((expected.len() - 1) as f64);
+        assert!(
+            chi_squared < critical,
+            "chi squared {} >= {}",
+            chi_squared,
+            critical
+        );
+
+        let mut rng = seeded(1);
+        assert_uniform(
+            "partition_weighted(4, [2, 3, 2])",
+            &bounded_compositions(4, &[2, 3, 2]),
+            || {
+                partitioner
+                    .partition_weighted_into(4, &[2, 3, 2], &[1.0; 3], &mut target, &mut rng)
+                    .unwrap();
+                target.clone()
+            },
+        );
+        assert!(matches!(
+            partitioner.partition_weighted_into(1, &[1], &[0.0], &mut target, &mut rng),
+            Err(Error::InvalidDistribution(_))
+        ));
+    }
+
+    #[test]
+    fn test_shapes() {
+        let mut rng = seeded(0);
+        let days = [Bounds { min: 0, max: 8 }; 21];
+        for (shape, favoured) in [
+            (Shape::Front, 0..7),
+            (Shape::Back, 14..21),
+            (Shape::Mid, 7..14),
+        ] {
+            let mut thirds = [0; 3];
+            for _ in 0..100 {
+                let parts = shape.distribute(80, &days, &mut rng).unwrap();
+                assert_eq!(parts.iter().sum::<u32>(), 80);
+                for (third, parts) in thirds.iter_mut().zip(parts.chunks(7)) {
+                    *third += parts.iter().sum::<u32>();
+                }
+            }
+            let most = thirds.iter().max().unwrap();
+            assert_eq!(
+                thirds[favoured.start / 7],
+                *most,
+                "{:?}: {:?}",
+                shape,
+                thirds
+            );
+        }
+        assert_eq!(Shape::Mid.weights(3), vec![1.0, 2.0, 1.0]);
+        assert_eq!(Shape::Front.weights(1), vec![2.0f64.sqrt()]);
+    }
+}
@@ -0,0 +1,353 @@
+//! The error type shared by all modules of this crate.
+
+use std::num::NonZeroU32;
+
+use serde::Serialize;
+
+use crate::calendar::{City, DateOfYear, State};
+
+/// Everything that can go wrong when computing calendars or generating times.
+///
+/// Serializes to `{ "code": "infeasible", "limits": { ... } }` where `code` is the snake case name
+/// of the variant and `limits` holds its fields.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error, Serialize)]
+#[serde(tag = "code", content = "limits", rename_all = "snake_case")]
+pub enum Error {
+    /// A number that isn't in `0..=6` was converted into a day of the week.
+    #[error("`{0}` isn't a day of the week")]
+    InvalidDayOfWeek(u32),
+    /// A string that isn't the abbreviation of a federal state.
+    #[error("`{0}` isn't a state")]
+    UnknownState(String),
+    /// A string that isn't the name of a city with holidays of its own.
+    #[error("`{0}` isn't a city with holidays of its own")]
+    UnknownCity(String),
+    /// A city was combined with a state it isn't in.
+    #[error("{city} isn't in {state}")]
+    CityOutsideState {
+        /// The city.
+        city: City,
+        /// The state it was combined with.
+        state: State,
+    },
+    /// A month that isn't in `1..=12`.
+    #[error("`{0}` isn't a month")]
+    InvalidMonth(u32),
+    /// A date that doesn't exist in the year.
+    #[error("{}.{}. isn't a valid date", .0.day, .0.month)]
+    InvalidDate(DateOfYear),
+    /// Moving a date by a number of days would leave its year.
+    #[error("Moving {}.{}. by {days} days leaves the year", date.day, date.month)]
+    DateOutOfYear {
+        /// The date that was moved.
+        date: DateOfYear,
+        /// The number of days it was moved by.
+        days: i32,
+    },
+    /// A rendered timesheet couldn't be read back.
+    #[error("Line {line}: {message}")]
+    Parse {
+        /// The line of the input, starting at 1.
+        line: usize,
+        /// What is wrong with the line.
+        message: String,
+    },
+    /// The earliest starting hour is after the latest stopping hour.
+    #[error("Earliest has to be before latest")]
+    InvalidWindow {
+        /// The earliest starting hour.
+        from: u32,
+        /// The latest stopping hour.
+        to: u32,
+    },
+    /// An hour of the day or a number of hours per day above 24.
+    #[error("{setting} has to be at most 24, not {value}")]
+    AboveDay {
+        /// What the number is, like `The latest hour`.
+        setting: String,
+        /// The number.
+        value: u32,
+    },
+    /// The maximum hours per day don't fit between the earliest and the latest hour.
+    #[error("Can't fit {max_per_day} hours per day between {from}:00 and {to}:00")]
+    WindowTooSmall {
+        /// The earliest starting hour.
+        from: u32,
+        /// The latest stopping hour.
+        to: u32,
+        /// The maximum number of hours per day.
+        max_per_day: u32,
+    },
+    /// An output that can't be written in another encoding than UTF-8, like JSON.
+    #[error("{0} is always UTF-8")]
+    AlwaysUtf8(String),
+    /// A layout that can't be written in a format.
+    #[error("The {layout} layout isn't available as {format}")]
+    UnsupportedLayout {
+        /// The layout.
+        layout: String,
+        /// The format.
+        format: String,
+    },
+    /// The calendar layout was requested for the timesheets of several jobs.
+    #[error("The calendar layout shows a single timesheet, not one per job")]
+    CalendarOfJobs,
+    /// Hours with minutes below a full hour were requested for a contract period.
+    #[error("Hours with minutes can't be pro-rated by the contract period")]
+    ProRatedMinutes,
+    /// The minutes below a full hour of the hours aren't a multiple of the rounding step.
+    #[error(
+        "The {minutes} minutes above the full hours can't be rounded to steps of {step} minutes"
+    )]
+    MinutesOffStep {
+        /// The minutes below a full hour.
+        minutes: u32,
+        /// The minutes the times are rounded to.
+        step: u32,
+    },
+    /// A break isn't a multiple of the rounding step, so the net times would be off it.
+    #[error("A break of {deduct} minutes can't be deducted from times rounded to {step} minutes")]
+    BreakOffStep {
+        /// The minutes of the break.
+        deduct: u32,
+        /// The minutes the times are rounded to.
+        step: u32,
+    },
+    /// A contract whose last day is before its first day.
+    #[error("The contract ends before it starts")]
+    EndBeforeStart,
+    /// Weights of the days that are neither one per working day nor one per day of the week.
+    #[error("Five or seven weights are needed, one per day from Monday on, not {count}")]
+    WeightsOfWeek {
+        /// The number of weights.
+        count: u32,
+    },
+    /// A `[realism.NAME]` profile that isn't in the configuration.
+    #[error("No `[realism.{0}]` profile in the configuration")]
+    UnknownProfile(String),
+    /// An option or a command needs a setting that isn't in the configuration.
+    #[error("{needed_by} requires {setting} in the configuration")]
+    MissingSetting {
+        /// The option or command, like `` `--jobs` ``.
+        needed_by: String,
+        /// The missing setting, like `` `[[jobs]]` ``.
+        setting: String,
+    },
+    /// The seeded random number generator was chosen without a seed.
+    #[error("The seeded generator requires a seed")]
+    MissingSeed,
+    /// A seed was given for another random number generator than the seeded one.
+    #[error("A seed can only be used with the seeded generator")]
+    SeedWithoutSeeded,
+    /// Something this build was compiled without.
+    #[error("{needed_by} requires the `{feature}` feature")]
+    MissingFeature {
+        /// What needs the feature.
+        needed_by: String,
+        /// The Cargo feature.
+        feature: String,
+    },
+    /// An option that only applies to another choice, like `--authorize` of another service than
+    /// Google Calendar.
+    #[error("{option} is only needed for {applies_to}")]
+    NeedlessOption {
+        /// The option.
+        option: String,
+        /// The choice it applies to.
+        applies_to: String,
+    },
+    /// Leave recorded from one year into the next.
+    #[error("Leave has to be recorded per year, not from {from} until {until}")]
+    LeaveAcrossYears {
+        /// The year of the first day.
+        from: u32,
+        /// The year of the last day.
+        until: u32,
+    },
+    /// A European time zone in a year before its rules are known, see
+    /// [`crate::timezone::FIRST_YEAR`].
+    #[error(
+        "The changes to summer time of `{zone}` are only known from {} on, give the offset in {year} like `+01:00` instead",
+        crate::timezone::FIRST_YEAR
+    )]
+    ZoneBeforeRules {
+        /// The name of the zone.
+        zone: String,
+        /// The year of the timesheet.
+        year: u32,
+    },
+    /// A constraint of a date outside of the month that is generated.
+    #[error("The constraint `{constraint}` isn't in {month}/{year}")]
+    ConstraintOutsideMonth {
+        /// The row of the constraint.
+        constraint: String,
+        /// The year that is generated.
+        year: u32,
+        /// The month that is generated, starting at 1.
+        month: NonZeroU32,
+    },
+    /// A string that isn't a time span like `12:00-13:30`, optionally prefixed with a weekday.
+    #[error("`{0}` isn't a blackout like `12:00-13:30` or `tue=10:00-12:00`")]
+    InvalidBlackout(String),
+    /// A string that isn't a window of hours of a weekday like `fri=8-14`.
+    #[error("`{0}` isn't a window of a weekday like `fri=8-14`")]
+    InvalidWeekdayWindow(String),
+    /// A string that isn't a note of a day like `12.03=Server migration`.
+    #[error("`{0}` isn't a note like `12.03=Server migration`")]
+    InvalidNote(String),
+    /// A string that isn't a [`crate::digest::SheetDigest`].
+    #[error("`{0}` isn't a digest like `stundenzettel:2024-03:42:` followed by 64 hex digits")]
+    InvalidDigest(String),
+    /// A timesheet isn't the one a [`crate::digest::SheetDigest`] was taken of.
+    #[error("The digest doesn't match the timesheet of {month}/{year}")]
+    DigestMismatch {
+        /// The year of the month.
+        year: u32,
+        /// The month.
+        month: NonZeroU32,
+    },
+    /// A string that isn't a [`crate::signing::SigningKey`] or [`crate::signing::PublicKey`].
+    #[error("`{0}` isn't an Ed25519 key of 32 bytes in base64")]
+    InvalidKey(String),
+    /// A string that isn't a [`crate::signing::Signature`].
+    #[error("`{0}` isn't an Ed25519 signature of 64 bytes in base64")]
+    InvalidSignature(String),
+    /// A timesheet without a signature in its header was verified.
+    #[error("The timesheet isn't signed")]
+    Unsigned,
+    /// A timesheet was altered since it was signed or signed with another key.
+    #[error("The signature doesn't match the timesheet of {month}/{year}")]
+    SignatureMismatch {
+        /// The year of the month.
+        year: u32,
+        /// The month.
+        month: NonZeroU32,
+    },
+    /// A string that isn't a quarter like `Q1-2024` or a semester like `WS24` or `SS25`.
+    #[error("`{0}` isn't a quarter like `Q1-2024` or a semester like `WS24` or `SS25`")]
+    InvalidTerm(String),
+    /// The contract doesn't cover a single working day of a month.
+    #[error("The contract doesn't cover any working day of {month}/{year}")]
+    OutsideContract {
+        /// The year of the month.
+        year: u32,
+        /// The month.
+        month: NonZeroU32,
+    },
+    /// There are more hours than the days can hold.
+    #[error(
+        "Can't distribute {hours} hours into {days} days with at most {max_per_day} hours per day"
+    )]
+    Infeasible {
+        /// The number of hours to distribute.
+        hours: u32,
+        /// The number of days.
+        days: u32,
+        /// The maximum number of hours per day.
+        max_per_day: u32,
+    },
+    /// The months of a term can't hold the hours banked onto them.
+    #[error("Can't bank {hours} hours into months holding at most {capacity} hours together")]
+    BankOverCapacity {
+        /// The hours of the contract in the whole term.
+        hours: u32,
+        /// The most hours all months can hold together.
+        capacity: u32,
+    },
+    /// The constraints of [`crate::distribute::distribute`] don't describe the buckets.
+    #[error("Invalid distribution: {0}")]
+    InvalidDistribution(String),
+    /// No timesheet of many attempts passed the audit.
+    #[error(
+        "No timesheet of {attempts} attempts scored at most {max_score}, the best scored {score}"
+    )]
+    Implausible {
+        /// The best score of all attempts.
+        score: u32,
+        /// The highest score that passes.
+        max_score: u32,
+        /// The number of generated timesheets.
+        attempts: u32,
+    },
+    /// The hours would earn more than a cap like the Minijob limit, see
+    /// [`crate::policy::EarningsCap`].
+    #[error(
+        "{}:{:02} hours at {}.{:02} € per hour earn more than the cap of {}.{:02} €, at most \
+         {max_hours} hours fit",
+        .minutes / 60, .minutes % 60, .rate / 100, .rate % 100, .cap / 100, .cap % 100
+    )]
+    EarningsCapExceeded {
+        /// The requested time in minutes.
+        minutes: u32,
+        /// The hourly rate in cents.
+        rate: u32,
+        /// The most earned a month in cents.
+        cap: u32,
+        /// The most full hours that stay below the cap.
+        max_hours: u32,
+    },
+    /// None of the days can take the minutes below a full hour.
+    #[error("No day can take the remaining {minutes} minutes")]
+    RemainderDoesNotFit {
+        /// The minutes below a full hour, a multiple of 15.
+        minutes: u32,
+    },
+    /// A day can't be lengthened by the break that falls due on it.
+    #[error("The break of {minutes} minutes due on the {day}. doesn't fit into the window")]
+    BreakDoesNotFit {
+        /// The day of the entry.
+        day: NonZeroU32,
+        /// The minutes of the break.
+        minutes: u32,
+    },
+    /// The hours of a week can't be moved below a [`crate::timesheet::WeeklyCap`].
+    #[error(
+        "Week {week} holds {}:{:02} hours, more than the cap of {}:{:02} hours allows",
+        .minutes / 60, .minutes % 60, .cap / 60, .cap % 60
+    )]
+    WeeklyCapExceeded {
+        /// The week within the month, starting at 1.
+        week: u32,
+        /// The minutes worked in the week, including those of the neighboring months.
+        minutes: u32,
+        /// The most minutes worked a week.
+        cap: u32,
+    },
+    /// No day has an hour to spare for a too long run of working days without work.
+    #[error(
+        "No day can spare an hour for {days} working days in a row without work, at most {max} \
+         are allowed"
+    )]
+    FreeStreakTooLong {
+        /// The working days in a row without work.
+        days: u32,
+        /// The most working days in a row without work.
+        max: u32,
+    },
+}
+
+/// A [`Result`](std::result::Result) with [`Error`] as the error type.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::error::Error;
+
+    #[test]
+    fn test_serialize() {
+        let error = Error::Infeasible {
+            hours: 200,
+            days: 20,
+            max_per_day: 8,
+        };
+        assert_eq!(
+            serde_json::to_value(error).unwrap(),
+            json!({
+                "code": "infeasible",
+                "limits": { "hours": 200, "days": 20, "max_per_day": 8 }
+            })
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! C bindings, see `include/stundenzettel.h`.
+//!
+//! All functions write into buffers owned by the caller, a month never has more than 31 entries
+//! and a year never has more than 16 holidays. A panic doesn't unwind into C but returns
+//! [`SzStatus::SzPanic`].
+//!
+//! The build generates the header from this file alone, `cargo test --features ffi` fails if the
+//! committed one differs. Copy it from `target/*/build/stundenzettel-*/out/stundenzettel.h` after
+//! changing the bindings.
+
+use std::ffi::{c_char, CStr};
+use std::num::NonZeroU32;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use crate::calendar::{Month, State, Year};
+use crate::error::Error;
+use crate::rng::seeded;
+use crate::timesheet::{generate_timesheet, SheetParameters};
+
+/// The result of a call.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SzStatus {
+    /// The call succeeded.
+    SzOk = 0,
+    /// A pointer was null, a string wasn't valid or a number was out of range.
+    SzInvalidArgument = 1,
+    /// The hours can't be distributed with the given constraints.
+    SzInfeasible = 2,
+    /// The buffer can't hold all results.
+    SzBufferTooSmall = 3,
+    /// The call failed unexpectedly, this is a bug.
+    SzPanic = 4,
+}
+
+impl From<Error> for SzStatus {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Infeasible { .. } | Error::WindowTooSmall { .. } => SzStatus::SzInfeasible,
+            _ => SzStatus::SzInvalidArgument,
+        }
+    }
+}
+
+/// A day worked, see [`crate::timesheet::Entry`].
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SzEntry {
+    /// The day of the month, starting at 1.
+    pub day: u32,
+    /// The starting minute after midnight.
+    pub from: u32,
+    /// The stopping minute after midnight.
+    pub to: u32,
+}
+
+/// A public holiday, see [`crate::calendar::Holiday`].
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SzHoliday {
+    /// The day of the month, starting at 1.
+    pub day: u32,
+    /// The month, starting at 1.
+    pub month: u32,
+}
+
+/// Runs the body of an exported function and turns a panic into [`SzStatus::SzPanic`], unwinding
+/// into C is undefined behavior.
+fn guard(body: impl FnOnce() -> SzStatus) -> SzStatus {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(SzStatus::SzPanic)
+}
+
+/// # Safety
+/// `state` has to be null or a valid nul terminated string.
+unsafe fn parse_state(state: *const c_char) -> Result<State, SzStatus> {
+    if state.is_null() {
+        return Err(SzStatus::SzInvalidArgument);
+    }
+    let state = CStr::from_ptr(state)
+        .to_str()
+        .map_err(|_| SzStatus::SzInvalidArgument)?;
+    state.parse().map_err(SzStatus::from)
+}
+
+/// # Safety
+/// `out` has to be null or valid for `capacity` writes and `len` has to be null or valid.
+unsafe fn write_out<T: Copy>(
+    values: &[T],
+    out: *mut T,
+    capacity: usize,
+    len: *mut usize,
+) -> SzStatus {
+    if len.is_null() || (out.is_null() && capacity != 0) {
+        return SzStatus::SzInvalidArgument;
+    }
+    *len = values.len();
+    if values.len() > capacity {
+        return SzStatus::SzBufferTooSmall;
+    }
+    if !values.is_empty() {
+        slice::from_raw_parts_mut(out, values.len()).copy_from_slice(values);
+    }
+    SzStatus::SzOk
+}
+
+/// Generates the timesheet of `month` in `year` for the state with the abbreviation `state`.
+///
+/// On success the days worked are written into `entries` and their number into `len`. If the
+/// buffer is too small `len` receives the required capacity. An `earliest`, `latest` or
+/// `max_per_day` above 24 is an invalid argument.
+///
+/// # Safety
+/// `state` has to be a valid nul terminated string, `entries` has to be valid for `capacity`
+/// writes and `len` has to be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn sz_generate(
+    month: u32,
+    year: u32,
+    hours: u32,
+    max_per_day: u32,
+    earliest: u32,
+    latest: u32,
+    state: *const c_char,
+    seed: u64,
+    entries: *mut SzEntry,
+    capacity: usize,
+    len: *mut usize,
+) -> SzStatus {
+    guard(|| {
+        let state = match parse_state(state) {
+            Ok(state) => state,
+            Err(status) => return status,
+        };
+        let parameters = SheetParameters {
+            state,
+            city: None,
+            hours,
+            from: earliest,
+            to: latest,
+            max_per_day,
+        };
+        let year = Year::new(year);
+        let month = parameters
+            .check()
+            .and_then(|()| NonZeroU32::new(month).ok_or(Error::InvalidMonth(month)))
+            .and_then(|month| Month::new(month, &year));
+        let sheet = month
+            .and_then(|month| generate_timesheet(&year, &month, parameters, &mut seeded(seed)));
+        match sheet {
+            Ok(sheet) => {
+                let values: Vec<SzEntry> = sheet
+                    .entries
+                    .iter()
+                    .map(|entry| SzEntry {
+                        day: entry.day.get(),
+                        from: entry.time.from,
+                        to: entry.time.to,
+                    })
+                    .collect();
+                write_out(&values, entries, capacity, len)
+            }
+            Err(e) => e.into(),
+        }
+    })
+}
+
+/// Writes the public holidays of `year` in the state with the abbreviation `state` into
+/// `holidays` and their number into `len`.
+///
+/// # Safety
+/// `state` has to be a valid nul terminated string, `holidays` has to be valid for `capacity`
+/// writes and `len` has to be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn sz_holidays(
+    year: u32,
+    state: *const c_char,
+    holidays: *mut SzHoliday,
+    capacity: usize,
+    len: *mut usize,
+) -> SzStatus {
+    guard(|| {
+        let state = match parse_state(state) {
+            Ok(state) => state,
+            Err(status) => return status,
+        };
+        let values: Vec<SzHoliday> = Year::new(year)
+            .holidays(state)
+            .iter()
+            .map(|holiday| SzHoliday {
+                day: holiday.date.day.get(),
+                month: holiday.date.month.get(),
+            })
+            .collect();
+        write_out(&values, holidays, capacity, len)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ffi::{guard, sz_generate, sz_holidays, SzEntry, SzHoliday, SzStatus};
+
+    #[test]
+    fn test_ffi() {
+        let mut entries = [SzEntry {
+            day: 0,
+            from: 0,
+            to: 0,
+        }; 31];
+        let mut len = 0;
+        let status = unsafe {
+            sz_generate(
+                2,
+                2024,
+                40,
+                8,
+                8,
+                20,
+                c"BY".as_ptr(),
+                1,
+                entries.as_mut_ptr(),
+                entries.len(),
+                &mut len,
+            )
+        };
+        assert_eq!(status, SzStatus::SzOk);
+        let minutes: u32 = entries[..len].iter().map(|e| e.to - e.from).sum();
+        assert_eq!(minutes, 40 * 60);
+
+        let status = unsafe {
+            sz_generate(
+                2,
+                2024,
+                40,
+                8,
+                8,
+                30,
+                c"BY".as_ptr(),
+                1,
+                entries.as_mut_ptr(),
+                entries.len(),
+                &mut len,
+            )
+        };
+        assert_eq!(status, SzStatus::SzInvalidArgument);
+
+        let mut holidays = [SzHoliday { day: 0, month: 0 }; 2];
+        let status = unsafe {
+            sz_holidays(
+                2024,
+                c"BY".as_ptr(),
+                holidays.as_mut_ptr(),
+                holidays.len(),
+                &mut len,
+            )
+        };
+        assert_eq!(status, SzStatus::SzBufferTooSmall);
+        assert_eq!(len, 13);
+    }
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(guard(|| SzStatus::SzOk), SzStatus::SzOk);
+        assert_eq!(guard(|| panic!("a bug")), SzStatus::SzPanic);
+    }
+
+    #[test]
+    fn test_header() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/stundenzettel.h"));
+        let committed = include_str!("../include/stundenzettel.h");
+        assert!(
+            generated == committed,
+            "include/stundenzettel.h is stale, copy the one generated into OUT_DIR"
+        );
+    }
+}
@@ -1,120 +1,657 @@
+//! Random generation of working times.
+
+use std::fmt;
+use std::num::NonZeroU32;
+
 use rand::distributions::uniform::{UniformInt, UniformSampler};
-use rand::Rng;
-
-pub fn partition_inner<R: Rng>(
-    n: u32,
-    k: u32,
-    max: u32,
-    target: &mut Vec<u32>,
-    offsets: &mut Vec<u32>,
-    r: &mut R,
-) -> bool {
-    let dist = UniformInt::<u32>::new(0, n);
-    offsets.clear();
-    offsets.resize_with(k as usize - 1, || dist.sample(r));
-    offsets.sort_unstable();
-
-    target.clear();
-    target.reserve(k as usize);
-    let mut last = 0;
-    for &mut i in offsets {
-        let p = i - last;
-
-        if p > max {
-            return false;
+use rand::{Rng, RngCore};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{DateOfYear, DayOfWeek, HolidaySource, Month, Year};
+pub use crate::distribute::{partition, Bounds, Partitioner};
+use crate::distribute::{Distribution, Uniform};
+use crate::error::{Error, Result};
+use crate::trace::trace;
+
+/// The minute the statutory night starts at, 23:00.
+pub const NIGHT_START: u32 = 23 * 60;
+
+/// The minute the statutory night ends at on the next day, 6:00.
+pub const NIGHT_END: u32 = 6 * 60;
+
+/// A span of working time within a day in minutes since midnight.
+///
+/// Serialized with `HH:MM` times.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TimeSpan {
+    /// The starting minute.
+    #[serde(with = "clock")]
+    #[schemars(schema_with = "clock::schema")]
+    pub from: u32,
+    /// The stopping minute.
+    #[serde(with = "clock")]
+    #[schemars(schema_with = "clock::schema")]
+    pub to: u32,
+}
+
+impl TimeSpan {
+    /// The span from the full hour `from` until the full hour `to`.
+    pub fn hours(from: u32, to: u32) -> Self {
+        TimeSpan {
+            from: from * 60,
+            to: to * 60,
         }
-        target.push(p);
-        last = i;
     }
 
-    let p = n - last;
-    if p > max {
-        return false;
+    /// The length of the span in minutes.
+    pub fn duration(&self) -> u32 {
+        self.to - self.from
+    }
+
+    /// Whether the spans share any minute, spans that only touch don't overlap.
+    pub fn overlaps(&self, other: &TimeSpan) -> bool {
+        self.from < other.to && other.from < self.to
+    }
+
+    /// The minutes of the span within the night from [`NIGHT_START`] until [`NIGHT_END`], which
+    /// are paid a surcharge.
+    pub fn night_minutes(&self) -> u32 {
+        let within = |from: u32, to: u32| self.to.min(to).saturating_sub(self.from.max(from));
+        within(0, NIGHT_END) + within(NIGHT_START, 24 * 60)
+    }
+
+    /// The span moved by `minutes`, `None` if it would leave the day.
+    pub fn shift(&self, minutes: i32) -> Option<TimeSpan> {
+        let from = self.from.checked_add_signed(minutes)?;
+        let to = self.to.checked_add_signed(minutes)?;
+        (to <= 24 * 60).then_some(TimeSpan { from, to })
+    }
+
+    /// The full hours within the span as the first starting and the last stopping hour, the
+    /// stopping hour is below the starting one if there are none.
+    fn full_hours(&self) -> (u32, u32) {
+        (self.from.div_ceil(60), self.to / 60)
     }
-    target.push(p);
-    true
 }
 
-pub fn partition<R: Rng>(n: u32, k: u32, max: u32, r: &mut R) -> Vec<u32> {
-    let mut result = Vec::new();
-    let mut offsets = Vec::new();
-    while !partition_inner(n, k, max, &mut result, &mut offsets, r) {}
-    result
+/// `HH:MM-HH:MM`.
+impl fmt::Display for TimeSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.from / 60,
+            self.from % 60,
+            self.to / 60,
+            self.to % 60
+        )
+    }
 }
 
-#[derive(Debug)]
-pub struct Time {
-    pub from: u32,
-    pub to: u32,
+/// Reads a time like `9:30` as minutes since midnight, `None` if it isn't a time of the day.
+pub fn parse_clock(time: &str) -> Option<u32> {
+    let (hour, minute) = time.trim().split_once(':')?;
+    let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+    let minutes = hour * 60 + minute;
+    (minute < 60 && minutes <= 24 * 60).then_some(minutes)
+}
+
+mod clock {
+    use schemars::{json_schema, Schema, SchemaGenerator};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::generate::parse_clock;
+
+    pub fn serialize<S: Serializer>(minutes: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:02}:{:02}", minutes / 60, minutes % 60))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let time = String::deserialize(deserializer)?;
+        parse_clock(&time)
+            .ok_or_else(|| D::Error::custom(format!("`{}` isn't a time like 09:30", time)))
+    }
+
+    pub fn schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A time of the day like `09:30`.",
+            "type": "string",
+            "pattern": r"^\d{1,2}:\d{2}$",
+        })
+    }
 }
 
+/// A day of a month that [`generate_times`] may put hours on, with everything that limits the
+/// time worked on it.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct DayCapacity {
+    /// The day of the month, starting at 1.
+    pub day: NonZeroU32,
+    /// The day of the week of `day`.
+    pub day_of_week: DayOfWeek,
+    /// The fewest hours worked on the day.
+    pub min: u32,
+    /// The most hours worked on the day, zero on days off.
+    pub max: u32,
+    /// The span the time is placed in, only its full hours are used.
+    pub window: TimeSpan,
+    /// The spans of the day that the time doesn't overlap.
+    pub blackouts: Vec<TimeSpan>,
+}
+
+impl DayCapacity {
+    /// All days of `month` in order within the `window`, the working days can hold `max_per_day`
+    /// hours and the weekends and holidays of `holidays` none.
+    pub fn of_month(
+        month: &Month,
+        year: &Year,
+        holidays: &dyn HolidaySource,
+        max_per_day: u32,
+        window: TimeSpan,
+    ) -> Vec<DayCapacity> {
+        let holidays = holidays.holidays(year);
+        month
+            .days()
+            .map(|day| {
+                let date = DateOfYear::new(day.day_of_month, month.month());
+                let off = day.day_of_week.is_weekend()
+                    || holidays.iter().any(|holiday| holiday.date == date);
+                DayCapacity {
+                    day: day.day_of_month,
+                    day_of_week: day.day_of_week,
+                    min: 0,
+                    max: if off { 0 } else { max_per_day },
+                    window,
+                    blackouts: Vec::new(),
+                }
+            })
+            .collect()
+    }
+
+    /// The most hours the day can hold, less than its maximum if the blackouts leave less of the
+    /// window.
+    pub fn room(&self) -> u32 {
+        let (from, to) = self.window.full_hours();
+        self.max.min(longest_free_hours(from, to, &self.blackouts))
+    }
+}
+
+/// The constraints for [`generate_times`].
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Parameters {
+    /// The total number of hours to distribute.
     pub hours: u32,
-    pub days: u32,
-    pub from: u32,
-    pub to: u32,
-    pub max_per_day: u32,
+    /// The days to distribute the hours into in order.
+    pub days: Vec<DayCapacity>,
+}
+
+/// Places a span of `duration` hours uniformly within the window.
+fn place<R: Rng + ?Sized>(duration: u32, from: u32, to: u32, r: &mut R) -> Option<TimeSpan> {
+    if duration == 0 {
+        None
+    } else {
+        let dist = UniformInt::<u32>::new_inclusive(from, to - duration);
+        let from = dist.sample(r);
+        Some(TimeSpan::hours(from, from + duration))
+    }
+}
+
+/// The spans of the window of full hours that don't overlap any of `busy`, in order.
+fn free_spans(from: u32, to: u32, busy: &[TimeSpan]) -> Vec<TimeSpan> {
+    let (from, to) = (from * 60, to * 60);
+    let mut busy = busy.to_vec();
+    busy.sort_by_key(|time| time.from);
+    let mut spans = Vec::new();
+    let mut start = from;
+    for time in busy {
+        if start < time.from.min(to) {
+            spans.push(TimeSpan {
+                from: start,
+                to: time.from.min(to),
+            });
+        }
+        start = start.max(time.to);
+    }
+    if start < to {
+        spans.push(TimeSpan { from: start, to });
+    }
+    spans
+}
+
+/// The most full hours in a row within the window that don't overlap any of `busy`.
+pub(crate) fn longest_free_hours(from: u32, to: u32, busy: &[TimeSpan]) -> u32 {
+    free_spans(from, to, busy)
+        .iter()
+        .map(|span| {
+            let (first, last) = span.full_hours();
+            last.saturating_sub(first)
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// Places a span of `duration` hours uniformly among all positions within the window that don't
+/// overlap `busy`, `None` if `duration` is zero or doesn't fit.
+fn place_around<R: Rng + ?Sized>(
+    duration: u32,
+    from: u32,
+    to: u32,
+    busy: &[TimeSpan],
+    r: &mut R,
+) -> Option<TimeSpan> {
+    if busy.is_empty() {
+        return place(duration, from, to, r);
+    }
+    if duration == 0 {
+        return None;
+    }
+    let spans = free_spans(from, to, busy);
+    let total: u32 = spans.iter().map(|span| starts_in(span, duration)).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut index = UniformInt::<u32>::new(0, total).sample(r);
+    for span in spans {
+        if index < starts_in(&span, duration) {
+            let from = span.full_hours().0 + index;
+            return Some(TimeSpan::hours(from, from + duration));
+        }
+        index -= starts_in(&span, duration);
+    }
+    unreachable!("the index is below the total number of starts")
+}
+
+/// The full hours a span of `duration` hours can start at within `span`.
+fn starts_in(span: &TimeSpan, duration: u32) -> u32 {
+    let (first, last) = span.full_hours();
+    (last + 1).saturating_sub(first + duration)
+}
+
+/// The positions [`place_around`] draws a span of `duration` hours from.
+fn start_count(duration: u32, from: u32, to: u32, busy: &[TimeSpan]) -> u32 {
+    if busy.is_empty() {
+        return (to + 1).saturating_sub(from + duration);
+    }
+    free_spans(from, to, busy)
+        .iter()
+        .map(|span| starts_in(span, duration))
+        .sum()
 }
 
-pub fn generate_times<R: Rng>(parameters: Parameters, r: &mut R) -> Vec<Option<Time>> {
-    let Parameters {
+/// Generates a working time for each of the days in `parameters`.
+///
+/// `r` may also be a `&mut dyn RngCore`, see [`crate::rng::make_rng`].
+///
+/// Days without any work are `None`, days off like weekends and holidays never get any. The time
+/// of a day is within its window and doesn't overlap its blackouts, its hours are at least its
+/// minimum and at most its maximum or the longest span of the window without blackouts. Fails if a
+/// window is empty, the maximum of a day doesn't fit into its window or the hours don't fit into
+/// the days.
+pub fn generate_times<R: Rng + ?Sized>(
+    parameters: &Parameters,
+    r: &mut R,
+) -> Result<Vec<Option<TimeSpan>>> {
+    generate_times_with(parameters, &Uniform, r)
+}
+
+/// Like [`generate_times`], but the hours are split onto the days that aren't off by
+/// `distribution`, which sees them in order. Fails if it doesn't meet the bounds of a day.
+pub fn generate_times_with<R: Rng + ?Sized>(
+    parameters: &Parameters,
+    distribution: &dyn Distribution,
+    mut r: &mut R,
+) -> Result<Vec<Option<TimeSpan>>> {
+    let Parameters { hours, days: all } = parameters;
+    let hours = *hours;
+    if let Some(day) = all.iter().find(|day| day.window.to < day.window.from) {
+        let (from, to) = (day.window.from / 60, day.window.to / 60);
+        return Err(Error::InvalidWindow { from, to });
+    }
+    let days: Vec<_> = all.iter().filter(|day| day.max > 0).collect();
+    if let Some(day) = days
+        .iter()
+        .filter(|day| {
+            let (from, to) = day.window.full_hours();
+            to.saturating_sub(from) < day.max
+        })
+        .max_by_key(|day| day.max)
+    {
+        let (from, to) = day.window.full_hours();
+        return Err(Error::WindowTooSmall {
+            from,
+            to,
+            max_per_day: day.max,
+        });
+    }
+
+    let max_per_day = days.iter().map(|day| day.max).max().unwrap_or_default();
+    let infeasible = Error::Infeasible {
         hours,
-        days,
-        from,
-        to,
+        days: days.len() as u32,
         max_per_day,
-    } = parameters;
-
-    let max_total = days * max_per_day;
-    assert!(hours <= max_total);
-    let distribute_free_time = max_total / 2 <= hours;
-    let distribute = if distribute_free_time {
-        max_total - hours
-    } else {
-        hours
     };
-    let durations = partition(distribute, days, max_per_day, r);
-
-    durations
+    let bounds: Vec<_> = days
         .iter()
-        .map(|&duration| {
-            if distribute_free_time {
-                max_per_day - duration
-            } else {
-                duration
-            }
+        .map(|day| Bounds {
+            min: day.min,
+            max: day.room(),
         })
-        .map(|duration| {
-            if duration == 0 {
-                None
-            } else {
-                let dist = UniformInt::<u32>::new_inclusive(from, to - duration);
-                let from = dist.sample(r);
-                let to = from + duration;
-                Some(Time { from, to })
+        .collect();
+    let sum = |bound: fn(&Bounds) -> u32| {
+        bounds
+            .iter()
+            .try_fold(0u32, |total, b| total.checked_add(bound(b)))
+    };
+    let feasible = bounds.iter().all(|b| b.min <= b.max)
+        && sum(|b| b.min).is_some_and(|min| min <= hours)
+        && sum(|b| b.max).is_none_or(|max| hours <= max);
+    if !feasible {
+        return Err(infeasible);
+    }
+    let durations = distribution.distribute(hours, &bounds, &mut r as &mut dyn RngCore)?;
+    if durations.len() != days.len() {
+        return Err(Error::InvalidDistribution(format!(
+            "{} parts for {} days",
+            durations.len(),
+            days.len()
+        )));
+    }
+    if let Some((day, (duration, b))) = days
+        .iter()
+        .zip(durations.iter().zip(&bounds))
+        .find(|(_, (&duration, b))| duration < b.min || b.max < duration)
+    {
+        return Err(Error::InvalidDistribution(format!(
+            "{} hours aren't within {} and {} hours of day {}",
+            duration, b.min, b.max, day.day
+        )));
+    }
+    trace!(
+        "split {} hours onto {} days within {}: {}",
+        hours,
+        days.len(),
+        bounds
+            .iter()
+            .map(|b| format!("{}-{}", b.min, b.max))
+            .collect::<Vec<_>>()
+            .join(" "),
+        durations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    let mut durations = durations.into_iter();
+    let times = all
+        .iter()
+        .map(|day| match day.max {
+            0 => None,
+            _ => {
+                let duration = durations.next().expect("a duration per day");
+                let (from, to) = day.window.full_hours();
+                let time = place_around(duration, from, to, &day.blackouts, r);
+                trace!(
+                    "day {}: {} hours in {}{}, {}",
+                    day.day,
+                    duration,
+                    day.window,
+                    day.blackouts
+                        .iter()
+                        .map(|blackout| format!(" except {}", blackout))
+                        .collect::<String>(),
+                    match time {
+                        Some(time) => format!(
+                            "drew {} among {} starts",
+                            time,
+                            start_count(duration, from, to, &day.blackouts)
+                        ),
+                        None => "not worked".into(),
+                    }
+                );
+                time
             }
         })
-        .collect()
+        .collect();
+    Ok(times)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::generate::{generate_times, Parameters};
-    use rand::thread_rng;
+    use std::num::NonZeroU32;
+
+    use rand::RngCore;
+
+    use crate::calendar::{DayOfWeek, Month, Region, State, Year};
+    use crate::distribute::Distribution;
+    use crate::error::{Error, Result};
+    use crate::generate::{
+        generate_times, generate_times_with, Bounds, DayCapacity, Parameters, TimeSpan,
+    };
+    use crate::rng::seeded;
+    use crate::trace::capture;
+
+    /// `count` working days from 8:00 until 20:00 that can hold `max` hours each.
+    fn days(count: u32, max: u32) -> Vec<DayCapacity> {
+        (1..=count)
+            .map(|day| DayCapacity {
+                day: NonZeroU32::new(day).unwrap(),
+                day_of_week: DayOfWeek::Monday,
+                min: 0,
+                max,
+                window: TimeSpan::hours(8, 20),
+                blackouts: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// The hours of each of the `times`, zero for days without work.
+    fn durations(times: &[Option<TimeSpan>]) -> Vec<u32> {
+        times
+            .iter()
+            .map(|time| time.map_or(0, |time| time.duration() / 60))
+            .collect()
+    }
 
     #[test]
     fn test_partition() {
-        let mut rng = thread_rng();
-        let values = generate_times(
-            Parameters {
-                hours: 40,
-                days: 20,
-                from: 8,
-                to: 20,
-                max_per_day: 8,
-            },
-            &mut rng,
+        let mut rng = seeded(0);
+        let parameters = Parameters {
+            hours: 40,
+            days: days(20, 8),
+        };
+        let values = generate_times(&parameters, &mut rng);
+        dbg!(values.unwrap());
+    }
+
+    #[test]
+    fn test_days_off() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let region = Region::new(State::Bavaria, None).unwrap();
+        let days = DayCapacity::of_month(&month, &year, &region, 8, TimeSpan::hours(8, 20));
+        assert_eq!(days.len(), 31);
+        assert_eq!(days.iter().filter(|day| day.max > 0).count(), 19);
+        let parameters = Parameters {
+            hours: 19 * 8,
+            days,
+        };
+        let times = generate_times(&parameters, &mut seeded(0)).unwrap();
+        for (day, time) in parameters.days.iter().zip(&times) {
+            assert_eq!(time.is_some(), day.max > 0);
+        }
+
+        /// Ignores the bounds and puts everything onto the first day.
+        struct Greedy;
+        impl Distribution for Greedy {
+            fn distribute(
+                &self,
+                total: u32,
+                bounds: &[Bounds],
+                _: &mut dyn RngCore,
+            ) -> Result<Vec<u32>> {
+                let mut parts = vec![0; bounds.len()];
+                parts[0] = total;
+                Ok(parts)
+            }
+        }
+        assert!(matches!(
+            generate_times_with(&parameters, &Greedy, &mut seeded(0)),
+            Err(Error::InvalidDistribution(_))
+        ));
+    }
+
+    #[test]
+    fn test_infeasible() {
+        let mut rng = seeded(0);
+        let parameters = Parameters {
+            hours: 41,
+            days: days(5, 8),
+        };
+        assert_eq!(
+            generate_times(&parameters, &mut rng).unwrap_err(),
+            Error::Infeasible {
+                hours: 41,
+                days: 5,
+                max_per_day: 8
+            }
+        );
+
+        let mut days = days(5, 8);
+        days[2].window = TimeSpan::hours(10, 16);
+        assert_eq!(
+            generate_times(&Parameters { hours: 8, days }, &mut rng).unwrap_err(),
+            Error::WindowTooSmall {
+                from: 10,
+                to: 16,
+                max_per_day: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut days = days(4, 8);
+        for (day, (min, max)) in days.iter_mut().zip([(2, 2), (0, 0), (1, 8), (0, 3)]) {
+            day.min = min;
+            day.max = max;
+        }
+        let mut parameters = Parameters { hours: 9, days };
+        let mut rng = seeded(0);
+        for _ in 0..100 {
+            let times = generate_times(&parameters, &mut rng).unwrap();
+            let durations = durations(&times);
+            assert_eq!(durations.iter().sum::<u32>(), 9);
+            assert!(durations
+                .iter()
+                .zip(&parameters.days)
+                .all(|(&duration, day)| day.min <= duration && duration <= day.max));
+            assert!(times
+                .iter()
+                .flatten()
+                .all(|time| 8 * 60 <= time.from && time.to <= 20 * 60));
+        }
+        parameters.hours = 14;
+        assert!(generate_times(&parameters, &mut rng).is_err());
+        parameters.hours = 2;
+        assert!(generate_times(&parameters, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_time_span() {
+        let time = TimeSpan {
+            from: 8 * 60 + 30,
+            to: 12 * 60,
+        };
+        assert_eq!(time.duration(), 210);
+        assert_eq!(time.to_string(), "08:30-12:00");
+        assert!(time.overlaps(&TimeSpan::hours(11, 13)));
+        assert!(!time.overlaps(&TimeSpan::hours(12, 13)));
+        assert_eq!(time.shift(-30), Some(TimeSpan { from: 480, to: 690 }));
+        assert_eq!(
+            time.shift(12 * 60),
+            Some(TimeSpan {
+                from: 1230,
+                to: 1440
+            })
+        );
+        assert_eq!(time.shift(12 * 60 + 1), None);
+        assert_eq!(time.shift(-9 * 60), None);
+
+        assert_eq!(time.night_minutes(), 0);
+        assert_eq!(TimeSpan::hours(4, 8).night_minutes(), 120);
+        assert_eq!(TimeSpan::hours(20, 24).night_minutes(), 60);
+        assert_eq!(TimeSpan::hours(0, 24).night_minutes(), 7 * 60);
+        let late = TimeSpan {
+            from: 22 * 60 + 45,
+            to: 23 * 60 + 15,
+        };
+        assert_eq!(late.night_minutes(), 15);
+
+        let json = serde_json::to_string(&time).unwrap();
+        assert_eq!(json, r#"{"from":"08:30","to":"12:00"}"#);
+        assert_eq!(serde_json::from_str::<TimeSpan>(&json).unwrap(), time);
+        assert!(serde_json::from_str::<TimeSpan>(r#"{"from":"9:60","to":"13:00"}"#).is_err());
+    }
+
+    #[test]
+    fn test_blackouts() {
+        let mut days = days(3, 8);
+        days[0].blackouts = vec![TimeSpan::hours(10, 15)];
+        days[2].blackouts = vec![TimeSpan::hours(8, 12), TimeSpan::hours(14, 20)];
+        days[1].window = TimeSpan {
+            from: 9 * 60 + 30,
+            to: 20 * 60,
+        };
+        let mut parameters = Parameters { hours: 12, days };
+        let mut rng = seeded(0);
+        for _ in 0..100 {
+            let times = generate_times(&parameters, &mut rng).unwrap();
+            let durations = durations(&times);
+            assert_eq!(durations.iter().sum::<u32>(), 12);
+            assert!(durations[0] <= 5 && durations[2] <= 2);
+            assert!(times[1].iter().all(|time| 10 * 60 <= time.from));
+            for (time, day) in times.iter().zip(&parameters.days) {
+                assert!(time.iter().all(|time| day.window.from <= time.from
+                    && time.to <= day.window.to
+                    && day.blackouts.iter().all(|busy| !time.overlaps(busy))));
+            }
+        }
+        parameters.hours = 16;
+        assert!(generate_times(&parameters, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut days = days(3, 8);
+        days[2].blackouts = vec![TimeSpan::hours(8, 12), TimeSpan::hours(14, 20)];
+        let parameters = Parameters { hours: 12, days };
+        let (times, trace) = capture(|| generate_times(&parameters, &mut seeded(0)).unwrap());
+        assert_eq!(times, generate_times(&parameters, &mut seeded(0)).unwrap());
+        let lines: Vec<_> = trace.lines().collect();
+        assert_eq!(lines.len(), 4);
+        let durations = durations(&times);
+        let parts: Vec<_> = durations.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "split 12 hours onto 3 days within 0-8 0-8 0-2: {}",
+                parts.join(" ")
+            )
+        );
+        let starts = 13 - durations[0];
+        assert_eq!(
+            lines[1],
+            format!(
+                "day 1: {} hours in 08:00-20:00, drew {} among {} starts",
+                durations[0],
+                times[0].unwrap(),
+                starts
+            )
         );
-        dbg!(values);
+        assert!(lines[3].starts_with("day 3: "));
+        assert!(lines[3].contains(" except 08:00-12:00 except 14:00-20:00, "));
     }
 }
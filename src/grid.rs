@@ -0,0 +1,207 @@
+//! The month of a timesheet laid out like a wall calendar.
+//!
+//! [`CalendarGrid`] renders a standalone HTML page with a row per week from Monday to Sunday,
+//! every day worked shows its time range. Browsers print the page to PDF like an
+//! [`crate::invoice`], one month fits on a landscape page. A QR code of the
+//! [`SheetDigest`] lets a scanned paper copy be checked against what was generated.
+
+use std::io::{self, Write};
+
+use crate::calendar::{Month, Year};
+use crate::digest::{self, SheetDigest};
+use crate::invoice::escape;
+use crate::output::{clock, Header, MONTH_NAMES};
+use crate::timesheet::Timesheet;
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const STYLE: &str = "@page { size: A4 landscape; } \
+                     body { font-family: sans-serif; margin: 1cm; } \
+                     table { border-collapse: collapse; width: 100%; table-layout: fixed; } \
+                     th, td { border: 1px solid #999; padding: 0.3em; vertical-align: top; } \
+                     td { height: 5em; } \
+                     .day { font-weight: bold; } \
+                     .weekend, .holiday { background: #eee; } \
+                     .outside { background: none; border: none; } \
+                     .note, .holiday-name { font-size: 0.8em; color: #555; }";
+
+/// A [`Timesheet`] as a calendar grid of its month.
+///
+/// A cell shows the day of the month and, if it is worked, its time range like `9:00–13:30`, the
+/// time worked after breaks if it has any and its note. Weekends and the holidays of the state of
+/// the timesheet are shaded, the holidays are named. The header is shown above the grid, the
+/// total time worked and the QR code of the digest with the seed of the header below it.
+pub struct CalendarGrid<'a> {
+    sheet: &'a Timesheet,
+    header: Option<&'a Header>,
+}
+
+impl<'a> CalendarGrid<'a> {
+    /// The grid of `sheet` headed by `header` if there is one.
+    pub fn new(sheet: &'a Timesheet, header: Option<&'a Header>) -> Self {
+        CalendarGrid { sheet, header }
+    }
+
+    /// The weeks of the month from Monday to Sunday, with the days outside of it `None`.
+    fn weeks(month: &Month) -> Vec<[Option<u32>; 7]> {
+        let mut weeks = vec![[None; 7]];
+        for day in month.days() {
+            let weekday = (day.day_of_week as usize + 6) % 7;
+            if weekday == 0 && day.day_of_month.get() > 1 {
+                weeks.push([None; 7]);
+            }
+            weeks.last_mut().expect("pushed")[weekday] = Some(day.day_of_month.get());
+        }
+        weeks
+    }
+
+    /// Writes the grid as an HTML page.
+    pub fn write_html(&self, w: &mut dyn Write) -> io::Result<()> {
+        let sheet = self.sheet;
+        let year = Year::new(sheet.year);
+        let month = Month::new(sheet.month, &year).expect("timesheets have valid months");
+        let holidays = year.holidays(sheet.state);
+        let title = format!(
+            "{} {}",
+            MONTH_NAMES[sheet.month.get() as usize - 1],
+            sheet.year
+        );
+        let breaks = sheet.has_breaks();
+        writeln!(w, "<!DOCTYPE html>")?;
+        writeln!(w, "<html lang=\"en\">")?;
+        writeln!(w, "<head>")?;
+        writeln!(w, "<meta charset=\"utf-8\">")?;
+        writeln!(w, "<title>Timesheet {}</title>", title)?;
+        writeln!(w, "<style>{} {}</style>", STYLE, digest::STYLE)?;
+        writeln!(w, "</head>")?;
+        writeln!(w, "<body>")?;
+        writeln!(w, "<h1>{}</h1>", title)?;
+        if let Some(header) = self.header {
+            let fields: Vec<_> = header
+                .fields()
+                .into_iter()
+                .filter(|(label, _)| *label != "Month")
+                .map(|(label, value)| format!("{}: {}", label, escape(&value)))
+                .collect();
+            writeln!(w, "<p>{}</p>", fields.join("<br>"))?;
+        }
+        writeln!(w, "<table>")?;
+        let names: Vec<_> = WEEKDAYS
+            .iter()
+            .map(|name| format!("<th>{}</th>", name))
+            .collect();
+        writeln!(w, "<thead><tr>{}</tr></thead>", names.concat())?;
+        writeln!(w, "<tbody>")?;
+        for week in Self::weeks(&month) {
+            write!(w, "<tr>")?;
+            for (weekday, day) in week.into_iter().enumerate() {
+                let Some(day) = day else {
+                    write!(w, "<td class=\"outside\"></td>")?;
+                    continue;
+                };
+                let holiday = holidays.iter().find(|holiday| {
+                    holiday.date.month == sheet.month && holiday.date.day.get() == day
+                });
+                let class = match holiday {
+                    Some(_) => " class=\"holiday\"",
+                    None if weekday >= 5 => " class=\"weekend\"",
+                    None => "",
+                };
+                write!(w, "<td{}><div class=\"day\">{}</div>", class, day)?;
+                if let Some(holiday) = holiday {
+                    write!(
+                        w,
+                        "<div class=\"holiday-name\">{}</div>",
+                        escape(&holiday.name)
+                    )?;
+                }
+                if let Some(entry) = sheet.entries.iter().find(|entry| entry.day.get() == day) {
+                    write!(
+                        w,
+                        "<div>{}–{}</div>",
+                        clock(entry.time.from),
+                        clock(entry.time.to)
+                    )?;
+                    if breaks {
+                        write!(w, "<div>{} net</div>", clock(sheet.net_minutes_of(entry)))?;
+                    }
+                    if let Some(note) = &entry.note {
+                        write!(w, "<div class=\"note\">{}</div>", escape(note))?;
+                    }
+                }
+                write!(w, "</td>")?;
+            }
+            writeln!(w, "</tr>")?;
+        }
+        writeln!(w, "</tbody>")?;
+        writeln!(w, "</table>")?;
+        let worked: u32 = sheet
+            .entries
+            .iter()
+            .map(|entry| sheet.net_minutes_of(entry))
+            .sum();
+        writeln!(w, "<p>Total: {}</p>", clock(worked))?;
+        let digest = SheetDigest::new(sheet, self.header.and_then(|header| header.seed));
+        writeln!(w, "{}", digest.html())?;
+        writeln!(w, "</body>")?;
+        writeln!(w, "</html>")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::{deduct_breaks, BreakRule};
+    use crate::calendar::{Month, State, Year};
+    use crate::digest::SheetDigest;
+    use crate::grid::CalendarGrid;
+    use crate::output::Header;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_grid() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let mut sheet = generate_timesheet(&year, &month, parameters, &mut seeded(3)).unwrap();
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        sheet.entries[0].note = Some("Review <RFC>".into());
+        let header = Header {
+            employee: Some("Erika Mustermann".into()),
+            ..Header::new(&sheet, 40, 1714521600)
+        };
+        let mut html = Vec::new();
+        CalendarGrid::new(&sheet, Some(&header))
+            .write_html(&mut html)
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        // May 2024 starts on a Wednesday and ends on a Friday
+        assert_eq!(CalendarGrid::weeks(&month).len(), 5);
+        assert_eq!(html.matches("<tr>").count(), 6);
+        let first = "<tr><td class=\"outside\"></td><td class=\"outside\"></td>\
+                     <td class=\"holiday\"><div class=\"day\">1</div>\
+                     <div class=\"holiday-name\">Tag der Arbeit</div></td>";
+        assert!(html.contains(first));
+        assert!(html.contains("Review &lt;RFC&gt;"));
+        let digest = SheetDigest::new(&sheet, None);
+        assert!(html.contains(&format!("<figcaption>{}</figcaption>", digest)));
+        insta::assert_snapshot!(html);
+    }
+}
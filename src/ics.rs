@@ -0,0 +1,227 @@
+//! iCalendar (RFC 5545) export of the public holidays and of the days worked.
+
+use std::fmt::Write;
+use std::num::NonZeroU32;
+
+use crate::calendar::{DateOfYear, State, Year};
+use crate::timesheet::Timesheet;
+use crate::timezone::Timezone;
+
+/// Escapes the characters RFC 5545 reserves in text values.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn format_date(year: u32, date: DateOfYear) -> String {
+    format!("{:04}{:02}{:02}", year, date.month, date.day)
+}
+
+/// An iCalendar with an all-day event for each holiday of `year` in `state`.
+///
+/// The output only depends on its arguments, so the events keep their UIDs when it is regenerated
+/// and calendar apps update them instead of adding duplicates.
+pub fn holidays(year: &Year, state: State) -> String {
+    let mut ics = String::new();
+    let mut line = |text: &str| {
+        ics.push_str(text);
+        ics.push_str("\r\n");
+    };
+    line("BEGIN:VCALENDAR");
+    line("VERSION:2.0");
+    line("PRODID:-//stundenzettel//Feiertage//DE");
+    line("CALSCALE:GREGORIAN");
+    line(&format!(
+        "X-WR-CALNAME:Feiertage {} {}",
+        state.abbreviation(),
+        year.year()
+    ));
+    for holiday in year.holidays(state) {
+        let start = format_date(year.year(), holiday.date);
+        let end = match holiday.date.add_days(1, year.is_leap()) {
+            Ok(next) => format_date(year.year(), next),
+            Err(_) => format!("{:04}0101", year.year() + 1),
+        };
+        let mut event = String::new();
+        let _ = write!(
+            event,
+            "BEGIN:VEVENT\r\n\
+             UID:{start}-{state}@stundenzettel\r\n\
+             DTSTAMP:{start}T000000Z\r\n\
+             DTSTART;VALUE=DATE:{start}\r\n\
+             DTEND;VALUE=DATE:{end}\r\n\
+             SUMMARY:{summary}\r\n\
+             TRANSP:TRANSPARENT\r\n\
+             END:VEVENT",
+            state = state.abbreviation().to_lowercase(),
+            summary = escape(&holiday.name),
+        );
+        line(&event);
+    }
+    line("END:VCALENDAR");
+    ics
+}
+
+/// The local time `minute` minutes after midnight on `day` of the month of `sheet` in `timezone`
+/// as a UTC date-time like `20240304T080000Z`.
+fn format_utc(sheet: &Timesheet, timezone: Timezone, day: NonZeroU32, minute: u32) -> String {
+    let offset = timezone.offset(sheet.year, sheet.month, day.get(), minute);
+    let minute = i64::from(minute) - i64::from(offset);
+    let days = minute.div_euclid(1440) as i32;
+    let minute = minute.rem_euclid(1440);
+    let date = DateOfYear {
+        day,
+        month: sheet.month,
+    };
+    let (year, date) = match date.add_days(days, Year::new(sheet.year).is_leap()) {
+        Ok(date) => (sheet.year, date),
+        Err(_) if days < 0 => (sheet.year - 1, DateOfYear::new_checked(31, 12).unwrap()),
+        Err(_) => (sheet.year + 1, DateOfYear::new_checked(1, 1).unwrap()),
+    };
+    format!(
+        "{}T{:02}{:02}00Z",
+        format_date(year, date),
+        minute / 60,
+        minute % 60
+    )
+}
+
+/// An iCalendar with an event for each entry of `sheet`, its times in UTC as they are in
+/// `timezone`.
+///
+/// An entry during the night the clocks change lasts as long as [`Timezone::elapsed`] says. Like
+/// [`holidays`], the UIDs only depend on the days worked.
+pub fn timesheet(sheet: &Timesheet, timezone: Timezone) -> String {
+    let mut ics = String::new();
+    let mut line = |text: &str| {
+        ics.push_str(text);
+        ics.push_str("\r\n");
+    };
+    line("BEGIN:VCALENDAR");
+    line("VERSION:2.0");
+    line("PRODID:-//stundenzettel//Stundenzettel//DE");
+    line("CALSCALE:GREGORIAN");
+    line(&format!(
+        "X-WR-CALNAME:Stundenzettel {:02}/{}",
+        sheet.month, sheet.year
+    ));
+    for entry in &sheet.entries {
+        let date = DateOfYear {
+            day: entry.day,
+            month: sheet.month,
+        };
+        let start = format_utc(sheet, timezone, entry.day, entry.time.from);
+        let mut event = String::new();
+        let _ = write!(
+            event,
+            "BEGIN:VEVENT\r\n\
+             UID:{day}-work@stundenzettel\r\n\
+             DTSTAMP:{start}\r\n\
+             DTSTART:{start}\r\n\
+             DTEND:{end}\r\n\
+             SUMMARY:Arbeit",
+            day = format_date(sheet.year, date),
+            end = format_utc(sheet, timezone, entry.day, entry.time.to),
+        );
+        if let Some(note) = &entry.note {
+            let _ = write!(event, "\r\nDESCRIPTION:{}", escape(note));
+        }
+        event.push_str("\r\nEND:VEVENT");
+        line(&event);
+    }
+    line("END:VCALENDAR");
+    ics
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{DayOfWeek, State, Year};
+    use crate::generate::TimeSpan;
+    use crate::ics::{escape, holidays, timesheet};
+    use crate::timesheet::{Entry, Timesheet};
+    use crate::timezone::Timezone;
+
+    #[test]
+    fn test_holidays() {
+        insta::assert_snapshot!(holidays(&Year::new(2025), State::Bavaria).replace("\r\n", "\n"));
+        assert_eq!(escape("a,b;c\\d"), "a\\,b\\;c\\\\d");
+    }
+
+    #[test]
+    fn test_timesheet() {
+        let entry = |day, from, to| Entry {
+            day: NonZeroU32::new(day).unwrap(),
+            day_of_week: DayOfWeek::Sunday,
+            time: TimeSpan::hours(from, to),
+            surcharge: None,
+            break_minutes: 0,
+            note: None,
+        };
+        let sheet = |month, entries| Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(month).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries,
+        };
+        let times = |sheet: &Timesheet, timezone: &str| {
+            let ics = timesheet(sheet, timezone.parse().unwrap());
+            ics.lines()
+                .filter_map(|line| {
+                    let (key, time) = line.split_once(':')?;
+                    ["DTSTART", "DTEND"].contains(&key).then(|| time.to_owned())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // the nights of 30./31.3.2024 and 26./27.10.2024 last five and seven hours, the weekends
+        // before and after six
+        let march = sheet(3, vec![entry(1, 0, 6), entry(24, 0, 6), entry(31, 0, 6)]);
+        assert_eq!(
+            times(&march, "Europe/Berlin"),
+            [
+                "20240229T230000Z",
+                "20240301T050000Z",
+                "20240323T230000Z",
+                "20240324T050000Z",
+                "20240330T230000Z",
+                "20240331T040000Z",
+            ]
+        );
+        let october = sheet(
+            10,
+            vec![entry(20, 0, 6), entry(27, 0, 6), entry(27, 22, 24)],
+        );
+        assert_eq!(
+            times(&october, "Europe/Berlin"),
+            [
+                "20241019T220000Z",
+                "20241020T040000Z",
+                "20241026T220000Z",
+                "20241027T050000Z",
+                "20241027T210000Z",
+                "20241027T230000Z",
+            ]
+        );
+        let mut new_year = sheet(1, vec![entry(1, 0, 2)]);
+        assert_eq!(
+            times(&new_year, "Europe/Berlin"),
+            ["20231231T230000Z", "20240101T010000Z"]
+        );
+        assert_eq!(
+            times(&new_year, "-05:00"),
+            ["20240101T050000Z", "20240101T070000Z"]
+        );
+
+        new_year.entries[0].note = Some("Inventur, Lager".into());
+        let ics = timesheet(&new_year, Timezone::default());
+        assert!(ics.contains("UID:20240101-work@stundenzettel\r\n"));
+        assert!(ics.contains("DESCRIPTION:Inventur\\, Lager\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    }
+}
@@ -0,0 +1,278 @@
+//! Invoices of freelancers for the time of a timesheet.
+//!
+//! An [`Invoice`] bills the time worked in a month at an hourly rate, see [`crate::pay`], with a
+//! line item per day or per week and the VAT on top. It renders as a standalone HTML page that
+//! browsers print to PDF with the QR code of the [`SheetDigest`] of the timesheet below the
+//! totals. [`StateFile::next_invoice_number`](crate::vacation::StateFile) keeps
+//! the sequence of the invoice numbers.
+
+use std::io::{self, Write};
+use std::num::NonZeroU32;
+
+use crate::digest::{self, SheetDigest};
+use crate::locale::Locale;
+use crate::output::{timestamp, MONTH_NAMES};
+use crate::pay::Pay;
+use crate::timesheet::Timesheet;
+
+/// What the line items of an [`Invoice`] cover.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Grouping {
+    /// A line item per day worked.
+    #[default]
+    Days,
+    /// A line item per week with days worked.
+    Weeks,
+}
+
+/// A billed day or week.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LineItem {
+    /// What was worked, like `7.5.2024` or `Week 2 of May 2024`.
+    pub description: String,
+    /// The minutes worked without breaks.
+    pub minutes: u32,
+    /// The net amount in cents.
+    pub cents: u64,
+}
+
+/// The invoice of a month.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Invoice {
+    /// The invoice number like `2024-001`.
+    pub number: String,
+    /// When the invoice was issued in seconds since the Unix epoch.
+    pub issued_at: u64,
+    /// The name and address of the freelancer, one line each, left out if `None`.
+    pub issuer: Option<String>,
+    /// The name and address of the client, one line each, left out if `None`.
+    pub recipient: Option<String>,
+    /// The year of the billed month.
+    pub year: u32,
+    /// The billed month, starting at 1.
+    pub month: NonZeroU32,
+    /// The hourly rate in cents.
+    pub rate: u32,
+    /// The VAT in percent, zero for small businesses that don't charge any.
+    pub vat: u32,
+    /// The billed days or weeks, in order.
+    pub items: Vec<LineItem>,
+    /// The digest of the billed timesheet.
+    pub digest: SheetDigest,
+}
+
+impl Invoice {
+    /// The invoice `number` of `sheet` at `rate` cents per hour plus `vat` percent, issued at
+    /// `issued_at` seconds since the Unix epoch.
+    pub fn new(
+        number: String,
+        sheet: &Timesheet,
+        rate: u32,
+        vat: u32,
+        grouping: Grouping,
+        issued_at: u64,
+    ) -> Self {
+        let pay = Pay::new(sheet, rate);
+        let month_name = MONTH_NAMES[sheet.month.get() as usize - 1];
+        let items = match grouping {
+            Grouping::Days => pay
+                .days
+                .iter()
+                .map(|day| LineItem {
+                    description: format!("{}.{}.{}", day.day, sheet.month, sheet.year),
+                    minutes: day.earnings.minutes,
+                    cents: day.earnings.cents,
+                })
+                .collect(),
+            Grouping::Weeks => pay
+                .weeks
+                .iter()
+                .map(|week| LineItem {
+                    description: format!("Week {} of {} {}", week.week, month_name, sheet.year),
+                    minutes: week.earnings.minutes,
+                    cents: week.earnings.cents,
+                })
+                .collect(),
+        };
+        Invoice {
+            number,
+            issued_at,
+            issuer: None,
+            recipient: None,
+            year: sheet.year,
+            month: sheet.month,
+            rate,
+            vat,
+            items,
+            digest: SheetDigest::new(sheet, None),
+        }
+    }
+
+    /// The sum of the line items in cents.
+    pub fn net(&self) -> u64 {
+        self.items.iter().map(|item| item.cents).sum()
+    }
+
+    /// The VAT on the net amount in cents, halves upwards.
+    pub fn tax(&self) -> u64 {
+        (self.net() * u64::from(self.vat) + 50) / 100
+    }
+
+    /// The net amount plus the VAT in cents.
+    pub fn total(&self) -> u64 {
+        self.net() + self.tax()
+    }
+
+    /// Writes the invoice as an HTML page with the amounts formatted after `locale`.
+    pub fn write_html(&self, locale: Locale, w: &mut dyn Write) -> io::Result<()> {
+        let month_name = MONTH_NAMES[self.month.get() as usize - 1];
+        writeln!(w, "<!DOCTYPE html>")?;
+        writeln!(w, "<html lang=\"en\">")?;
+        writeln!(w, "<head>")?;
+        writeln!(w, "<meta charset=\"utf-8\">")?;
+        writeln!(w, "<title>Invoice {}</title>", escape(&self.number))?;
+        writeln!(w, "<style>{} {}</style>", STYLE, digest::STYLE)?;
+        writeln!(w, "</head>")?;
+        writeln!(w, "<body>")?;
+        for (class, address) in [("issuer", &self.issuer), ("recipient", &self.recipient)] {
+            if let Some(address) = address {
+                let lines: Vec<_> = address.lines().map(escape).collect();
+                writeln!(w, "<p class=\"{}\">{}</p>", class, lines.join("<br>"))?;
+            }
+        }
+        writeln!(w, "<h1>Invoice {}</h1>", escape(&self.number))?;
+        writeln!(
+            w,
+            "<p>Date: {}<br>Period: {} {}</p>",
+            &timestamp(self.issued_at)[..10],
+            month_name,
+            self.year
+        )?;
+        writeln!(w, "<table>")?;
+        writeln!(
+            w,
+            "<thead><tr><th>Item</th><th class=\"amount\">Hours</th>\
+             <th class=\"amount\">Rate</th><th class=\"amount\">Amount</th></tr></thead>"
+        )?;
+        writeln!(w, "<tbody>")?;
+        let rate = locale.currency(self.rate.into());
+        for item in &self.items {
+            writeln!(
+                w,
+                "<tr><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td>\
+                 <td class=\"amount\">{}</td></tr>",
+                escape(&item.description),
+                locale.decimal(f64::from(item.minutes) / 60.0, 2),
+                rate,
+                locale.currency(item.cents)
+            )?;
+        }
+        writeln!(w, "</tbody>")?;
+        writeln!(w, "<tfoot>")?;
+        let total = |w: &mut dyn Write, cell: &str, label: String, cents: u64| {
+            writeln!(
+                w,
+                "<tr><{cell} colspan=\"3\">{}</{cell}><{cell} class=\"amount\">{}</{cell}></tr>",
+                label,
+                locale.currency(cents),
+                cell = cell
+            )
+        };
+        total(w, "td", "Net".into(), self.net())?;
+        total(w, "td", format!("VAT {} %", self.vat), self.tax())?;
+        total(w, "th", "Total".into(), self.total())?;
+        writeln!(w, "</tfoot>")?;
+        writeln!(w, "</table>")?;
+        writeln!(w, "{}", self.digest.html())?;
+        writeln!(w, "</body>")?;
+        writeln!(w, "</html>")
+    }
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2cm; } \
+                     table { border-collapse: collapse; width: 100%; } \
+                     th, td { padding: 0.3em 0.5em; border-bottom: 1px solid #ccc; text-align: left; } \
+                     .amount { text-align: right; } \
+                     .recipient { margin-bottom: 2em; }";
+
+/// `text` with the characters that HTML reserves replaced by entities.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::digest::SheetDigest;
+    use crate::invoice::{escape, Grouping, Invoice};
+    use crate::locale::Locale;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_invoice() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 20,
+            from: 8,
+            to: 20,
+            max_per_day: 6,
+        };
+        let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(7)).unwrap();
+        let invoice = Invoice::new(
+            "2024-001".into(),
+            &sheet,
+            7550,
+            19,
+            Grouping::Days,
+            1714521600,
+        );
+        assert_eq!(invoice.items.len(), sheet.entries.len());
+        assert_eq!(invoice.net(), 20 * 7550);
+        assert_eq!(invoice.tax(), 28690);
+        assert_eq!(invoice.total(), 20 * 7550 + 28690);
+
+        let weekly = Invoice::new(
+            "2024-002".into(),
+            &sheet,
+            7550,
+            0,
+            Grouping::Weeks,
+            1714521600,
+        );
+        assert!(weekly.items.len() < invoice.items.len());
+        assert_eq!(weekly.total(), invoice.net());
+
+        let invoice = Invoice {
+            issuer: Some("Erika Mustermann\nHauptstraße 1".into()),
+            recipient: Some("Muster & Co.".into()),
+            ..invoice
+        };
+        let mut html = Vec::new();
+        invoice.write_html(Locale::DeDe, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        let digest = SheetDigest::new(&sheet, None);
+        assert!(html.contains(&format!("<figcaption>{}</figcaption>", digest)));
+        insta::assert_snapshot!(html);
+
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Generates randomized but plausible timesheets for a month.
+//!
+//! The crate is split into two parts:
+//! - [`calendar`] knows about days of the week, Easter and the public holidays and enumerates the
+//!   working days of a month, [`ics`] exports the holidays and the days worked as an iCalendar.
+//! - [`generate`] randomly distributes a number of hours onto the days of a month while respecting
+//!   the window, the blackouts and the bounds of the hours of each day, none on weekends and
+//!   holidays,
+//!   [`distribute`] holds the underlying constrained random partitions.
+//! - [`timesheet`] combines both into the times worked on each day of a month, optionally with the
+//!   per-day exceptions of [`constraints`].
+//! - [`verify`] checks that a timesheet satisfies its constraints, [`plan`] checks the options of
+//!   a timesheet against each other and [`policy`] adjusts the parameters that can't be met,
+//!   [`stats`] shows how the hours of many timesheets distribute and [`audit`] scores how
+//!   suspicious a timesheet looks, [`warnings`] points out what payroll may ask about, [`breaks`]
+//!   deducts the breaks due from the printed times.
+//! - [`output`] streams timesheets as text, CSV, JSON or Markdown into stdout, files or memory,
+//!   [`grid`] draws them as a wall calendar and [`parser`] reads CSV and JSON back, [`notes`]
+//!   adds the activity of a day to its entry, [`smooth`] cleans the raw times of a punch clock
+//!   into timesheets, [`rounding`] rounds generated and cleaned times before they are written.
+//! - [`archive`] stores generated timesheets per month, [`config`] loads the user settings including
+//!   the [`contract`], [`vacation`] keeps the leave budget and [`report`] compares the archived
+//!   hours with the contract, [`pay`] sums up the earnings at an hourly rate and [`invoice`] bills
+//!   them, [`paths`] finds where they are kept on each platform.
+//! - [`request`] holds the JSON parameters of a timesheet, [`locale`] formats the numbers of the
+//!   tables and [`timezone`] turns the local times into timestamps for exports.
+//! - [`rng`] creates the random number generators, including seeded ones for reproducible times,
+//!   [`trace`] records the decisions drawn from them and [`progress`] reports how far commands
+//!   generating many timesheets got.
+//! - [`schema`] holds the JSON Schemas of the JSON output, the constraints and the configuration.
+//!
+//! Optional features add front ends and integrations:
+//! - `server`: an HTTP interface in `server`.
+//! - `python`: a Python module built with `maturin`.
+//! - `ffi`: C bindings in `ffi`.
+//! - `wasm`: compiles to `wasm32-unknown-unknown` and exposes JavaScript bindings in `wasm`.
+//! - `mail`: sending timesheets via SMTP in `mail`.
+//! - `webdav`: uploading timesheets to WebDAV shares in `upload`.
+//! - `push`: creating the entries in time tracking services in `push`.
+//! - `nager`: fetching the holidays of other countries in `nager`.
+//! - `signing`: signing exported timesheets with Ed25519 in `signing`.
+//! - `update`: checking GitHub for newer releases in `update`.
+//!
+//! All fallible operations return the crate wide [`Error`] instead of panicking.
+//!
+//! The public items of these modules follow semantic versioning.
+#![warn(missing_docs)]
+
+pub mod archive;
+pub mod audit;
+pub mod breaks;
+pub mod calendar;
+pub mod config;
+pub mod constraints;
+pub mod contract;
+pub mod digest;
+pub mod distribute;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod generate;
+pub mod grid;
+pub mod ics;
+pub mod invoice;
+pub mod locale;
+#[cfg(feature = "mail")]
+pub mod mail;
+#[cfg(feature = "nager")]
+pub mod nager;
+pub mod notes;
+pub mod output;
+pub mod parser;
+pub mod paths;
+pub mod pay;
+pub mod plan;
+pub mod policy;
+pub mod progress;
+#[cfg(feature = "push")]
+pub mod push;
+#[cfg(feature = "python")]
+mod python;
+pub mod report;
+pub mod request;
+pub mod rng;
+pub mod rounding;
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "signing")]
+pub mod signing;
+pub mod smooth;
+pub mod stats;
+pub mod timesheet;
+pub mod timezone;
+pub mod trace;
+#[cfg(feature = "update")]
+pub mod update;
+#[cfg(feature = "webdav")]
+pub mod upload;
+pub mod vacation;
+pub mod verify;
+pub mod warnings;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::{Error, Result};
@@ -0,0 +1,172 @@
+//! Formats numbers after the conventions of a locale.
+//!
+//! Spreadsheets parse the numbers they import after the locale of the system, a German Excel
+//! reads `7.5` as a date. The tables of [`crate::stats`], [`crate::report`] and [`crate::pay`] can
+//! therefore be rendered with the decimal separator and thousands grouping of a [`Locale`].
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The conventions numbers are written in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Locale {
+    /// Decimal points and no grouping, independent of any language.
+    #[default]
+    C,
+    /// Decimal points and commas grouping the thousands, `1,234.5`.
+    EnUs,
+    /// Decimal commas and points grouping the thousands, `1.234,5`.
+    DeDe,
+}
+
+impl Locale {
+    /// All locales.
+    pub const ALL: [Locale; 3] = [Locale::C, Locale::EnUs, Locale::DeDe];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::C => "C",
+            Locale::EnUs => "en-US",
+            Locale::DeDe => "de-DE",
+        }
+    }
+
+    /// The decimal separator and the separator of the thousands if they are grouped.
+    fn separators(&self) -> (char, Option<char>) {
+        match self {
+            Locale::C => ('.', None),
+            Locale::EnUs => ('.', Some(',')),
+            Locale::DeDe => (',', Some('.')),
+        }
+    }
+
+    /// The digits of a non-negative integer grouped by thousands.
+    fn group(&self, digits: &str) -> String {
+        let Some(separator) = self.separators().1 else {
+            return digits.into();
+        };
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// `value` as an integer like `-1.234`.
+    pub fn integer(&self, value: i64) -> String {
+        let digits = self.group(&value.unsigned_abs().to_string());
+        if value < 0 {
+            format!("-{}", digits)
+        } else {
+            digits
+        }
+    }
+
+    /// `value` rounded to `places` decimal places like `1.234,50`.
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let formatted = format!("{:.*}", places, value);
+        let (sign, unsigned) = match formatted.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", formatted.as_str()),
+        };
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        let mut result = format!("{}{}", sign, self.group(integer));
+        if !fraction.is_empty() {
+            result.push(self.separators().0);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// `cents` as an amount of euros like `1.234,50 €`, with the sign before it in `en-US`.
+    pub fn currency(&self, cents: u64) -> String {
+        let (separator, _) = self.separators();
+        let amount = format!(
+            "{}{}{:02}",
+            self.group(&(cents / 100).to_string()),
+            separator,
+            cents % 100
+        );
+        match self {
+            Locale::EnUs => format!("€{}", amount),
+            Locale::C | Locale::DeDe => format!("{} €", amount),
+        }
+    }
+
+    /// Displays `value` with the numbers in this locale.
+    pub fn display<T: Localize + ?Sized>(self, value: &T) -> Localized<'_, T> {
+        Localized {
+            value,
+            locale: self,
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Accepts the names case insensitively and with `_` instead of `-`, like `de_DE`.
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.replace('_', "-");
+        Locale::ALL
+            .into_iter()
+            .find(|locale| locale.name().eq_ignore_ascii_case(&name))
+            .ok_or_else(|| format!("`{}` isn't a supported locale", s))
+    }
+}
+
+/// A value whose displayed numbers depend on the [`Locale`].
+pub trait Localize {
+    /// Writes `self` with the numbers formatted after `locale`.
+    fn fmt_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result;
+}
+
+/// Displays the value in a locale, see [`Locale::display`].
+pub struct Localized<'a, T: ?Sized> {
+    value: &'a T,
+    locale: Locale,
+}
+
+impl<T: Localize + ?Sized> fmt::Display for Localized<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_localized(f, self.locale)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::locale::Locale;
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(Locale::C.decimal(1234.5, 2), "1234.50");
+        assert_eq!(Locale::EnUs.decimal(1234.5, 2), "1,234.50");
+        assert_eq!(Locale::DeDe.decimal(7.5, 1), "7,5");
+        assert_eq!(Locale::DeDe.decimal(-1234567.3, 1), "-1.234.567,3");
+        assert_eq!(Locale::DeDe.decimal(999.0, 0), "999");
+        assert_eq!(Locale::C.integer(-1920), "-1920");
+        assert_eq!(Locale::DeDe.integer(-1920), "-1.920");
+        assert_eq!(Locale::EnUs.integer(123456), "123,456");
+        assert_eq!(Locale::C.currency(123405), "1234.05 €");
+        assert_eq!(Locale::EnUs.currency(123405), "€1,234.05");
+        assert_eq!(Locale::DeDe.currency(50), "0,50 €");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("de-DE".parse(), Ok(Locale::DeDe));
+        assert_eq!("de_de".parse(), Ok(Locale::DeDe));
+        assert_eq!("c".parse(), Ok(Locale::C));
+        assert!("fr-FR".parse::<Locale>().is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Sends timesheets via SMTP with the settings of a [`MailConfig`].
+//!
+//! The timesheet is attached as CSV, PDF isn't supported.
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use crate::config::{MailConfig, Security};
+use crate::output::{Csv, Formatter};
+use crate::timesheet::Timesheet;
+
+/// The reasons sending a timesheet can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum MailError {
+    /// A sender or recipient isn't a valid address.
+    #[error("Invalid address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+    /// The message couldn't be built.
+    #[error("Invalid message: {0}")]
+    Message(#[from] lettre::error::Error),
+    /// The SMTP server couldn't be reached or rejected the message.
+    #[error("Sending failed: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+}
+
+/// Replaces `{year}`, `{month}`, `{hours}` and `{state}` in `template` with the values of `sheet`.
+pub fn expand(template: &str, sheet: &Timesheet) -> String {
+    template
+        .replace("{year}", &sheet.year.to_string())
+        .replace("{month}", &format!("{:02}", sheet.month))
+        .replace("{hours}", &sheet.hours().to_string())
+        .replace("{state}", sheet.state.abbreviation())
+}
+
+/// The message sending `sheet` to `to`.
+pub fn message(config: &MailConfig, to: &str, sheet: &Timesheet) -> Result<Message, MailError> {
+    let attachment = Attachment::new(format!(
+        "stundenzettel-{}-{:02}.csv",
+        sheet.year, sheet.month
+    ))
+    .body(
+        Csv.render(sheet),
+        ContentType::parse("text/csv").expect("text/csv is a valid content type"),
+    );
+    let message = Message::builder()
+        .from(config.from.parse::<Mailbox>()?)
+        .to(to.parse::<Mailbox>()?)
+        .subject(expand(&config.subject, sheet))
+        .multipart(
+            MultiPart::mixed()
+                .singlepart(SinglePart::plain(expand(&config.body, sheet)))
+                .singlepart(attachment),
+        )?;
+    Ok(message)
+}
+
+/// Sends `sheet` to `to`.
+pub fn send(config: &MailConfig, to: &str, sheet: &Timesheet) -> Result<(), MailError> {
+    let message = message(config, to, sheet)?;
+    let mut transport = match config.security {
+        Security::Tls => SmtpTransport::relay(&config.host)?,
+        Security::Starttls => SmtpTransport::starttls_relay(&config.host)?,
+        Security::None => SmtpTransport::builder_dangerous(&config.host),
+    };
+    if let Some(port) = config.port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &config.username {
+        transport = transport.credentials(Credentials::new(
+            username.clone(),
+            config.password.clone().unwrap_or_default(),
+        ));
+    }
+    transport.build().send(&message)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::config::Config;
+    use crate::mail::{expand, message};
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_message() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Hesse,
+            city: None,
+            hours: 20,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+        assert_eq!(
+            expand("{month}/{year}: {hours}h in {state}", &sheet),
+            "03/2024: 20h in HE"
+        );
+
+        let config = Config::parse("[mail]\nhost = \"localhost\"\nfrom = \"Me <me@example.com>\"")
+            .unwrap()
+            .mail
+            .unwrap();
+        let formatted = String::from_utf8(
+            message(&config, "boss@example.com", &sheet)
+                .unwrap()
+                .formatted(),
+        )
+        .unwrap();
+        assert!(formatted.contains("Subject: Stundenzettel 03/2024"));
+        assert!(formatted.contains("stundenzettel-2024-03.csv"));
+        assert!(message(&config, "not an address", &sheet).is_err());
+    }
+}
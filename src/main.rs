@@ -1,20 +1,927 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
 use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::process;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use clap::builder::{EnumValueParser, MapValueParser, TypedValueParser};
 use clap::error::ErrorKind;
-use clap::{CommandFactory, Parser};
-use rand::thread_rng;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use rand::RngCore;
 
-use crate::calendar::{non_holidays_of_month, Month, Year};
-use crate::generate::{generate_times, Parameters};
+use stundenzettel::archive::Archive;
+use stundenzettel::audit::Audit;
+use stundenzettel::breaks::{add_breaks, deduct_breaks, BreakRule};
+use stundenzettel::calendar::{
+    analyze_holidays, non_holidays_of_month, City, DateOfYear, DayOfWeek, HolidaySource, Month,
+    Region, State, Year,
+};
+#[cfg(feature = "webdav")]
+use stundenzettel::config::WebdavConfig;
+use stundenzettel::config::{Config, Overrides, Setup};
+use stundenzettel::constraints::{
+    exclude_short_days, parse_constraints, window_blackouts, Blackout, DateConstraint,
+    WeekdayWindow,
+};
+use stundenzettel::contract::{self, BankedMonth, Contract, Period, Term};
+use stundenzettel::digest::SheetDigest;
+use stundenzettel::distribute::Shape;
+use stundenzettel::generate::TimeSpan;
+use stundenzettel::grid::CalendarGrid;
+use stundenzettel::ics;
+use stundenzettel::invoice::{Grouping, Invoice};
+use stundenzettel::locale::Locale;
+#[cfg(feature = "nager")]
+use stundenzettel::nager::NagerHolidays;
+use stundenzettel::notes::{
+    attach_notes, draw_activities, parse_notes, redact_notes, Note, Rotation,
+};
+use stundenzettel::output::{
+    Encoder, Encoding, Format, Header, Layout, LineEnding, OutputSink, Pauses, StdoutSink, Weekly,
+};
+use stundenzettel::parser::parse;
+use stundenzettel::paths::Paths;
+use stundenzettel::pay::{CostSplit, Pay};
+use stundenzettel::plan::{self, Plan};
+use stundenzettel::policy::{EarningsCap, Policy};
+use stundenzettel::progress::{self, Progress};
+#[cfg(feature = "push")]
+use stundenzettel::push::{clockify, gcal, send, toggl, PushError};
+use stundenzettel::report::Report;
+use stundenzettel::request::{GenerateRequest, Reproduction};
+use stundenzettel::rng::{make_rng, seeded, Generator, RngSource};
+use stundenzettel::rounding::{Rounding, RoundingPolicy};
+use stundenzettel::schema::{toml_to_json, Schema};
+#[cfg(feature = "signing")]
+use stundenzettel::signing::{PublicKey, SignedHeader, SigningKey};
+use stundenzettel::smooth::{parse_punches, SmoothingPolicy};
+use stundenzettel::stats::{Distribution, Simulation};
+use stundenzettel::timesheet::Timesheet;
+use stundenzettel::timesheet::{
+    avoid_round_weeks, cap_weeks, cluster_starts, generate_jobs, generate_like,
+    generate_timesheet_with, limit_free_streaks, place_free_days, place_remainder, plan_timesheet,
+    work_off_days, FreeDays, Remainder, SheetParameters, Strategy, WeeklyCap, GRANULARITY,
+};
+use stundenzettel::timezone::Timezone;
+use stundenzettel::trace;
+#[cfg(feature = "update")]
+use stundenzettel::update::{Release, Version};
+#[cfg(feature = "webdav")]
+use stundenzettel::upload::Credentials;
+use stundenzettel::vacation::{working_days, StateFile};
+use stundenzettel::verify::{verify, Violation};
+use stundenzettel::warnings::warnings;
+use stundenzettel::Error;
 
-mod calendar;
-mod generate;
+const EXIT_CODES: &str = "Exit codes:
+  0  The timesheet was generated or passed `--check-only`
+  2  The arguments are invalid or the constraints can't be met
+  3  Reading, writing or sending failed
+  4  A timesheet, the configuration or another input doesn't parse, the validated or checked
+     timesheet violates the constraints or the configuration is invalid or lacks a setting";
+
+/// The constraints can't be met, this is also what clap uses for invalid arguments.
+const EXIT_INFEASIBLE: i32 = 2;
+const EXIT_IO: i32 = 3;
+/// An input, a timesheet or the configuration is invalid.
+const EXIT_INVALID: i32 = 4;
+
+/// How often `--max-score` generates a timesheet before giving up.
+const MAX_ATTEMPTS: u32 = 100;
 
 #[derive(Parser)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, after_help = EXIT_CODES)]
 struct Arguments {
+    /// Format of errors printed to stderr
+    #[clap(long, value_enum, global = true, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+    /// The configuration file instead of `~/.config/stundenzettel/config.toml` or
+    /// `Application Support` on macOS and `%APPDATA%` on Windows
+    #[clap(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+    /// The directory of the archive and the state file instead of `~/.local/share/stundenzettel` or
+    /// `Application Support` on macOS and `%APPDATA%` on Windows
+    #[clap(long, value_name = "DIR", global = true)]
+    state_dir: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// Where the configuration, the archive and the state file are, see [`stundenzettel::paths`], with
+/// `--config` and `--state-dir` applied. `None` if the home directory is unknown and no override was
+/// given.
+struct Locations {
+    config: Option<PathBuf>,
+    /// Whether the configuration was given with `--config` and has to exist.
+    explicit_config: bool,
+    data: Option<PathBuf>,
+}
+
+static LOCATIONS: OnceLock<Locations> = OnceLock::new();
+
+fn locations() -> &'static Locations {
+    LOCATIONS.get_or_init(|| Locations::new(None, None))
+}
+
+impl Locations {
+    fn new(config: Option<PathBuf>, state_dir: Option<PathBuf>) -> Self {
+        let paths = Paths::from_environment();
+        Locations {
+            explicit_config: config.is_some(),
+            config: config.or_else(|| paths.as_ref().map(|paths| paths.config.clone())),
+            data: state_dir.or_else(|| paths.map(|paths| paths.data)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ErrorFormat {
+    /// Human readable messages
+    Text,
+    /// A `{ "code", "message", "limits" }` object per error
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the timesheet of a month
+    Generate(Box<GenerateArguments>),
+    /// Export the days worked of an archived or given timesheet as an iCalendar
+    Calendar(CalendarArguments),
+    /// Create, show or check the configuration file
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+    /// Check the digest of a printed timesheet as scanned from its QR code against the archived
+    /// timesheet
+    Digest(DigestArguments),
+    /// Export the public holidays
+    #[clap(subcommand)]
+    Holidays(HolidaysCommand),
+    /// Bill the hours of an archived or given timesheet as an HTML invoice, print it to PDF from a
+    /// browser
+    Invoice(InvoiceArguments),
+    /// List the archived timesheets
+    List,
+    /// Compare the archived hours of a year with the hours of the contract
+    Report(ReportArguments),
+    /// Print the JSON Schema of a machine readable format, e.g. for the validation in editors
+    Schema {
+        /// The format
+        #[clap(value_enum)]
+        format: SchemaKind,
+    },
+    /// Generate a month many times and show how the worked days and the longest days distribute
+    Simulate(SimulateArguments),
+    /// Round and clean raw clock-in and clock-out times like the export of a punch clock into a
+    /// timesheet
+    Smooth(SmoothArguments),
+    /// Track the yearly leave budget, recorded leave isn't worked when generating
+    #[clap(subcommand)]
+    Vacation(VacationCommand),
+    /// Check a generated CSV or JSON timesheet against constraints
+    Validate(ValidateArguments),
+    /// Create the entries of an archived timesheet in a time tracking service
+    #[cfg(feature = "push")]
+    Push(PushArguments),
+    /// Serve timesheets over HTTP
+    #[cfg(feature = "server")]
+    Serve(ServeArguments),
+    /// Create or show the Ed25519 key `generate` signs CSV and JSON timesheets with
+    #[cfg(feature = "signing")]
+    #[clap(subcommand)]
+    Key(KeyCommand),
+    /// Check that a signed CSV or JSON timesheet wasn't altered since it was generated
+    #[cfg(feature = "signing")]
+    Verify(VerifyArguments),
+    /// Manage the installed binary
+    #[cfg(feature = "update")]
+    #[clap(name = "self", subcommand)]
+    Itself(SelfCommand),
+}
+
+#[derive(Args, Clone)]
+struct GenerateArguments {
+    /// The month
+    #[clap(
+        value_parser = month_in_range,
+        required_unless_present_any = ["stdin", "reproduce", "period"]
+    )]
+    month: Option<NonZeroU32>,
+    /// The year
+    #[clap(
+        value_parser = year_in_range,
+        required_unless_present_any = ["stdin", "reproduce", "period"]
+    )]
+    year: Option<u32>,
+    /// Hours to assign like `40` or in quarter hours like `37:30`, derived from the `[contract]`
+    /// of the configuration if missing
+    #[clap(value_parser = hours_and_minutes)]
+    hours: Option<(NonZeroU32, u32)>,
+    /// Where the minutes below a full hour of `HOURS` are worked
+    #[clap(long, value_enum, default_value_t = RemainderKind::Spread)]
+    remainder: RemainderKind,
+
+    /// Read the parameters as a JSON object from stdin, e.g.
+    /// `{ "month": 2, "year": 2024, "hours": 40, "state": "NW", "seed": 1 }`
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "month", "year", "hours", "max_per_day", "earliest", "latest", "state", "city", "seed",
+            "contract_from", "contract_to",
+        ]
+    )]
+    stdin: bool,
+    /// Generate the timesheet of a previous run again, `BLOB` is the JSON object it printed to
+    /// stderr after `--reproduce`
+    #[clap(
+        long,
+        value_name = "BLOB",
+        conflicts_with_all = [
+            "stdin", "month", "year", "hours", "remainder", "max_per_day", "earliest", "latest",
+            "state", "city", "rng", "seed", "constraints", "blackout", "window", "min_per_day",
+            "strict", "contract_from", "contract_to", "jobs", "stats", "max_score",
+            "avoid_round_weeks", "max_per_week", "realism", "realism_profile", "free_days",
+            "max_free_streak", "off_days", "distribution", "weights", "shape", "like", "jitter",
+            "step", "rounding", "breaks", "no_breaks", "earnings_cap", "rate", "activities",
+        ]
+    )]
+    reproduce: Option<String>,
+
+    /// Output format, defaults to the `format` of the configuration or else text
+    #[clap(long, value_parser = kinds::<FormatKind, Format>(), ignore_case = true)]
+    format: Option<Format>,
+    /// Shorthand for `--format csv`
+    #[clap(long, conflicts_with = "format")]
+    csv: bool,
+    /// Character encoding of the output. Spreadsheets like German Excel need a byte order mark or
+    /// Latin-1 to show umlauts, JSON is always UTF-8
+    #[clap(long, value_parser = kinds::<EncodingKind, Encoding>(), ignore_case = true, default_value_t = Encoding::Utf8)]
+    encoding: Encoding,
+    /// Line ending of the output. CSV ending its lines with `crlf` and without the header complies
+    /// with RFC 4180, as HR imports on Windows often require
+    #[clap(
+        long,
+        value_parser = kinds::<LineEndingKind, LineEnding>(),
+        ignore_case = true,
+        default_value_t = LineEnding::Lf
+    )]
+    line_ending: LineEnding,
+    /// Lay out the days as `daily` rows, as `weekly` tables with a column per day from Monday to
+    /// Sunday and a row per job, as some employers require, as a `calendar` of the month or as
+    /// `pauses` with the breaks as rows like `12:00-12:30 Pause` between the times worked. Weekly
+    /// tables are text, CSV or Markdown, the calendar is an HTML page browsers print to PDF, the
+    /// pauses are text or CSV
+    #[clap(
+        long,
+        value_parser = kinds::<LayoutKind, Layout>(),
+        ignore_case = true,
+        default_value_t = Layout::Daily
+    )]
+    layout: Layout,
+    /// Leave out the header with the employee, month, state, hours, time of generation and seed,
+    /// e.g. for programs reading the CSV. `SOURCE_DATE_EPOCH` overrides the time of generation
+    #[clap(long)]
+    no_header: bool,
+    /// Leave out the employee and the employers and replace the notes with placeholders like
+    /// `Activity 1`, e.g. to share the timesheet publicly when reporting an issue. The archive
+    /// keeps the notes
+    #[clap(long)]
+    redact: bool,
+
+    /// Maximum assignable hours per day
+    #[clap(value_parser = hour_in_range, default_value_t = 8)]
+    max_per_day: u32,
+    /// Earliest assignable starting hour
+    #[clap(value_parser = hour_in_range, default_value_t = 8)]
+    earliest: u32,
+    /// Latest assignable stopping hour
+    #[clap(value_parser = hour_in_range, default_value_t = 20)]
+    latest: u32,
+
+    /// Federal state whose holidays aren't worked on, defaults to the state of the contract or `BY`
+    #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true)]
+    state: Option<State>,
+    /// City within the state whose holidays aren't worked on either, e.g. `Augsburg`
+    #[clap(long)]
+    city: Option<City>,
+    /// Take the holidays of a country or region from the Nager.Date API instead, e.g. `AT` or
+    /// `CH-ZH`. Every year is only fetched once and then read from `~/.cache/stundenzettel/nager`
+    #[cfg(feature = "nager")]
+    #[clap(
+        long,
+        value_name = "COUNTRY",
+        requires = "hours",
+        conflicts_with_all = ["state", "city", "contract_from", "contract_to", "jobs"]
+    )]
+    holidays_from_api: Option<String>,
+
+    /// Random number generator to use, defaults to `seeded` if a seed is given
+    #[clap(long, value_enum)]
+    rng: Option<RngKind>,
+    /// Seed for the `seeded` random number generator
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// Don't store the timesheet as `<year>/<month>.json` in the archive, see `--state-dir`
+    #[clap(long)]
+    no_archive: bool,
+
+    /// CSV file of per-day exceptions: `date,pin`, `date,exclude`, `date,cap,hours` or
+    /// `date,fill,from,to`
+    #[clap(long, value_name = "FILE")]
+    constraints: Option<PathBuf>,
+    /// Describe the activity of a day in its entry like `12.03="Server migration"`, notes of days
+    /// that aren't worked are left out with a warning. Can be repeated
+    #[clap(long, value_name = "NOTE", conflicts_with = "jobs")]
+    note: Vec<Note>,
+    /// File of notes like `--note`, one `day.month=text` per line
+    #[clap(long, value_name = "FILE", conflicts_with = "jobs")]
+    notes: Option<PathBuf>,
+    /// Note the days without a note with the `[[activities]]` of the configuration
+    #[clap(long, value_enum, value_name = "ROTATION", conflicts_with = "jobs")]
+    activities: Option<RotationKind>,
+
+    /// Never work during this time, e.g. `12:00-13:30` every day or `tue=10:00-12:00` on Tuesdays.
+    /// Can be repeated
+    #[clap(long, value_name = "SPAN")]
+    blackout: Vec<Blackout>,
+    /// Earliest starting and latest stopping hour of a weekday instead of `EARLIEST` and `LATEST`,
+    /// e.g. `fri=8-14`. Can be repeated
+    #[clap(long, value_name = "WINDOW", conflicts_with = "jobs")]
+    window: Vec<WeekdayWindow>,
+    /// Skip the days whose window left by `--blackout` and `--window` has fewer than this many
+    /// hours in a row instead of working a short block on them
+    #[clap(long, value_parser = hour_in_range, value_name = "HOURS", conflicts_with = "jobs")]
+    min_per_day: Option<u32>,
+
+    /// Fail instead of adjusting parameters that can't be met, like a maximum of hours per day that
+    /// doesn't fit between the earliest and the latest hour
+    #[clap(long)]
+    strict: bool,
+    /// Reduce the hours to stay below these earnings a month at `--rate`, e.g. `538` for a
+    /// Minijob. Fails with `--strict` instead
+    #[clap(long, value_name = "EUROS", value_parser = euros, requires = "rate", conflicts_with = "jobs")]
+    earnings_cap: Option<u32>,
+    /// The hourly rate, e.g. `15.50`. Prints the pay of each day, week and the month to stderr
+    /// with the numbers of `--locale`
+    #[clap(long, value_name = "EUROS", value_parser = euros, conflicts_with_all = ["jobs", "stats"])]
+    rate: Option<u32>,
+    /// Add a column with the pay at `--rate` to the timesheet
+    #[clap(long, requires = "rate")]
+    pay_column: bool,
+    /// Zone of the times like `Europe/Berlin` from 1996 on or `+01:00`. JSON adds the start and end
+    /// of each entry as timestamps with the offset to UTC of the day, the archived timesheet keeps
+    /// the zone for `push`
+    #[clap(long, conflicts_with_all = ["jobs", "stats"])]
+    timezone: Option<Timezone>,
+
+    /// First day of employment, e.g. `15.03.2024`. Earlier days aren't worked and the hours are
+    /// pro-rated by the remaining working days of the month
+    #[clap(long, value_parser = date_in_range, value_name = "DATE")]
+    contract_from: Option<(u32, DateOfYear)>,
+    /// Last day of employment, later days aren't worked and the hours are pro-rated like with
+    /// `--contract-from`
+    #[clap(long, value_parser = date_in_range, value_name = "DATE")]
+    contract_to: Option<(u32, DateOfYear)>,
+    /// Generate the months of a quarter like `Q1-2024` or of a semester like `WS24` (October until
+    /// March) or `SS25` (April until September) one after another. The hours are derived from the
+    /// `[contract]` of the configuration and a seed is increased by one per month
+    #[clap(
+        long,
+        value_name = "TERM",
+        conflicts_with_all = ["month", "year", "hours", "stdin", "reproduce", "jobs", "stats"]
+    )]
+    period: Option<Term>,
+    /// Let the months of `--period` work more or less than the contract as long as the whole
+    /// period matches it, each month works at least half of its hours. The split is printed to
+    /// stderr
+    #[clap(long, requires = "period")]
+    bank: bool,
+    /// Most hours `--bank` puts into a single month
+    #[clap(long, value_name = "HOURS", requires = "bank")]
+    month_cap: Option<u32>,
+    /// Report the months of `--period` to stderr as they are generated, as a bar or as a JSON
+    /// object per month for tools wrapping the binary
+    #[clap(
+        long,
+        value_name = "STYLE",
+        value_parser = kinds::<ProgressKind, progress::Style>(),
+        num_args = 0..=1,
+        default_missing_value = "bar",
+        requires = "period"
+    )]
+    progress: Option<progress::Style>,
+    /// The hours `--bank` assigned to the month, replacing those of the contract
+    #[clap(skip)]
+    banked: Option<u32>,
+
+    /// Generate a timesheet for each of the `[[jobs]]` of the configuration with times that don't
+    /// overlap, the timesheets aren't archived. The pay of the jobs with a `rate` is split by job
+    /// to stderr
+    #[clap(
+        long,
+        requires = "year",
+        conflicts_with_all = [
+            "stdin", "hours", "state", "city", "contract_from", "contract_to", "if_missing",
+        ]
+    )]
+    jobs: bool,
+
+    /// Exit without output if the timesheet of the month is already archived
+    #[clap(long, conflicts_with = "no_archive")]
+    if_missing: bool,
+    /// Generate the timesheet and check it against the hours, the window and the daily maximum
+    /// without printing, archiving or sending it, the exit code tells whether the constraints can
+    /// be met
+    #[clap(long, conflicts_with_all = ["jobs", "stats"])]
+    check_only: bool,
+    /// Print the plan of the month instead of a timesheet: the hours per day each day can hold
+    /// within its window and blackouts and the dates filled by `--constraints`, before any time is
+    /// drawn. Nothing is archived
+    #[clap(long, conflicts_with_all = ["jobs", "stats", "check_only", "like"])]
+    show_plan: bool,
+
+    /// Instead of a timesheet, print how the hours of this many timesheets with consecutive seeds
+    /// distribute over the weekdays and weeks. The seeds start at `--seed` or 0, nothing is
+    /// archived
+    #[clap(
+        long,
+        value_name = "SAMPLES",
+        conflicts_with_all = ["rng", "jobs", "if_missing"]
+    )]
+    stats: Option<NonZeroU32>,
+    /// Number formatting of the `--stats` and `--rate` tables and of the pay split of `--jobs` like
+    /// `de-DE` for decimal commas
+    #[clap(long, value_parser = kinds::<LocaleKind, Locale>(), ignore_case = true, default_value_t)]
+    locale: Locale,
+
+    /// Score the timesheet for suspicious patterns like identical rows, days of maximal length and
+    /// round weekly totals and print the audit to stderr
+    #[clap(long, conflicts_with_all = ["jobs", "stats"])]
+    audit: bool,
+    /// Generate the timesheet again until its audit scores at most this from 0 to 100, fails after
+    /// 100 attempts
+    #[clap(long, value_name = "SCORE", requires = "audit")]
+    max_score: Option<u32>,
+    /// Write every random decision to this file or, without one or with `-`, to stderr: how the
+    /// hours were split onto the days, which start each day drew within its window and blackouts
+    /// and which attempts of `--avoid-round-weeks` and `--max-score` were rejected
+    #[clap(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    trace: Option<PathBuf>,
+
+    /// Move single hours between the weeks until the weekly totals are neither all the same nor
+    /// multiples of five hours, the total of the month stays exact
+    #[clap(long, conflicts_with = "jobs")]
+    avoid_round_weeks: bool,
+    /// Move time out of the weeks from Monday to Sunday that would be worked longer than this many
+    /// hours. The weeks shared with the previous or next month also count the times archived for
+    /// it
+    #[clap(long, value_name = "HOURS", conflicts_with = "jobs")]
+    max_per_week: Option<u32>,
+    /// Bundle heuristics for believable times: `standard` ends Fridays by 15:00, starts Mondays an
+    /// hour later, deducts the statutory breaks and starts the days around the same time. Explicit
+    /// `--window` and `--break` options take precedence
+    #[clap(long, value_enum, conflicts_with_all = ["jobs", "like"])]
+    realism: Option<RealismKind>,
+    /// Apply the `[realism.<NAME>]` profile of the configuration with its weights, windows, start
+    /// clustering, round weeks and breaks. Explicit options take precedence
+    #[clap(long, value_name = "NAME", conflicts_with_all = ["jobs", "like", "realism"])]
+    realism_profile: Option<String>,
+    /// Where the working days without work fall: `mon` to `fri` for that weekday first, `random`
+    /// or `spread` for evenly over the month. Their number stays the same
+    #[clap(long, value_name = "DAYS", value_parser = free_days, conflicts_with_all = ["jobs", "like"])]
+    free_days: Option<FreeDays>,
+    /// Work an hour taken from the longest days on every working day that would follow this many
+    /// working days in a row without work. Days of `--constraints` end these runs
+    #[clap(long, value_name = "DAYS", conflicts_with_all = ["jobs", "like"])]
+    max_free_streak: Option<u32>,
+    /// Move this percentage of the hours onto weekends and holidays and mark the rows of these days
+    /// with a `saturday`, `sunday` or `holiday` surcharge for payroll
+    #[clap(long, value_name = "PERCENT", value_parser = clap::value_parser!(u32).range(1..=100), conflicts_with = "jobs")]
+    off_days: Option<u32>,
+    /// Shape of the hours of the month, `uniform` if missing
+    #[clap(long, value_enum, conflicts_with = "jobs")]
+    distribution: Option<DistributionKind>,
+    /// Weights of the days from Monday to Friday or Sunday for `--distribution weighted`, e.g.
+    /// `3,3,2,2,1`. Weekends default to zero
+    #[clap(
+        long,
+        value_delimiter = ',',
+        required_if_eq("distribution", "weighted")
+    )]
+    weights: Vec<u32>,
+    /// Bias the uniformly random hours towards the start, the end or the middle of the month, e.g.
+    /// for a deadline crunch
+    #[clap(long, value_enum, conflicts_with_all = ["distribution", "jobs"])]
+    shape: Option<ShapeKind>,
+    /// Copy the times of the archived month `YYYY-MM`, e.g. the previous one, onto the days with
+    /// the same position among their weekday like the second Tuesday and fit them to `HOURS`
+    #[clap(
+        long,
+        value_name = "MONTH",
+        value_parser = year_and_month,
+        conflicts_with_all = [
+            "constraints", "blackout", "window", "min_per_day", "jobs", "period", "stats",
+            "distribution", "shape",
+        ]
+    )]
+    like: Option<(u32, NonZeroU32)>,
+    /// Move every start copied with `--like` by up to this much, e.g. `30m` or `1h30m`, defaults
+    /// to `1h`
+    #[clap(long, value_parser = minutes, value_name = "DURATION", requires = "like")]
+    jitter: Option<u32>,
+    /// Round the times to this many minutes before writing them, defaults to the `[rounding]` of
+    /// the configuration. The hours of the month may change
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..=60), conflicts_with_all = ["jobs", "stats"])]
+    step: Option<u32>,
+    /// How the times are rounded before writing them, `nearest` if missing
+    #[clap(long, value_enum, conflicts_with_all = ["jobs", "stats"])]
+    rounding: Option<RoundingKind>,
+    /// Lengthen the days worked for more than a duration by a break, e.g. `6h=30m`, so that the
+    /// time worked still adds up to the hours, and show the gross and net times. Replaces the
+    /// `[[breaks]]` of the configuration. Can be repeated
+    #[clap(
+        long = "break",
+        value_name = "RULE",
+        value_parser = break_rule,
+        conflicts_with_all = ["jobs", "stats"]
+    )]
+    breaks: Vec<BreakRule>,
+    /// Don't deduct the `[[breaks]]` of the configuration
+    #[clap(long, conflicts_with = "breaks")]
+    no_breaks: bool,
+
+    /// Send the timesheet to this address with the `[mail]` settings of the configuration
+    #[cfg(feature = "mail")]
+    #[clap(long, value_name = "ADDRESS", conflicts_with_all = ["jobs", "stats"])]
+    mail_to: Option<String>,
+
+    /// Upload the timesheet to a WebDAV folder, e.g. `webdav://cloud.example.com/remote.php/dav/files/me/HR/`
+    #[cfg(feature = "webdav")]
+    #[clap(long, value_name = "URL", conflicts_with_all = ["jobs", "stats"])]
+    upload: Option<String>,
+}
+
+#[derive(Args)]
+struct ValidateArguments {
+    /// The timesheet, `-` reads from stdin
+    file: PathBuf,
+    /// Hours the timesheet has to contain
+    hours: u32,
+
+    /// Maximum hours per day
+    #[clap(value_parser = hour_in_range, default_value_t = 8)]
+    max_per_day: u32,
+    /// Earliest starting hour
+    #[clap(value_parser = hour_in_range, default_value_t = 8)]
+    earliest: u32,
+    /// Latest stopping hour
+    #[clap(value_parser = hour_in_range, default_value_t = 20)]
+    latest: u32,
+
+    /// Federal state whose holidays mustn't be worked on without a surcharge, defaults to the
+    /// state of a JSON timesheet or `BY`
+    #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true)]
+    state: Option<State>,
+    /// City within the state whose holidays mustn't be worked on either, e.g. `Augsburg`
+    #[clap(long)]
+    city: Option<City>,
+}
+
+#[derive(Args)]
+struct CalendarArguments {
+    /// The month of the archived timesheet
+    #[clap(value_parser = month_in_range, required_unless_present = "file")]
+    month: Option<NonZeroU32>,
+    /// The year of the archived timesheet
+    #[clap(value_parser = year_in_range, required_unless_present = "file")]
+    year: Option<u32>,
+    /// Export a CSV or JSON timesheet instead of an archived one, `-` reads from stdin
+    #[clap(long, conflicts_with_all = ["month", "year"])]
+    file: Option<PathBuf>,
+    /// Zone of the times like `Europe/Berlin` or `+01:00`, defaults to the zone the timesheet was
+    /// generated with or else the `timezone` of the configuration. The events are written in UTC
+    #[clap(long)]
+    timezone: Option<Timezone>,
+}
+
+#[derive(Args)]
+struct DigestArguments {
+    /// The digest like `stundenzettel:2024-03:42:` followed by 64 hex digits
+    digest: SheetDigest,
+    /// Check a CSV or JSON timesheet instead of the archived one, `-` reads from stdin
+    #[clap(long)]
+    file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct InvoiceArguments {
+    /// The month of the archived timesheet
+    #[clap(value_parser = month_in_range, required_unless_present = "file")]
+    month: Option<NonZeroU32>,
+    /// The year of the archived timesheet
+    #[clap(value_parser = year_in_range, required_unless_present = "file")]
+    year: Option<u32>,
+    /// Bill a CSV or JSON timesheet instead of an archived one, `-` reads from stdin
+    #[clap(long, conflicts_with_all = ["month", "year"])]
+    file: Option<PathBuf>,
+
+    /// Hourly rate in euros like `45` or `52.50`, defaults to the `rate` of the `[contract]` of the
+    /// configuration
+    #[clap(long, value_parser = euros)]
+    rate: Option<u32>,
+    /// VAT in percent, defaults to the `[invoice]` of the configuration or 19
+    #[clap(long, value_parser = clap::value_parser!(u32).range(0..=100))]
+    vat: Option<u32>,
+    /// A line item per week instead of per day
+    #[clap(long)]
+    weekly: bool,
+    /// The invoice number, the next number of the year in the state file like `2024-001` if
+    /// missing. A given number doesn't advance the sequence
+    #[clap(long)]
+    number: Option<String>,
+    /// Number formatting of the amounts like `de-DE` for decimal commas
+    #[clap(long, value_parser = kinds::<LocaleKind, Locale>(), ignore_case = true, default_value_t)]
+    locale: Locale,
+}
+
+#[derive(Args)]
+struct SmoothArguments {
+    /// The export with rows of `date,in,out` like `2024-03-04,08:03,16:47`, `-` reads from stdin
+    file: PathBuf,
+
+    /// Output format
+    #[clap(long, value_parser = kinds::<FormatKind, Format>(), ignore_case = true, default_value_t = Format::Text)]
+    format: Format,
+    /// Shorthand for `--format csv`
+    #[clap(long, conflicts_with = "format")]
+    csv: bool,
+    /// Leave out the header with the employee, month, state, hours and time of generation
+    #[clap(long)]
+    no_header: bool,
+
+    /// Minutes the punches are rounded to, defaults to the `[rounding]` of the configuration or
+    /// 15
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..=60))]
+    step: Option<u32>,
+    /// How the punches are rounded, `nearest` if missing
+    #[clap(long, value_enum)]
+    rounding: Option<RoundingKind>,
+    /// Deduct a break from the days that didn't take it after working more than a duration, e.g.
+    /// `6h=30m`. Replaces the `[[breaks]]` of the configuration or else the statutory 30 minutes
+    /// after six hours and 45 minutes after nine hours. Can be repeated
+    #[clap(long = "break", value_name = "RULE", value_parser = break_rule)]
+    breaks: Vec<BreakRule>,
+    /// Don't deduct any breaks
+    #[clap(long, conflicts_with = "breaks")]
+    no_breaks: bool,
+
+    /// Federal state whose holidays are marked with a surcharge, defaults to `BY`
+    #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true)]
+    state: Option<State>,
+    /// City within the state whose holidays are marked as well, e.g. `Augsburg`
+    #[clap(long)]
+    city: Option<City>,
+}
+
+#[derive(Args)]
+struct ShowArguments {
+    /// Show the passwords, keys and tokens
+    #[clap(long)]
+    secrets: bool,
+
+    /// Replace the state of the `[contract]` like `generate --state`
+    #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true)]
+    state: Option<State>,
+    /// Replace the city of the `[contract]` like `generate --city`
+    #[clap(long)]
+    city: Option<City>,
+    /// Replace the first day of the `[contract]` like `generate --contract-from`
+    #[clap(long, value_parser = date_in_range, value_name = "DATE")]
+    contract_from: Option<(u32, DateOfYear)>,
+    /// Replace the last day of the `[contract]` like `generate --contract-to`
+    #[clap(long, value_parser = date_in_range, value_name = "DATE")]
+    contract_to: Option<(u32, DateOfYear)>,
+    /// Show only the `[realism.<NAME>]` profile like `generate --realism-profile` applies it
+    #[clap(long, value_name = "NAME")]
+    realism_profile: Option<String>,
+    /// Replace the output format like `generate --format`
+    #[clap(long, value_parser = kinds::<FormatKind, Format>(), ignore_case = true)]
+    format: Option<Format>,
+    /// Replace the zone of the times like `generate --timezone`
+    #[clap(long)]
+    timezone: Option<Timezone>,
+}
+
+#[cfg(feature = "signing")]
+#[derive(Subcommand)]
+enum KeyCommand {
+    /// Print a new private key for the `[signing]` section of the configuration
+    Generate,
+    /// Print the public key of the configured key, hand it out to those verifying the timesheets
+    Public,
+}
+
+#[cfg(feature = "signing")]
+#[derive(Args)]
+struct VerifyArguments {
+    /// The timesheet, `-` reads from stdin
+    file: PathBuf,
+    /// The public key of the signer, defaults to the one of the key in the configuration
+    #[clap(long)]
+    public_key: Option<PublicKey>,
+}
+
+#[cfg(feature = "update")]
+#[derive(Subcommand)]
+enum SelfCommand {
+    /// Tell whether a newer release is published on GitHub, nothing is downloaded
+    CheckUpdate,
+}
+
+#[cfg(feature = "push")]
+#[derive(Args)]
+struct PushArguments {
+    /// The time tracking service
+    #[clap(value_enum)]
+    service: Service,
+    /// The month of the archived timesheet
+    #[clap(value_parser = month_in_range, required_unless_present = "authorize")]
+    month: Option<NonZeroU32>,
+    /// The year of the archived timesheet
+    #[clap(value_parser = year_in_range, required_unless_present = "authorize")]
+    year: Option<u32>,
+
+    /// Print the requests instead of sending them
+    #[clap(long)]
+    dry_run: bool,
+    /// Zone of the times like `Europe/Berlin` or `+01:00`, defaults to the zone the timesheet was
+    /// generated with or else the `timezone` of the configuration
+    #[clap(long)]
+    timezone: Option<Timezone>,
+    /// Obtain the refresh token for `gcal` instead of pushing
+    #[clap(long, conflicts_with_all = ["month", "year", "dry_run"])]
+    authorize: bool,
+}
+
+#[cfg(feature = "push")]
+#[derive(Copy, Clone, ValueEnum)]
+enum Service {
+    /// Clockify, configured in the `[clockify]` section
+    Clockify,
+    /// Toggl Track, configured in the `[toggl]` section
+    Toggl,
+    /// Google Calendar, configured in the `[gcal]` section
+    Gcal,
+}
+
+#[cfg(feature = "server")]
+#[derive(Args)]
+struct ServeArguments {
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1:8080")]
+    address: String,
+}
+
+#[derive(Subcommand)]
+enum HolidaysCommand {
+    /// Print the public holidays of a year
+    Export {
+        /// The year
+        #[clap(long, value_parser = year_in_range)]
+        year: u32,
+        /// Federal state whose holidays are exported
+        #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true, default_value_t = State::Bavaria)]
+        state: State,
+        /// Output format
+        #[clap(long, value_enum, default_value_t = HolidayFormat::Text)]
+        format: HolidayFormat,
+        /// Character encoding of the text
+        #[clap(long, value_parser = kinds::<EncodingKind, Encoding>(), ignore_case = true, default_value_t = Encoding::Utf8)]
+        encoding: Encoding,
+    },
+    /// Report per month how many free days the holidays gain and which fall on a weekend
+    Analyze {
+        /// The year
+        #[clap(long, value_parser = year_in_range)]
+        year: u32,
+        /// Federal state whose holidays are analyzed
+        #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true, default_value_t = State::Bavaria)]
+        state: State,
+        /// City within the state whose holidays are analyzed as well, e.g. `Augsburg`
+        #[clap(long)]
+        city: Option<City>,
+        /// Output format
+        #[clap(long, value_enum, default_value_t = AnalysisFormat::Text)]
+        format: AnalysisFormat,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum HolidayFormat {
+    /// One `day.month.year name` line per holiday
+    Text,
+    /// A JSON array of `{ "date": { "day", "month" }, "name" }` objects
+    Json,
+    /// An iCalendar with an all-day event per holiday
+    Ics,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum AnalysisFormat {
+    /// A summary line per month followed by its holidays
+    Text,
+    /// A JSON array with an object per month
+    Json,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Ask for the state, the contract, the window, the name and the output format and write them
+    /// as a commented configuration file, see `--config`
+    Init {
+        /// Overwrite an existing configuration
+        #[clap(long)]
+        force: bool,
+    },
+    /// Print the configuration file with the defaults and the inherited settings filled in,
+    /// preceded by where the configuration and the state are. Of the environment only the WebDAV
+    /// credentials `STUNDENZETTEL_WEBDAV_USERNAME` and `STUNDENZETTEL_WEBDAV_PASSWORD` are applied
+    /// in builds with the `webdav` feature, of the options of `generate` only the ones `show`
+    /// takes. Passwords, keys and tokens are shown as `...`
+    Show(ShowArguments),
+    /// Check a configuration against its JSON Schema and for settings that can't work together,
+    /// like breaks the rounding can't show
+    Validate {
+        /// The configuration, defaults to the one of `--config`, `-` reads from stdin
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VacationCommand {
+    /// Set the number of days of leave in a year
+    Budget {
+        /// The year
+        #[clap(value_parser = year_in_range)]
+        year: u32,
+        /// The number of days of leave
+        days: u32,
+    },
+    /// Record the working days from a date until another one as leave
+    Take {
+        /// The first day, e.g. `23.12.2024`
+        #[clap(value_parser = date_in_range)]
+        from: (u32, DateOfYear),
+        /// The last day, defaults to the first one
+        #[clap(value_parser = date_in_range)]
+        until: Option<(u32, DateOfYear)>,
+        /// Federal state whose holidays aren't counted as leave
+        #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true, default_value_t = State::Bavaria)]
+        state: State,
+    },
+    /// Remove the recorded leave from a date until another one
+    Cancel {
+        /// The first day, e.g. `23.12.2024`
+        #[clap(value_parser = date_in_range)]
+        from: (u32, DateOfYear),
+        /// The last day, defaults to the first one
+        #[clap(value_parser = date_in_range)]
+        until: Option<(u32, DateOfYear)>,
+    },
+    /// Show the taken and remaining leave of a year
+    Show {
+        /// The year
+        #[clap(value_parser = year_in_range)]
+        year: u32,
+    },
+}
+
+#[derive(Args)]
+struct ReportArguments {
+    /// The year
+    #[clap(value_parser = year_in_range)]
+    year: u32,
+    /// Hours of the contract per month
+    #[clap(long)]
+    target: u32,
+    /// Output format
+    #[clap(long, value_enum, default_value_t = ReportFormat::Table)]
+    format: ReportFormat,
+    /// Number formatting of the table like `de-DE` for points grouping the thousands
+    #[clap(long, value_parser = kinds::<LocaleKind, Locale>(), ignore_case = true, default_value_t)]
+    locale: Locale,
+}
+
+#[derive(Args)]
+struct SimulateArguments {
     /// The month
-    #[clap(value_parser  = month_in_range)]
+    #[clap(value_parser = month_in_range)]
     month: NonZeroU32,
     /// The year
     #[clap(value_parser = year_in_range)]
@@ -22,10 +929,6 @@ struct Arguments {
     /// Hours to assign
     hours: NonZeroU32,
 
-    /// Output csv
-    #[clap(long)]
-    csv: bool,
-
     /// Maximum assignable hours per day
     #[clap(value_parser = hour_in_range, default_value_t = 8)]
     max_per_day: u32,
@@ -35,6 +938,499 @@ struct Arguments {
     /// Latest assignable stopping hour
     #[clap(value_parser = hour_in_range, default_value_t = 20)]
     latest: u32,
+
+    /// Federal state whose holidays aren't worked on
+    #[clap(long, value_parser = kinds::<StateKind, State>(), ignore_case = true, default_value_t)]
+    state: State,
+    /// City within the state whose holidays aren't worked on either, e.g. `Augsburg`
+    #[clap(long)]
+    city: Option<City>,
+
+    /// Number of timesheets to generate
+    #[clap(long, default_value_t = NonZeroU32::new(1000).unwrap())]
+    runs: NonZeroU32,
+    /// Seed of the first run, the following runs use consecutive seeds
+    #[clap(long, default_value_t)]
+    seed: u64,
+    /// Report the runs to stderr as they are generated, as a bar or as a JSON object per run with
+    /// its seed
+    #[clap(
+        long,
+        value_name = "STYLE",
+        value_parser = kinds::<ProgressKind, progress::Style>(),
+        num_args = 0..=1,
+        default_missing_value = "bar"
+    )]
+    progress: Option<progress::Style>,
+
+    /// CSV file of per-day exceptions like with `generate --constraints`
+    #[clap(long, value_name = "FILE")]
+    constraints: Option<PathBuf>,
+    /// Never work during this time like with `generate --blackout`. Can be repeated
+    #[clap(long, value_name = "SPAN")]
+    blackout: Vec<Blackout>,
+    /// Earliest starting and latest stopping hour of a weekday like with `generate --window`. Can
+    /// be repeated
+    #[clap(long, value_name = "WINDOW")]
+    window: Vec<WeekdayWindow>,
+
+    /// Output format
+    #[clap(long, value_enum, default_value_t = SimulateFormat::Table)]
+    format: SimulateFormat,
+    /// Number formatting of the table like `de-DE` for decimal commas
+    #[clap(long, value_parser = kinds::<LocaleKind, Locale>(), ignore_case = true, default_value_t)]
+    locale: Locale,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum SimulateFormat {
+    /// A row per number of worked days and per length of the longest day with the share of runs
+    Table,
+    /// The simulation as a JSON object
+    Json,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ReportFormat {
+    /// A row per month with the hours, the target and the cumulative balance
+    Table,
+    /// The report as a JSON object
+    Json,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum SchemaKind {
+    /// The JSON of `generate --format json`, also read by `validate`
+    Timesheet,
+    /// The JSON object of `generate --stdin`
+    Request,
+    /// The rows of `generate --constraints`
+    Constraints,
+    /// The configuration file, see `--config`
+    Config,
+}
+
+impl From<SchemaKind> for Schema {
+    fn from(kind: SchemaKind) -> Self {
+        match kind {
+            SchemaKind::Timesheet => Schema::Timesheet,
+            SchemaKind::Request => Schema::Request,
+            SchemaKind::Constraints => Schema::Constraints,
+            SchemaKind::Config => Schema::Config,
+        }
+    }
+}
+
+/// A parser of the library type `T` offering the values of `K` to the help and the completions.
+fn kinds<K, T>() -> MapValueParser<EnumValueParser<K>, fn(K) -> T>
+where
+    K: ValueEnum + Clone + Send + Sync + 'static,
+    T: From<K> + Clone + Send + Sync + 'static,
+{
+    EnumValueParser::<K>::new().map(T::from)
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum StateKind {
+    /// Baden-Württemberg
+    #[value(name = "BW")]
+    BadenWuerttemberg,
+    /// Bayern
+    #[value(name = "BY")]
+    Bavaria,
+    /// Berlin
+    #[value(name = "BE")]
+    Berlin,
+    /// Brandenburg
+    #[value(name = "BB")]
+    Brandenburg,
+    /// Bremen
+    #[value(name = "HB")]
+    Bremen,
+    /// Hamburg
+    #[value(name = "HH")]
+    Hamburg,
+    /// Hessen
+    #[value(name = "HE")]
+    Hesse,
+    /// Niedersachsen
+    #[value(name = "NI")]
+    LowerSaxony,
+    /// Mecklenburg-Vorpommern
+    #[value(name = "MV")]
+    MecklenburgVorpommern,
+    /// Nordrhein-Westfalen
+    #[value(name = "NW")]
+    NorthRhineWestphalia,
+    /// Rheinland-Pfalz
+    #[value(name = "RP")]
+    RhinelandPalatinate,
+    /// Saarland
+    #[value(name = "SL")]
+    Saarland,
+    /// Sachsen
+    #[value(name = "SN")]
+    Saxony,
+    /// Sachsen-Anhalt
+    #[value(name = "ST")]
+    SaxonyAnhalt,
+    /// Schleswig-Holstein
+    #[value(name = "SH")]
+    SchleswigHolstein,
+    /// Thüringen
+    #[value(name = "TH")]
+    Thuringia,
+}
+
+impl From<StateKind> for State {
+    fn from(kind: StateKind) -> Self {
+        match kind {
+            StateKind::BadenWuerttemberg => State::BadenWuerttemberg,
+            StateKind::Bavaria => State::Bavaria,
+            StateKind::Berlin => State::Berlin,
+            StateKind::Brandenburg => State::Brandenburg,
+            StateKind::Bremen => State::Bremen,
+            StateKind::Hamburg => State::Hamburg,
+            StateKind::Hesse => State::Hesse,
+            StateKind::LowerSaxony => State::LowerSaxony,
+            StateKind::MecklenburgVorpommern => State::MecklenburgVorpommern,
+            StateKind::NorthRhineWestphalia => State::NorthRhineWestphalia,
+            StateKind::RhinelandPalatinate => State::RhinelandPalatinate,
+            StateKind::Saarland => State::Saarland,
+            StateKind::Saxony => State::Saxony,
+            StateKind::SaxonyAnhalt => State::SaxonyAnhalt,
+            StateKind::SchleswigHolstein => State::SchleswigHolstein,
+            StateKind::Thuringia => State::Thuringia,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum FormatKind {
+    /// A line per day for the terminal
+    Text,
+    /// Comma separated values for spreadsheets
+    Csv,
+    /// A JSON object, see `schema timesheet`
+    Json,
+    /// A table for the issue trackers and wikis rendering Markdown
+    Markdown,
+    /// The CSV of the attendance import of Personio
+    Personio,
+}
+
+impl From<FormatKind> for Format {
+    fn from(kind: FormatKind) -> Self {
+        match kind {
+            FormatKind::Text => Format::Text,
+            FormatKind::Csv => Format::Csv,
+            FormatKind::Json => Format::Json,
+            FormatKind::Markdown => Format::Markdown,
+            FormatKind::Personio => Format::Personio,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum LocaleKind {
+    /// Decimal points and no grouping, `1234.5`
+    #[value(name = "C")]
+    C,
+    /// Decimal points and commas grouping the thousands, `1,234.5`
+    #[value(name = "en-US", alias = "en_US")]
+    EnUs,
+    /// Decimal commas and points grouping the thousands, `1.234,5`
+    #[value(name = "de-DE", alias = "de_DE")]
+    DeDe,
+}
+
+impl From<LocaleKind> for Locale {
+    fn from(kind: LocaleKind) -> Self {
+        match kind {
+            LocaleKind::C => Locale::C,
+            LocaleKind::EnUs => Locale::EnUs,
+            LocaleKind::DeDe => Locale::DeDe,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum LayoutKind {
+    /// A row per day worked
+    Daily,
+    /// A table per week with a column per day from Monday to Sunday
+    Weekly,
+    /// The month as an HTML wall calendar
+    Calendar,
+    /// A row per day worked split by a `Pause` row per break
+    Pauses,
+}
+
+impl From<LayoutKind> for Layout {
+    fn from(kind: LayoutKind) -> Self {
+        match kind {
+            LayoutKind::Daily => Layout::Daily,
+            LayoutKind::Weekly => Layout::Weekly,
+            LayoutKind::Calendar => Layout::Calendar,
+            LayoutKind::Pauses => Layout::Pauses,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ProgressKind {
+    /// A bar redrawn in place
+    Bar,
+    /// A JSON object per step like `{"done":2,"total":6,"item":"2024-11"}`
+    Json,
+}
+
+impl From<ProgressKind> for progress::Style {
+    fn from(kind: ProgressKind) -> Self {
+        match kind {
+            ProgressKind::Bar => progress::Style::Bar,
+            ProgressKind::Json => progress::Style::Json,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum EncodingKind {
+    /// UTF-8
+    Utf8,
+    /// UTF-8 starting with a byte order mark
+    Utf8Bom,
+    /// ISO 8859-1, characters outside of it are written as `?`
+    Latin1,
+}
+
+impl From<EncodingKind> for Encoding {
+    fn from(kind: EncodingKind) -> Self {
+        match kind {
+            EncodingKind::Utf8 => Encoding::Utf8,
+            EncodingKind::Utf8Bom => Encoding::Utf8Bom,
+            EncodingKind::Latin1 => Encoding::Latin1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum LineEndingKind {
+    /// A line feed
+    Lf,
+    /// A carriage return followed by a line feed
+    Crlf,
+}
+
+impl From<LineEndingKind> for LineEnding {
+    fn from(kind: LineEndingKind) -> Self {
+        match kind {
+            LineEndingKind::Lf => LineEnding::Lf,
+            LineEndingKind::Crlf => LineEnding::Crlf,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum RotationKind {
+    /// Draw the activity of every day on its own by the weights
+    Independent,
+    /// Draw every activity at least once and split the remaining days by the weights
+    Balanced,
+}
+
+impl From<RotationKind> for Rotation {
+    fn from(kind: RotationKind) -> Self {
+        match kind {
+            RotationKind::Independent => Rotation::Independent,
+            RotationKind::Balanced => Rotation::Balanced,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum RngKind {
+    /// Random number generator of the current thread
+    Thread,
+    /// Deterministic generator seeded with `--seed`
+    Seeded,
+    /// Random number generator of the operating system
+    Os,
+}
+
+impl From<RngKind> for Generator {
+    fn from(kind: RngKind) -> Self {
+        match kind {
+            RngKind::Thread => Generator::Thread,
+            RngKind::Seeded => Generator::Seeded,
+            RngKind::Os => Generator::Os,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum RemainderKind {
+    /// A quarter of an hour each on different random days
+    Spread,
+    /// All of it on the last day
+    LastDay,
+    /// All of it on one random day
+    RandomDay,
+}
+
+#[derive(Copy, Clone, PartialEq, ValueEnum)]
+enum DistributionKind {
+    /// Every distribution of the hours is equally likely
+    Uniform,
+    /// The days are as equally long as possible
+    Even,
+    /// The days are weighted by `--weights`
+    Weighted,
+    /// The days at the start of the month are longer
+    FrontLoaded,
+    /// The days at the end of the month are longer
+    BackLoaded,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum RoundingKind {
+    /// Both ends to the nearest step
+    Nearest,
+    /// Starts upwards and ends downwards
+    Employer,
+    /// Starts downwards and ends upwards
+    Employee,
+    /// The start to the nearest step and the time of the day to the nearest step
+    Daily,
+}
+
+impl From<RoundingKind> for Rounding {
+    fn from(kind: RoundingKind) -> Self {
+        match kind {
+            RoundingKind::Nearest => Rounding::Nearest,
+            RoundingKind::Employer => Rounding::Employer,
+            RoundingKind::Employee => Rounding::Employee,
+            RoundingKind::Daily => Rounding::Daily,
+        }
+    }
+}
+
+/// The rules of `--break`, else the `[[breaks]]` of the configuration or `fallback` if it has none.
+fn break_rules(
+    breaks: Vec<BreakRule>,
+    no_breaks: bool,
+    fallback: &[BreakRule],
+    error_format: ErrorFormat,
+) -> Vec<BreakRule> {
+    if no_breaks {
+        return Vec::new();
+    }
+    if !breaks.is_empty() {
+        return breaks;
+    }
+    let configured = config(error_format).breaks;
+    if configured.is_empty() {
+        fallback.to_vec()
+    } else {
+        configured
+    }
+}
+
+/// The rounding of `step` and `kind`, the `[rounding]` of the configuration if both are missing.
+fn rounding_policy(
+    step: Option<u32>,
+    kind: Option<RoundingKind>,
+    error_format: ErrorFormat,
+) -> Option<RoundingPolicy> {
+    if step.is_none() && kind.is_none() {
+        return config(error_format).rounding;
+    }
+    let default = RoundingPolicy::default();
+    Some(RoundingPolicy {
+        step: step.unwrap_or(default.step),
+        mode: kind.map_or(default.mode, Rounding::from),
+    })
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum RealismKind {
+    /// Earlier Fridays, later Mondays, lunch breaks and clustered starts
+    Standard,
+}
+
+/// The windows of `--realism standard` within `earliest` and `latest` for the Mondays and Fridays
+/// without one of the `windows`.
+fn realistic_windows(earliest: u32, latest: u32, windows: &[WeekdayWindow]) -> Vec<WeekdayWindow> {
+    let monday = (earliest + 1 < latest).then(|| WeekdayWindow {
+        day_of_week: DayOfWeek::Monday,
+        time: TimeSpan::hours(earliest + 1, latest),
+    });
+    let friday = (earliest + 1 < 15 && 15 < latest).then(|| WeekdayWindow {
+        day_of_week: DayOfWeek::Friday,
+        time: TimeSpan::hours(earliest, 15),
+    });
+    [monday, friday]
+        .into_iter()
+        .flatten()
+        .filter(|window| {
+            !windows
+                .iter()
+                .any(|other| other.day_of_week == window.day_of_week)
+        })
+        .collect()
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum ShapeKind {
+    /// Towards the start of the month
+    Front,
+    /// Towards the end of the month
+    Back,
+    /// Towards the middle of the month
+    Mid,
+}
+
+impl From<ShapeKind> for Shape {
+    fn from(kind: ShapeKind) -> Self {
+        match kind {
+            ShapeKind::Front => Shape::Front,
+            ShapeKind::Back => Shape::Back,
+            ShapeKind::Mid => Shape::Mid,
+        }
+    }
+}
+
+/// The strategy of `--distribution` with the `--weights` of a weighted one.
+fn strategy(kind: DistributionKind, weights: &[u32], error_format: ErrorFormat) -> Strategy {
+    match kind {
+        DistributionKind::Uniform => Strategy::Uniform,
+        DistributionKind::Even => Strategy::Even,
+        DistributionKind::Weighted => {
+            if weights.len() != 5 && weights.len() != 7 {
+                exit_with(
+                    error_format,
+                    Error::WeightsOfWeek {
+                        count: weights.len() as u32,
+                    },
+                )
+            }
+            let mut week = [0; 7];
+            week[..weights.len()].copy_from_slice(weights);
+            Strategy::Weighted(week)
+        }
+        DistributionKind::FrontLoaded => Strategy::FrontLoaded,
+        DistributionKind::BackLoaded => Strategy::BackLoaded,
+    }
+}
+
+impl From<RemainderKind> for Remainder {
+    fn from(kind: RemainderKind) -> Self {
+        match kind {
+            RemainderKind::Spread => Remainder::Spread,
+            RemainderKind::LastDay => Remainder::LastDay,
+            RemainderKind::RandomDay => Remainder::RandomDay,
+        }
+    }
 }
 
 fn hour_in_range(s: &str) -> Result<u32, String> {
@@ -46,6 +1442,23 @@ fn hour_in_range(s: &str) -> Result<u32, String> {
     }
 }
 
+fn hours_and_minutes(s: &str) -> Result<(NonZeroU32, u32), String> {
+    let invalid = || format!("`{}` isn't a number of hours like `40` or `37:30`", s);
+    let (hours, minutes): (_, u32) = match s.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes.parse().map_err(|_| invalid())?),
+        None => (s, 0),
+    };
+    let hours: NonZeroU32 = hours.parse().map_err(|_| invalid())?;
+    if minutes < 60 && minutes.is_multiple_of(GRANULARITY) {
+        Ok((hours, minutes))
+    } else {
+        Err(format!(
+            "Minutes have to be a multiple of {} below 60",
+            GRANULARITY
+        ))
+    }
+}
+
 fn year_in_range(s: &str) -> Result<u32, String> {
     let year: u32 = s.parse().map_err(|_| format!("`{}` isn't a year", s))?;
     if year >= 1970 {
@@ -64,94 +1477,2192 @@ fn month_in_range(s: &str) -> Result<NonZeroU32, String> {
     }
 }
 
-fn main() {
-    let Arguments {
-        month,
-        year,
-        hours,
-        max_per_day,
-        earliest,
-        latest,
-        csv,
-    } = Arguments::parse();
+fn year_and_month(s: &str) -> Result<(u32, NonZeroU32), String> {
+    let (year, month) = s
+        .split_once('-')
+        .ok_or_else(|| format!("`{}` isn't a month, expected `year-month`", s))?;
+    Ok((year_in_range(year)?, month_in_range(month)?))
+}
 
-    if latest < earliest {
-        Arguments::command()
-            .error(
-                ErrorKind::ArgumentConflict,
-                &format!("Earliest has to be before latest"),
-            )
-            .exit();
-    }
+fn free_days(s: &str) -> Result<FreeDays, String> {
+    let day_of_week = match s.to_ascii_lowercase().as_str() {
+        "random" => return Ok(FreeDays::Random),
+        "spread" => return Ok(FreeDays::Spread),
+        "mon" => DayOfWeek::Monday,
+        "tue" => DayOfWeek::Tuesday,
+        "wed" => DayOfWeek::Wednesday,
+        "thu" => DayOfWeek::Thursday,
+        "fri" => DayOfWeek::Friday,
+        _ => {
+            return Err(format!(
+                "`{}` isn't a weekday from `mon` to `fri`, `random` or `spread`",
+                s
+            ))
+        }
+    };
+    Ok(FreeDays::Weekday(day_of_week))
+}
 
-    let max_per_day = if earliest + max_per_day <= latest {
-        max_per_day
+fn minutes(s: &str) -> Result<u32, String> {
+    let invalid = || format!("`{}` isn't a duration like `1h`, `45m` or `1h30m`", s);
+    let (hours, rest) = match s.split_once('h') {
+        Some((hours, rest)) => (hours.parse::<u32>().map_err(|_| invalid())?, rest),
+        None => (0, s),
+    };
+    let minutes = match rest {
+        "" if s.ends_with('h') => 0,
+        rest => rest
+            .strip_suffix('m')
+            .and_then(|minutes| minutes.parse::<u32>().ok())
+            .ok_or_else(invalid)?,
+    };
+    let minutes = hours * 60 + minutes;
+    if minutes.is_multiple_of(GRANULARITY) {
+        Ok(minutes)
     } else {
-        let max_per_day = latest - earliest;
-        println!(
-            "Reducing max hours per day to {} (time restrictions)",
-            max_per_day
+        Err(format!(
+            "Durations have to be a multiple of {} minutes",
+            GRANULARITY
+        ))
+    }
+}
+
+/// Reads an amount of euros like `538`, `13.5` or `13,50` into cents.
+fn euros(s: &str) -> Result<u32, String> {
+    let invalid = || format!("`{}` isn't an amount of euros like `13.50`", s);
+    let (euros, cents) = s.split_once(['.', ',']).unwrap_or((s, ""));
+    if cents.len() > 2 || !cents.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let euros: u32 = euros.parse().map_err(|_| invalid())?;
+    let cents: u32 = format!("{:0<2}", cents).parse().map_err(|_| invalid())?;
+    match euros
+        .checked_mul(100)
+        .and_then(|euros| euros.checked_add(cents))
+    {
+        Some(0) => Err("The amount has to be positive".into()),
+        Some(cents) => Ok(cents),
+        None => Err(invalid()),
+    }
+}
+
+fn break_rule(s: &str) -> Result<BreakRule, String> {
+    let (after, deduct) = s
+        .split_once('=')
+        .ok_or_else(|| format!("`{}` isn't a break like `6h=30m`", s))?;
+    Ok(BreakRule {
+        after: minutes(after)?,
+        deduct: minutes(deduct)?,
+    })
+}
+
+/// The date of a contract from the year and day of `--contract-from` or `--contract-to`.
+fn contract_date((year, date): (u32, DateOfYear)) -> contract::Date {
+    contract::Date {
+        year,
+        month: date.month,
+        day: date.day,
+    }
+}
+
+fn date_in_range(s: &str) -> Result<(u32, DateOfYear), String> {
+    let invalid = || format!("`{}` isn't a date, expected `day.month.year`", s);
+    let [day, month, year] = s.split('.').collect::<Vec<_>>()[..] else {
+        return Err(invalid());
+    };
+    let year = year_in_range(year)?;
+    let month = month_in_range(month)?;
+    let day: NonZeroU32 = day.parse().map_err(|_| invalid())?;
+    if day.get() > Year::new(year).days_of_month(month) {
+        return Err(invalid());
+    }
+    Ok((year, DateOfYear::new(day, month)))
+}
+
+fn exit_with(format: ErrorFormat, error: Error) -> ! {
+    match format {
+        ErrorFormat::Text => {
+            let _ = Arguments::command()
+                .error(ErrorKind::ArgumentConflict, &error)
+                .print();
+        }
+        ErrorFormat::Json => {
+            let mut value = serde_json::to_value(&error).expect("errors serialize to json");
+            value["message"] = error.to_string().into();
+            eprintln!("{}", value);
+        }
+    }
+    let code = match error {
+        Error::Parse { .. } | Error::UnknownProfile(_) | Error::MissingSetting { .. } => {
+            EXIT_INVALID
+        }
+        _ => EXIT_INFEASIBLE,
+    };
+    process::exit(code)
+}
+
+fn exit_with_io(format: ErrorFormat, error: io::Error) -> ! {
+    // a file that was read but doesn't parse, like a broken configuration or archived timesheet
+    let (code, exit_code) = match error.kind() {
+        io::ErrorKind::InvalidData => ("invalid_data", EXIT_INVALID),
+        _ => ("io", EXIT_IO),
+    };
+    match format {
+        ErrorFormat::Text => {
+            let _ = Arguments::command().error(ErrorKind::Io, &error).print();
+        }
+        ErrorFormat::Json => {
+            let value = serde_json::json!({ "code": code, "message": error.to_string() });
+            eprintln!("{}", value);
+        }
+    }
+    process::exit(exit_code)
+}
+
+fn main() {
+    let Arguments {
+        error_format,
+        config,
+        state_dir,
+        command,
+    } = Arguments::parse();
+    let _ = LOCATIONS.set(Locations::new(config, state_dir));
+    match command {
+        Command::Generate(arguments) => generate(*arguments, error_format),
+        Command::Calendar(arguments) => calendar(arguments, error_format),
+        Command::Config(ConfigCommand::Init { force }) => init_config(force, error_format),
+        Command::Config(ConfigCommand::Show(arguments)) => show_config(arguments, error_format),
+        Command::Config(ConfigCommand::Validate { file }) => validate_config(file, error_format),
+        Command::Digest(arguments) => digest(arguments, error_format),
+        Command::Holidays(HolidaysCommand::Export {
+            year,
+            state,
+            format,
+            encoding,
+        }) => holidays(year, state, format, encoding, error_format),
+        Command::Holidays(HolidaysCommand::Analyze {
+            year,
+            state,
+            city,
+            format,
+        }) => analyze(year, state, city, format, error_format),
+        Command::Invoice(arguments) => invoice(arguments, error_format),
+        Command::List => list(error_format),
+        Command::Report(arguments) => report(arguments, error_format),
+        Command::Schema { format } => print!("{}", Schema::from(format).json()),
+        Command::Simulate(arguments) => simulate(arguments, error_format),
+        Command::Smooth(arguments) => smooth(arguments, error_format),
+        Command::Vacation(command) => vacation(command, error_format),
+        Command::Validate(arguments) => validate(arguments, error_format),
+        #[cfg(feature = "push")]
+        Command::Push(arguments) => push(arguments, error_format),
+        #[cfg(feature = "server")]
+        Command::Serve(ServeArguments { address }) => {
+            println!("Listening on http://{}", address);
+            if let Err(e) = stundenzettel::server::serve(&address) {
+                exit_with_io(error_format, e);
+            }
+        }
+        #[cfg(feature = "signing")]
+        Command::Key(command) => key(command, error_format),
+        #[cfg(feature = "signing")]
+        Command::Verify(arguments) => verify_signature(arguments, error_format),
+        #[cfg(feature = "update")]
+        Command::Itself(SelfCommand::CheckUpdate) => check_update(error_format),
+    }
+}
+
+fn generate(arguments: GenerateArguments, error_format: ErrorFormat) {
+    if let Some(path) = &arguments.trace {
+        let sink: Box<dyn Write> = if path.as_os_str() == "-" {
+            Box::new(io::stderr())
+        } else {
+            Box::new(fs::File::create(path).unwrap_or_else(|e| exit_with_io(error_format, e)))
+        };
+        trace::start(sink);
+    }
+    if let Some(term) = arguments.period {
+        let banked = arguments.bank.then(|| bank(term, &arguments, error_format));
+        let mut progress = arguments
+            .progress
+            .map(|style| Progress::new(style, term.months().count() as u32, io::stderr()));
+        for ((year, month), offset) in term.months().zip(0..) {
+            let banked = banked.as_ref().map(|banked| banked[offset as usize].hours);
+            if banked != Some(0) {
+                generate(
+                    GenerateArguments {
+                        month: Some(month),
+                        year: Some(year),
+                        seed: arguments.seed.map(|seed| seed.wrapping_add(offset)),
+                        period: None,
+                        progress: None,
+                        banked,
+                        trace: None,
+                        ..arguments.clone()
+                    },
+                    error_format,
+                );
+            }
+            if let Some(progress) = &mut progress {
+                // the progress is only informative, failing to write it doesn't stop generating
+                let _ = progress.advance(format_args!("{}-{:02}", year, month));
+            }
+        }
+        if let Some(progress) = progress {
+            let _ = progress.finish();
+        }
+        return;
+    }
+    let mut config = config(error_format);
+    let GenerateArguments {
+        month,
+        year,
+        hours,
+        remainder,
+        max_per_day,
+        earliest,
+        latest,
+        state,
+        city,
+        #[cfg(feature = "nager")]
+        mut holidays_from_api,
+        format,
+        csv,
+        encoding,
+        line_ending,
+        layout,
+        no_header,
+        redact,
+        rng,
+        seed,
+        no_archive,
+        stdin,
+        reproduce,
+        if_missing,
+        check_only,
+        show_plan,
+        stats,
+        locale,
+        audit,
+        mut max_score,
+        trace: _,
+        avoid_round_weeks: mut roughen,
+        max_per_week,
+        realism,
+        realism_profile,
+        mut free_days,
+        mut max_free_streak,
+        mut off_days,
+        distribution,
+        weights,
+        shape,
+        mut like,
+        mut jitter,
+        step,
+        rounding,
+        breaks,
+        no_breaks,
+        constraints,
+        note,
+        notes,
+        activities: rotation,
+        mut blackout,
+        mut window,
+        min_per_day,
+        strict,
+        earnings_cap,
+        rate,
+        pay_column,
+        timezone,
+        contract_from,
+        contract_to,
+        period: _,
+        bank: _,
+        month_cap: _,
+        progress: _,
+        banked,
+        jobs,
+        #[cfg(feature = "mail")]
+        mail_to,
+        #[cfg(feature = "webdav")]
+        upload,
+    } = arguments;
+
+    let format = match format {
+        _ if csv => Format::Csv,
+        Some(format) => format,
+        None => config.format.unwrap_or_default(),
+    };
+    let policy = if strict {
+        Policy::Strict
+    } else {
+        Policy::Adjust
+    };
+    if jobs {
+        let (Some(month), Some(year)) = (month, year) else {
+            unreachable!("clap requires the month and year with --jobs")
+        };
+        let plan = validate_plan(
+            plan::Arguments {
+                year,
+                month,
+                parameters: SheetParameters {
+                    state: state.unwrap_or_default(),
+                    city,
+                    hours: 0,
+                    from: earliest,
+                    to: latest,
+                    max_per_day,
+                },
+                minutes: 0,
+                pro_rated: false,
+                windows: &window,
+                rounding: None,
+                breaks: &[],
+                constraints: &[],
+                format,
+                layout,
+                encoding,
+                jobs,
+                policy,
+                rng: rng.map(Generator::from),
+                seed,
+                timezone,
+                activities: rotation.is_some(),
+                contract_hours: false,
+                api_holidays: false,
+                config: &config,
+            },
+            error_format,
+        );
+        let (contracts, sheets) = generate_config_jobs(
+            month,
+            year,
+            plan.source,
+            constraints,
+            &blackout,
+            policy,
+            error_format,
         );
-        max_per_day
+        let output = Output {
+            format,
+            layout,
+            encoding,
+            line_ending,
+            redact,
+        };
+        write_jobs(&contracts, &sheets, output, error_format);
+        if contracts.iter().any(|contract| contract.rate.is_some()) {
+            let split = CostSplit::new(
+                contracts
+                    .iter()
+                    .zip(&sheets)
+                    .map(|(contract, sheet)| (contract.employer.as_deref(), sheet, contract.rate)),
+            );
+            eprint!("{}", locale.display(&split));
+        }
+        return;
+    }
+
+    let profile = realism_profile.map(|name| {
+        config
+            .realism
+            .remove(&name)
+            .unwrap_or_else(|| exit_with(error_format, Error::UnknownProfile(name)))
+    });
+    let reproduction = reproduce.map(|blob| {
+        serde_json::from_str::<Reproduction>(&blob).unwrap_or_else(|e| {
+            exit_with(
+                error_format,
+                Error::Parse {
+                    line: e.line(),
+                    message: e.to_string(),
+                },
+            )
+        })
+    });
+    let mut contract_constraints = Vec::new();
+    let mut minutes = 0;
+    let mut pro_rated = false;
+    let mut remainder = Remainder::from(remainder);
+    let mut weekly_cap = None;
+    let mut cluster = realism.is_some();
+    let mut strategy = distribution
+        .map(|kind| self::strategy(kind, &weights, error_format))
+        .or(shape.map(|kind| Strategy::Shaped(kind.into())));
+    if let Some(profile) = &profile {
+        if strategy.is_none() && !profile.weights.is_empty() {
+            strategy = Some(self::strategy(
+                DistributionKind::Weighted,
+                &profile.weights,
+                error_format,
+            ));
+        }
+        cluster |= profile.cluster_starts;
+        roughen |= profile.avoid_round_weeks;
+    }
+    let (mut rounding, mut breaks) = if reproduction.is_none() {
+        (
+            rounding_policy(step, rounding, error_format),
+            break_rules(
+                match &profile {
+                    Some(profile) if breaks.is_empty() => profile.breaks.clone(),
+                    _ => breaks,
+                },
+                no_breaks,
+                if realism.is_some() {
+                    &BreakRule::STATUTORY
+                } else {
+                    &[]
+                },
+                error_format,
+            ),
+        )
+    } else {
+        (None, Vec::new())
     };
+    let rotated = rotation.is_some();
+    let mut rotation = rotation.map(Rotation::from);
+    let mut activities = match rotation {
+        Some(_) => config.activities.clone(),
+        None => Vec::new(),
+    };
+    let contract_hours = reproduction.is_none() && !stdin && hours.is_none();
+    let api_holidays = reproduction
+        .as_ref()
+        .is_some_and(|reproduction| reproduction.holidays_from_api.is_some());
+    let request = if let Some(reproduction) = &reproduction {
+        minutes = reproduction.minutes;
+        remainder = reproduction.remainder;
+        roughen = reproduction.avoid_round_weeks;
+        weekly_cap = reproduction.weekly_cap;
+        cluster = reproduction.cluster_starts;
+        free_days = reproduction.free_days;
+        max_free_streak = reproduction.max_free_streak;
+        off_days = reproduction.off_days;
+        strategy = reproduction.distribution;
+        like = reproduction.like.as_deref().map(|month| {
+            year_and_month(month).unwrap_or_else(|message| {
+                exit_with(error_format, Error::Parse { line: 1, message })
+            })
+        });
+        jitter = reproduction.jitter;
+        rounding = reproduction.rounding;
+        breaks = reproduction.breaks.clone();
+        max_score = reproduction.max_score;
+        rotation = reproduction.rotation;
+        activities = reproduction.activities.clone();
+        #[cfg(feature = "nager")]
+        {
+            holidays_from_api = reproduction.holidays_from_api.clone();
+        }
+        reproduction.request.clone()
+    } else if stdin {
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut input) {
+            exit_with_io(error_format, e);
+        }
+        serde_json::from_str(&input).unwrap_or_else(|e| {
+            exit_with(
+                error_format,
+                Error::Parse {
+                    line: e.line(),
+                    message: e.to_string(),
+                },
+            )
+        })
+    } else {
+        let (Some(month), Some(year)) = (month, year) else {
+            unreachable!("clap requires the month and year without --stdin")
+        };
+        let calendar_year = Year::new(year);
+        let calendar_month =
+            Month::new(month, &calendar_year).unwrap_or_else(|e| exit_with(error_format, e));
+        let (from, to) = (
+            contract_from.map(contract_date),
+            contract_to.map(contract_date),
+        );
+        match hours {
+            Some((hours, remainder)) => {
+                let state = state.unwrap_or_default();
+                let period = Period {
+                    start: from,
+                    end: to,
+                };
+                minutes = remainder;
+                let hours = if period == Period::default() {
+                    hours.get()
+                } else {
+                    pro_rated = true;
+                    contract_constraints = period.constraints(year, &calendar_month);
+                    Region::new(state, city)
+                        .and_then(|region| {
+                            period.prorate(hours.get(), &calendar_year, &calendar_month, region)
+                        })
+                        .unwrap_or_else(|e| exit_with(error_format, e))
+                };
+                GenerateRequest {
+                    month,
+                    year,
+                    hours,
+                    max_per_day,
+                    earliest,
+                    latest,
+                    state,
+                    city,
+                    seed,
+                }
+            }
+            None => match config.contract.clone() {
+                // `plan::validate` rejects generating without the hours and the contract
+                None => GenerateRequest {
+                    month,
+                    year,
+                    hours: 0,
+                    max_per_day,
+                    earliest,
+                    latest,
+                    state: state.unwrap_or_default(),
+                    city,
+                    seed,
+                },
+                Some(mut contract) => {
+                    contract.start = from.or(contract.start);
+                    contract.end = to.or(contract.end);
+                    contract_constraints = contract.period().constraints(year, &calendar_month);
+                    let request = contract
+                        .request(&calendar_year, &calendar_month)
+                        .unwrap_or_else(|e| exit_with(error_format, e));
+                    let (state, city) = match state {
+                        Some(state) => (state, city),
+                        None => (request.state, city.or(request.city)),
+                    };
+                    GenerateRequest {
+                        hours: banked.unwrap_or(request.hours),
+                        state,
+                        city,
+                        seed,
+                        ..request
+                    }
+                }
+            },
+        }
+    };
+    let GenerateRequest {
+        month,
+        year,
+        mut hours,
+        max_per_day,
+        earliest,
+        latest,
+        state,
+        city,
+        seed,
+    } = request;
+
+    // a random seed instead of the thread generator so that the run can be reproduced
+    let seed = match (rng, seed, stats) {
+        (None, None, None) => Some(make_rng(RngSource::Thread).next_u64()),
+        _ => seed,
+    };
+
+    if let (Some(cap), Some(rate)) = (earnings_cap, rate) {
+        let cap = EarningsCap { cap, rate };
+        let adjustment = policy
+            .cap_earnings(&mut hours, &mut minutes, &cap)
+            .unwrap_or_else(|e| exit_with(error_format, e));
+        warn(adjustment, error_format);
+    }
+
+    if let Some(profile) = &profile {
+        let given: Vec<_> = window.iter().map(|window| window.day_of_week).collect();
+        window.extend(
+            profile
+                .windows
+                .iter()
+                .filter(|window| !given.contains(&window.day_of_week)),
+        );
+    }
+    if realism.is_some() {
+        window.extend(realistic_windows(earliest, latest, &window));
+    }
 
     let year = Year::new(year);
-    let month = Month::new(month, &year);
-    let days = non_holidays_of_month(&month, &year);
-
-    if max_per_day.saturating_mul(days.len().try_into().unwrap()) < hours.get() {
-        Arguments::command()
-            .error(
-                ErrorKind::ArgumentConflict,
-                &format!(
-                    "Can't distribute {} hours into {} days with at most {} hours per day",
-                    hours.get(),
-                    days.len(),
-                    max_per_day
+    let month = Month::new(month, &year).unwrap_or_else(|e| exit_with(error_format, e));
+    let mut constraints = match &reproduction {
+        Some(reproduction) => {
+            let blackouts = reproduction
+                .blackouts()
+                .unwrap_or_else(|e| exit_with(error_format, e));
+            blackout.extend(blackouts);
+            reproduction
+                .date_constraints()
+                .unwrap_or_else(|e| exit_with(error_format, e))
+        }
+        None => read_constraints(constraints, &year, &month, error_format),
+    };
+    constraints.extend(contract_constraints);
+    let plan = validate_plan(
+        plan::Arguments {
+            year: year.year(),
+            month: month.month(),
+            parameters: SheetParameters {
+                state,
+                city,
+                hours,
+                from: earliest,
+                to: latest,
+                max_per_day,
+            },
+            minutes,
+            pro_rated,
+            windows: &window,
+            rounding,
+            breaks: &breaks,
+            constraints: &constraints,
+            format,
+            layout,
+            encoding,
+            jobs,
+            policy,
+            rng: rng.map(Generator::from),
+            seed,
+            timezone,
+            activities: rotated,
+            contract_hours,
+            api_holidays,
+            config: &config,
+        },
+        error_format,
+    );
+    blackout.extend(plan.blackouts);
+    let parameters = plan.parameters;
+    let rng_source = plan.source;
+
+    if if_missing && archive(error_format).contains(year.year(), month.month()) {
+        return;
+    }
+
+    if let Some(hours) = max_per_week {
+        let load = |(year, month): (u32, u32)| {
+            let month = NonZeroU32::new(month).expect("months start at 1");
+            archive(error_format)
+                .load(year, month)
+                .unwrap_or_else(|e| exit_with_io(error_format, e))
+        };
+        let (y, m) = (year.year(), month.month().get());
+        let previous = load(if m == 1 { (y - 1, 12) } else { (y, m - 1) });
+        let next = load(if m == 12 { (y + 1, 1) } else { (y, m + 1) });
+        weekly_cap = Some(WeeklyCap::new(
+            hours * 60,
+            &month,
+            previous.as_ref(),
+            next.as_ref(),
+        ));
+    }
+
+    if let Some(min_per_day) = min_per_day {
+        constraints.extend(exclude_short_days(
+            &year,
+            &month,
+            parameters.from,
+            parameters.to,
+            &blackout,
+            min_per_day,
+        ));
+    }
+
+    let region = Region::new(state, city).unwrap_or_else(|e| exit_with(error_format, e));
+    #[cfg(feature = "nager")]
+    let api = holidays_from_api.as_ref().map(|selection| {
+        let cache = NagerHolidays::default_cache();
+        NagerHolidays::fetch(selection, year.year(), cache.as_deref())
+            .unwrap_or_else(|e| exit_with_io(error_format, io::Error::other(e)))
+    });
+    #[cfg(feature = "nager")]
+    let holidays: &dyn HolidaySource = match &api {
+        Some(api) => api,
+        None => &region,
+    };
+    #[cfg(not(feature = "nager"))]
+    let holidays: &dyn HolidaySource = &region;
+
+    let plan = || {
+        plan_timesheet(&year, &month, parameters, holidays, &constraints, &blackout)
+            .unwrap_or_else(|e| exit_with(error_format, e))
+    };
+    if show_plan {
+        let plan = plan();
+        if format == Format::Json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&plan).expect("plans serialize")
+            );
+        } else {
+            print!("{}", plan);
+        }
+        return;
+    }
+
+    let finish = |sheet: &mut Timesheet, rng: &mut dyn RngCore| {
+        if let Some(free_days) = free_days {
+            place_free_days(
+                sheet,
+                free_days,
+                &parameters,
+                holidays,
+                &constraints,
+                &blackout,
+                rng,
+            )?;
+        }
+        if let Some(max) = max_free_streak {
+            limit_free_streaks(
+                sheet,
+                max,
+                &parameters,
+                holidays,
+                &constraints,
+                &blackout,
+                rng,
+            )?;
+        }
+        if let Some(percent) = off_days {
+            work_off_days(
+                sheet,
+                percent,
+                &parameters,
+                holidays,
+                &constraints,
+                &blackout,
+                rng,
+            )?;
+        }
+        if minutes != 0 {
+            place_remainder(
+                sheet,
+                minutes,
+                remainder,
+                &parameters,
+                &constraints,
+                &blackout,
+                rng,
+            )?;
+        }
+        if roughen {
+            avoid_round_weeks(sheet, &parameters, &constraints, &blackout, rng)?;
+        }
+        if cluster {
+            cluster_starts(sheet, &parameters, &constraints, &blackout, rng);
+        }
+        if let Some(cap) = &weekly_cap {
+            cap_weeks(sheet, cap, &parameters, &constraints, &blackout, rng)?;
+        }
+        if !breaks.is_empty() {
+            add_breaks(sheet, &breaks, &parameters, &constraints, &blackout)?;
+        }
+        Ok(())
+    };
+    if let Some(samples) = stats {
+        let start = seed.unwrap_or_default();
+        let plan = plan();
+        let sheets = (0..samples.get())
+            .map(|i| {
+                let mut rng = seeded(start.wrapping_add(i.into()));
+                let mut sheet = plan.execute(strategy.unwrap_or_default(), &mut rng)?;
+                finish(&mut sheet, &mut rng)?;
+                Ok(sheet)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|e| exit_with(error_format, e));
+        let working_days = non_holidays_of_month(&month, &year, holidays);
+        let distribution = Distribution::new(&month, &working_days, &sheets);
+        if format == Format::Json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&distribution).expect("distributions serialize")
+            );
+        } else {
+            print!("{}", locale.display(&distribution));
+        }
+        return;
+    }
+
+    let mut rng = make_rng(rng_source);
+    let template = like.map(|(year, month)| {
+        archive(error_format)
+            .load(year, month)
+            .unwrap_or_else(|e| exit_with_io(error_format, e))
+            .unwrap_or_else(|| {
+                exit_with_io(
+                    error_format,
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("No timesheet of {:02}/{} is archived", month, year),
+                    ),
+                )
+            })
+    });
+    let jitter = like.map(|_| jitter.unwrap_or(60));
+    let plan = template.is_none().then(plan);
+    let mut generate = || {
+        let sheet = match &template {
+            Some(template) => generate_like(
+                template,
+                &year,
+                &month,
+                parameters,
+                holidays,
+                jitter.unwrap_or_default(),
+                &mut *rng,
+            ),
+            None => plan
+                .as_ref()
+                .expect("months without a template are planned")
+                .execute(strategy.unwrap_or_default(), &mut *rng),
+        };
+        let mut sheet = sheet.unwrap_or_else(|e| exit_with(error_format, e));
+        finish(&mut sheet, &mut *rng).unwrap_or_else(|e| exit_with(error_format, e));
+        sheet
+    };
+    let mut sheet = generate();
+    if audit || max_score.is_some() {
+        let mut result = Audit::new(&sheet, parameters.max_per_day);
+        if let Some(max_score) = max_score {
+            let (mut attempts, mut best) = (1, result.score());
+            while result.score() > max_score {
+                trace::record(format_args!(
+                    "max score, attempt {}: scored {}, rejected",
+                    attempts,
+                    result.score()
+                ));
+                if attempts == MAX_ATTEMPTS {
+                    exit_with(
+                        error_format,
+                        Error::Implausible {
+                            score: best,
+                            max_score,
+                            attempts,
+                        },
+                    );
+                }
+                sheet = generate();
+                result = Audit::new(&sheet, parameters.max_per_day);
+                best = best.min(result.score());
+                attempts += 1;
+            }
+        }
+        eprint!("{}", result);
+    }
+
+    if check_only {
+        exit_on_violations(verify(&sheet, &parameters), error_format);
+        return;
+    }
+
+    if seed.is_some() {
+        let request = GenerateRequest {
+            month: month.month(),
+            year: year.year(),
+            hours,
+            max_per_day: parameters.max_per_day,
+            earliest: parameters.from,
+            latest: parameters.to,
+            state,
+            city,
+            seed,
+        };
+        let month_constraints: Vec<_> = constraints
+            .iter()
+            .filter(|c| c.year == year.year() && c.month == month.month())
+            .copied()
+            .collect();
+        let reproduction = Reproduction {
+            minutes,
+            remainder,
+            avoid_round_weeks: roughen,
+            weekly_cap,
+            cluster_starts: cluster,
+            free_days,
+            max_free_streak,
+            off_days,
+            distribution: strategy,
+            like: like.map(|(year, month)| format!("{}-{:02}", year, month)),
+            jitter,
+            rounding,
+            breaks: breaks.clone(),
+            max_score,
+            rotation,
+            activities: activities.clone(),
+            #[cfg(feature = "nager")]
+            holidays_from_api: holidays_from_api.clone(),
+            ..Reproduction::new(request, &month_constraints, &blackout)
+        };
+        eprintln!(
+            "Reproduce with --reproduce '{}'",
+            serde_json::to_string(&reproduction).expect("reproductions serialize")
+        );
+    }
+
+    if let Some(rounding) = &rounding {
+        rounding.apply(&mut sheet);
+    }
+    if !breaks.is_empty() {
+        deduct_breaks(&mut sheet, &breaks);
+    }
+    let hours = match rounding {
+        Some(_) => sheet.net_minutes() / 60,
+        None => hours,
+    };
+    if let Some(rate) = rate {
+        eprint!("{}", locale.display(&Pay::new(&sheet, rate)));
+        if pay_column {
+            sheet.rate = Some(rate);
+        }
+    }
+    sheet.timezone = timezone;
+    let mut notes = match notes {
+        Some(path) => {
+            let input = read_input(&path).unwrap_or_else(|e| exit_with_io(error_format, e));
+            parse_notes(&input).unwrap_or_else(|e| exit_with(error_format, e))
+        }
+        None => Vec::new(),
+    };
+    notes.extend(note);
+    let left_out = attach_notes(&mut sheet, &notes).into_iter().map(|note| {
+        format!(
+            "The {}. isn't worked, its note `{}` is left out",
+            note.date.day, note.text
+        )
+    });
+    warn(left_out, error_format);
+    if let Some(rotation) = rotation {
+        draw_activities(&mut sheet, &activities, rotation, &mut *rng);
+    }
+    let silenced = config.warnings.silence;
+    warn(
+        warnings(&sheet, parameters.max_per_day, &silenced),
+        error_format,
+    );
+
+    let redacted = redact.then(|| {
+        let mut redacted = sheet.clone();
+        redact_notes(&mut redacted);
+        redacted
+    });
+    let printed = redacted.as_ref().unwrap_or(&sheet);
+    let header = (!no_header).then(|| Header {
+        employee: config.employee.clone().filter(|_| !redact),
+        seed,
+        ..Header::new(printed, hours, generated_at())
+    });
+    #[cfg(feature = "signing")]
+    let header = header.map(|header| {
+        let signed = layout == Layout::Daily && matches!(format, Format::Csv | Format::Json);
+        let key = signed.then(|| signing_key(error_format)).flatten();
+        Header {
+            signature: key.map(|key| key.sign(&header, printed).to_string()),
+            ..header
+        }
+    });
+    let mut stdout = StdoutSink::encoded(encoding, line_ending);
+    let written = match layout {
+        Layout::Daily => format.stream(printed, header.as_ref(), &mut stdout),
+        Layout::Pauses => {
+            format.stream_with(&Pauses(format), printed, header.as_ref(), &mut stdout)
+        }
+        Layout::Weekly => {
+            let employer = config.contract.clone().and_then(|c| c.employer);
+            let project = employer
+                .filter(|_| !redact)
+                .unwrap_or_else(|| "Work".into());
+            write_weekly(&[(project, printed)], header.as_ref(), format, &mut stdout)
+        }
+        Layout::Calendar => CalendarGrid::new(printed, header.as_ref())
+            .write_html(stdout.writer())
+            .and_then(|()| stdout.finish()),
+    };
+    if let Err(e) = written {
+        exit_with_io(error_format, e);
+    }
+
+    if !no_archive {
+        if let Err(e) = archive(error_format).store(&sheet) {
+            exit_with_io(error_format, e);
+        }
+    }
+
+    #[cfg(feature = "mail")]
+    if let Some(to) = mail_to {
+        mail(&to, &sheet, error_format);
+    }
+
+    #[cfg(feature = "webdav")]
+    if let Some(url) = upload {
+        let credentials = Credentials::resolve(config.webdav.as_ref());
+        if let Err(e) = stundenzettel::upload::upload(&url, credentials.as_ref(), &sheet, format) {
+            exit_with_io(error_format, io::Error::other(e));
+        }
+    }
+}
+
+/// Applies `policy` to `parameters` and reports the adjustments on stderr.
+fn adjust(policy: Policy, parameters: &mut SheetParameters, error_format: ErrorFormat) {
+    let adjustments = policy
+        .apply(parameters)
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    warn(adjustments, error_format);
+}
+
+/// The [`plan::validate`] of `arguments`, printing its adjustments.
+fn validate_plan(arguments: plan::Arguments, error_format: ErrorFormat) -> Plan {
+    let plan = plan::validate(&arguments).unwrap_or_else(|e| exit_with(error_format, e));
+    warn(&plan.adjustments, error_format);
+    plan
+}
+
+/// Prints `warnings` like adjustments on stderr.
+fn warn(warnings: impl IntoIterator<Item = impl fmt::Display>, error_format: ErrorFormat) {
+    for warning in warnings {
+        match error_format {
+            ErrorFormat::Text => eprintln!("warning: {}", warning),
+            ErrorFormat::Json => {
+                eprintln!("{}", serde_json::json!({ "warning": warning.to_string() }))
+            }
+        }
+    }
+}
+
+/// The constraints of the file in `path` and the recorded leave of `month`.
+fn read_constraints(
+    path: Option<PathBuf>,
+    year: &Year,
+    month: &Month,
+    error_format: ErrorFormat,
+) -> Vec<DateConstraint> {
+    let mut constraints = match path {
+        Some(path) => {
+            let input = read_input(&path).unwrap_or_else(|e| exit_with_io(error_format, e));
+            parse_constraints(&input).unwrap_or_else(|e| exit_with(error_format, e))
+        }
+        None => Vec::new(),
+    };
+    constraints.extend(load_state(error_format).constraints(year.year(), month));
+    constraints
+}
+
+/// Splits the hours of the `[contract]` in `term` onto its months and prints the split to stderr.
+fn bank(term: Term, arguments: &GenerateArguments, error_format: ErrorFormat) -> Vec<BankedMonth> {
+    let Some(mut contract) = config(error_format).contract else {
+        exit_with(
+            error_format,
+            Error::MissingSetting {
+                needed_by: "Banking hours".into(),
+                setting: "a `[contract]`".into(),
+            },
+        )
+    };
+    contract.start = arguments
+        .contract_from
+        .map(contract_date)
+        .or(contract.start);
+    contract.end = arguments.contract_to.map(contract_date).or(contract.end);
+    let source = plan::check_rng(arguments.rng.map(Generator::from), arguments.seed)
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    let months = contract
+        .bank(term, arguments.month_cap, &mut make_rng(source))
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    eprintln!("{:<10}{:>8}  {:>6}", "Month", "Contract", "Banked");
+    for month in &months {
+        let date = format!("{:02}/{}", month.month, month.year);
+        eprintln!("{:<10}{:>8}  {:>6}", date, month.contract, month.hours);
+    }
+    let total: u32 = months.iter().map(|month| month.contract).sum();
+    eprintln!("{:<10}{:>8}  {:>6}", "Total", total, total);
+    months
+}
+
+/// The `[[jobs]]` of the configuration and their timesheets of `month`.
+fn generate_config_jobs(
+    month: NonZeroU32,
+    year: u32,
+    rng_source: RngSource,
+    constraints: Option<PathBuf>,
+    blackouts: &[Blackout],
+    policy: Policy,
+    error_format: ErrorFormat,
+) -> (Vec<Contract>, Vec<Timesheet>) {
+    let contracts = config(error_format).jobs;
+    let year = Year::new(year);
+    let month = Month::new(month, &year).unwrap_or_else(|e| exit_with(error_format, e));
+    let constraints = read_constraints(constraints, &year, &month, error_format);
+    let jobs: Vec<_> = contracts
+        .iter()
+        .map(|contract| {
+            let request = contract
+                .request(&year, &month)
+                .unwrap_or_else(|e| exit_with(error_format, e));
+            let mut parameters = request.parameters();
+            adjust(policy, &mut parameters, error_format);
+            let mut job_constraints = contract.period().constraints(year.year(), &month);
+            job_constraints.extend_from_slice(&constraints);
+            (parameters, job_constraints)
+        })
+        .collect();
+
+    let mut rng = make_rng(rng_source);
+    let sheets = generate_jobs(&year, &month, &jobs, blackouts, &mut *rng)
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    (contracts, sheets)
+}
+
+/// How `generate` writes its timesheets.
+struct Output {
+    format: Format,
+    layout: Layout,
+    encoding: Encoding,
+    line_ending: LineEnding,
+    redact: bool,
+}
+
+/// Writes the named timesheets of `projects` as [`Layout::Weekly`] tables headed by `header`.
+fn write_weekly(
+    projects: &[(String, &Timesheet)],
+    header: Option<&Header>,
+    format: Format,
+    sink: &mut dyn OutputSink,
+) -> io::Result<()> {
+    sink.begin(format)?;
+    if let Some(header) = header {
+        format.formatter().write_header(header, sink.writer())?;
+    }
+    Weekly::new(projects.to_vec()).write(format, sink.writer())?;
+    sink.finish()
+}
+
+/// Writes the timesheet of each job headed by its employer, or a row per job of weekly tables.
+fn write_jobs(
+    contracts: &[Contract],
+    sheets: &[Timesheet],
+    output: Output,
+    error_format: ErrorFormat,
+) {
+    let Output {
+        format,
+        layout,
+        encoding,
+        line_ending,
+        redact,
+    } = output;
+    let mut stdout = StdoutSink::encoded(encoding, line_ending);
+    let name = |index: usize, contract: &Contract| {
+        let employer = contract.employer.clone().filter(|_| !redact);
+        employer.unwrap_or_else(|| format!("Job {}", index + 1))
+    };
+    if layout == Layout::Weekly {
+        let projects: Vec<_> = contracts
+            .iter()
+            .zip(sheets)
+            .enumerate()
+            .map(|(index, (contract, sheet))| (name(index, contract), sheet))
+            .collect();
+        if let Err(e) = write_weekly(&projects, None, format, &mut stdout) {
+            exit_with_io(error_format, e);
+        }
+        return;
+    }
+    let mut write = || -> io::Result<()> {
+        for (index, (contract, sheet)) in contracts.iter().zip(sheets).enumerate() {
+            if index > 0 {
+                writeln!(stdout.writer())?;
+            }
+            writeln!(stdout.writer(), "{}", name(index, contract))?;
+            match layout {
+                Layout::Pauses => format.stream_with(&Pauses(format), sheet, None, &mut stdout)?,
+                _ => format.stream(sheet, None, &mut stdout)?,
+            }
+        }
+        Ok(())
+    };
+    if let Err(e) = write() {
+        exit_with_io(error_format, e);
+    }
+}
+
+#[cfg(feature = "push")]
+fn push(arguments: PushArguments, error_format: ErrorFormat) {
+    let PushArguments {
+        service,
+        month,
+        year,
+        dry_run,
+        timezone,
+        authorize,
+    } = arguments;
+    let config = config(error_format);
+    if authorize {
+        authorize_gcal(service, &config, error_format);
+        return;
+    }
+    let (Some(month), Some(year)) = (month, year) else {
+        unreachable!("clap requires the month and year without --authorize")
+    };
+    let sheet = archive(error_format)
+        .load(year, month)
+        .unwrap_or_else(|e| exit_with_io(error_format, e))
+        .unwrap_or_else(|| {
+            exit_with_io(
+                error_format,
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "No timesheet of {:02}/{} is archived, generate it first",
+                        month, year
+                    ),
                 ),
             )
-            .exit();
+        });
+    let timezone = timezone
+        .or(sheet.timezone)
+        .unwrap_or_else(|| config.timezone());
+    timezone
+        .check(sheet.year)
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    let (requests, interval) = match service {
+        Service::Clockify => (
+            config
+                .clockify
+                .as_ref()
+                .ok_or(PushError::MissingConfig("clockify"))
+                .map(|clockify| clockify::requests(clockify, timezone, &sheet)),
+            clockify::INTERVAL,
+        ),
+        Service::Toggl => (
+            config
+                .toggl
+                .as_ref()
+                .ok_or(PushError::MissingConfig("toggl"))
+                .map(|toggl| toggl::requests(toggl, timezone, &sheet)),
+            toggl::INTERVAL,
+        ),
+        Service::Gcal => (
+            config
+                .gcal
+                .as_ref()
+                .ok_or(PushError::MissingConfig("gcal"))
+                .and_then(|gcal| {
+                    let access_token = if dry_run {
+                        "<access token>".to_string()
+                    } else {
+                        gcal::access_token(gcal)?
+                    };
+                    Ok(gcal::requests(gcal, timezone, &sheet, &access_token))
+                }),
+            gcal::INTERVAL,
+        ),
+    };
+    let result = requests.and_then(|requests| {
+        if dry_run {
+            for request in requests {
+                println!("{}", request);
+            }
+            Ok(())
+        } else {
+            send(&requests, interval)
+        }
+    });
+    if let Err(e) = result {
+        exit_with_io(error_format, io::Error::other(e));
+    }
+}
+
+#[cfg(feature = "push")]
+fn authorize_gcal(service: Service, config: &Config, error_format: ErrorFormat) {
+    if !matches!(service, Service::Gcal) {
+        exit_with(
+            error_format,
+            Error::NeedlessOption {
+                option: "`--authorize`".into(),
+                applies_to: "gcal".into(),
+            },
+        );
+    }
+    let result = config
+        .gcal
+        .as_ref()
+        .ok_or(PushError::MissingConfig("gcal"))
+        .and_then(|gcal| {
+            let authorization = gcal::Authorization::start(gcal)?;
+            println!("Open this URL to authorize the access to your calendar:");
+            println!("{}", authorization.url());
+            authorization.finish()
+        });
+    match result {
+        Ok(refresh_token) => {
+            println!("Add this to the [gcal] section of the configuration:");
+            println!("refresh_token = \"{}\"", refresh_token);
+        }
+        Err(e) => exit_with_io(error_format, io::Error::other(e)),
+    }
+}
+
+/// The seconds since the Unix epoch, `SOURCE_DATE_EPOCH` if it is set.
+fn generated_at() -> u64 {
+    env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        })
+}
+
+fn config(error_format: ErrorFormat) -> Config {
+    let locations = locations();
+    if let Some(path) = locations
+        .config
+        .as_ref()
+        .filter(|_| locations.explicit_config)
+    {
+        if !path.exists() {
+            exit_with_io(
+                error_format,
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("The configuration {} doesn't exist", path.display()),
+                ),
+            );
+        }
     }
+    locations
+        .config
+        .as_ref()
+        .map_or(Ok(Config::default()), |path| Config::load(path))
+        .unwrap_or_else(|e| exit_with_io(error_format, e))
+}
 
-    let mut rng = thread_rng();
-    let times = generate_times(
-        Parameters {
-            hours: hours.get(),
-            days: days.len().try_into().unwrap(),
-            from: earliest,
-            to: latest,
-            max_per_day,
+#[cfg(feature = "mail")]
+fn mail(to: &str, sheet: &Timesheet, error_format: ErrorFormat) {
+    let Some(mail) = config(error_format).mail else {
+        exit_with_io(
+            error_format,
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "The configuration has no [mail] section",
+            ),
+        );
+    };
+    if let Err(e) = stundenzettel::mail::send(&mail, to, sheet) {
+        exit_with_io(error_format, io::Error::other(e));
+    }
+}
+
+fn archive(error_format: ErrorFormat) -> Archive {
+    Archive::new(data_dir(error_format))
+}
+
+fn state_path(error_format: ErrorFormat) -> PathBuf {
+    Paths::state_in(&data_dir(error_format))
+}
+
+/// The directory of the archive and the state, exits if the home directory is unknown and
+/// `--state-dir` wasn't given.
+fn data_dir(error_format: ErrorFormat) -> PathBuf {
+    locations().data.clone().unwrap_or_else(|| {
+        exit_with_io(
+            error_format,
+            io::Error::new(io::ErrorKind::NotFound, "The home directory is unknown"),
+        )
+    })
+}
+
+/// The state file, empty if the home directory is unknown.
+fn load_state(error_format: ErrorFormat) -> StateFile {
+    locations()
+        .data
+        .as_ref()
+        .map_or(Ok(StateFile::default()), |data| {
+            StateFile::load(&Paths::state_in(data))
+        })
+        .unwrap_or_else(|e| exit_with_io(error_format, e))
+}
+
+/// Asks `question` until the answer parses, an empty answer or the end of the input takes
+/// `default`.
+fn ask<T>(
+    input: &mut impl BufRead,
+    question: &str,
+    default: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+    error_format: ErrorFormat,
+) -> T {
+    loop {
+        if default.is_empty() {
+            print!("{}: ", question);
+        } else {
+            print!("{} [{}]: ", question, default);
+        }
+        io::stdout()
+            .flush()
+            .unwrap_or_else(|e| exit_with_io(error_format, e));
+        let mut answer = String::new();
+        input
+            .read_line(&mut answer)
+            .unwrap_or_else(|e| exit_with_io(error_format, e));
+        let answer = match answer.trim() {
+            "" => default,
+            answer => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return value,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+/// The configuration file, exits if the home directory is unknown and `--config` wasn't given.
+fn config_path(error_format: ErrorFormat) -> PathBuf {
+    locations().config.clone().unwrap_or_else(|| {
+        exit_with_io(
+            error_format,
+            io::Error::new(io::ErrorKind::NotFound, "The home directory is unknown"),
+        )
+    })
+}
+
+fn init_config(force: bool, error_format: ErrorFormat) {
+    let path = config_path(error_format);
+    if path.exists() && !force {
+        exit_with_io(
+            error_format,
+            io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists, pass --force to overwrite it", path.display()),
+            ),
+        );
+    }
+    let mut input = io::stdin().lock();
+    let employee = ask(
+        &mut input,
+        "Name on the timesheets, empty for none",
+        "",
+        |name| Ok((!name.is_empty()).then(|| name.to_string())),
+        error_format,
+    );
+    let states: Vec<_> = State::ALL.iter().map(State::abbreviation).collect();
+    let state = ask(
+        &mut input,
+        &format!("Federal state, one of {}", states.join(", ")),
+        State::default().abbreviation(),
+        |state| state.parse().map_err(|e: Error| e.to_string()),
+        error_format,
+    );
+    let weekly_hours = ask(
+        &mut input,
+        "Hours worked per week",
+        "40",
+        |hours| {
+            hours
+                .parse::<NonZeroU32>()
+                .map(NonZeroU32::get)
+                .map_err(|_| format!("`{}` isn't a positive number of hours", hours))
         },
-        &mut rng,
+        error_format,
     );
+    let earliest = ask(
+        &mut input,
+        "Earliest starting hour",
+        "8",
+        hour_in_range,
+        error_format,
+    );
+    let latest = ask(
+        &mut input,
+        "Latest stopping hour",
+        "20",
+        |hour| match hour_in_range(hour)? {
+            hour if hour <= earliest => Err("Latest has to be after earliest".into()),
+            hour => Ok(hour),
+        },
+        error_format,
+    );
+    let formats: Vec<_> = Format::ALL.iter().map(Format::name).collect();
+    let format = ask(
+        &mut input,
+        &format!("Output format, one of {}", formats.join(", ")),
+        Format::default().name(),
+        str::parse,
+        error_format,
+    );
+    let setup = Setup {
+        employee,
+        state,
+        weekly_hours,
+        earliest,
+        latest,
+        format,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap_or_else(|e| exit_with_io(error_format, e));
+    }
+    fs::write(&path, setup.to_toml()).unwrap_or_else(|e| exit_with_io(error_format, e));
+    println!("Wrote {}", path.display());
+}
 
-    let check = times
-        .iter()
-        .map(|v| v.as_ref().map(|t| t.to - t.from).unwrap_or_default())
-        .sum::<u32>();
-    assert_eq!(check, hours.get());
+fn show_config(arguments: ShowArguments, error_format: ErrorFormat) {
+    let overrides = Overrides {
+        state: arguments.state,
+        city: arguments.city,
+        start: arguments.contract_from.map(contract_date),
+        end: arguments.contract_to.map(contract_date),
+        realism_profile: arguments.realism_profile,
+        format: arguments.format,
+        timezone: arguments.timezone,
+    };
+    let mut config = config(error_format);
+    config
+        .apply(&overrides)
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    #[cfg(feature = "webdav")]
+    if let Some(Credentials { username, password }) = Credentials::resolve(config.webdav.as_ref()) {
+        config.webdav = Some(WebdavConfig {
+            username: Some(username),
+            password: Some(password),
+        });
+    }
+    let config = if arguments.secrets {
+        config
+    } else {
+        config.redacted()
+    };
+    let locations = locations();
+    if let Some(path) = &locations.config {
+        println!("# configuration: {}", path.display());
+    }
+    if let Some(data) = &locations.data {
+        println!("# state: {}", data.display());
+    }
+    let toml = toml::to_string(&config).expect("the configuration is TOML");
+    print!("{}", toml);
+}
 
-    times.iter().zip(&days).for_each(|(time, day)| {
-        if let Some(time) = time {
-            if csv {
-                println!(
-                    "{}.{}.{},{}:00,{}:00",
-                    day.day_of_month,
-                    month.month(),
+fn validate_config(file: Option<PathBuf>, error_format: ErrorFormat) {
+    let file = file.unwrap_or_else(|| config_path(error_format));
+    let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+    let problems = match toml::from_str(&input) {
+        Err(e) => vec![e.to_string()],
+        Ok(value) => match Schema::Config.validate(&toml_to_json(value)) {
+            errors if !errors.is_empty() => errors,
+            _ => match Config::parse(&input) {
+                Err(e) => vec![e.to_string()],
+                Ok(config) => config.check().iter().map(ToString::to_string).collect(),
+            },
+        },
+    };
+    if problems.is_empty() {
+        println!("{} is valid", file.display());
+        return;
+    }
+    match error_format {
+        ErrorFormat::Text => problems.iter().for_each(|problem| eprintln!("{}", problem)),
+        ErrorFormat::Json => {
+            let value = serde_json::json!({
+                "code": "invalid_config",
+                "message": "The configuration is invalid",
+                "problems": problems,
+            });
+            eprintln!("{}", value);
+        }
+    }
+    process::exit(EXIT_INVALID)
+}
+
+fn vacation(command: VacationCommand, error_format: ErrorFormat) {
+    let path = state_path(error_format);
+    let mut state_file = StateFile::load(&path).unwrap_or_else(|e| exit_with_io(error_format, e));
+    let range = |from: (u32, DateOfYear), until: Option<(u32, DateOfYear)>| {
+        let until = until.unwrap_or(from);
+        if from.0 != until.0 {
+            exit_with(
+                error_format,
+                Error::LeaveAcrossYears {
+                    from: from.0,
+                    until: until.0,
+                },
+            );
+        }
+        (from.0, from.1, until.1)
+    };
+    match command {
+        VacationCommand::Budget { year, days } => {
+            state_file.vacation.entry(year).or_default().entitlement = days;
+        }
+        VacationCommand::Take { from, until, state } => {
+            let (year, from, until) = range(from, until);
+            let days = working_days(&Year::new(year), state, from, until)
+                .unwrap_or_else(|e| exit_with(error_format, e));
+            let budget = state_file.vacation.entry(year).or_default();
+            let taken = budget.take(days);
+            println!(
+                "Recorded {} days of leave, {} remaining in {}",
+                taken,
+                budget.remaining(),
+                year
+            );
+        }
+        VacationCommand::Cancel { from, until } => {
+            let (year, from, until) = range(from, until);
+            let budget = state_file.vacation.entry(year).or_default();
+            let cancelled = budget.cancel(from, until);
+            println!(
+                "Removed {} days of leave, {} remaining in {}",
+                cancelled,
+                budget.remaining(),
+                year
+            );
+        }
+        VacationCommand::Show { year } => {
+            let budget = state_file.vacation.get(&year).cloned().unwrap_or_default();
+            println!(
+                "{}: {} days, {} taken, {} remaining",
+                year,
+                budget.entitlement,
+                budget.taken.len(),
+                budget.remaining()
+            );
+            for day in budget.taken {
+                println!("{}.{}.{}", day.day, day.month, year);
+            }
+            return;
+        }
+    }
+    if let Err(e) = state_file.save(&path) {
+        exit_with_io(error_format, e);
+    }
+}
+
+fn digest(arguments: DigestArguments, error_format: ErrorFormat) {
+    let DigestArguments { digest, file } = arguments;
+    let (year, month) = (digest.year, digest.month);
+    let sheet = match file {
+        Some(file) => {
+            let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+            parse(&input, State::default()).unwrap_or_else(|e| exit_with(error_format, e))
+        }
+        None => archive(error_format)
+            .load(year, month)
+            .unwrap_or_else(|e| exit_with_io(error_format, e))
+            .unwrap_or_else(|| {
+                exit_with_io(
+                    error_format,
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("No timesheet of {:02}/{} is archived", month, year),
+                    ),
+                )
+            }),
+    };
+    if !digest.matches(&sheet) {
+        exit_with(error_format, Error::DigestMismatch { year, month });
+    }
+    println!("The digest matches the timesheet of {:02}/{}", month, year);
+}
+
+fn calendar(arguments: CalendarArguments, error_format: ErrorFormat) {
+    let CalendarArguments {
+        month,
+        year,
+        file,
+        timezone,
+    } = arguments;
+    let sheet = match (file, month, year) {
+        (Some(file), _, _) => {
+            let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+            parse(&input, State::default()).unwrap_or_else(|e| exit_with(error_format, e))
+        }
+        (None, Some(month), Some(year)) => archive(error_format)
+            .load(year, month)
+            .unwrap_or_else(|e| exit_with_io(error_format, e))
+            .unwrap_or_else(|| {
+                exit_with_io(
+                    error_format,
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!(
+                            "No timesheet of {:02}/{} is archived, generate it first",
+                            month, year
+                        ),
+                    ),
+                )
+            }),
+        _ => unreachable!("clap requires the month and year without --file"),
+    };
+    let timezone = timezone
+        .or(sheet.timezone)
+        .unwrap_or_else(|| config(error_format).timezone());
+    timezone
+        .check(sheet.year)
+        .unwrap_or_else(|e| exit_with(error_format, e));
+    print!("{}", ics::timesheet(&sheet, timezone));
+}
+
+fn invoice(arguments: InvoiceArguments, error_format: ErrorFormat) {
+    let InvoiceArguments {
+        month,
+        year,
+        file,
+        rate,
+        vat,
+        weekly,
+        number,
+        locale,
+    } = arguments;
+    let sheet = match (file, month, year) {
+        (Some(file), _, _) => {
+            let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+            parse(&input, State::default()).unwrap_or_else(|e| exit_with(error_format, e))
+        }
+        (None, Some(month), Some(year)) => archive(error_format)
+            .load(year, month)
+            .unwrap_or_else(|e| exit_with_io(error_format, e))
+            .unwrap_or_else(|| {
+                exit_with_io(
+                    error_format,
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!(
+                            "No timesheet of {:02}/{} is archived, generate it first",
+                            month, year
+                        ),
+                    ),
+                )
+            }),
+        _ => unreachable!("clap requires the month and year without --file"),
+    };
+    let config = config(error_format);
+    let settings = config.invoice.unwrap_or_default();
+    let vat = vat.or(settings.vat).unwrap_or(19);
+    let Some(rate) = rate.or(config.contract.as_ref().and_then(|contract| contract.rate)) else {
+        exit_with(
+            error_format,
+            Error::MissingSetting {
+                needed_by: "`invoice` without `--rate`".into(),
+                setting: "a `rate` in the `[contract]`".into(),
+            },
+        )
+    };
+    let grouping = if weekly {
+        Grouping::Weeks
+    } else {
+        Grouping::Days
+    };
+
+    let path = state_path(error_format);
+    let mut state_file = StateFile::load(&path).unwrap_or_else(|e| exit_with_io(error_format, e));
+    let advanced = number.is_none();
+    let number = number.unwrap_or_else(|| state_file.next_invoice_number(sheet.year));
+    let invoice = Invoice {
+        issuer: settings.issuer.or(config.employee),
+        recipient: settings.recipient,
+        ..Invoice::new(number, &sheet, rate, vat, grouping, generated_at())
+    };
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = invoice.write_html(locale, &mut stdout) {
+        exit_with_io(error_format, e);
+    }
+    if advanced {
+        if let Err(e) = state_file.save(&path) {
+            exit_with_io(error_format, e);
+        }
+    }
+}
+
+fn holidays(
+    year: u32,
+    state: State,
+    format: HolidayFormat,
+    encoding: Encoding,
+    error_format: ErrorFormat,
+) {
+    let year = Year::new(year);
+    if !matches!(format, HolidayFormat::Text) && encoding != Encoding::Utf8 {
+        let format = match format {
+            HolidayFormat::Ics => "iCalendar",
+            _ => "JSON",
+        };
+        exit_with(error_format, Error::AlwaysUtf8(format.into()))
+    }
+    match format {
+        HolidayFormat::Text => {
+            let mut stdout = Encoder::new(io::stdout().lock(), encoding);
+            for holiday in year.holidays(state) {
+                let line = writeln!(
+                    stdout,
+                    "{}.{}.{} {}",
+                    holiday.date.day,
+                    holiday.date.month,
                     year.year(),
-                    time.from,
-                    time.to
+                    holiday.name
                 );
-            } else {
+                line.unwrap_or_else(|e| exit_with_io(error_format, e));
+            }
+        }
+        HolidayFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&year.holidays(state))
+                .expect("holidays serialize to json")
+        ),
+        HolidayFormat::Ics => print!("{}", ics::holidays(&year, state)),
+    }
+}
+
+fn analyze(
+    year: u32,
+    state: State,
+    city: Option<City>,
+    format: AnalysisFormat,
+    error_format: ErrorFormat,
+) {
+    let region = Region::new(state, city).unwrap_or_else(|e| exit_with(error_format, e));
+    let year = Year::new(year);
+    let analysis = analyze_holidays(&year, region);
+    match format {
+        AnalysisFormat::Text => {
+            for month in analysis {
                 println!(
-                    "{}.{}.{}: {}:00-{}:00",
-                    day.day_of_month,
-                    month.month(),
+                    "{}/{}: {} working days, {} extra free days",
+                    month.month,
                     year.year(),
-                    time.from,
-                    time.to
+                    month.working_days,
+                    month.extra_free_days
                 );
+                let weekday = month.weekday_holidays.iter().map(|holiday| (holiday, ""));
+                let weekend = month
+                    .weekend_holidays
+                    .iter()
+                    .map(|holiday| (holiday, " (weekend, no free day)"));
+                let mut holidays: Vec<_> = weekday.chain(weekend).collect();
+                holidays.sort_by_key(|(holiday, _)| holiday.date.day);
+                for (holiday, note) in holidays {
+                    println!(
+                        "  {}.{} {}{}",
+                        holiday.date.day, holiday.date.month, holiday.name, note
+                    );
+                }
+            }
+        }
+        AnalysisFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&analysis).expect("analysis serializes to json")
+        ),
+    }
+}
+
+fn list(error_format: ErrorFormat) {
+    let archived = archive(error_format)
+        .list()
+        .unwrap_or_else(|e| exit_with_io(error_format, e));
+    for archived in archived {
+        println!(
+            "{}-{:02}  {}",
+            archived.year,
+            archived.month,
+            archived.path.display()
+        );
+    }
+}
+
+fn report(arguments: ReportArguments, error_format: ErrorFormat) {
+    let ReportArguments {
+        year,
+        target,
+        format,
+        locale,
+    } = arguments;
+    let archive = archive(error_format);
+    let mut sheets = Vec::new();
+    for month in (1..=12).filter_map(NonZeroU32::new) {
+        match archive.load(year, month) {
+            Ok(sheet) => sheets.extend(sheet),
+            Err(e) => exit_with_io(error_format, e),
+        }
+    }
+    let report = Report::new(year, target, &sheets);
+    match format {
+        ReportFormat::Table => print!("{}", locale.display(&report)),
+        ReportFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("reports serialize to json")
+        ),
+    }
+}
+
+fn simulate(arguments: SimulateArguments, error_format: ErrorFormat) {
+    let SimulateArguments {
+        month,
+        year,
+        hours,
+        max_per_day,
+        earliest,
+        latest,
+        state,
+        city,
+        runs,
+        seed,
+        progress,
+        constraints,
+        mut blackout,
+        window,
+        format,
+        locale,
+    } = arguments;
+    if latest < earliest {
+        exit_with(
+            error_format,
+            Error::InvalidWindow {
+                from: earliest,
+                to: latest,
+            },
+        );
+    }
+    let (outer, window_blackouts) = window_blackouts(earliest, latest, &window);
+    blackout.extend(window_blackouts);
+    let mut parameters = SheetParameters {
+        state,
+        city,
+        hours: hours.get(),
+        from: outer.from / 60,
+        to: outer.to / 60,
+        max_per_day,
+    };
+    adjust(Policy::Adjust, &mut parameters, error_format);
+
+    let year = Year::new(year);
+    let month = Month::new(month, &year).unwrap_or_else(|e| exit_with(error_format, e));
+    let constraints = read_constraints(constraints, &year, &month, error_format);
+    let region = Region::new(state, city).unwrap_or_else(|e| exit_with(error_format, e));
+    let mut progress = progress.map(|style| Progress::new(style, runs.get(), io::stderr()));
+    let results: Vec<_> = (0..runs.get())
+        .map(|i| {
+            let seed = seed.wrapping_add(i.into());
+            let mut rng = seeded(seed);
+            let result = generate_timesheet_with(
+                &year,
+                &month,
+                parameters,
+                &region,
+                &constraints,
+                &blackout,
+                &mut rng,
+            );
+            if let Some(progress) = &mut progress {
+                let _ = progress.advance(seed);
             }
+            result
+        })
+        .collect();
+    if let Some(progress) = progress {
+        let _ = progress.finish();
+    }
+    let simulation = Simulation::new(&results);
+    match format {
+        SimulateFormat::Table => print!("{}", locale.display(&simulation)),
+        SimulateFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&simulation).expect("simulations serialize to json")
+        ),
+    }
+}
+
+fn read_input(path: &PathBuf) -> io::Result<String> {
+    if path.as_os_str() == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+fn smooth(arguments: SmoothArguments, error_format: ErrorFormat) {
+    let SmoothArguments {
+        file,
+        format,
+        csv,
+        no_header,
+        step,
+        rounding,
+        breaks,
+        no_breaks,
+        state,
+        city,
+    } = arguments;
+
+    let format = if csv { Format::Csv } else { format };
+    let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+    let punches = parse_punches(&input).unwrap_or_else(|e| exit_with(error_format, e));
+    let state = state.unwrap_or_default();
+    let region = Region::new(state, city).unwrap_or_else(|e| exit_with(error_format, e));
+    let policy = SmoothingPolicy {
+        rounding: rounding_policy(step, rounding, error_format).unwrap_or_default(),
+        breaks: break_rules(breaks, no_breaks, &BreakRule::STATUTORY, error_format),
+    };
+    let sheets = stundenzettel::smooth::smooth(&punches, &policy, state, &region);
+    let employee = (!no_header)
+        .then(|| config(error_format).employee)
+        .flatten();
+    let mut stdout = StdoutSink::new();
+    let mut write = || -> io::Result<()> {
+        for (index, sheet) in sheets.iter().enumerate() {
+            if index > 0 {
+                writeln!(stdout.writer())?;
+            }
+            let header = (!no_header).then(|| Header {
+                employee: employee.clone(),
+                ..Header::new(sheet, sheet.hours(), generated_at())
+            });
+            format.stream(sheet, header.as_ref(), &mut stdout)?;
+        }
+        Ok(())
+    };
+    if let Err(e) = write() {
+        exit_with_io(error_format, e);
+    }
+}
+
+/// The key of the `[signing]` section of the configuration, if there is one.
+#[cfg(feature = "signing")]
+fn signing_key(error_format: ErrorFormat) -> Option<SigningKey> {
+    let signing = config(error_format).signing?;
+    Some(
+        signing
+            .key
+            .parse()
+            .unwrap_or_else(|e| exit_with(error_format, e)),
+    )
+}
+
+#[cfg(feature = "signing")]
+fn key(command: KeyCommand, error_format: ErrorFormat) {
+    match command {
+        KeyCommand::Generate => {
+            let key = SigningKey::generate();
+            println!("{}", key);
+            eprintln!(
+                "Add it as `key` to the `[signing]` section of the configuration, its public key is {}",
+                key.public_key()
+            );
         }
+        KeyCommand::Public => match signing_key(error_format) {
+            Some(key) => println!("{}", key.public_key()),
+            None => exit_with(
+                error_format,
+                Error::MissingSetting {
+                    needed_by: "`key public`".into(),
+                    setting: "a `[signing]` key".into(),
+                },
+            ),
+        },
+    }
+}
+
+#[cfg(feature = "signing")]
+fn verify_signature(arguments: VerifyArguments, error_format: ErrorFormat) {
+    let VerifyArguments { file, public_key } = arguments;
+    let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+    let sheet = parse(&input, State::default()).unwrap_or_else(|e| exit_with(error_format, e));
+    let public_key = public_key
+        .or_else(|| signing_key(error_format).map(|key| key.public_key()))
+        .unwrap_or_else(|| {
+            exit_with(
+                error_format,
+                Error::MissingSetting {
+                    needed_by: "`verify` without `--public-key`".into(),
+                    setting: "a `[signing]` key".into(),
+                },
+            )
+        });
+    let header = SignedHeader::find(&input)
+        .unwrap_or_else(|e| exit_with(error_format, e))
+        .unwrap_or_else(|| exit_with(error_format, Error::Unsigned));
+    if !public_key.verify(&header, &sheet) {
+        exit_with(
+            error_format,
+            Error::SignatureMismatch {
+                year: sheet.year,
+                month: sheet.month,
+            },
+        );
+    }
+    println!(
+        "The signature matches the timesheet of {:02}/{}",
+        sheet.month, sheet.year
+    );
+}
+
+#[cfg(feature = "update")]
+fn check_update(error_format: ErrorFormat) {
+    let current = Version::current();
+    let newer = Release::fetch_latest().and_then(|release| {
+        let version = release.newer_than(current)?;
+        Ok(version.map(|version| (version, release.html_url)))
     });
+    match newer {
+        Ok(Some((version, url))) => println!(
+            "stundenzettel {} is available, this is {}. Download it from {}",
+            version, current, url
+        ),
+        Ok(None) => println!("stundenzettel {} is up to date", current),
+        Err(e) => exit_with_io(error_format, io::Error::other(e)),
+    }
+}
+
+fn validate(arguments: ValidateArguments, error_format: ErrorFormat) {
+    let ValidateArguments {
+        file,
+        hours,
+        max_per_day,
+        earliest,
+        latest,
+        state,
+        city,
+    } = arguments;
+
+    let input = read_input(&file).unwrap_or_else(|e| exit_with_io(error_format, e));
+    let sheet =
+        parse(&input, state.unwrap_or_default()).unwrap_or_else(|e| exit_with(error_format, e));
+    let state = state.unwrap_or(sheet.state);
+    Region::new(state, city).unwrap_or_else(|e| exit_with(error_format, e));
+    let parameters = SheetParameters {
+        state,
+        city,
+        hours,
+        from: earliest,
+        to: latest,
+        max_per_day,
+    };
+    exit_on_violations(verify(&sheet, &parameters), error_format);
+}
+
+/// Prints the `violations` of a timesheet to stderr and exits, returns if there are none.
+fn exit_on_violations(violations: Vec<Violation>, error_format: ErrorFormat) {
+    if violations.is_empty() {
+        return;
+    }
+    match error_format {
+        ErrorFormat::Text => violations
+            .iter()
+            .for_each(|violation| eprintln!("{}", violation)),
+        ErrorFormat::Json => {
+            let violations: Vec<_> = violations.iter().map(ToString::to_string).collect();
+            let value = serde_json::json!({
+                "code": "invalid_sheet",
+                "message": "The timesheet violates the constraints",
+                "violations": violations,
+            });
+            eprintln!("{}", value);
+        }
+    }
+    process::exit(EXIT_INVALID)
 }
@@ -0,0 +1,208 @@
+//! Public holidays of other countries from the [Nager.Date](https://date.nager.at) API.
+//!
+//! A country is selected by its ISO 3166-1 code like `AT`, a region within it by its ISO 3166-2
+//! code like `CH-ZH`. The answer for a year is cached in `~/.cache/stundenzettel/nager`, so every
+//! country and year is only fetched once.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::calendar::{DateOfYear, Holiday, HolidaySource, Year};
+use crate::paths::Paths;
+
+/// The base URL of the API.
+pub const API: &str = "https://date.nager.at/api/v3";
+
+/// The reasons fetching holidays can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum NagerError {
+    /// The selection isn't a country or region code.
+    #[error("`{0}` isn't a country like `AT` or a region like `CH-ZH`")]
+    InvalidCountry(String),
+    /// The API couldn't be reached or doesn't know the country.
+    #[error("Fetching the holidays failed: {0}")]
+    Http(#[from] ureq::Error),
+    /// The answer of the API or the cache isn't a list of holidays.
+    #[error("The holidays can't be read: {0}")]
+    Parse(#[from] serde_json::Error),
+    /// Reading or writing the cache failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A holiday as returned by the API.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiHoliday {
+    /// The date as `YYYY-MM-DD`.
+    pub date: String,
+    /// The name in the language of the country.
+    pub local_name: String,
+    /// Whether the holiday applies to the whole country.
+    pub global: bool,
+    /// The regions the holiday applies to if it isn't global.
+    #[serde(default)]
+    pub counties: Option<Vec<String>>,
+}
+
+/// The holidays of one country or region in one year.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NagerHolidays {
+    year: u32,
+    region: Option<String>,
+    holidays: Vec<ApiHoliday>,
+}
+
+/// The country and the optional region of `selection`, both upper case.
+fn split_selection(selection: &str) -> Result<(String, Option<String>), NagerError> {
+    let selection = selection.trim().to_ascii_uppercase();
+    let country = selection.split('-').next().unwrap_or_default();
+    let valid = country.len() == 2
+        && country.bytes().all(|b| b.is_ascii_uppercase())
+        && selection
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-');
+    if !valid || selection.matches('-').count() > 1 || selection.ends_with('-') {
+        return Err(NagerError::InvalidCountry(selection));
+    }
+    let country = country.to_string();
+    let region = (selection.len() > 2).then_some(selection);
+    Ok((country, region))
+}
+
+impl NagerHolidays {
+    /// The cache in `~/.cache/stundenzettel/nager` or the cache directory of [`crate::paths`] on
+    /// this platform, `None` if the home directory is unknown.
+    pub fn default_cache() -> Option<PathBuf> {
+        Some(Paths::from_environment()?.cache.join("nager"))
+    }
+
+    /// Reads the answer of the API for `year`, only keeping the holidays of `selection`.
+    pub fn parse(selection: &str, year: u32, json: &str) -> Result<Self, NagerError> {
+        let (_, region) = split_selection(selection)?;
+        Ok(NagerHolidays {
+            year,
+            region,
+            holidays: serde_json::from_str(json)?,
+        })
+    }
+
+    /// The holidays of `selection` in `year`, read from `cache` if they were fetched before.
+    pub fn fetch(selection: &str, year: u32, cache: Option<&Path>) -> Result<Self, NagerError> {
+        let (country, _) = split_selection(selection)?;
+        let cached = cache.map(|cache| cache.join(format!("{}-{}.json", year, country)));
+        if let Some(json) = cached
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+        {
+            return Self::parse(selection, year, &json);
+        }
+        let json = ureq::get(format!("{}/PublicHolidays/{}/{}", API, year, country))
+            .call()?
+            .body_mut()
+            .read_to_string()?;
+        let holidays = Self::parse(selection, year, &json)?;
+        if let Some(path) = cached {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, json)?;
+        }
+        Ok(holidays)
+    }
+}
+
+/// Holidays of other years are unknown, and so are days the API answers with invalid dates.
+impl HolidaySource for NagerHolidays {
+    fn holidays(&self, year: &Year) -> Vec<Holiday> {
+        if year.year() != self.year {
+            return Vec::new();
+        }
+        let date = |date: &str| {
+            let [_, month, day] = date.split('-').collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            let date = DateOfYear::new(day.parse().ok()?, month.parse::<NonZeroU32>().ok()?);
+            date.add_days(0, year.is_leap()).ok()
+        };
+        let mut holidays: Vec<_> = self
+            .holidays
+            .iter()
+            .filter(|holiday| {
+                holiday.global
+                    || self.region.as_ref().is_some_and(|region| {
+                        holiday
+                            .counties
+                            .iter()
+                            .flatten()
+                            .any(|county| county == region)
+                    })
+            })
+            .filter_map(|holiday| {
+                Some(Holiday {
+                    date: date(&holiday.date)?,
+                    name: Cow::Owned(holiday.local_name.clone()),
+                })
+            })
+            .collect();
+        holidays.sort_by_key(|holiday| (holiday.date.month, holiday.date.day));
+        holidays
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::calendar::{DateOfYear, HolidaySource, Year};
+    use crate::nager::{NagerError, NagerHolidays};
+
+    const ANSWER: &str = r#"[
+        {"date": "2024-01-06", "localName": "Heilige Drei Könige", "name": "Epiphany",
+         "countryCode": "AT", "fixed": true, "global": true, "counties": null},
+        {"date": "2024-03-19", "localName": "Josefitag", "name": "Saint Joseph's Day",
+         "countryCode": "AT", "fixed": true, "global": false, "counties": ["AT-2", "AT-6"]},
+        {"date": "2024-01-01", "localName": "Neujahr", "name": "New Year's Day",
+         "countryCode": "AT", "fixed": true, "global": true, "counties": null}
+    ]"#;
+
+    #[test]
+    fn test_holidays() {
+        let year = Year::new(2024);
+        let country = NagerHolidays::parse("at", 2024, ANSWER).unwrap();
+        let holidays = country.holidays(&year);
+        assert_eq!(holidays.len(), 2);
+        assert_eq!(holidays[0].date, DateOfYear::new_checked(1, 1).unwrap());
+        assert_eq!(holidays[1].name, "Heilige Drei Könige");
+
+        let region = NagerHolidays::parse("AT-6", 2024, ANSWER).unwrap();
+        assert_eq!(region.holidays(&year).len(), 3);
+        assert_eq!(region.holidays(&Year::new(2025)), []);
+
+        for invalid in ["A", "AUT", "AT-", "AT-6-1", "A1"] {
+            assert!(
+                matches!(
+                    NagerHolidays::parse(invalid, 2024, ANSWER),
+                    Err(NagerError::InvalidCountry(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+        assert!(matches!(
+            NagerHolidays::parse("AT", 2024, "{}"),
+            Err(NagerError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = tempfile::tempdir().unwrap();
+        std::fs::write(cache.path().join("2024-AT.json"), ANSWER).unwrap();
+        let holidays = NagerHolidays::fetch("AT-2", 2024, Some(cache.path())).unwrap();
+        assert_eq!(holidays.holidays(&Year::new(2024)).len(), 3);
+    }
+}
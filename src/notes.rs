@@ -0,0 +1,443 @@
+//! Descriptions of the activity on single days, which some employers require next to the times.
+//!
+//! A [`Note`] is written as `day.month=text`, a notes file has one per line. Empty lines and lines
+//! starting with `#` are skipped.
+//!
+//! ```text
+//! # March
+//! 12.03=Server migration
+//! 13.03=Code review
+//! ```
+//!
+//! Notes don't change which days are worked, [`attach_notes`] only adds them to the entries of the
+//! days that are. [`draw_activities`] notes the remaining days with activities of a pool,
+//! [`redact_notes`] hides them when sharing a timesheet.
+
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::DateOfYear;
+use crate::error::{Error, Result};
+use crate::parser::parse_error;
+use crate::rng::index;
+use crate::timesheet::Timesheet;
+
+/// The description of the activity on a date like `12.03=Server migration`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Note {
+    /// The date the note belongs to in any year.
+    pub date: DateOfYear,
+    /// The description, without quotes around all of it.
+    pub text: String,
+}
+
+impl FromStr for Note {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidNote(s.into());
+        let (date, text) = s.split_once('=').ok_or_else(invalid)?;
+        let (day, month) = date.trim().split_once('.').ok_or_else(invalid)?;
+        let (day, month): (u32, u32) = (
+            day.parse().map_err(|_| invalid())?,
+            month.parse().map_err(|_| invalid())?,
+        );
+        let date = DateOfYear::new_checked(day, month)
+            .filter(|_| day <= 31 && month <= 12)
+            .ok_or_else(invalid)?;
+        let text = text.trim();
+        let text = text
+            .strip_prefix('"')
+            .and_then(|text| text.strip_suffix('"'))
+            .filter(|text| !text.contains('"'))
+            .unwrap_or(text);
+        if text.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Note {
+            date,
+            text: text.into(),
+        })
+    }
+}
+
+/// `day.month=text` like `12.03=Server migration`.
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}={}", self.date.day, self.date.month, self.text)
+    }
+}
+
+/// Reads a notes file, one [`Note`] per line.
+pub fn parse_notes(input: &str) -> Result<Vec<Note>> {
+    let mut notes = Vec::new();
+    for (index, text) in input.lines().enumerate() {
+        if text.trim().is_empty() || text.starts_with('#') {
+            continue;
+        }
+        notes.push(
+            text.parse()
+                .map_err(|e: Error| parse_error(index + 1, e.to_string()))?,
+        );
+    }
+    Ok(notes)
+}
+
+/// Adds the notes of the month of `sheet` to its entries, a later note of the same day replaces an
+/// earlier one.
+///
+/// Returns the notes of the month whose day isn't worked, which are left out.
+pub fn attach_notes<'a>(sheet: &mut Timesheet, notes: &'a [Note]) -> Vec<&'a Note> {
+    let mut left_out = Vec::new();
+    for note in notes.iter().filter(|note| note.date.month == sheet.month) {
+        match sheet
+            .entries
+            .iter_mut()
+            .find(|entry| entry.day == note.date.day)
+        {
+            Some(entry) => entry.note = Some(note.text.clone()),
+            None => left_out.push(note),
+        }
+    }
+    left_out
+}
+
+/// An activity of a pool the notes of the days are drawn from.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Activity {
+    /// The description written as the note.
+    pub text: String,
+    /// How often the activity is drawn relative to the others.
+    #[serde(default = "one")]
+    pub weight: NonZeroU32,
+}
+
+fn one() -> NonZeroU32 {
+    NonZeroU32::MIN
+}
+
+/// How [`draw_activities`] picks the activities of the days.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    /// Every day draws an activity by the weights on its own, so an activity can be missing from a
+    /// month.
+    #[default]
+    Independent,
+    /// Every activity is drawn at least once and the remaining days are split by the weights, in
+    /// random order. With fewer days than activities every day gets a different one.
+    Balanced,
+}
+
+/// The index of an activity drawn by the weights among `activities`.
+fn draw<R: Rng + ?Sized>(activities: &[&Activity], r: &mut R) -> usize {
+    let total: u32 = activities
+        .iter()
+        .map(|activity| activity.weight.get())
+        .sum();
+    let mut drawn = index(r, total as usize) as u32;
+    activities
+        .iter()
+        .position(|activity| {
+            let hit = drawn < activity.weight.get();
+            drawn = drawn.saturating_sub(activity.weight.get());
+            hit
+        })
+        .expect("the draw is below the total weight")
+}
+
+/// How often each of `activities` appears on `days` days with [`Rotation::Balanced`]: once each,
+/// the remaining days split by the weights with the largest remainders rounded up.
+fn balanced(activities: &[Activity], days: usize) -> Vec<usize> {
+    let total: usize = activities.iter().map(|a| a.weight.get() as usize).sum();
+    let remaining = days - activities.len();
+    let shares: Vec<_> = activities
+        .iter()
+        .map(|activity| remaining * activity.weight.get() as usize)
+        .collect();
+    let mut counts: Vec<_> = shares.iter().map(|share| 1 + share / total).collect();
+    let mut order: Vec<_> = (0..activities.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(shares[i] % total));
+    let missing = days - counts.iter().sum::<usize>();
+    for &i in &order[..missing] {
+        counts[i] += 1;
+    }
+    counts
+}
+
+/// Notes the entries of `sheet` without a note with the text of one of `activities`, nothing
+/// happens without activities.
+pub fn draw_activities<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    activities: &[Activity],
+    rotation: Rotation,
+    r: &mut R,
+) {
+    if activities.is_empty() {
+        return;
+    }
+    let days = sheet
+        .entries
+        .iter()
+        .filter(|entry| entry.note.is_none())
+        .count();
+    let mut drawn: Vec<&Activity> = match rotation {
+        Rotation::Independent => {
+            let pool: Vec<_> = activities.iter().collect();
+            (0..days).map(|_| pool[draw(&pool, r)]).collect()
+        }
+        Rotation::Balanced if days < activities.len() => {
+            let mut pool: Vec<_> = activities.iter().collect();
+            (0..days)
+                .map(|_| {
+                    let i = draw(&pool, r);
+                    pool.remove(i)
+                })
+                .collect()
+        }
+        Rotation::Balanced => activities
+            .iter()
+            .zip(balanced(activities, days))
+            .flat_map(|(activity, count)| std::iter::repeat_n(activity, count))
+            .collect(),
+    };
+    for i in (1..drawn.len()).rev() {
+        drawn.swap(i, index(r, i + 1));
+    }
+    let entries = sheet
+        .entries
+        .iter_mut()
+        .filter(|entry| entry.note.is_none());
+    for (entry, activity) in entries.zip(drawn) {
+        entry.note = Some(activity.text.clone());
+    }
+}
+
+/// Replaces the notes of `sheet` with placeholders like `Activity 1`, numbered by their first
+/// appearance so that days with the same note keep sharing one.
+pub fn redact_notes(sheet: &mut Timesheet) {
+    let mut texts: Vec<String> = Vec::new();
+    for note in sheet
+        .entries
+        .iter_mut()
+        .filter_map(|entry| entry.note.as_mut())
+    {
+        let number = match texts.iter().position(|text| text == note) {
+            Some(index) => index + 1,
+            None => {
+                texts.push(note.clone());
+                texts.len()
+            }
+        };
+        *note = format!("Activity {}", number);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{DateOfYear, Month, State, Year};
+    use crate::error::Error;
+    use crate::notes::{
+        attach_notes, balanced, draw_activities, parse_notes, redact_notes, Activity, Note,
+        Rotation,
+    };
+    use crate::output::{Csv, Formatter, Markdown, Personio, Text};
+    use crate::parser::parse;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters, Timesheet};
+
+    fn march(hours: u32) -> Timesheet {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap()
+    }
+
+    fn activity(text: &str, weight: u32) -> Activity {
+        Activity {
+            text: text.into(),
+            weight: NonZeroU32::new(weight).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let note: Note = "12.03=\"Server migration\"".parse().unwrap();
+        assert_eq!(
+            note,
+            Note {
+                date: DateOfYear::new_checked(12, 3).unwrap(),
+                text: "Server migration".into(),
+            }
+        );
+        assert_eq!(note.to_string(), "12.03=Server migration");
+        assert_eq!(
+            "1.5= Review, planning = done".parse::<Note>().unwrap().text,
+            "Review, planning = done"
+        );
+        for invalid in [
+            "12.03",
+            "12.03=",
+            "12.13=x",
+            "32.1=x",
+            "12=x",
+            "12.03.2024=x",
+        ] {
+            assert_eq!(
+                invalid.parse::<Note>(),
+                Err(Error::InvalidNote(invalid.into()))
+            );
+        }
+
+        let notes = parse_notes("# March\n12.03=Server migration\n\n13.3=Code review\n").unwrap();
+        assert_eq!(notes.len(), 2);
+        let error = parse_notes("12.03=Server migration\n13.03\n");
+        assert!(matches!(error, Err(Error::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn test_attach() {
+        let mut sheet = march(40);
+        let days: Vec<_> = sheet.entries.iter().map(|entry| entry.day.get()).collect();
+        let free = (1..=31).find(|day| !days.contains(day)).unwrap();
+        let notes: Vec<Note> = [
+            format!("{}.03=Server migration", days[0]),
+            format!("{}.03=Review \"v2\", planning | retro", days[1]),
+            format!("{}.03=Nothing", free),
+            format!("{}.04=Next month", days[0]),
+        ]
+        .iter()
+        .map(|note| note.parse().unwrap())
+        .collect();
+        assert_eq!(attach_notes(&mut sheet, &notes), vec![&notes[2]]);
+        assert!(sheet.has_notes());
+        assert_eq!(sheet.entries[0].note.as_deref(), Some("Server migration"));
+        assert_eq!(
+            sheet.entries[1].note.as_deref(),
+            Some("Review \"v2\", planning | retro")
+        );
+        assert_eq!(sheet.entries[2].note, None);
+
+        let csv = Csv.render(&sheet);
+        let lines: Vec<_> = csv.lines().collect();
+        assert!(lines[0].ends_with(",\"Server migration\""));
+        assert!(lines[1].ends_with(",\"Review \"\"v2\"\", planning | retro\""));
+        assert!(lines[2].ends_with(",\"\""));
+        assert_eq!(parse(&csv, State::Bavaria), Ok(sheet.clone()));
+        assert!(Text
+            .render(&sheet)
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(" note Server migration"));
+        assert!(Markdown
+            .render(&sheet)
+            .contains("| Review \"v2\", planning \\| retro |"));
+        assert!(Personio
+            .render(&sheet)
+            .starts_with("Date,Start,End,Break,Comment\n"));
+    }
+
+    #[test]
+    fn test_activities() {
+        let activities = [
+            activity("Development", 3),
+            activity("Code review", 1),
+            activity("Support", 1),
+            activity("Documentation", 1),
+        ];
+        assert_eq!(balanced(&activities, 4), vec![1, 1, 1, 1]);
+        assert_eq!(balanced(&activities, 7), vec![3, 2, 1, 1]);
+        assert_eq!(balanced(&activities, 10), vec![4, 2, 2, 2]);
+        assert_eq!(balanced(&activities, 16), vec![7, 3, 3, 3]);
+
+        let count = |sheet: &Timesheet, text: &str| {
+            let notes = sheet
+                .entries
+                .iter()
+                .filter_map(|entry| entry.note.as_deref());
+            notes.filter(|note| *note == text).count()
+        };
+        for seed in 0..20 {
+            let mut sheet = march(100);
+            sheet.entries[0].note = Some("Server migration".into());
+            let days = sheet.entries.len() - 1;
+            draw_activities(
+                &mut sheet,
+                &activities,
+                Rotation::Balanced,
+                &mut seeded(seed),
+            );
+            assert_eq!(sheet.entries[0].note.as_deref(), Some("Server migration"));
+            let counts: Vec<_> = activities.iter().map(|a| count(&sheet, &a.text)).collect();
+            assert_eq!(counts, balanced(&activities, days));
+
+            let mut sheet = march(100);
+            draw_activities(
+                &mut sheet,
+                &activities,
+                Rotation::Independent,
+                &mut seeded(seed),
+            );
+            assert!(sheet.entries.iter().all(|entry| entry.note.is_some()));
+
+            let mut short = march(3);
+            draw_activities(
+                &mut short,
+                &activities,
+                Rotation::Balanced,
+                &mut seeded(seed),
+            );
+            let counts: Vec<_> = activities.iter().map(|a| count(&short, &a.text)).collect();
+            assert!(counts.iter().all(|count| *count <= 1));
+            assert_eq!(counts.iter().sum::<usize>(), short.entries.len());
+        }
+
+        let mut sheet = march(40);
+        draw_activities(&mut sheet, &[], Rotation::Balanced, &mut seeded(1));
+        assert!(!sheet.has_notes());
+    }
+
+    #[test]
+    fn test_redact() {
+        let mut sheet = march(40);
+        for (entry, text) in
+            sheet
+                .entries
+                .iter_mut()
+                .zip(["Payroll for ACME", "Code review", "Payroll for ACME"])
+        {
+            entry.note = Some(text.into());
+        }
+        let notes = |sheet: &Timesheet| -> Vec<_> {
+            sheet
+                .entries
+                .iter()
+                .map(|entry| entry.note.clone())
+                .collect()
+        };
+        let mut expected = vec![None; sheet.entries.len()];
+        expected[..3].clone_from_slice(&[
+            Some("Activity 1".into()),
+            Some("Activity 2".into()),
+            Some("Activity 1".into()),
+        ]);
+        redact_notes(&mut sheet);
+        assert_eq!(notes(&sheet), expected);
+    }
+}
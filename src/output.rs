@@ -0,0 +1,1537 @@
+//! Rendering of timesheets.
+//!
+//! A [`Formatter`] writes the rows of a timesheet one by one into an [`OutputSink`] as it renders
+//! them, nothing is collected into a string unless the sink is a [`MemorySink`]. Every format can
+//! start with a [`Header`] naming the employee and month.
+//!
+//! Once a timesheet has breaks, see [`crate::breaks`], every format shows the time present, the
+//! break and the time worked of each entry. Once it has a rate, all formats but [`Personio`] show
+//! the [`crate::pay`] of each entry in euros with a decimal point like `108.50`. Once it has notes,
+//! see [`crate::notes`], every format shows them last.
+//!
+//! [`Weekly`] lays out the timesheets of several projects as one table per ISO week instead, in
+//! the formats [`Layout::Weekly`] supports. [`Pauses`] writes the breaks as rows of their own
+//! between the times worked.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, StdoutLock, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::str::FromStr;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::breaks::pause;
+use crate::calendar::{IsoWeek, Month, State, Year};
+use crate::generate::TimeSpan;
+use crate::pay::pay;
+use crate::timesheet::{Entry, Timesheet};
+use crate::timezone::Timezone;
+
+pub(crate) const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A time of the day like `9:30` from minutes since midnight.
+pub(crate) fn clock(minutes: u32) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// `seconds` since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`.
+pub(crate) fn timestamp(seconds: u64) -> String {
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    // the civil date of a day count, shifted to eras of 400 years starting on March 1st
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// The seconds since the Unix epoch of a `YYYY-MM-DDTHH:MM:SSZ` [`timestamp`].
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    // the inverse of `timestamp`, the years start on March 1st
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146097 + day_of_era - 719468).ok()?;
+    (second <= 59).then_some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+fn serialize_timestamp<S: Serializer>(seconds: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&timestamp(*seconds))
+}
+
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let timestamp = String::deserialize(deserializer)?;
+    parse_timestamp(&timestamp).ok_or_else(|| {
+        serde::de::Error::custom(format!("`{}` isn't a timestamp in UTC", timestamp))
+    })
+}
+
+/// The block above the rows naming whose timesheet of which month it is and how it was generated.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Header {
+    /// The name of the employee, left out if `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub employee: Option<String>,
+    /// The year of the month.
+    pub year: u32,
+    /// The month, starting at 1.
+    #[schemars(range(max = 12))]
+    pub month: NonZeroU32,
+    /// The state whose holidays were excluded.
+    pub state: State,
+    /// The hours the timesheet has to contain according to the contract.
+    pub hours: u32,
+    /// When the timesheet was generated in seconds since the Unix epoch, serialized as an ISO 8601
+    /// timestamp in UTC.
+    #[serde(
+        serialize_with = "serialize_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    #[schemars(with = "String", extend("format" = "date-time"))]
+    pub generated_at: u64,
+    /// The seed the times were generated with, left out if `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// The base64 of the Ed25519 signature of the timesheet by the `[signing]` key of the
+    /// configuration, left out if `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl Header {
+    /// The header of `sheet`, which has to contain `hours` and was generated at `generated_at`
+    /// seconds since the Unix epoch.
+    pub fn new(sheet: &Timesheet, hours: u32, generated_at: u64) -> Self {
+        Header {
+            employee: None,
+            year: sheet.year,
+            month: sheet.month,
+            state: sheet.state,
+            hours,
+            generated_at,
+            seed: None,
+            signature: None,
+        }
+    }
+
+    /// The labels and values of the header in order, without the missing ones.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let month = MONTH_NAMES[self.month.get() as usize - 1];
+        let mut fields = Vec::new();
+        if let Some(employee) = &self.employee {
+            fields.push(("Employee", employee.clone()));
+        }
+        fields.push(("Month", format!("{} {}", month, self.year)));
+        fields.push(("State", self.state.to_string()));
+        fields.push(("Hours", self.hours.to_string()));
+        fields.push(("Generated at", timestamp(self.generated_at)));
+        if let Some(seed) = self.seed {
+            fields.push(("Seed", seed.to_string()));
+        }
+        if let Some(signature) = &self.signature {
+            fields.push(("Signature", signature.clone()));
+        }
+        fields
+    }
+}
+
+/// The gross time, the break and the net time of `entry` of `sheet` like `8:30`.
+fn durations(sheet: &Timesheet, entry: &Entry) -> [String; 3] {
+    [
+        clock(sheet.gross_minutes_of(entry)),
+        clock(entry.break_minutes),
+        clock(sheet.net_minutes_of(entry)),
+    ]
+}
+
+/// The pay of `entry` of `sheet` at `rate` cents per hour like `108.50`.
+fn pay_of(sheet: &Timesheet, entry: &Entry, rate: u32) -> String {
+    let cents = pay(sheet.net_minutes_of(entry), rate);
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// `text` as a quoted CSV column, quotes within it are doubled.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Writes the fields of `header` as `# label: value` comment lines.
+fn write_comment_header(header: &Header, w: &mut dyn Write) -> io::Result<()> {
+    for (label, value) in header.fields() {
+        writeln!(w, "# {}: {}", label, value)?;
+    }
+    Ok(())
+}
+
+/// Renders a [`Timesheet`] in one specific format.
+pub trait Formatter {
+    /// Writes `sheet` to `w`.
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()>;
+
+    /// Writes `header` to `w`, by default as `# label: value` lines.
+    fn write_header(&self, header: &Header, w: &mut dyn Write) -> io::Result<()> {
+        write_comment_header(header, w)
+    }
+
+    /// Writes `sheet` headed by `header` to `w`.
+    fn write_with_header(
+        &self,
+        header: &Header,
+        sheet: &Timesheet,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        self.write_header(header, w)?;
+        self.write(sheet, w)
+    }
+
+    /// Renders `sheet` into a string.
+    fn render(&self, sheet: &Timesheet) -> String {
+        let mut sink = MemorySink::new();
+        self.write(sheet, sink.writer())
+            .expect("writing to a vector can't fail");
+        sink.into_string()
+    }
+}
+
+/// Where a rendered timesheet goes, like stdout, a file or the body of an HTTP response.
+pub trait OutputSink {
+    /// Starts a timesheet rendered as `format`, before any of its rows.
+    fn begin(&mut self, _format: Format) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// The writer the rows are streamed to.
+    fn writer(&mut self) -> &mut dyn Write;
+
+    /// Ends the timesheet after its last row.
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+/// The character encoding of rendered timesheets, [`Json`] is always UTF-8.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Encoding {
+    /// UTF-8.
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark, which spreadsheets like Excel need to detect it.
+    Utf8Bom,
+    /// ISO 8859-1, characters outside of it like `€` are written as `?`.
+    Latin1,
+}
+
+impl Encoding {
+    /// All encodings.
+    pub const ALL: [Encoding; 3] = [Encoding::Utf8, Encoding::Utf8Bom, Encoding::Latin1];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf8",
+            Encoding::Utf8Bom => "utf8-bom",
+            Encoding::Latin1 => "latin1",
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Encoding::ALL
+            .into_iter()
+            .find(|encoding| encoding.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("`{}` isn't an encoding", s))
+    }
+}
+
+/// The line ending of rendered timesheets.
+///
+/// [`LineEnding::Crlf`] makes the [`Csv`] and [`Personio`] output comply with RFC 4180, whose
+/// records end with CRLF. Importers on Windows often reject files without it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum LineEnding {
+    /// A line feed, `\n`.
+    #[default]
+    Lf,
+    /// A carriage return followed by a line feed, `\r\n`.
+    Crlf,
+}
+
+impl LineEnding {
+    /// All line endings.
+    pub const ALL: [LineEnding; 2] = [LineEnding::Lf, LineEnding::Crlf];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "lf",
+            LineEnding::Crlf => "crlf",
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LineEnding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LineEnding::ALL
+            .into_iter()
+            .find(|ending| ending.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("`{}` isn't a line ending", s))
+    }
+}
+
+/// Writes the UTF-8 written to it to another writer in an [`Encoding`], the byte order mark of
+/// [`Encoding::Utf8Bom`] precedes the first write. The line feeds the formatters end their lines
+/// with are replaced by the [`LineEnding`], by default they are kept.
+pub struct Encoder<W> {
+    inner: W,
+    encoding: Encoding,
+    line_ending: LineEnding,
+    started: bool,
+    /// The start of a character whose remaining bytes are still to be written.
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Encoder<W> {
+    /// Encodes into `inner`.
+    pub fn new(inner: W, encoding: Encoding) -> Self {
+        Encoder {
+            inner,
+            encoding,
+            line_ending: LineEnding::Lf,
+            started: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Ends the lines with `line_ending`.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    fn latin1(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.pending.extend_from_slice(buf);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => self.pending.len(),
+        };
+        let encoded: Vec<_> = String::from_utf8_lossy(&self.pending[..complete])
+            .chars()
+            .map(|c| u8::try_from(c).unwrap_or(b'?'))
+            .collect();
+        self.pending.drain(..complete);
+        self.inner.write_all(&encoded)
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.started {
+            self.started = true;
+            if self.encoding == Encoding::Utf8Bom {
+                self.inner.write_all("\u{feff}".as_bytes())?;
+            }
+        }
+        let lines = match self.line_ending {
+            LineEnding::Lf => Cow::Borrowed(buf),
+            LineEnding::Crlf => Cow::Owned(buf.iter().fold(Vec::new(), |mut lines, &byte| {
+                if byte == b'\n' {
+                    lines.push(b'\r');
+                }
+                lines.push(byte);
+                lines
+            })),
+        };
+        match self.encoding {
+            Encoding::Utf8 | Encoding::Utf8Bom => self.inner.write_all(&lines)?,
+            Encoding::Latin1 => self.latin1(&lines)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Streams to the standard output.
+pub struct StdoutSink(Encoder<StdoutLock<'static>>);
+
+impl StdoutSink {
+    /// Locks the standard output until the sink is dropped.
+    pub fn new() -> Self {
+        Self::encoded(Encoding::Utf8, LineEnding::Lf)
+    }
+
+    /// Locks the standard output until the sink is dropped and writes in `encoding` with lines
+    /// ending in `line_ending`.
+    pub fn encoded(encoding: Encoding, line_ending: LineEnding) -> Self {
+        let stdout = Encoder::new(io::stdout().lock(), encoding);
+        StdoutSink(stdout.with_line_ending(line_ending))
+    }
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputSink for StdoutSink {
+    fn writer(&mut self) -> &mut dyn Write {
+        &mut self.0
+    }
+}
+
+/// Streams to a file through a buffer.
+pub struct FileSink(BufWriter<File>);
+
+impl FileSink {
+    /// Creates the file at `path`, replacing an existing one.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(FileSink(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl OutputSink for FileSink {
+    fn writer(&mut self) -> &mut dyn Write {
+        &mut self.0
+    }
+}
+
+/// Collects the output in memory, for bodies that need their length up front.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    format: Option<Format>,
+    buffer: Vec<u8>,
+}
+
+impl MemorySink {
+    /// An empty sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The format of the last timesheet begun, `None` if there wasn't any.
+    pub fn format(&self) -> Option<Format> {
+        self.format
+    }
+
+    /// The collected output.
+    pub fn into_string(self) -> String {
+        String::from_utf8(self.buffer).expect("formatters write utf-8")
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn begin(&mut self, format: Format) -> io::Result<()> {
+        self.format = Some(format);
+        Ok(())
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        &mut self.buffer
+    }
+}
+
+/// One `day.month.year: from-to` line per entry followed by the code of its surcharge and its
+/// night hours like `night 1:30` if it has any, the header is a `label: value` line per field
+/// followed by an empty line. If any entry has a break, every line has its durations like
+/// `gross 8:00 break 0:30 net 7:30`, with a rate it ends with the pay like `pay 108.50`. A note
+/// ends the line like `note Server migration`.
+pub struct Text;
+
+impl Formatter for Text {
+    fn write_header(&self, header: &Header, w: &mut dyn Write) -> io::Result<()> {
+        for (label, value) in header.fields() {
+            writeln!(w, "{:<13} {}", format!("{}:", label), value)?;
+        }
+        writeln!(w)
+    }
+
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()> {
+        let breaks = sheet.has_breaks();
+        for entry in &sheet.entries {
+            write!(
+                w,
+                "{}.{}.{}: {}-{}",
+                entry.day,
+                sheet.month,
+                sheet.year,
+                clock(entry.time.from),
+                clock(entry.time.to)
+            )?;
+            if let Some(surcharge) = entry.surcharge {
+                write!(w, " {}", surcharge.code())?;
+            }
+            let night = sheet.night_minutes_of(entry);
+            if night > 0 {
+                write!(w, " night {}", clock(night))?;
+            }
+            if breaks {
+                let [gross, pause, net] = durations(sheet, entry);
+                write!(w, " gross {} break {} net {}", gross, pause, net)?;
+            }
+            if let Some(rate) = sheet.rate {
+                write!(w, " pay {}", pay_of(sheet, entry, rate))?;
+            }
+            if let Some(note) = &entry.note {
+                write!(w, " note {}", note)?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// One `day.month.year,from,to` line per entry, the header is made of `#` comments.
+///
+/// If any entry has a surcharge, every line has a column with its code, empty without one. If any
+/// entry is worked during the night, every line has a column of its night hours like `1:30`. If
+/// any entry has a break, every line has the columns `gross,break,net` like `8:00,0:30,7:30`. With
+/// a rate, every line has a column of the pay. If any entry has a note, every line ends with it
+/// quoted like `"Server migration"`, `""` without one.
+///
+/// Written with [`LineEnding::Crlf`] and without the header, the output is CSV as of RFC 4180:
+/// every record has the same columns and the only column that may contain commas, quotes or line
+/// breaks, the note, is quoted.
+pub struct Csv;
+
+impl Formatter for Csv {
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()> {
+        let (surcharges, night, breaks, notes) = (
+            sheet.has_surcharges(),
+            sheet.has_night(),
+            sheet.has_breaks(),
+            sheet.has_notes(),
+        );
+        for entry in &sheet.entries {
+            write!(
+                w,
+                "{}.{}.{},{},{}",
+                entry.day,
+                sheet.month,
+                sheet.year,
+                clock(entry.time.from),
+                clock(entry.time.to)
+            )?;
+            if surcharges {
+                write!(w, ",{}", entry.surcharge.map_or("", |s| s.code()))?;
+            }
+            if night {
+                write!(w, ",{}", clock(sheet.night_minutes_of(entry)))?;
+            }
+            if breaks {
+                write!(w, ",{}", durations(sheet, entry).join(","))?;
+            }
+            if let Some(rate) = sheet.rate {
+                write!(w, ",{}", pay_of(sheet, entry, rate))?;
+            }
+            if notes {
+                write!(w, ",{}", quoted(entry.note.as_deref().unwrap_or_default()))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// The times of `entry` split by its break as `(span, is pause)`, see [`pause`].
+fn stretches(entry: &Entry) -> Vec<(TimeSpan, bool)> {
+    let TimeSpan { from, to } = entry.time;
+    match pause(entry) {
+        Some(pause) => [
+            (
+                TimeSpan {
+                    from,
+                    to: pause.from,
+                },
+                false,
+            ),
+            (pause, true),
+            (TimeSpan { from: pause.to, to }, false),
+        ]
+        .into_iter()
+        .filter(|(span, _)| span.duration() > 0)
+        .collect(),
+        None => vec![(entry.time, false)],
+    }
+}
+
+/// [`Layout::Pauses`] in [`Text`] or [`Csv`]: the times worked of each entry are split by its break,
+/// which is a row of its own where [`crate::breaks::pause`] places it.
+///
+/// [`Text`] writes lines like `4.3.2024: 12:00-12:30 Pause`, the surcharge and the note of an entry
+/// follow its first row. [`Csv`] writes `day.month.year,from,to,kind` lines whose kind is `Pause`
+/// or empty, followed by the quoted note of the first row if any entry has a note. The header is
+/// the one of the format. The gross and net times, the night hours and the pay aren't shown, they
+/// follow from the rows. The rows can't be read back by [`crate::parser`].
+pub struct Pauses(pub Format);
+
+impl Formatter for Pauses {
+    fn write_header(&self, header: &Header, w: &mut dyn Write) -> io::Result<()> {
+        self.0.formatter().write_header(header, w)
+    }
+
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()> {
+        let notes = sheet.has_notes();
+        for entry in &sheet.entries {
+            let date = format!("{}.{}.{}", entry.day, sheet.month, sheet.year);
+            for (index, (span, is_pause)) in stretches(entry).into_iter().enumerate() {
+                let (from, to) = (clock(span.from), clock(span.to));
+                let first = index == 0;
+                if self.0 == Format::Csv {
+                    let kind = if is_pause { "Pause" } else { "" };
+                    write!(w, "{},{},{},{}", date, from, to, kind)?;
+                    if notes {
+                        let note = entry.note.as_deref().filter(|_| first);
+                        write!(w, ",{}", quoted(note.unwrap_or_default()))?;
+                    }
+                } else {
+                    write!(w, "{}: {}-{}", date, from, to)?;
+                    if is_pause {
+                        write!(w, " Pause")?;
+                    }
+                    if let Some(surcharge) = entry.surcharge.filter(|_| first) {
+                        write!(w, " {}", surcharge.code())?;
+                    }
+                    if let Some(note) = entry.note.as_ref().filter(|_| first) {
+                        write!(w, " note {}", note)?;
+                    }
+                }
+                writeln!(w)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The version of the [`Json`] format, [`crate::parser::parse_json`] reads every earlier one.
+///
+/// It is incremented whenever a field is renamed, removed or changes its meaning, adding an
+/// optional field keeps the version.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The serialized [`Timesheet`], the header is its additional `header` field and
+/// `format_version` is the [`FORMAT_VERSION`].
+///
+/// If any entry has a break, every entry has additional `gross` and `net` fields with the minutes
+/// present and worked. With a rate, every entry has a `pay` field in cents. With a time zone,
+/// every entry has `start` and `end` fields with RFC 3339 timestamps.
+pub struct Json;
+
+/// An entry with its gross and net minutes, its pay and its timestamps.
+#[derive(Serialize, JsonSchema)]
+struct NetEntry<'a> {
+    #[serde(flatten)]
+    entry: &'a Entry,
+    /// The minutes present including the break, set on every entry if any has a break.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gross: Option<u32>,
+    /// The minutes worked without the break, set on every entry if any has a break.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    net: Option<u32>,
+    /// The pay of the minutes worked in cents, set if the timesheet has a rate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pay: Option<u64>,
+    /// The start as an RFC 3339 timestamp, set if the timesheet has a time zone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("format" = "date-time"))]
+    start: Option<String>,
+    /// The end as an RFC 3339 timestamp, set if the timesheet has a time zone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("format" = "date-time"))]
+    end: Option<String>,
+}
+
+/// A timesheet serialized with the format version, the gross and net minutes of its entries if
+/// any has a break, their pay if it has a rate and their timestamps if it has a time zone.
+#[derive(Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub(crate) struct JsonTimesheet<'a> {
+    /// Whose timesheet of which month it is and how it was generated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header: Option<&'a Header>,
+    /// The version of the format, `validate` and the archive read earlier versions as well.
+    #[schemars(extend("const" = FORMAT_VERSION))]
+    format_version: u32,
+    /// The year of the month.
+    year: u32,
+    /// The month, starting at 1.
+    #[schemars(range(max = 12))]
+    month: NonZeroU32,
+    /// The state whose holidays were excluded.
+    state: State,
+    /// The hourly rate in cents, every entry has its pay if it is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rate: Option<u32>,
+    /// The zone of the times, every entry has its start and end as timestamps if it is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timezone: Option<Timezone>,
+    /// The days worked, in order.
+    entries: Vec<NetEntry<'a>>,
+}
+
+impl<'a> JsonTimesheet<'a> {
+    fn new(header: Option<&'a Header>, sheet: &'a Timesheet) -> Self {
+        let breaks = sheet.has_breaks();
+        let timestamp = |entry: &Entry, minute| {
+            sheet.timezone.map(|timezone| {
+                timezone.timestamp(sheet.year, sheet.month, entry.day.get(), minute)
+            })
+        };
+        let entries = sheet
+            .entries
+            .iter()
+            .map(|entry| NetEntry {
+                entry,
+                gross: breaks.then(|| sheet.gross_minutes_of(entry)),
+                net: breaks.then(|| sheet.net_minutes_of(entry)),
+                pay: sheet
+                    .rate
+                    .map(|rate| pay(sheet.net_minutes_of(entry), rate)),
+                start: timestamp(entry, entry.time.from),
+                end: timestamp(entry, entry.time.to),
+            })
+            .collect();
+        JsonTimesheet {
+            header,
+            format_version: FORMAT_VERSION,
+            year: sheet.year,
+            month: sheet.month,
+            state: sheet.state,
+            rate: sheet.rate,
+            timezone: sheet.timezone,
+            entries,
+        }
+    }
+}
+
+impl Formatter for Json {
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *w, &JsonTimesheet::new(None, sheet))?;
+        writeln!(w)
+    }
+
+    /// Writes the header as an object of its own, JSON has no comments.
+    fn write_header(&self, header: &Header, w: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *w, header)?;
+        writeln!(w)
+    }
+
+    fn write_with_header(
+        &self,
+        header: &Header,
+        sheet: &Timesheet,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *w, &JsonTimesheet::new(Some(header), sheet))?;
+        writeln!(w)
+    }
+}
+
+/// A table with a row per entry and columns of the surcharges, night hours, breaks and notes if
+/// there are any and of the pay if there is a rate, the header is a heading with the month and a list of the other fields.
+pub struct Markdown;
+
+impl Formatter for Markdown {
+    fn write_header(&self, header: &Header, w: &mut dyn Write) -> io::Result<()> {
+        let (month, fields): (Vec<_>, Vec<_>) = header
+            .fields()
+            .into_iter()
+            .partition(|(label, _)| *label == "Month");
+        for (_, value) in month {
+            writeln!(w, "# {}", value)?;
+            writeln!(w)?;
+        }
+        for (label, value) in fields {
+            writeln!(w, "- **{}:** {}", label, value)?;
+        }
+        writeln!(w)
+    }
+
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()> {
+        let (surcharges, night, breaks, notes) = (
+            sheet.has_surcharges(),
+            sheet.has_night(),
+            sheet.has_breaks(),
+            sheet.has_notes(),
+        );
+        write!(w, "| Date | From | To |")?;
+        if surcharges {
+            write!(w, " Surcharge |")?;
+        }
+        if night {
+            write!(w, " Night |")?;
+        }
+        if breaks {
+            write!(w, " Gross | Break | Net |")?;
+        }
+        if sheet.rate.is_some() {
+            write!(w, " Pay |")?;
+        }
+        if notes {
+            write!(w, " Note |")?;
+        }
+        writeln!(w)?;
+        write!(w, "|------|------|----|")?;
+        if surcharges {
+            write!(w, "-----------|")?;
+        }
+        if night {
+            write!(w, "-------|")?;
+        }
+        if breaks {
+            write!(w, "-------|-------|-----|")?;
+        }
+        if sheet.rate.is_some() {
+            write!(w, "-----|")?;
+        }
+        if notes {
+            write!(w, "------|")?;
+        }
+        writeln!(w)?;
+        for entry in &sheet.entries {
+            write!(
+                w,
+                "| {}.{}.{} | {} | {} |",
+                entry.day,
+                sheet.month,
+                sheet.year,
+                clock(entry.time.from),
+                clock(entry.time.to)
+            )?;
+            if surcharges {
+                write!(w, " {} |", entry.surcharge.map_or("", |s| s.code()))?;
+            }
+            if night {
+                write!(w, " {} |", clock(sheet.night_minutes_of(entry)))?;
+            }
+            if breaks {
+                write!(w, " {} |", durations(sheet, entry).join(" | "))?;
+            }
+            if let Some(rate) = sheet.rate {
+                write!(w, " {} |", pay_of(sheet, entry, rate))?;
+            }
+            if notes {
+                let note = entry.note.as_deref().unwrap_or_default();
+                write!(w, " {} |", note.replace('|', "\\|"))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// The attendance bulk import of Personio, one `date,start,end,break` row per entry.
+///
+/// Dates are ISO 8601, times `HH:MM` and breaks in minutes, Personio derives the time worked from
+/// them. The header is made of `#` comments above the column names. The import has no column for surcharges, Personio derives them itself.
+/// If any entry has a note, every row ends with a quoted `Comment`.
+pub struct Personio;
+
+impl Formatter for Personio {
+    fn write(&self, sheet: &Timesheet, w: &mut dyn Write) -> io::Result<()> {
+        let notes = sheet.has_notes();
+        write!(w, "Date,Start,End,Break")?;
+        if notes {
+            write!(w, ",Comment")?;
+        }
+        writeln!(w)?;
+        for entry in &sheet.entries {
+            write!(
+                w,
+                "{}-{:02}-{:02},{:0>5},{:0>5},{}",
+                sheet.year,
+                sheet.month,
+                entry.day,
+                clock(entry.time.from),
+                clock(entry.time.to),
+                entry.break_minutes
+            )?;
+            if notes {
+                write!(w, ",{}", quoted(entry.note.as_deref().unwrap_or_default()))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// All available output formats.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Format {
+    /// See [`Text`].
+    #[default]
+    Text,
+    /// See [`Csv`].
+    Csv,
+    /// See [`Json`].
+    Json,
+    /// See [`Markdown`].
+    Markdown,
+    /// See [`Personio`].
+    Personio,
+}
+
+impl Format {
+    /// All formats.
+    pub const ALL: [Format; 5] = [
+        Format::Text,
+        Format::Csv,
+        Format::Json,
+        Format::Markdown,
+        Format::Personio,
+    ];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Markdown => "markdown",
+            Format::Personio => "personio",
+        }
+    }
+
+    /// The MIME type of the rendered output.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Text => "text/plain",
+            Format::Csv => "text/csv",
+            Format::Json => "application/json",
+            Format::Markdown => "text/markdown",
+            Format::Personio => "text/csv",
+        }
+    }
+
+    /// The file name extension of the rendered output.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Markdown => "md",
+            Format::Personio => "csv",
+        }
+    }
+
+    /// The formatter rendering this format.
+    pub fn formatter(&self) -> &'static dyn Formatter {
+        match self {
+            Format::Text => &Text,
+            Format::Csv => &Csv,
+            Format::Json => &Json,
+            Format::Markdown => &Markdown,
+            Format::Personio => &Personio,
+        }
+    }
+
+    /// Streams `sheet` rendered in this format to `sink`, headed by `header` if there is one.
+    pub fn stream(
+        &self,
+        sheet: &Timesheet,
+        header: Option<&Header>,
+        sink: &mut dyn OutputSink,
+    ) -> io::Result<()> {
+        self.stream_with(self.formatter(), sheet, header, sink)
+    }
+
+    /// Streams `sheet` rendered by `formatter`, which writes this format in another layout like
+    /// [`Pauses`], to `sink`, headed by `header` if there is one.
+    pub fn stream_with(
+        &self,
+        formatter: &dyn Formatter,
+        sheet: &Timesheet,
+        header: Option<&Header>,
+        sink: &mut dyn OutputSink,
+    ) -> io::Result<()> {
+        sink.begin(*self)?;
+        match header {
+            Some(header) => formatter.write_with_header(header, sheet, sink.writer())?,
+            None => formatter.write(sheet, sink.writer())?,
+        }
+        sink.finish()
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Serializes into the name used on the command line like `csv`.
+impl Serialize for Format {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserializes from the name used on the command line like `csv`.
+impl<'de> Deserialize<'de> for Format {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// One of the names used on the command line like `csv`.
+impl JsonSchema for Format {
+    fn schema_name() -> Cow<'static, str> {
+        "Format".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "enum": Format::ALL.map(|format| format.name()) })
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("`{}` isn't a format", s))
+    }
+}
+
+/// How the days of a timesheet are laid out.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Layout {
+    /// A row per day worked, as written by the [`Formatter`] of the [`Format`].
+    #[default]
+    Daily,
+    /// A table per week, see [`Weekly`].
+    Weekly,
+    /// A wall calendar of the month as an HTML page, see [`crate::grid`]. It takes the place of
+    /// the [`Text`] format, the other formats can't be laid out like this.
+    Calendar,
+    /// A row per stretch worked and per break, see [`Pauses`].
+    Pauses,
+}
+
+impl Layout {
+    /// All layouts.
+    pub const ALL: [Layout; 4] = [
+        Layout::Daily,
+        Layout::Weekly,
+        Layout::Calendar,
+        Layout::Pauses,
+    ];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Daily => "daily",
+            Layout::Weekly => "weekly",
+            Layout::Calendar => "calendar",
+            Layout::Pauses => "pauses",
+        }
+    }
+
+    /// Whether timesheets can be laid out like this in `format`.
+    pub fn supports(&self, format: Format) -> bool {
+        match self {
+            Layout::Daily => true,
+            Layout::Weekly => matches!(format, Format::Text | Format::Csv | Format::Markdown),
+            Layout::Calendar => format == Format::Text,
+            Layout::Pauses => matches!(format, Format::Text | Format::Csv),
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Layout::ALL
+            .into_iter()
+            .find(|layout| layout.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("`{}` isn't a layout", s))
+    }
+}
+
+pub(crate) const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// The timesheets of the projects of a month as one table per ISO week, with a column per day
+/// from Monday to Sunday and a row per project.
+///
+/// A cell has the time worked on the day like `7:30`, it is empty if the project wasn't worked on
+/// or the day is outside of the month. The last column has the total of the row. [`Text`] and
+/// [`Markdown`] head each table with its week like `2024-W10` and label the columns with their
+/// dates like `Mon 4.3.`, with more than one project the last row has the totals of the days.
+/// [`Csv`] writes a single table with the week and the project in the first two columns, the week
+/// and the weekday of a column determine its date.
+pub struct Weekly<'a> {
+    projects: Vec<(String, &'a Timesheet)>,
+}
+
+/// A week of [`Weekly`] with the days of the month from Monday to Sunday and the minutes of each
+/// project on them.
+struct Week {
+    week: IsoWeek,
+    days: [Option<u32>; 7],
+    rows: Vec<(String, [Option<u32>; 7])>,
+}
+
+impl<'a> Weekly<'a> {
+    /// The named timesheets of the projects, which are all of the same month.
+    pub fn new(projects: Vec<(String, &'a Timesheet)>) -> Self {
+        Weekly { projects }
+    }
+
+    fn weeks(&self) -> Vec<Week> {
+        let Some((_, first)) = self.projects.first() else {
+            return Vec::new();
+        };
+        let year = Year::new(first.year);
+        let month = Month::new(first.month, &year).expect("timesheets have valid months");
+        let mut weeks: Vec<Week> = Vec::new();
+        for day in month.days() {
+            let week = year.iso_week(&month, day.day_of_month.get());
+            if weeks.last().is_none_or(|last| last.week != week) {
+                weeks.push(Week {
+                    week,
+                    days: [None; 7],
+                    rows: self
+                        .projects
+                        .iter()
+                        .map(|(name, _)| (name.clone(), [None; 7]))
+                        .collect(),
+                });
+            }
+            let weekday = (day.day_of_week as usize + 6) % 7;
+            weeks.last_mut().expect("pushed").days[weekday] = Some(day.day_of_month.get());
+        }
+        for (index, (_, sheet)) in self.projects.iter().enumerate() {
+            for entry in &sheet.entries {
+                let week = year.iso_week(&month, entry.day.get());
+                let week = weeks
+                    .iter_mut()
+                    .find(|w| w.week == week)
+                    .expect("entries are within the month");
+                let cell = &mut week.rows[index].1[(entry.day_of_week as usize + 6) % 7];
+                *cell = Some(cell.unwrap_or(0) + sheet.net_minutes_of(entry));
+            }
+        }
+        weeks
+    }
+
+    /// Writes the tables to `w` in `format`, which is one [`Layout::Weekly`] supports.
+    pub fn write(&self, format: Format, w: &mut dyn Write) -> io::Result<()> {
+        let month = self.projects.first().map(|(_, sheet)| sheet.month);
+        let cell = |minutes: Option<u32>| minutes.map(clock).unwrap_or_default();
+        let total = |minutes: &[Option<u32>; 7]| clock(minutes.iter().flatten().sum());
+        let weeks = self.weeks();
+        if format == Format::Csv {
+            writeln!(w, "Week,Project,{},Total", WEEKDAYS.join(","))?;
+            for week in &weeks {
+                for (name, minutes) in &week.rows {
+                    let cells: Vec<_> = minutes.iter().map(|&m| cell(m)).collect();
+                    let (name, cells) = (quoted(name), cells.join(","));
+                    writeln!(w, "{},{},{},{}", week.week, name, cells, total(minutes))?;
+                }
+            }
+            return Ok(());
+        }
+        for (index, week) in weeks.iter().enumerate() {
+            let mut table = vec![std::iter::once("Project".to_string())
+                .chain(
+                    WEEKDAYS
+                        .iter()
+                        .zip(week.days)
+                        .map(|(name, day)| match (day, month) {
+                            (Some(day), Some(month)) => format!("{} {}.{}.", name, day, month),
+                            _ => name.to_string(),
+                        }),
+                )
+                .chain(std::iter::once("Total".to_string()))
+                .collect::<Vec<_>>()];
+            let mut row = |name: &str, minutes: &[Option<u32>; 7]| {
+                let cells = minutes.iter().map(|&m| cell(m));
+                table.push(
+                    std::iter::once(name.to_string())
+                        .chain(cells)
+                        .chain(std::iter::once(total(minutes)))
+                        .collect(),
+                );
+            };
+            for (name, minutes) in &week.rows {
+                row(name, minutes);
+            }
+            if week.rows.len() > 1 {
+                let mut sums = [None; 7];
+                for (_, minutes) in &week.rows {
+                    for (sum, minutes) in sums.iter_mut().zip(minutes) {
+                        if let Some(minutes) = minutes {
+                            *sum = Some(sum.unwrap_or(0) + minutes);
+                        }
+                    }
+                }
+                row("Total", &sums);
+            }
+            if index > 0 {
+                writeln!(w)?;
+            }
+            match format {
+                Format::Markdown => {
+                    writeln!(w, "## {}", week.week)?;
+                    writeln!(w)?;
+                    for (index, row) in table.iter().enumerate() {
+                        let cells: Vec<_> = row.iter().map(|c| c.replace('|', "\\|")).collect();
+                        writeln!(w, "| {} |", cells.join(" | "))?;
+                        if index == 0 {
+                            let dashes: Vec<_> = row.iter().map(|c| "-".repeat(c.len())).collect();
+                            writeln!(w, "|-{}-|", dashes.join("-|-"))?;
+                        }
+                    }
+                }
+                _ => {
+                    writeln!(w, "{}", week.week)?;
+                    let widths: Vec<_> = (0..table[0].len())
+                        .map(|column| {
+                            let cells = table.iter().map(|row| row[column].chars().count());
+                            cells.max().unwrap_or(0)
+                        })
+                        .collect();
+                    for row in &table {
+                        let mut line = format!("{:<1$}", row[0], widths[0]);
+                        for (cell, width) in row.iter().zip(&widths).skip(1) {
+                            line.push_str(&format!("  {:>1$}", cell, width));
+                        }
+                        writeln!(w, "{}", line)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::{deduct_breaks, BreakRule};
+    use crate::calendar::{Month, State, Year};
+    use crate::generate::TimeSpan;
+    use std::io::Write;
+
+    use crate::output::{
+        clock, parse_timestamp, timestamp, Encoder, Encoding, Format, Formatter, Header, Layout,
+        LineEnding, MemorySink, Pauses, Weekly,
+    };
+    use crate::parser::parse;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, work_off_days, SheetParameters, Timesheet};
+
+    fn sheet() -> Timesheet {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 20,
+            from: 8,
+            to: 20,
+            max_per_day: 6,
+        };
+        generate_timesheet(&year, &month, parameters, &mut seeded(7)).unwrap()
+    }
+
+    #[test]
+    fn test_formats() {
+        let sheet = sheet();
+        for format in Format::ALL {
+            insta::assert_snapshot!(format.name(), format.formatter().render(&sheet));
+        }
+    }
+
+    #[test]
+    fn test_weekly() {
+        let sheet = sheet();
+        let mut second = sheet.clone();
+        second.entries.retain(|entry| entry.day.get() % 2 == 0);
+        let weekly = Weekly::new(vec![
+            ("Acme".into(), &sheet),
+            ("Beta | Gamma".into(), &second),
+        ]);
+        for format in Format::ALL {
+            if Layout::Weekly.supports(format) {
+                let mut output = Vec::new();
+                weekly.write(format, &mut output).unwrap();
+                let name = format!("weekly_{}", format.name());
+                insta::assert_snapshot!(name, String::from_utf8(output).unwrap());
+            }
+        }
+        let mut single = Vec::new();
+        Weekly::new(vec![("Acme".into(), &sheet)])
+            .write(Format::Text, &mut single)
+            .unwrap();
+        let single = String::from_utf8(single).unwrap();
+        assert!(!single.contains("\nTotal"));
+        assert!(single.starts_with("2024-W18\n"));
+        assert!(!Layout::Weekly.supports(Format::Json));
+    }
+
+    #[test]
+    fn test_memory_sink() {
+        let sheet = sheet();
+        let mut sink = MemorySink::new();
+        Format::Csv.stream(&sheet, None, &mut sink).unwrap();
+        assert_eq!(sink.format(), Some(Format::Csv));
+        assert_eq!(sink.into_string(), Format::Csv.formatter().render(&sheet));
+    }
+
+    #[test]
+    fn test_encodings() {
+        let encode = |encoding, writes: &[&[u8]]| {
+            let mut encoder = Encoder::new(Vec::new(), encoding);
+            for bytes in writes {
+                encoder.write_all(bytes).unwrap();
+            }
+            encoder.inner
+        };
+        let text = "Mariä Himmelfahrt, 5 €\n".as_bytes();
+        assert_eq!(encode(Encoding::Utf8, &[text]), text);
+        assert_eq!(
+            encode(Encoding::Utf8Bom, &[text, text]),
+            [&b"\xef\xbb\xbf"[..], text, text].concat()
+        );
+        assert_eq!(
+            encode(Encoding::Latin1, &[text]),
+            b"Mari\xe4 Himmelfahrt, 5 ?\n"
+        );
+        // characters split between writes
+        let (start, end) = text.split_at(5);
+        assert_eq!(
+            encode(Encoding::Latin1, &[start, end]),
+            b"Mari\xe4 Himmelfahrt, 5 ?\n"
+        );
+        assert_eq!("UTF8-BOM".parse(), Ok(Encoding::Utf8Bom));
+        assert!("cp1252".parse::<Encoding>().is_err());
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let mut sheet = sheet();
+        sheet.entries[0].note = Some("Review \"RFC 4180\", CSV".into());
+        let encoder = Encoder::new(Vec::new(), Encoding::Utf8);
+        let mut encoder = encoder.with_line_ending(LineEnding::Crlf);
+        Format::Csv.formatter().write(&sheet, &mut encoder).unwrap();
+        let csv = String::from_utf8(encoder.inner).unwrap();
+        assert!(csv.starts_with(&format!(
+            "{}.5.2024,{}",
+            sheet.entries[0].day,
+            clock(sheet.entries[0].time.from)
+        )));
+        assert!(csv.contains(",\"Review \"\"RFC 4180\"\", CSV\"\r\n"));
+        assert_eq!(csv.matches("\r\n").count(), sheet.entries.len());
+        assert_eq!(csv.matches('\n').count(), sheet.entries.len());
+        assert_eq!(parse(&csv, State::Bavaria).unwrap().entries, sheet.entries);
+        assert_eq!("CRLF".parse(), Ok(LineEnding::Crlf));
+    }
+
+    #[test]
+    fn test_headers() {
+        let sheet = sheet();
+        let header = Header {
+            employee: Some("Erika Mustermann".into()),
+            seed: Some(7),
+            ..Header::new(&sheet, 20, 1714521600)
+        };
+        for format in Format::ALL {
+            let mut rendered = Vec::new();
+            format
+                .formatter()
+                .write_with_header(&header, &sheet, &mut rendered)
+                .unwrap();
+            insta::assert_snapshot!(
+                format!("{}-header", format.name()),
+                String::from_utf8(rendered).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_surcharges() {
+        let mut sheet = sheet();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 20,
+            from: 8,
+            to: 20,
+            max_per_day: 6,
+        };
+        work_off_days(
+            &mut sheet,
+            30,
+            &parameters,
+            &State::Bavaria,
+            &[],
+            &[],
+            &mut seeded(7),
+        )
+        .unwrap();
+        for format in [Format::Text, Format::Csv, Format::Markdown] {
+            insta::assert_snapshot!(
+                format!("{}-surcharges", format.name()),
+                format.formatter().render(&sheet)
+            );
+        }
+    }
+
+    #[test]
+    fn test_night() {
+        let mut sheet = sheet();
+        sheet.entries[0].time = TimeSpan::hours(4, 8);
+        sheet.entries[1].time = TimeSpan {
+            from: 20 * 60,
+            to: 23 * 60 + 30,
+        };
+        for format in [Format::Text, Format::Csv, Format::Markdown] {
+            insta::assert_snapshot!(
+                format!("{}-night", format.name()),
+                format.formatter().render(&sheet)
+            );
+        }
+    }
+
+    #[test]
+    fn test_breaks() {
+        let mut sheet = sheet();
+        sheet.entries[0].time = TimeSpan::hours(8, 15);
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        for format in Format::ALL {
+            insta::assert_snapshot!(
+                format!("{}-breaks", format.name()),
+                format.formatter().render(&sheet)
+            );
+        }
+    }
+
+    #[test]
+    fn test_pauses() {
+        let mut sheet = sheet();
+        sheet.entries[0].time = TimeSpan::hours(8, 15);
+        sheet.entries[1].note = Some("Server migration".into());
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        for format in [Format::Text, Format::Csv] {
+            assert!(Layout::Pauses.supports(format));
+            insta::assert_snapshot!(
+                format!("{}-pauses", format.name()),
+                Pauses(format).render(&sheet)
+            );
+        }
+        assert!(!Layout::Pauses.supports(Format::Json));
+    }
+
+    #[test]
+    fn test_pay() {
+        let mut sheet = sheet();
+        sheet.rate = Some(1550);
+        for format in [Format::Text, Format::Csv, Format::Json, Format::Markdown] {
+            insta::assert_snapshot!(
+                format!("{}-pay", format.name()),
+                format.formatter().render(&sheet)
+            );
+        }
+    }
+
+    #[test]
+    fn test_timezone() {
+        let mut sheet = sheet();
+        sheet.timezone = Some("Europe/Berlin".parse().unwrap());
+        let json = Format::Json.formatter().render(&sheet);
+        insta::assert_snapshot!("json-timezone", json);
+        assert_eq!(parse(&json, State::default()), Ok(sheet));
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(951825600), "2000-02-29T12:00:00Z");
+        assert_eq!(timestamp(1735689599), "2024-12-31T23:59:59Z");
+        for seconds in [0, 951825600, 1735689599, 4107542400] {
+            assert_eq!(parse_timestamp(&timestamp(seconds)), Some(seconds));
+        }
+        assert_eq!(parse_timestamp("1969-12-31T23:59:59Z"), None);
+        assert_eq!(parse_timestamp("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_timestamp("2024-03-01T00:00:00+01:00"), None);
+    }
+}
@@ -0,0 +1,395 @@
+//! Reads timesheets rendered by [`Csv`](crate::output::Csv) and [`Json`](crate::output::Json)
+//! back into a [`Timesheet`].
+
+use std::cmp::Ordering;
+use std::num::NonZeroU32;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::calendar::{Month, State, Year};
+use crate::error::{Error, Result};
+use crate::generate::{parse_clock, TimeSpan};
+use crate::output::FORMAT_VERSION;
+use crate::timesheet::{Entry, Surcharge, Timesheet};
+
+pub(crate) fn parse_error(line: usize, message: impl Into<String>) -> Error {
+    Error::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+pub(crate) fn parse_number<T: std::str::FromStr>(line: usize, s: &str, what: &str) -> Result<T> {
+    s.trim()
+        .parse()
+        .map_err(|_| parse_error(line, format!("`{}` isn't a {}", s, what)))
+}
+
+pub(crate) fn parse_hour(line: usize, s: &str) -> Result<u32> {
+    let hour = s
+        .trim()
+        .strip_suffix(":00")
+        .ok_or_else(|| parse_error(line, format!("`{}` isn't a full hour", s)))?;
+    parse_number(line, hour, "hour")
+}
+
+/// Reads a time of the day like `9:30` as minutes since midnight.
+pub(crate) fn parse_time(line: usize, s: &str) -> Result<u32> {
+    parse_clock(s).ok_or_else(|| parse_error(line, format!("`{}` isn't a time", s)))
+}
+
+/// Reads a `day.month.year` date without checking that the day exists.
+pub(crate) fn parse_date(line: usize, date: &str) -> Result<(NonZeroU32, NonZeroU32, u32)> {
+    let [day, month, year] = date.trim().split('.').collect::<Vec<_>>()[..] else {
+        return Err(parse_error(line, format!("`{}` isn't a date", date)));
+    };
+    Ok((
+        parse_number(line, day, "day")?,
+        parse_number(line, month, "month")?,
+        parse_number(line, year, "year")?,
+    ))
+}
+
+/// Reads the code of a surcharge, `None` if the column is empty.
+fn parse_surcharge(line: usize, code: &str) -> Result<Option<Surcharge>> {
+    let code = code.trim();
+    if code.is_empty() {
+        return Ok(None);
+    }
+    Surcharge::ALL
+        .into_iter()
+        .find(|surcharge| surcharge.code() == code)
+        .map(Some)
+        .ok_or_else(|| parse_error(line, format!("`{}` isn't a surcharge", code)))
+}
+
+/// Splits the quoted note off the end of a row of [`Csv`](crate::output::Csv), `None` if the row
+/// has no column of notes or the note is empty.
+fn split_note(line: usize, row: &str) -> Result<(&str, Option<String>)> {
+    if !row.trim_end().ends_with('"') {
+        return Ok((row, None));
+    }
+    let unquoted = || parse_error(line, "Expected a note like `\"Server migration\"`");
+    let row = row.trim_end();
+    let start = row.find(",\"").ok_or_else(unquoted)?;
+    let quoted = row[start + 1..]
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .filter(|quoted| !quoted.replace("\"\"", "").contains('"'))
+        .ok_or_else(unquoted)?;
+    let note = quoted.replace("\"\"", "\"");
+    Ok((&row[..start], (!note.is_empty()).then_some(note)))
+}
+
+/// Reads the output of [`Csv`](crate::output::Csv).
+///
+/// The CSV doesn't contain the state, the entries are assumed to belong to `state`. Lines starting
+/// with `#` like those of the header are skipped, a column of night hours and the columns of the
+/// breaks have to match the times. A quoted last column is the note. A column of the pay is skipped since the CSV doesn't contain
+/// the rate. Fails on an empty input since its month is unknown.
+pub fn parse_csv(input: &str, state: State) -> Result<Timesheet> {
+    let mut month_of_sheet: Option<(u32, NonZeroU32, Month)> = None;
+    let mut entries = Vec::new();
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        if text.trim().is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let (text, note) = split_note(line, text)?;
+        let columns: Vec<_> = text.split(',').collect();
+        let [date, from, to, ref extra @ ..] = columns[..] else {
+            return Err(parse_error(line, "Expected `date,from,to`"));
+        };
+        let is_clock = |column: &&str| parse_clock(column).is_some();
+        let is_amount = |column: &str| {
+            column.trim().split_once('.').is_some_and(|(euros, cents)| {
+                [euros, cents]
+                    .iter()
+                    .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+            })
+        };
+        let extra = match *extra {
+            [ref rest @ .., pay] if is_amount(pay) => rest,
+            _ => extra,
+        };
+        let (extra, durations) = match *extra {
+            [ref rest @ .., gross, pause, net] if [gross, pause, net].iter().all(is_clock) => {
+                (rest, Some((gross, pause, net)))
+            }
+            _ => (extra, None),
+        };
+        let (surcharge, night) = match *extra {
+            [] => ("", None),
+            [night] if parse_clock(night.trim()).is_some() => ("", Some(night)),
+            [surcharge] => (surcharge, None),
+            [surcharge, night] => (surcharge, Some(night)),
+            _ => return Err(parse_error(line, "Expected `date,from,to`")),
+        };
+        let (day, month, year) = parse_date(line, date)?;
+
+        let calendar_month = match &month_of_sheet {
+            Some((sheet_year, sheet_month, calendar_month)) => {
+                if (*sheet_year, *sheet_month) != (year, month) {
+                    return Err(parse_error(
+                        line,
+                        "All entries have to be in the same month",
+                    ));
+                }
+                calendar_month
+            }
+            None => {
+                let calendar_year = Year::new(year);
+                let calendar_month = Month::new(month, &calendar_year)
+                    .map_err(|e| parse_error(line, e.to_string()))?;
+                &month_of_sheet.insert((year, month, calendar_month)).2
+            }
+        };
+        if day.get() > calendar_month.num_days() {
+            return Err(parse_error(line, format!("`{}` isn't a date", date)));
+        }
+        let time = TimeSpan {
+            from: parse_time(line, from)?,
+            to: parse_time(line, to)?,
+        };
+        let wrong = |night: &&str| parse_clock(night.trim()) != Some(time.night_minutes());
+        if let Some(night) = night.filter(wrong) {
+            return Err(parse_error(
+                line,
+                format!("`{}` aren't the night hours of {}", night, time),
+            ));
+        }
+        let mut break_minutes = 0;
+        if let Some(columns @ (gross, pause, net)) = durations {
+            let [gross, pause, net] =
+                [gross, pause, net].map(|column| parse_clock(column).unwrap_or_default());
+            if gross != time.duration() || net != gross.saturating_sub(pause) {
+                return Err(parse_error(
+                    line,
+                    format!(
+                        "`{},{},{}` aren't the durations of {}",
+                        columns.0, columns.1, columns.2, time
+                    ),
+                ));
+            }
+            break_minutes = pause;
+        }
+        entries.push(Entry {
+            day,
+            day_of_week: calendar_month.day_of_week(day.get()),
+            time,
+            surcharge: parse_surcharge(line, surcharge)?,
+            break_minutes,
+            note,
+        });
+    }
+    let (year, month, _) = month_of_sheet.ok_or_else(|| parse_error(1, "No entries"))?;
+    Ok(Timesheet {
+        year,
+        month,
+        state,
+        rate: None,
+        timezone: None,
+        entries,
+    })
+}
+
+/// The migrations of the [`Json`](crate::output::Json) format, the one at index `i` turns
+/// version `i` into version `i + 1`.
+const MIGRATIONS: [fn(&mut Value); FORMAT_VERSION as usize] = [clocks_of_hours];
+
+/// Version 0 has no `format_version` and stored full hours as plain numbers like `9`.
+fn clocks_of_hours(sheet: &mut Value) {
+    let entries = sheet.get_mut("entries").and_then(Value::as_array_mut);
+    for entry in entries.into_iter().flatten() {
+        let Some(time) = entry.get_mut("time").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for clock in time
+            .iter_mut()
+            .filter(|(key, _)| *key == "from" || *key == "to")
+        {
+            if let Some(hour) = clock.1.as_u64() {
+                *clock.1 = format!("{:02}:00", hour).into();
+            }
+        }
+    }
+}
+
+/// Reads the output of [`Json`](crate::output::Json) of any version up to [`FORMAT_VERSION`].
+///
+/// Inputs of earlier versions are migrated one version at a time, errors in them are reported on
+/// the first line. Fails on inputs of later versions.
+pub fn parse_json(input: &str) -> Result<Timesheet> {
+    #[derive(Deserialize)]
+    struct Versioned {
+        #[serde(default)]
+        format_version: u32,
+    }
+    let error = |e: serde_json::Error| parse_error(e.line().max(1), e.to_string());
+    let version = serde_json::from_str::<Versioned>(input)
+        .map_err(error)?
+        .format_version;
+    match version.cmp(&FORMAT_VERSION) {
+        Ordering::Equal => serde_json::from_str(input).map_err(error),
+        Ordering::Less => {
+            let mut sheet: Value = serde_json::from_str(input).map_err(error)?;
+            for migrate in &MIGRATIONS[version as usize..] {
+                migrate(&mut sheet);
+            }
+            serde_json::from_value(sheet).map_err(error)
+        }
+        Ordering::Greater => Err(parse_error(
+            1,
+            format!(
+                "Version {} of the format is newer than the supported version {}",
+                version, FORMAT_VERSION
+            ),
+        )),
+    }
+}
+
+/// Reads either format, inputs starting with `{` are read as JSON. A byte order mark is skipped.
+pub fn parse(input: &str, state: State) -> Result<Timesheet> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    if input.trim_start().starts_with('{') {
+        parse_json(input)
+    } else {
+        parse_csv(input, state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::{deduct_breaks, BreakRule};
+    use crate::calendar::{Month, State, Year};
+    use crate::error::Error;
+    use crate::generate::TimeSpan;
+    use crate::output::{Csv, Formatter, Header, Json, FORMAT_VERSION};
+    use crate::parser::{parse, parse_json};
+    use crate::rng::seeded;
+    use crate::timesheet::{
+        generate_timesheet, work_off_days, SheetParameters, Surcharge, Timesheet,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        for (month, state, seed) in [(2, State::Bavaria, 1), (10, State::Saxony, 2)] {
+            let year = Year::new(2024);
+            let month = Month::new(NonZeroU32::new(month).unwrap(), &year).unwrap();
+            let parameters = SheetParameters {
+                state,
+                city: None,
+                hours: 40,
+                from: 8,
+                to: 20,
+                max_per_day: 8,
+            };
+            let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(seed)).unwrap();
+            assert_eq!(parse(&Csv.render(&sheet), state), Ok(sheet.clone()));
+            assert_eq!(parse(&Json.render(&sheet), state), Ok(sheet.clone()));
+
+            let header = Header::new(&sheet, 40, 0);
+            for formatter in [&Csv as &dyn Formatter, &Json] {
+                let mut rendered = Vec::new();
+                formatter
+                    .write_with_header(&header, &sheet, &mut rendered)
+                    .unwrap();
+                let rendered = String::from_utf8(rendered).unwrap();
+                assert_eq!(parse(&rendered, state), Ok(sheet.clone()));
+            }
+
+            let mut worked = sheet.clone();
+            work_off_days(
+                &mut worked,
+                20,
+                &parameters,
+                &state,
+                &[],
+                &[],
+                &mut seeded(seed),
+            )
+            .unwrap();
+            assert!(worked.has_surcharges());
+            assert_eq!(parse(&Csv.render(&worked), state), Ok(worked.clone()));
+            assert_eq!(parse(&Json.render(&worked), state), Ok(worked.clone()));
+
+            let rules = [BreakRule {
+                after: 3 * 60,
+                deduct: 15,
+            }];
+            deduct_breaks(&mut worked, &rules);
+            assert!(worked.has_breaks());
+            assert_eq!(parse(&Csv.render(&worked), state), Ok(worked.clone()));
+            assert_eq!(parse(&Json.render(&worked), state), Ok(worked.clone()));
+
+            let paid = Timesheet {
+                rate: Some(1550),
+                timezone: None,
+                ..worked.clone()
+            };
+            assert_eq!(parse(&Csv.render(&paid), state), Ok(worked));
+            assert_eq!(parse(&Json.render(&paid), state), Ok(paid));
+        }
+    }
+
+    #[test]
+    fn test_versions() {
+        let unversioned = r#"{
+            "year": 2024,
+            "month": 2,
+            "state": "BY",
+            "entries": [{ "day": 1, "day_of_week": "Thursday", "time": { "from": 9, "to": 13 } }]
+        }"#;
+        let sheet = parse_json(unversioned).unwrap();
+        assert_eq!(sheet.entries[0].time, TimeSpan::hours(9, 13));
+        let rendered = Json.render(&sheet);
+        assert!(rendered.contains(&format!(r#""format_version": {}"#, FORMAT_VERSION)));
+        assert_eq!(parse_json(&rendered), Ok(sheet));
+
+        let later = unversioned.replacen('{', r#"{ "format_version": 99,"#, 1);
+        assert!(matches!(
+            parse_json(&later),
+            Err(Error::Parse { line: 1, .. })
+        ));
+        let current = unversioned.replacen('{', r#"{ "format_version": 1,"#, 1);
+        assert!(matches!(
+            parse_json(&current),
+            Err(Error::Parse { line: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse("1.2.2024,8:00,9:00\n30.2.2024,8:00,9:00", State::Bavaria);
+        assert!(matches!(error, Err(Error::Parse { line: 2, .. })));
+        let error = parse("1.2.2024,8:00,9:00\n1.3.2024,8:00,9:00", State::Bavaria);
+        assert!(matches!(error, Err(Error::Parse { line: 2, .. })));
+        let quarter = parse("1.2.2024,8:30,9:15", State::Bavaria).unwrap();
+        assert_eq!(quarter.entries[0].time.duration(), 45);
+        let error = parse("1.2.2024,8:75,9:00", State::Bavaria);
+        assert!(matches!(error, Err(Error::Parse { line: 1, .. })));
+        let error = parse("3.2.2024,8:00,9:00,weekend", State::Bavaria);
+        assert!(matches!(error, Err(Error::Parse { line: 1, .. })));
+
+        let night = parse(
+            "1.2.2024,4:00,8:00,2:00\n3.2.2024,20:00,24:00,saturday,1:00",
+            State::Bavaria,
+        )
+        .unwrap();
+        assert_eq!(night.entries[0].surcharge, None);
+        assert_eq!(night.entries[1].surcharge, Some(Surcharge::Saturday));
+        let error = parse("1.2.2024,4:00,8:00,1:00", State::Bavaria);
+        assert!(matches!(error, Err(Error::Parse { line: 1, .. })));
+
+        let bom = parse("\u{feff}1.2.2024,8:00,9:00", State::Bavaria).unwrap();
+        assert_eq!(bom.entries.len(), 1);
+
+        let breaks = parse("1.2.2024,8:00,15:00,7:00,0:30,6:30", State::Bavaria).unwrap();
+        assert_eq!(breaks.entries[0].break_minutes, 30);
+        let error = parse("1.2.2024,8:00,15:00,7:00,0:30,7:00", State::Bavaria);
+        assert!(matches!(error, Err(Error::Parse { line: 1, .. })));
+    }
+}
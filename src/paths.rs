@@ -0,0 +1,215 @@
+//! Where the configuration, the archive, the state and the caches are kept.
+//!
+//! Linux and the other Unix systems follow the XDG base directories: the configuration is
+//! `$XDG_CONFIG_HOME/stundenzettel/config.toml`, the archive and the state file are in
+//! `$XDG_DATA_HOME/stundenzettel` and the caches in `$XDG_CACHE_HOME/stundenzettel`, which default
+//! to `~/.config`, `~/.local/share` and `~/.cache`. macOS keeps the configuration, the archive and
+//! the state in `~/Library/Application Support/stundenzettel` and the caches in
+//! `~/Library/Caches/stundenzettel`, Windows in `%APPDATA%\stundenzettel` and
+//! `%LOCALAPPDATA%\stundenzettel`. An XDG variable that is set is followed on macOS as well.
+//!
+//! The binary replaces the configuration with `--config` and the directory of the archive and the
+//! state with `--state-dir`.
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// The name of the directories of this program.
+const NAME: &str = "stundenzettel";
+
+/// The conventions of an operating system for where programs keep their files.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Platform {
+    /// The XDG base directories of Linux and the other Unix systems.
+    Xdg,
+    /// The `~/Library` of macOS.
+    MacOs,
+    /// The application data of Windows.
+    Windows,
+}
+
+impl Platform {
+    /// The platform this binary was built for.
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(windows) {
+            Platform::Windows
+        } else {
+            Platform::Xdg
+        }
+    }
+}
+
+/// The files and directories of this program.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Paths {
+    /// The configuration file, see [`crate::config`].
+    pub config: PathBuf,
+    /// The directory of the [`crate::archive`] and the state file of [`crate::vacation`].
+    pub data: PathBuf,
+    /// The directory of the caches like the one of [`crate::nager`].
+    pub cache: PathBuf,
+}
+
+impl Paths {
+    /// The paths of the current platform from the environment, `None` if the home directory is
+    /// unknown.
+    pub fn from_environment() -> Option<Self> {
+        Self::resolve(Platform::current(), |name| env::var_os(name))
+    }
+
+    /// The paths of `platform` with the environment variables returned by `var`.
+    ///
+    /// XDG variables that are empty or relative are ignored like the specification demands.
+    pub fn resolve(platform: Platform, var: impl Fn(&str) -> Option<OsString>) -> Option<Self> {
+        let xdg = |name: &str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        let set = |name: &str| {
+            var(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        let (config, data, cache) = match platform {
+            Platform::Xdg => {
+                let home = set("HOME");
+                let or_home = |name: &str, default: &str| {
+                    xdg(name).or_else(|| Some(home.as_ref()?.join(default)))
+                };
+                (
+                    or_home("XDG_CONFIG_HOME", ".config")?,
+                    or_home("XDG_DATA_HOME", ".local/share")?,
+                    or_home("XDG_CACHE_HOME", ".cache")?,
+                )
+            }
+            Platform::MacOs => {
+                let library = set("HOME")?.join("Library");
+                let support = library.join("Application Support");
+                (
+                    xdg("XDG_CONFIG_HOME").unwrap_or_else(|| support.clone()),
+                    xdg("XDG_DATA_HOME").unwrap_or(support),
+                    xdg("XDG_CACHE_HOME").unwrap_or_else(|| library.join("Caches")),
+                )
+            }
+            Platform::Windows => {
+                let roaming = set("APPDATA")?;
+                let local = set("LOCALAPPDATA").unwrap_or_else(|| roaming.clone());
+                (roaming.clone(), roaming, local)
+            }
+        };
+        Some(Paths {
+            config: config.join(NAME).join("config.toml"),
+            data: data.join(NAME),
+            cache: cache.join(NAME),
+        })
+    }
+
+    /// The state file in [`Paths::data`].
+    pub fn state(&self) -> PathBuf {
+        Self::state_in(&self.data)
+    }
+
+    /// The state file in the data directory `data`, like one given with `--state-dir`.
+    pub fn state_in(data: &Path) -> PathBuf {
+        data.join("state.json")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    use crate::paths::{Paths, Platform};
+
+    fn resolve(platform: Platform, vars: &[(&str, &str)]) -> Option<Paths> {
+        Paths::resolve(platform, |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| OsString::from(value))
+        })
+    }
+
+    fn paths(config: &str, data: &str, cache: &str) -> Option<Paths> {
+        Some(Paths {
+            config: PathBuf::from(config),
+            data: PathBuf::from(data),
+            cache: PathBuf::from(cache),
+        })
+    }
+
+    #[test]
+    fn test_xdg() {
+        assert_eq!(
+            resolve(Platform::Xdg, &[("HOME", "/home/erika")]),
+            paths(
+                "/home/erika/.config/stundenzettel/config.toml",
+                "/home/erika/.local/share/stundenzettel",
+                "/home/erika/.cache/stundenzettel"
+            )
+        );
+        let vars = [
+            ("HOME", "/home/erika"),
+            ("XDG_CONFIG_HOME", "/etc/erika"),
+            ("XDG_DATA_HOME", "relative"),
+            ("XDG_CACHE_HOME", ""),
+        ];
+        let resolved = resolve(Platform::Xdg, &vars).unwrap();
+        assert_eq!(
+            resolved.config,
+            PathBuf::from("/etc/erika/stundenzettel/config.toml")
+        );
+        assert_eq!(
+            resolved.state(),
+            PathBuf::from("/home/erika/.local/share/stundenzettel/state.json")
+        );
+        assert_eq!(
+            resolved.cache,
+            PathBuf::from("/home/erika/.cache/stundenzettel")
+        );
+        // without a home every directory has to be given
+        assert_eq!(resolve(Platform::Xdg, &vars[1..]), None);
+        assert_eq!(resolve(Platform::Xdg, &[]), None);
+    }
+
+    #[test]
+    fn test_platforms() {
+        assert_eq!(
+            resolve(Platform::MacOs, &[("HOME", "/Users/erika")]),
+            paths(
+                "/Users/erika/Library/Application Support/stundenzettel/config.toml",
+                "/Users/erika/Library/Application Support/stundenzettel",
+                "/Users/erika/Library/Caches/stundenzettel"
+            )
+        );
+        let macos = [
+            ("HOME", "/Users/erika"),
+            ("XDG_CONFIG_HOME", "/Users/erika/.config"),
+        ];
+        assert_eq!(
+            resolve(Platform::MacOs, &macos).unwrap().config,
+            PathBuf::from("/Users/erika/.config/stundenzettel/config.toml")
+        );
+
+        let windows = resolve(
+            Platform::Windows,
+            &[
+                ("APPDATA", r"C:\Users\Erika\AppData\Roaming"),
+                ("LOCALAPPDATA", r"C:\Users\Erika\AppData\Local"),
+            ],
+        )
+        .unwrap();
+        let roaming = PathBuf::from(r"C:\Users\Erika\AppData\Roaming").join("stundenzettel");
+        assert_eq!(windows.config, roaming.join("config.toml"));
+        assert_eq!(windows.data, roaming);
+        assert_eq!(
+            windows.cache,
+            PathBuf::from(r"C:\Users\Erika\AppData\Local").join("stundenzettel")
+        );
+        assert_eq!(resolve(Platform::Windows, &[("HOME", "/home/erika")]), None);
+    }
+}
@@ -0,0 +1,306 @@
+//! The gross pay of a timesheet at an hourly rate.
+//!
+//! The pay is earned for the time worked without the breaks of [`crate::breaks`], each day, week
+//! and the month are rounded to cents on their own. [`CostSplit`] splits the pay of several jobs
+//! with rates of their own.
+
+use std::fmt;
+use std::num::NonZeroU32;
+
+use serde::Serialize;
+
+use crate::calendar::{Month, Year};
+use crate::locale::{Locale, Localize};
+use crate::stats::week_of;
+use crate::timesheet::Timesheet;
+
+/// The pay of `minutes` at `rate` cents per hour in cents, halves upwards.
+pub fn pay(minutes: u32, rate: u32) -> u64 {
+    (u64::from(minutes) * u64::from(rate) + 30) / 60
+}
+
+/// The time worked and the pay of a day, a week or a month.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize)]
+pub struct Earnings {
+    /// The minutes worked without breaks.
+    pub minutes: u32,
+    /// The gross pay in cents.
+    pub cents: u64,
+}
+
+impl Earnings {
+    fn new(minutes: u32, rate: u32) -> Self {
+        Earnings {
+            minutes,
+            cents: pay(minutes, rate),
+        }
+    }
+}
+
+/// The earnings of a day of a [`Pay`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct DayPay {
+    /// The day of the month, starting at 1.
+    pub day: NonZeroU32,
+    /// The earnings of the day.
+    #[serde(flatten)]
+    pub earnings: Earnings,
+}
+
+/// The earnings of a week of a [`Pay`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct WeekPay {
+    /// The week within the month, starting at 1 with the week of the first day.
+    pub week: u32,
+    /// The earnings of the week.
+    #[serde(flatten)]
+    pub earnings: Earnings,
+}
+
+/// The gross pay of a timesheet per day, week and month.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Pay {
+    /// The year of the month.
+    pub year: u32,
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The hourly rate in cents.
+    pub rate: u32,
+    /// The days worked, in order.
+    pub days: Vec<DayPay>,
+    /// The weeks with days worked, in order.
+    pub weeks: Vec<WeekPay>,
+    /// The earnings of the month.
+    pub total: Earnings,
+}
+
+impl Pay {
+    /// The pay of `sheet` at `rate` cents per hour.
+    pub fn new(sheet: &Timesheet, rate: u32) -> Self {
+        let year = Year::new(sheet.year);
+        let month = Month::new(sheet.month, &year).expect("timesheets are of existing months");
+        let mut weeks: Vec<(u32, u32)> = Vec::new();
+        for entry in &sheet.entries {
+            let week = week_of(&month, entry.day.get());
+            match weeks.last_mut() {
+                Some((last, minutes)) if *last == week => *minutes += sheet.net_minutes_of(entry),
+                _ => weeks.push((week, sheet.net_minutes_of(entry))),
+            }
+        }
+        Pay {
+            year: sheet.year,
+            month: sheet.month,
+            rate,
+            days: sheet
+                .entries
+                .iter()
+                .map(|entry| DayPay {
+                    day: entry.day,
+                    earnings: Earnings::new(sheet.net_minutes_of(entry), rate),
+                })
+                .collect(),
+            weeks: weeks
+                .into_iter()
+                .map(|(week, minutes)| WeekPay {
+                    week,
+                    earnings: Earnings::new(minutes, rate),
+                })
+                .collect(),
+            total: Earnings::new(sheet.net_minutes(), rate),
+        }
+    }
+}
+
+/// The time worked and the pay of a job of a [`CostSplit`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct JobPay {
+    /// The name of the employer, if any.
+    pub employer: Option<String>,
+    /// The hourly rate in cents, `None` if the job has none and isn't paid.
+    pub rate: Option<u32>,
+    /// The earnings of the job, without pay if it has no rate.
+    #[serde(flatten)]
+    pub earnings: Earnings,
+}
+
+/// The pay of the timesheets of several jobs of a month, each at its own rate.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct CostSplit {
+    /// The jobs, in order.
+    pub jobs: Vec<JobPay>,
+    /// The sum of the earnings of all jobs.
+    pub total: Earnings,
+}
+
+impl CostSplit {
+    /// The split of the timesheets of `jobs`, each given with its employer and hourly rate in
+    /// cents.
+    pub fn new<'a>(
+        jobs: impl IntoIterator<Item = (Option<&'a str>, &'a Timesheet, Option<u32>)>,
+    ) -> Self {
+        let jobs: Vec<_> = jobs
+            .into_iter()
+            .map(|(employer, sheet, rate)| {
+                let minutes = sheet.net_minutes();
+                JobPay {
+                    employer: employer.map(str::to_string),
+                    rate,
+                    earnings: Earnings {
+                        minutes,
+                        cents: rate.map_or(0, |rate| pay(minutes, rate)),
+                    },
+                }
+            })
+            .collect();
+        let total = Earnings {
+            minutes: jobs.iter().map(|job| job.earnings.minutes).sum(),
+            cents: jobs.iter().map(|job| job.earnings.cents).sum(),
+        };
+        CostSplit { jobs, total }
+    }
+}
+
+/// A row per job with its hours, rate and pay followed by the total, jobs without a rate show `-`.
+impl Localize for CostSplit {
+    fn fmt_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let hours = |earnings: &Earnings| locale.decimal(f64::from(earnings.minutes) / 60.0, 2);
+        let width = self
+            .jobs
+            .iter()
+            .filter_map(|job| job.employer.as_ref())
+            .map(|employer| employer.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(10);
+        writeln!(
+            f,
+            "{:<width$}{:>7}  {:>12}  {:>12}",
+            "Job",
+            "Hours",
+            "Rate",
+            "Pay",
+            width = width
+        )?;
+        for (index, job) in self.jobs.iter().enumerate() {
+            let name = job
+                .employer
+                .clone()
+                .unwrap_or_else(|| format!("Job {}", index + 1));
+            let (rate, cents) = match job.rate {
+                Some(rate) => (
+                    locale.currency(rate.into()),
+                    locale.currency(job.earnings.cents),
+                ),
+                None => ("-".into(), "-".into()),
+            };
+            writeln!(
+                f,
+                "{:<width$}{:>7}  {:>12}  {:>12}",
+                name,
+                hours(&job.earnings),
+                rate,
+                cents,
+                width = width
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<width$}{:>7}  {:>12}  {:>12}",
+            "Total",
+            hours(&self.total),
+            "",
+            locale.currency(self.total.cents),
+            width = width
+        )
+    }
+}
+
+impl fmt::Display for CostSplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_localized(f, Locale::default())
+    }
+}
+
+/// A table with a row per day, a row per week and the total of the month followed by the rate.
+impl Localize for Pay {
+    fn fmt_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let hours = |earnings: &Earnings| locale.decimal(f64::from(earnings.minutes) / 60.0, 2);
+        writeln!(f, "{:<10}{:>7}  {:>12}", "Date", "Hours", "Pay")?;
+        for day in &self.days {
+            let date = format!("{}.{}.{}", day.day, self.month, self.year);
+            let earnings = &day.earnings;
+            let cents = locale.currency(earnings.cents);
+            writeln!(f, "{:<10}{:>7}  {:>12}", date, hours(earnings), cents)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<10}{:>7}  {:>12}", "Week", "Hours", "Pay")?;
+        for week in &self.weeks {
+            let earnings = &week.earnings;
+            let cents = locale.currency(earnings.cents);
+            writeln!(f, "{:<10}{:>7}  {:>12}", week.week, hours(earnings), cents)?;
+        }
+        writeln!(f)?;
+        let total = locale.currency(self.total.cents);
+        writeln!(f, "{:<10}{:>7}  {:>12}", "Month", hours(&self.total), total)?;
+        let rate = locale.currency(self.rate.into());
+        writeln!(f, "{:<10}{:>7}  {:>12}", "Rate", "", rate)
+    }
+}
+
+impl fmt::Display for Pay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_localized(f, Locale::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::{deduct_breaks, BreakRule};
+    use crate::calendar::{Month, State, Year};
+    use crate::locale::Locale;
+    use crate::pay::{pay, CostSplit, Pay};
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_pay() {
+        assert_eq!(pay(60, 1550), 1550);
+        assert_eq!(pay(15, 1550), 388);
+        assert_eq!(pay(0, 1550), 0);
+
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let mut sheet = generate_timesheet(&year, &month, parameters, &mut seeded(7)).unwrap();
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        let pay = Pay::new(&sheet, 1550);
+        assert_eq!(pay.days.len(), sheet.entries.len());
+        let weekly: u32 = pay.weeks.iter().map(|week| week.earnings.minutes).sum();
+        assert_eq!(weekly, sheet.net_minutes());
+        assert_eq!(pay.total.cents, u64::from(sheet.net_minutes()) * 1550 / 60);
+        insta::assert_snapshot!(Locale::DeDe.display(&pay).to_string());
+
+        let split = CostSplit::new([
+            (Some("Lehrstuhl"), &sheet, Some(1550)),
+            (None, &sheet, Some(2000)),
+            (Some("Café"), &sheet, None),
+        ]);
+        assert_eq!(split.jobs[0].earnings, pay.total);
+        assert_eq!(split.jobs[2].earnings.cents, 0);
+        assert_eq!(split.total.minutes, 3 * sheet.net_minutes());
+        assert_eq!(
+            split.total.cents,
+            pay.total.cents + u64::from(sheet.net_minutes()) * 2000 / 60
+        );
+        insta::assert_snapshot!(Locale::DeDe.display(&split).to_string());
+    }
+}
@@ -0,0 +1,562 @@
+//! Cross-checks of the options of `generate` before anything is generated.
+//!
+//! Options that are fine on their own can contradict each other, like a maximum of hours per day
+//! that doesn't fit into the window, a break that times rounded to whole hours can't show or an
+//! option that needs a section of the configuration that is missing. [`validate`] checks all of
+//! them in one place and returns the [`Plan`] the generator runs with, so that a conflict is
+//! reported before any time is generated, archived or sent.
+
+use std::num::NonZeroU32;
+
+use crate::breaks::BreakRule;
+use crate::config::Config;
+use crate::constraints::{window_blackouts, Blackout, DateConstraint, WeekdayWindow};
+use crate::error::{Error, Result};
+use crate::output::{Encoding, Format, Layout};
+use crate::policy::{Adjustment, Policy};
+use crate::rng::{Generator, RngSource};
+use crate::rounding::RoundingPolicy;
+use crate::timesheet::SheetParameters;
+use crate::timezone::Timezone;
+
+/// The options of a timesheet that depend on each other.
+#[derive(Debug, Clone)]
+pub struct Arguments<'a> {
+    /// The year that is generated.
+    pub year: u32,
+    /// The month that is generated, starting at 1.
+    pub month: NonZeroU32,
+    /// The parameters with the earliest and the latest hour of the days without a window of their
+    /// own as `from` and `to`.
+    pub parameters: SheetParameters,
+    /// The minutes below a full hour worked on top of the hours.
+    pub minutes: u32,
+    /// Whether the hours are pro-rated by the period of the contract.
+    pub pro_rated: bool,
+    /// The windows of single weekdays.
+    pub windows: &'a [WeekdayWindow],
+    /// The rounding of the times before they are written.
+    pub rounding: Option<RoundingPolicy>,
+    /// The breaks deducted from the written times.
+    pub breaks: &'a [BreakRule],
+    /// The per-day exceptions.
+    pub constraints: &'a [DateConstraint],
+    /// The format of the output.
+    pub format: Format,
+    /// The layout of the output.
+    pub layout: Layout,
+    /// The encoding of the output.
+    pub encoding: Encoding,
+    /// Whether a timesheet is generated for each of the `[[jobs]]` of the configuration, which
+    /// bring their own parameters, so that only the output is checked.
+    pub jobs: bool,
+    /// Whether impossible parameters are adjusted or rejected.
+    pub policy: Policy,
+    /// The generator picked with `--rng`.
+    pub rng: Option<Generator>,
+    /// The seed of the generator.
+    pub seed: Option<u64>,
+    /// The zone of the times given with `--timezone`.
+    pub timezone: Option<Timezone>,
+    /// Whether the notes rotate through the `[[activities]]` of the configuration.
+    pub activities: bool,
+    /// Whether the hours are those of the `[contract]` of the configuration since none were given.
+    pub contract_hours: bool,
+    /// Whether a reproduction replays holidays of the Nager.Date API.
+    pub api_holidays: bool,
+    /// The configuration as far as it is resolved.
+    pub config: &'a Config,
+}
+
+/// The checked parameters of a timesheet.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// The parameters with the window covering the windows of all weekdays as `from` and `to`.
+    pub parameters: SheetParameters,
+    /// The blackouts cutting every weekday back to its own window.
+    pub blackouts: Vec<Blackout>,
+    /// What [`Policy::Adjust`] changed about the parameters.
+    pub adjustments: Vec<Adjustment>,
+    /// The generator of the times, see [`check_rng`].
+    pub source: RngSource,
+}
+
+/// Checks that `arguments` don't contradict each other and lays out the windows of the weekdays.
+///
+/// Fails with the first conflict:
+/// - a generator and a seed that don't belong together, see [`check_rng`],
+/// - a time zone whose rules aren't known in the year,
+/// - `--activities` without `[[activities]]`, the hours of a missing `[contract]` or the
+///   `[[jobs]]` of a configuration without any,
+/// - holidays of the Nager.Date API in a build without the `nager` feature,
+/// - JSON and the calendar layout in another encoding than UTF-8, a layout in a format it isn't
+///   available in or the calendar of several jobs,
+/// - an hour of the window or a maximum of hours per day above 24, see [`SheetParameters::check`],
+/// - a latest hour before the earliest or, under [`Policy::Strict`], a maximum of hours per day
+///   that doesn't fit into the window,
+/// - minutes below a full hour that are pro-rated or that aren't a multiple of the rounding step,
+/// - a break that isn't a multiple of the rounding step,
+/// - a constraint of another month.
+pub fn validate(arguments: &Arguments) -> Result<Plan> {
+    let Arguments {
+        year,
+        month,
+        mut parameters,
+        minutes,
+        pro_rated,
+        windows,
+        rounding,
+        breaks,
+        constraints,
+        format,
+        layout,
+        encoding,
+        jobs,
+        policy,
+        rng,
+        seed,
+        timezone,
+        activities,
+        contract_hours,
+        api_holidays,
+        config,
+    } = *arguments;
+
+    let source = check_rng(rng, seed)?;
+    if let Some(timezone) = timezone {
+        timezone.check(year)?;
+    }
+    let missing = |needed_by: &str, setting: &str| Error::MissingSetting {
+        needed_by: needed_by.into(),
+        setting: setting.into(),
+    };
+    if activities && config.activities.is_empty() {
+        return Err(missing("`--activities`", "`[[activities]]`"));
+    }
+    if contract_hours && config.contract.is_none() {
+        return Err(missing("Generating without the hours", "a `[contract]`"));
+    }
+    if jobs && config.jobs.is_empty() {
+        return Err(missing("`--jobs`", "`[[jobs]]`"));
+    }
+    if api_holidays && !cfg!(feature = "nager") {
+        return Err(Error::MissingFeature {
+            needed_by: "Reproducing holidays of the Nager.Date API".into(),
+            feature: "nager".into(),
+        });
+    }
+    if format == Format::Json && encoding != Encoding::Utf8 {
+        return Err(Error::AlwaysUtf8("JSON".into()));
+    }
+    if !layout.supports(format) {
+        return Err(Error::UnsupportedLayout {
+            layout: layout.to_string(),
+            format: format.to_string(),
+        });
+    }
+    if layout == Layout::Calendar {
+        if jobs {
+            return Err(Error::CalendarOfJobs);
+        }
+        if encoding != Encoding::Utf8 {
+            return Err(Error::AlwaysUtf8("The calendar layout".into()));
+        }
+    }
+    if jobs {
+        return Ok(Plan {
+            parameters,
+            blackouts: Vec::new(),
+            adjustments: Vec::new(),
+            source,
+        });
+    }
+
+    parameters.check()?;
+    let SheetParameters { from, to, .. } = parameters;
+    if to < from {
+        return Err(Error::InvalidWindow { from, to });
+    }
+    if minutes != 0 && pro_rated {
+        return Err(Error::ProRatedMinutes);
+    }
+    if let Some(RoundingPolicy { step, .. }) = rounding {
+        if minutes % step.max(1) != 0 {
+            return Err(Error::MinutesOffStep { minutes, step });
+        }
+        if let Some(rule) = breaks.iter().find(|rule| rule.deduct % step.max(1) != 0) {
+            return Err(Error::BreakOffStep {
+                deduct: rule.deduct,
+                step,
+            });
+        }
+    }
+    if let Some(constraint) = constraints
+        .iter()
+        .find(|c| c.year != year || c.month != month)
+    {
+        return Err(Error::ConstraintOutsideMonth {
+            constraint: constraint.to_string(),
+            year,
+            month,
+        });
+    }
+
+    let (outer, blackouts) = window_blackouts(from, to, windows);
+    parameters.from = outer.from / 60;
+    parameters.to = outer.to / 60;
+    let adjustments = policy.apply(&mut parameters)?;
+    Ok(Plan {
+        parameters,
+        blackouts,
+        adjustments,
+        source,
+    })
+}
+
+/// Checks that a seed is given exactly for the seeded generator and returns the source of the
+/// generator. A seed alone picks the seeded generator, neither the one of the thread.
+pub fn check_rng(generator: Option<Generator>, seed: Option<u64>) -> Result<RngSource> {
+    match (generator, seed) {
+        (Some(Generator::Seeded), None) => Err(Error::MissingSeed),
+        (Some(Generator::Thread | Generator::Os), Some(_)) => Err(Error::SeedWithoutSeeded),
+        (_, Some(seed)) => Ok(RngSource::Seeded(seed)),
+        #[cfg(feature = "os-rng")]
+        (Some(Generator::Os), None) => Ok(RngSource::Os),
+        #[cfg(feature = "os-rng")]
+        (_, None) => Ok(RngSource::Thread),
+        #[cfg(not(feature = "os-rng"))]
+        (_, None) => Err(Error::MissingFeature {
+            needed_by: "Generating without a seed".into(),
+            feature: "os-rng".into(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::BreakRule;
+    use crate::calendar::State;
+    use crate::config::Config;
+    use crate::constraints::{Constraint, DateConstraint};
+    use crate::error::Error;
+    use crate::output::{Encoding, Format, Layout};
+    use crate::plan::{validate, Arguments};
+    use crate::policy::{Adjustment, Policy};
+    use crate::rng::{Generator, RngSource};
+    use crate::rounding::{Rounding, RoundingPolicy};
+    use crate::timesheet::SheetParameters;
+
+    fn arguments() -> Arguments<'static> {
+        Arguments {
+            year: 2024,
+            month: NonZeroU32::new(5).unwrap(),
+            parameters: SheetParameters {
+                state: State::Bavaria,
+                city: None,
+                hours: 40,
+                from: 8,
+                to: 20,
+                max_per_day: 8,
+            },
+            minutes: 0,
+            pro_rated: false,
+            windows: &[],
+            rounding: None,
+            breaks: &[],
+            constraints: &[],
+            format: Format::Text,
+            layout: Layout::Daily,
+            encoding: Encoding::Utf8,
+            jobs: false,
+            policy: Policy::Adjust,
+            rng: None,
+            seed: None,
+            timezone: None,
+            activities: false,
+            contract_hours: false,
+            api_holidays: false,
+            config: Box::leak(Box::default()),
+        }
+    }
+
+    /// A configuration with a job.
+    fn jobs() -> &'static Config {
+        Box::leak(Box::new(
+            Config::parse("[[jobs]]\nweekly_hours = 5\n").unwrap(),
+        ))
+    }
+
+    fn rounding(step: u32) -> Option<RoundingPolicy> {
+        Some(RoundingPolicy {
+            step,
+            mode: Rounding::Nearest,
+        })
+    }
+
+    #[test]
+    fn test_output() {
+        let json = Arguments {
+            format: Format::Json,
+            encoding: Encoding::Latin1,
+            ..arguments()
+        };
+        assert_eq!(
+            validate(&json).unwrap_err(),
+            Error::AlwaysUtf8("JSON".into())
+        );
+        let calendar = Arguments {
+            layout: Layout::Calendar,
+            ..arguments()
+        };
+        assert!(validate(&calendar).is_ok());
+        assert!(matches!(
+            validate(&Arguments {
+                format: Format::Csv,
+                ..calendar.clone()
+            }),
+            Err(Error::UnsupportedLayout { .. })
+        ));
+        assert_eq!(
+            validate(&Arguments {
+                jobs: true,
+                config: jobs(),
+                ..calendar.clone()
+            })
+            .unwrap_err(),
+            Error::CalendarOfJobs
+        );
+        assert_eq!(
+            validate(&Arguments {
+                encoding: Encoding::Utf8Bom,
+                ..calendar
+            })
+            .unwrap_err(),
+            Error::AlwaysUtf8("The calendar layout".into())
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let rng = |rng, seed| {
+            validate(&Arguments {
+                rng,
+                seed,
+                ..arguments()
+            })
+        };
+        assert_eq!(rng(None, Some(1)).unwrap().source, RngSource::Seeded(1));
+        assert!(rng(Some(Generator::Seeded), Some(1)).is_ok());
+        assert_eq!(
+            rng(Some(Generator::Os), None).unwrap().source,
+            RngSource::Os
+        );
+        assert_eq!(rng(None, None).unwrap().source, RngSource::Thread);
+        assert_eq!(
+            rng(Some(Generator::Seeded), None).unwrap_err(),
+            Error::MissingSeed
+        );
+        assert_eq!(
+            rng(Some(Generator::Thread), Some(1)).unwrap_err(),
+            Error::SeedWithoutSeeded
+        );
+        let berlin = "Europe/Berlin".parse().ok();
+        assert!(validate(&Arguments {
+            timezone: berlin,
+            ..arguments()
+        })
+        .is_ok());
+        assert!(matches!(
+            validate(&Arguments {
+                year: 1990,
+                timezone: berlin,
+                ..arguments()
+            }),
+            Err(Error::ZoneBeforeRules { year: 1990, .. })
+        ));
+
+        let missing = |needed_by: &str, setting: &str| Error::MissingSetting {
+            needed_by: needed_by.into(),
+            setting: setting.into(),
+        };
+        let activities = Arguments {
+            activities: true,
+            ..arguments()
+        };
+        assert_eq!(
+            validate(&activities).unwrap_err(),
+            missing("`--activities`", "`[[activities]]`")
+        );
+        let config = Config::parse("[[activities]]\ntext = \"Review\"\n").unwrap();
+        assert!(validate(&Arguments {
+            config: &config,
+            ..activities
+        })
+        .is_ok());
+        let contract = Arguments {
+            contract_hours: true,
+            ..arguments()
+        };
+        assert_eq!(
+            validate(&contract).unwrap_err(),
+            missing("Generating without the hours", "a `[contract]`")
+        );
+        let config = Config::parse("[contract]\nweekly_hours = 10\n").unwrap();
+        assert!(validate(&Arguments {
+            config: &config,
+            ..contract
+        })
+        .is_ok());
+        assert_eq!(
+            validate(&Arguments {
+                jobs: true,
+                ..arguments()
+            })
+            .unwrap_err(),
+            missing("`--jobs`", "`[[jobs]]`")
+        );
+        assert_eq!(
+            validate(&Arguments {
+                api_holidays: true,
+                ..arguments()
+            })
+            .is_ok(),
+            cfg!(feature = "nager")
+        );
+    }
+
+    #[test]
+    fn test_window() {
+        let mut arguments = arguments();
+        arguments.parameters.max_per_day = 10;
+        arguments.parameters.to = 16;
+        let plan = validate(&arguments).unwrap();
+        assert_eq!(plan.parameters.max_per_day, 8);
+        assert_eq!(
+            plan.adjustments,
+            [Adjustment::MaxPerDay {
+                requested: 10,
+                reduced: 8
+            }]
+        );
+        arguments.policy = Policy::Strict;
+        assert_eq!(
+            validate(&arguments).unwrap_err(),
+            Error::WindowTooSmall {
+                from: 8,
+                to: 16,
+                max_per_day: 10
+            }
+        );
+        // a weekday window wider than the others widens the window of the sheet
+        let friday = ["fri=6-18".parse().unwrap()];
+        arguments.windows = &friday;
+        let plan = validate(&arguments).unwrap();
+        assert_eq!((plan.parameters.from, plan.parameters.to), (6, 18));
+        assert!(!plan.blackouts.is_empty());
+        assert!(plan.adjustments.is_empty());
+
+        arguments.parameters.from = 17;
+        assert_eq!(
+            validate(&arguments).unwrap_err(),
+            Error::InvalidWindow { from: 17, to: 16 }
+        );
+        arguments.parameters.to = 30;
+        assert!(matches!(
+            validate(&arguments),
+            Err(Error::AboveDay { value: 30, .. })
+        ));
+        // jobs bring their own parameters
+        arguments.jobs = true;
+        arguments.config = jobs();
+        assert!(validate(&arguments).is_ok());
+    }
+
+    #[test]
+    fn test_granularity() {
+        let fractional = Arguments {
+            minutes: 30,
+            ..arguments()
+        };
+        assert!(validate(&fractional).is_ok());
+        assert!(validate(&Arguments {
+            rounding: rounding(15),
+            ..fractional.clone()
+        })
+        .is_ok());
+        assert_eq!(
+            validate(&Arguments {
+                rounding: rounding(60),
+                ..fractional.clone()
+            })
+            .unwrap_err(),
+            Error::MinutesOffStep {
+                minutes: 30,
+                step: 60
+            }
+        );
+        assert_eq!(
+            validate(&Arguments {
+                pro_rated: true,
+                ..fractional
+            })
+            .unwrap_err(),
+            Error::ProRatedMinutes
+        );
+    }
+
+    #[test]
+    fn test_breaks() {
+        let statutory = Arguments {
+            breaks: &BreakRule::STATUTORY,
+            ..arguments()
+        };
+        assert!(validate(&statutory).is_ok());
+        assert!(validate(&Arguments {
+            rounding: rounding(15),
+            ..statutory.clone()
+        })
+        .is_ok());
+        assert_eq!(
+            validate(&Arguments {
+                rounding: rounding(30),
+                ..statutory
+            })
+            .unwrap_err(),
+            Error::BreakOffStep {
+                deduct: 45,
+                step: 30
+            }
+        );
+    }
+
+    #[test]
+    fn test_constraints() {
+        let constraint = |day, month| DateConstraint {
+            year: 2024,
+            month: NonZeroU32::new(month).unwrap(),
+            day: NonZeroU32::new(day).unwrap(),
+            constraint: Constraint::Exclude,
+        };
+        let vacation = [constraint(10, 5), constraint(31, 5)];
+        assert!(validate(&Arguments {
+            constraints: &vacation,
+            ..arguments()
+        })
+        .is_ok());
+        let vacation = [constraint(10, 5), constraint(3, 6)];
+        assert_eq!(
+            validate(&Arguments {
+                constraints: &vacation,
+                ..arguments()
+            })
+            .unwrap_err(),
+            Error::ConstraintOutsideMonth {
+                constraint: "3.6.2024,exclude".into(),
+                year: 2024,
+                month: NonZeroU32::new(5).unwrap()
+            }
+        );
+    }
+}
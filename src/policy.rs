@@ -0,0 +1,239 @@
+//! Automatic adjustments of parameters that can't be met as given.
+//!
+//! All front ends route their adjustments through a [`Policy`], which either makes them and
+//! reports what changed or refuses them with the error the generator would fail with.
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::timesheet::SheetParameters;
+
+/// Whether parameters that can't be met are adjusted or rejected.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Policy {
+    /// Adjust the parameters and report each [`Adjustment`].
+    #[default]
+    Adjust,
+    /// Fail instead of adjusting anything.
+    Strict,
+}
+
+/// A change made to the parameters under [`Policy::Adjust`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Adjustment {
+    /// The maximum hours per day didn't fit between the earliest and the latest hour.
+    MaxPerDay {
+        /// The requested maximum.
+        requested: u32,
+        /// The maximum that is used instead.
+        reduced: u32,
+    },
+    /// The hours would have earned more than an [`EarningsCap`].
+    Earnings {
+        /// The requested time in minutes.
+        requested: u32,
+        /// The full hours that are used instead.
+        reduced: u32,
+        /// The cap in cents.
+        cap: u32,
+    },
+}
+
+/// A cap on the earnings of a month at an hourly rate, like the 538 € of a Minijob.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EarningsCap {
+    /// The most earned a month in cents.
+    pub cap: u32,
+    /// The hourly rate in cents, not zero.
+    pub rate: u32,
+}
+
+impl EarningsCap {
+    /// The most full hours that stay below the cap.
+    pub fn max_hours(&self) -> u32 {
+        self.cap / self.rate
+    }
+
+    /// Whether working `minutes` earns more than the cap.
+    pub fn exceeded_by(&self, minutes: u32) -> bool {
+        u64::from(minutes) * u64::from(self.rate) > u64::from(self.cap) * 60
+    }
+}
+
+/// `cents` like `538.00 €`.
+fn euros(cents: u32) -> String {
+    format!("{}.{:02} €", cents / 100, cents % 100)
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Adjustment::MaxPerDay { requested, reduced } => write!(
+                f,
+                "Reducing max hours per day from {} to {} to fit the time window",
+                requested, reduced
+            ),
+            Adjustment::Earnings {
+                requested,
+                reduced,
+                cap,
+            } => write!(
+                f,
+                "Reducing the hours from {}:{:02} to {} to stay below the earnings cap of {}",
+                requested / 60,
+                requested % 60,
+                reduced,
+                euros(*cap)
+            ),
+        }
+    }
+}
+
+impl Policy {
+    /// Makes `parameters` feasible, returning the adjustments that were made.
+    ///
+    /// Fails if the window is empty, or under [`Policy::Strict`] if anything would have to be
+    /// adjusted.
+    pub fn apply(&self, parameters: &mut SheetParameters) -> Result<Vec<Adjustment>> {
+        let SheetParameters {
+            from,
+            to,
+            max_per_day,
+            ..
+        } = *parameters;
+        if to < from {
+            return Err(Error::InvalidWindow { from, to });
+        }
+        let mut adjustments = Vec::new();
+        if to - from < max_per_day {
+            if *self == Policy::Strict {
+                return Err(Error::WindowTooSmall {
+                    from,
+                    to,
+                    max_per_day,
+                });
+            }
+            parameters.max_per_day = to - from;
+            adjustments.push(Adjustment::MaxPerDay {
+                requested: max_per_day,
+                reduced: to - from,
+            });
+        }
+        Ok(adjustments)
+    }
+
+    /// Reduces `hours` and the `minutes` below a full hour to the most full hours that stay below
+    /// `cap`, returning the adjustment if one was made.
+    ///
+    /// Fails under [`Policy::Strict`] if they have to be reduced.
+    pub fn cap_earnings(
+        &self,
+        hours: &mut u32,
+        minutes: &mut u32,
+        cap: &EarningsCap,
+    ) -> Result<Option<Adjustment>> {
+        let requested = *hours * 60 + *minutes;
+        if !cap.exceeded_by(requested) {
+            return Ok(None);
+        }
+        if *self == Policy::Strict {
+            return Err(Error::EarningsCapExceeded {
+                minutes: requested,
+                rate: cap.rate,
+                cap: cap.cap,
+                max_hours: cap.max_hours(),
+            });
+        }
+        (*hours, *minutes) = (cap.max_hours(), 0);
+        Ok(Some(Adjustment::Earnings {
+            requested,
+            reduced: *hours,
+            cap: cap.cap,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::calendar::State;
+    use crate::error::Error;
+    use crate::policy::{Adjustment, EarningsCap, Policy};
+    use crate::timesheet::SheetParameters;
+
+    #[test]
+    fn test_apply() {
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 14,
+            max_per_day: 8,
+        };
+        let mut adjusted = parameters;
+        assert_eq!(
+            Policy::Adjust.apply(&mut adjusted),
+            Ok(vec![Adjustment::MaxPerDay {
+                requested: 8,
+                reduced: 6
+            }])
+        );
+        assert_eq!(adjusted.max_per_day, 6);
+        assert_eq!(Policy::Strict.apply(&mut adjusted), Ok(vec![]));
+
+        let mut strict = parameters;
+        assert_eq!(
+            Policy::Strict.apply(&mut strict),
+            Err(Error::WindowTooSmall {
+                from: 8,
+                to: 14,
+                max_per_day: 8
+            })
+        );
+        let mut empty = SheetParameters {
+            to: 6,
+            ..parameters
+        };
+        assert!(matches!(
+            Policy::Adjust.apply(&mut empty),
+            Err(Error::InvalidWindow { from: 8, to: 6 })
+        ));
+    }
+
+    #[test]
+    fn test_cap_earnings() {
+        let minijob = EarningsCap {
+            cap: 53800,
+            rate: 1350,
+        };
+        assert_eq!(minijob.max_hours(), 39);
+        assert!(!minijob.exceeded_by(39 * 60 + 45));
+        assert!(minijob.exceeded_by(40 * 60));
+
+        let (mut hours, mut minutes) = (39, 45);
+        assert_eq!(
+            Policy::Strict.cap_earnings(&mut hours, &mut minutes, &minijob),
+            Ok(None)
+        );
+        let (mut hours, mut minutes) = (40, 30);
+        assert_eq!(
+            Policy::Strict.cap_earnings(&mut hours, &mut minutes, &minijob),
+            Err(Error::EarningsCapExceeded {
+                minutes: 40 * 60 + 30,
+                rate: 1350,
+                cap: 53800,
+                max_hours: 39
+            })
+        );
+        assert_eq!((hours, minutes), (40, 30));
+        let adjustment = Policy::Adjust
+            .cap_earnings(&mut hours, &mut minutes, &minijob)
+            .unwrap()
+            .unwrap();
+        assert_eq!((hours, minutes), (39, 0));
+        assert_eq!(
+            adjustment.to_string(),
+            "Reducing the hours from 40:30 to 39 to stay below the earnings cap of 538.00 €"
+        );
+    }
+}
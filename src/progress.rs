@@ -0,0 +1,130 @@
+//! Reports the progress of commands generating many timesheets, like the months of
+//! `generate --period` or the runs of `simulate`.
+//!
+//! The progress is written to stderr so that it doesn't mix with the timesheets on stdout, either
+//! as a bar redrawn in place for people or as a JSON object per line for tools wrapping the binary.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+/// How the progress is written.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Style {
+    /// A bar like `[######--------------] 2/6 2024-11`, redrawn in place.
+    Bar,
+    /// A JSON object like `{"done":2,"total":6,"item":"2024-11"}` per line.
+    Json,
+}
+
+/// The progress after a step, as written by [`Style::Json`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Step {
+    /// The steps done so far, including this one.
+    pub done: u32,
+    /// All steps.
+    pub total: u32,
+    /// What was done in this step, like the month that was generated.
+    pub item: String,
+}
+
+/// The width of the bar of [`Style::Bar`] in characters.
+const WIDTH: u32 = 20;
+
+/// Writes the progress of `total` steps into a sink.
+#[derive(Debug)]
+pub struct Progress<W> {
+    style: Style,
+    done: u32,
+    total: u32,
+    sink: W,
+}
+
+impl<W: Write> Progress<W> {
+    /// Starts at no step done of `total`.
+    pub fn new(style: Style, total: u32, sink: W) -> Self {
+        Progress {
+            style,
+            done: 0,
+            total,
+            sink,
+        }
+    }
+
+    /// Counts a step that did `item`.
+    pub fn advance(&mut self, item: impl Display) -> io::Result<()> {
+        self.done = (self.done + 1).min(self.total);
+        let item = item.to_string();
+        match self.style {
+            Style::Bar => self.draw(&item),
+            Style::Json => {
+                let step = Step {
+                    done: self.done,
+                    total: self.total,
+                    item,
+                };
+                serde_json::to_writer(&mut self.sink, &step)?;
+                writeln!(self.sink)
+            }
+        }
+    }
+
+    /// Ends the line of the bar.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.style == Style::Bar {
+            writeln!(self.sink)?;
+        }
+        self.sink.flush()
+    }
+
+    fn draw(&mut self, item: &str) -> io::Result<()> {
+        let filled = (self.done * WIDTH).checked_div(self.total).unwrap_or(WIDTH);
+        let bar: String = (0..WIDTH)
+            .map(|i| if i < filled { '#' } else { '-' })
+            .collect();
+        // clears the rest of a longer item drawn before
+        write!(
+            self.sink,
+            "\r[{}] {}/{} {}\x1b[K",
+            bar, self.done, self.total, item
+        )?;
+        self.sink.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::progress::{Progress, Style};
+
+    #[test]
+    fn test_bar() {
+        let mut sink = Vec::new();
+        let mut progress = Progress::new(Style::Bar, 4, &mut sink);
+        progress.advance("2024-10").unwrap();
+        progress.advance("2024-11").unwrap();
+        progress.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            "\r[#####---------------] 1/4 2024-10\x1b[K\
+             \r[##########----------] 2/4 2024-11\x1b[K\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let mut sink = Vec::new();
+        let mut progress = Progress::new(Style::Json, 2, &mut sink);
+        progress.advance(7).unwrap();
+        progress.advance(8).unwrap();
+        // more steps than announced stay at the total
+        progress.advance(9).unwrap();
+        progress.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            "{\"done\":1,\"total\":2,\"item\":\"7\"}\n\
+             {\"done\":2,\"total\":2,\"item\":\"8\"}\n\
+             {\"done\":2,\"total\":2,\"item\":\"9\"}\n"
+        );
+    }
+}
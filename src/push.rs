@@ -0,0 +1,226 @@
+//! Creates the entries of a timesheet in time tracking services.
+//!
+//! The requests are built as plain [`PushRequest`]s by the module of each service and then sent
+//! with [`send`], spaced out by the rate limit of the service. A request the service rejects with
+//! `429 Too Many Requests` is sent again after its `Retry-After` or an exponential backoff.
+
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+use serde_json::Value;
+
+use crate::timesheet::Timesheet;
+use crate::timezone::Timezone;
+
+pub mod clockify;
+pub mod gcal;
+pub mod toggl;
+
+/// The reasons pushing a timesheet can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum PushError {
+    /// The configuration lacks the section of the service.
+    #[error("The configuration has no [{0}] section")]
+    MissingConfig(&'static str),
+    /// The service couldn't be reached.
+    #[error("Pushing failed: {0}")]
+    Http(#[from] ureq::Error),
+    /// The service rejected a request, or still limited the rate after [`MAX_RETRIES`] retries.
+    #[error("The service answered with status {0}")]
+    Status(u16),
+    /// A request failed after the ones before it created their entries, pushing the timesheet
+    /// again creates those a second time.
+    #[error("{source}, {created} of {total} entries were created before")]
+    Interrupted {
+        /// The entries created before the failure.
+        created: usize,
+        /// All entries.
+        total: usize,
+        /// Why the request failed.
+        source: Box<PushError>,
+    },
+    /// Obtaining an OAuth token failed.
+    #[error("Authorization failed: {0}")]
+    Authorization(String),
+    /// Waiting for the OAuth redirect failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A `POST` of a JSON body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushRequest {
+    /// The endpoint.
+    pub url: String,
+    /// The headers besides `Content-Type`, usually for authentication.
+    pub headers: Vec<(&'static str, String)>,
+    /// The JSON body.
+    pub body: Value,
+}
+
+impl fmt::Display for PushRequest {
+    /// The request without its headers, which contain the credentials.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "POST {}\n{}", self.url, self.body)
+    }
+}
+
+/// The value of a `Authorization` header for HTTP basic authentication.
+pub(crate) fn basic_auth(username: &str, password: &str) -> String {
+    let encoded =
+        base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+    format!("Basic {}", encoded)
+}
+
+/// The local time `minute` minutes after midnight on `day` of `sheet` in `timezone` as an RFC 3339
+/// timestamp.
+pub(crate) fn timestamp(sheet: &Timesheet, day: u32, minute: u32, timezone: Timezone) -> String {
+    timezone.timestamp(sheet.year, sheet.month, day, minute)
+}
+
+/// How often a request limited by `429 Too Many Requests` is retried.
+pub const MAX_RETRIES: u32 = 5;
+
+/// The wait before the first retry without a `Retry-After`, doubled for every further one.
+const BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest wait before a retry, also for a longer `Retry-After`.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// The status of the answer to a request and its `Retry-After` in seconds.
+type Answer = (u16, Option<u64>);
+
+/// Sends `requests` in order with at least `interval` between two requests, stopping at the first
+/// failure. Fails with [`PushError::Interrupted`] if entries were created before it.
+pub fn send(requests: &[PushRequest], interval: Duration) -> Result<(), PushError> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let post = |request: &PushRequest| -> Result<Answer, PushError> {
+        let mut builder = agent
+            .post(&request.url)
+            .header("Content-Type", "application/json");
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        let response = builder.send(request.body.to_string())?;
+        let retry_after = response
+            .headers()
+            .get("Retry-After")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+        Ok((response.status().as_u16(), retry_after))
+    };
+    send_with(requests, interval, post, thread::sleep)
+}
+
+/// [`send`] with `post` sending a request and `sleep` waiting.
+fn send_with(
+    requests: &[PushRequest],
+    interval: Duration,
+    mut post: impl FnMut(&PushRequest) -> Result<Answer, PushError>,
+    mut sleep: impl FnMut(Duration),
+) -> Result<(), PushError> {
+    let interrupted = |created: usize, error: PushError| match created {
+        0 => error,
+        _ => PushError::Interrupted {
+            created,
+            total: requests.len(),
+            source: Box::new(error),
+        },
+    };
+    for (created, request) in requests.iter().enumerate() {
+        if created > 0 {
+            sleep(interval);
+        }
+        let mut retries = 0;
+        loop {
+            let (status, retry_after) = post(request).map_err(|e| interrupted(created, e))?;
+            match status {
+                200..=299 => break,
+                429 if retries < MAX_RETRIES => {
+                    let backoff = BACKOFF * 2u32.pow(retries);
+                    sleep(
+                        retry_after
+                            .map_or(backoff, Duration::from_secs)
+                            .min(MAX_WAIT),
+                    );
+                    retries += 1;
+                }
+                _ => return Err(interrupted(created, PushError::Status(status))),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use crate::push::{send_with, PushError, PushRequest, MAX_RETRIES};
+
+    fn requests(count: usize) -> Vec<PushRequest> {
+        (0..count)
+            .map(|i| PushRequest {
+                url: format!("https://example.com/{}", i),
+                headers: Vec::new(),
+                body: json!({ "entry": i }),
+            })
+            .collect()
+    }
+
+    /// Sends `count` requests to a service answering with `answers` in turn, the sent URLs and the
+    /// waits in seconds.
+    fn run(
+        count: usize,
+        answers: &[(u16, Option<u64>)],
+    ) -> (Result<(), PushError>, Vec<String>, Vec<u64>) {
+        let (mut sent, mut waits) = (Vec::new(), Vec::new());
+        let mut answers = answers.iter().copied();
+        let result = send_with(
+            &requests(count),
+            Duration::from_secs(1),
+            |request| {
+                sent.push(request.url.clone());
+                Ok(answers.next().unwrap_or((201, None)))
+            },
+            |wait| waits.push(wait.as_secs()),
+        );
+        (result, sent, waits)
+    }
+
+    #[test]
+    fn test_retry() {
+        let (result, sent, waits) = run(2, &[(201, None), (429, Some(7)), (429, None)]);
+        assert!(result.is_ok());
+        assert_eq!(sent.len(), 4);
+        assert_eq!(sent[1], sent[3]);
+        // the interval, the Retry-After and the second backoff
+        assert_eq!(waits, [1, 7, 2]);
+
+        let limited = vec![(429, Some(600)); MAX_RETRIES as usize + 1];
+        let (result, sent, waits) = run(1, &limited);
+        assert!(matches!(result, Err(PushError::Status(429))));
+        assert_eq!(sent.len(), MAX_RETRIES as usize + 1);
+        assert!(waits.iter().all(|&wait| wait == 60));
+    }
+
+    #[test]
+    fn test_interrupted() {
+        let (result, sent, _) = run(3, &[(201, None), (201, None), (400, None)]);
+        assert_eq!(sent.len(), 3);
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The service answered with status 400, 2 of 3 entries were created before"
+        );
+        let (result, _, _) = run(3, &[(500, None)]);
+        assert!(matches!(result, Err(PushError::Status(500))));
+    }
+}
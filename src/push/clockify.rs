@@ -0,0 +1,97 @@
+//! Time entries in [Clockify](https://clockify.me) via its REST API.
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::config::ClockifyConfig;
+use crate::push::{timestamp, PushRequest};
+use crate::timesheet::Timesheet;
+use crate::timezone::Timezone;
+
+const API: &str = "https://api.clockify.me/api/v1";
+
+/// Clockify allows 50 requests per second.
+pub const INTERVAL: Duration = Duration::from_millis(20);
+
+/// One request creating a time entry per entry of `sheet`, described by its note if it has one.
+pub fn requests(
+    config: &ClockifyConfig,
+    timezone: Timezone,
+    sheet: &Timesheet,
+) -> Vec<PushRequest> {
+    let url = format!("{}/workspaces/{}/time-entries", API, config.workspace);
+    sheet
+        .entries
+        .iter()
+        .map(|entry| {
+            let day = entry.day.get();
+            PushRequest {
+                url: url.clone(),
+                headers: vec![("X-Api-Key", config.api_key.clone())],
+                body: json!({
+                    "start": timestamp(sheet, day, entry.time.from, timezone),
+                    "end": timestamp(sheet, day, entry.time.to, timezone),
+                    "projectId": config.project,
+                    "description": entry.note.as_ref().unwrap_or(&config.description),
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use serde_json::json;
+
+    use crate::calendar::{DayOfWeek, State};
+    use crate::config::Config;
+    use crate::generate::TimeSpan;
+    use crate::push::clockify::requests;
+    use crate::timesheet::{Entry, Timesheet};
+
+    #[test]
+    fn test_requests() {
+        let config =
+            Config::parse("[clockify]\napi_key = \"key\"\nworkspace = \"ws\"\nproject = \"p\"")
+                .unwrap();
+        let sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(3).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: vec![Entry {
+                day: NonZeroU32::new(4).unwrap(),
+                day_of_week: DayOfWeek::Monday,
+                time: TimeSpan::hours(9, 15),
+                surcharge: None,
+                break_minutes: 0,
+                note: None,
+            }],
+        };
+        let mut noted = sheet.clone();
+        noted.entries[0].note = Some("Server migration".into());
+        let noted = requests(config.clockify.as_ref().unwrap(), config.timezone(), &noted);
+        assert_eq!(noted[0].body["description"], "Server migration");
+
+        let requests = requests(config.clockify.as_ref().unwrap(), config.timezone(), &sheet);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://api.clockify.me/api/v1/workspaces/ws/time-entries"
+        );
+        assert_eq!(requests[0].headers, vec![("X-Api-Key", "key".to_string())]);
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "start": "2024-03-04T09:00:00Z",
+                "end": "2024-03-04T15:00:00Z",
+                "projectId": "p",
+                "description": "Arbeitszeit",
+            })
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Events in a [Google Calendar](https://calendar.google.com) via the Calendar v3 API.
+//!
+//! Google requires OAuth: create an OAuth client of type "Desktop app" in the Google Cloud console,
+//! put its ID and secret into the `[gcal]` section and run `push gcal --authorize` once to obtain
+//! the refresh token. Pushing exchanges it for a short lived access token every time.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::GcalConfig;
+use crate::push::{timestamp, PushError, PushRequest};
+use crate::timesheet::Timesheet;
+use crate::timezone::Timezone;
+
+const API: &str = "https://www.googleapis.com/calendar/v3";
+const AUTHORIZE: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN: &str = "https://oauth2.googleapis.com/token";
+const SCOPE: &str = "https://www.googleapis.com/auth/calendar.events";
+
+/// Google allows about ten requests per second and user.
+pub const INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986.
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Reverses [`encode`], `None` if an escape is malformed.
+fn decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.bytes();
+    while let Some(b) = rest.next() {
+        match b {
+            b'%' => {
+                let hex = [rest.next()?, rest.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn token(form: &[(&str, &str)]) -> Result<TokenResponse, PushError> {
+    let response = ureq::post(TOKEN)
+        .send_form(form.iter().copied())?
+        .body_mut()
+        .read_to_string()?;
+    serde_json::from_str(&response).map_err(|e| PushError::Authorization(e.to_string()))
+}
+
+/// A running authorization, see [`Authorization::start`].
+pub struct Authorization<'a> {
+    config: &'a GcalConfig,
+    listener: TcpListener,
+    redirect_uri: String,
+}
+
+impl<'a> Authorization<'a> {
+    /// Starts listening for the redirect of Google's consent screen on a local port.
+    pub fn start(config: &'a GcalConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let redirect_uri = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+        Ok(Authorization {
+            config,
+            listener,
+            redirect_uri,
+        })
+    }
+
+    /// The URL of the consent screen to open in a browser.
+    pub fn url(&self) -> String {
+        format!(
+            "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent",
+            AUTHORIZE,
+            encode(&self.config.client_id),
+            encode(&self.redirect_uri),
+            encode(SCOPE)
+        )
+    }
+
+    /// Waits for the redirect and returns the refresh token for [`GcalConfig::refresh_token`].
+    pub fn finish(self) -> Result<String, PushError> {
+        let (mut stream, _) = self.listener.accept()?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let query = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|target| target.split_once('?'))
+            .map_or("", |(_, query)| query);
+        let parameter = |name: &str| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                .and_then(decode)
+        };
+        let code = parameter("code");
+        let message = if code.is_some() {
+            "Authorized, you can close this window."
+        } else {
+            "Authorization failed, see the terminal."
+        };
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            message.len(),
+            message
+        )?;
+        let code = code.ok_or_else(|| {
+            PushError::Authorization(parameter("error").unwrap_or_else(|| "no code".to_string()))
+        })?;
+
+        let response = token(&[
+            ("code", &code),
+            ("client_id", &self.config.client_id),
+            ("client_secret", &self.config.client_secret),
+            ("redirect_uri", &self.redirect_uri),
+            ("grant_type", "authorization_code"),
+        ])?;
+        response
+            .refresh_token
+            .ok_or_else(|| PushError::Authorization("Google sent no refresh token".to_string()))
+    }
+}
+
+/// Exchanges the refresh token of `config` for an access token.
+pub fn access_token(config: &GcalConfig) -> Result<String, PushError> {
+    let refresh_token = config.refresh_token.as_deref().ok_or_else(|| {
+        PushError::Authorization("No refresh token, run `push gcal --authorize` first".to_string())
+    })?;
+    let response = token(&[
+        ("refresh_token", refresh_token),
+        ("client_id", &config.client_id),
+        ("client_secret", &config.client_secret),
+        ("grant_type", "refresh_token"),
+    ])?;
+    Ok(response.access_token)
+}
+
+/// One request inserting an event per entry of `sheet`, authenticated with `access_token`.
+///
+/// The note of an entry becomes the description of its event.
+pub fn requests(
+    config: &GcalConfig,
+    timezone: Timezone,
+    sheet: &Timesheet,
+    access_token: &str,
+) -> Vec<PushRequest> {
+    let url = format!("{}/calendars/{}/events", API, encode(&config.calendar));
+    sheet
+        .entries
+        .iter()
+        .map(|entry| {
+            let day = entry.day.get();
+            // a named zone lets recurring views and other clients show the local time
+            let time = |minute| match timezone.name() {
+                Some(name) => json!({
+                    "dateTime": timestamp(sheet, day, minute, timezone),
+                    "timeZone": name,
+                }),
+                None => json!({ "dateTime": timestamp(sheet, day, minute, timezone) }),
+            };
+            let mut body = json!({
+                "summary": config.description,
+                "start": time(entry.time.from),
+                "end": time(entry.time.to),
+            });
+            if let Some(note) = &entry.note {
+                body["description"] = note.as_str().into();
+            }
+            PushRequest {
+                url: url.clone(),
+                headers: vec![("Authorization", format!("Bearer {}", access_token))],
+                body,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use serde_json::json;
+
+    use crate::calendar::{DayOfWeek, State};
+    use crate::config::Config;
+    use crate::generate::TimeSpan;
+    use crate::push::gcal::{decode, encode, requests, Authorization};
+    use crate::timesheet::{Entry, Timesheet};
+
+    #[test]
+    fn test_requests() {
+        let config = Config::parse(
+            "[gcal]\nclient_id = \"id\"\nclient_secret = \"secret\"\ncalendar = \"work@group.calendar.google.com\"",
+        )
+        .unwrap();
+        let gcal = config.gcal.as_ref().unwrap();
+        let sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(3).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: vec![Entry {
+                day: NonZeroU32::new(4).unwrap(),
+                day_of_week: DayOfWeek::Monday,
+                time: TimeSpan::hours(9, 15),
+                surcharge: None,
+                break_minutes: 0,
+                note: None,
+            }],
+        };
+        let zoned = requests(gcal, "Europe/Berlin".parse().unwrap(), &sheet, "token");
+        assert_eq!(
+            zoned[0].body["end"],
+            json!({ "dateTime": "2024-03-04T15:00:00+01:00", "timeZone": "Europe/Berlin" })
+        );
+        let mut noted = sheet.clone();
+        noted.entries[0].note = Some("Server migration".into());
+        let noted = requests(gcal, config.timezone(), &noted, "token");
+        assert_eq!(noted[0].body["summary"], "Arbeitszeit");
+        assert_eq!(noted[0].body["description"], "Server migration");
+
+        let requests = requests(gcal, config.timezone(), &sheet, "token");
+        assert_eq!(
+            requests[0].url,
+            "https://www.googleapis.com/calendar/v3/calendars/work%40group.calendar.google.com/events"
+        );
+        assert_eq!(
+            requests[0].headers,
+            vec![("Authorization", "Bearer token".to_string())]
+        );
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "summary": "Arbeitszeit",
+                "start": { "dateTime": "2024-03-04T09:00:00Z" },
+                "end": { "dateTime": "2024-03-04T15:00:00Z" },
+            })
+        );
+
+        let authorization = Authorization::start(gcal).unwrap();
+        assert!(authorization
+            .url()
+            .contains("redirect_uri=http%3A%2F%2F127.0.0.1%3A"));
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(encode("4/0Ab c"), "4%2F0Ab%20c");
+        assert_eq!(decode("4%2F0Ab+c").unwrap(), "4/0Ab c");
+        assert_eq!(decode(&encode("ä@x")).unwrap(), "ä@x");
+        assert_eq!(decode("%4"), None);
+    }
+}
@@ -0,0 +1,105 @@
+//! Python bindings, the module is called `stundenzettel`.
+//!
+//! ```python
+//! import stundenzettel
+//!
+//! for entry in stundenzettel.generate_timesheet(2, 2024, 40, state="NW", seed=1):
+//!     print(entry["day"], entry["from"], entry["to"])
+//! ```
+
+use std::num::NonZeroU32;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::calendar::{Month, State, Year};
+use crate::error::Error;
+use crate::rng::{make_rng, RngSource};
+use crate::timesheet;
+use crate::timesheet::SheetParameters;
+
+fn value_error(error: Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// Generates the timesheet of `month` in `year`.
+///
+/// Returns a list with a `{ day, month, year, weekday, from, to }` dict per day worked, `from` and
+/// `to` in minutes after midnight, and raises a `ValueError` if the constraints can't be met.
+#[pyfunction]
+#[pyo3(signature = (month, year, hours, max_per_day = 8, earliest = 8, latest = 20, state = "BY", seed = None))]
+#[allow(clippy::too_many_arguments)]
+fn generate_timesheet<'py>(
+    py: Python<'py>,
+    month: u32,
+    year: u32,
+    hours: u32,
+    max_per_day: u32,
+    earliest: u32,
+    latest: u32,
+    state: &str,
+    seed: Option<u64>,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let state: State = state.parse().map_err(value_error)?;
+    let year = Year::new(year);
+    let month = NonZeroU32::new(month).ok_or(Error::InvalidMonth(month));
+    let month = month
+        .and_then(|month| Month::new(month, &year))
+        .map_err(value_error)?;
+    let source = seed.map_or(RngSource::Thread, RngSource::Seeded);
+    let sheet = timesheet::generate_timesheet(
+        &year,
+        &month,
+        SheetParameters {
+            state,
+            city: None,
+            hours,
+            from: earliest,
+            to: latest,
+            max_per_day,
+        },
+        &mut *make_rng(source),
+    )
+    .map_err(value_error)?;
+
+    sheet
+        .entries
+        .iter()
+        .map(|entry| {
+            let dict = PyDict::new(py);
+            dict.set_item("day", entry.day.get())?;
+            dict.set_item("month", sheet.month.get())?;
+            dict.set_item("year", sheet.year)?;
+            dict.set_item("weekday", format!("{:?}", entry.day_of_week))?;
+            dict.set_item("from", entry.time.from)?;
+            dict.set_item("to", entry.time.to)?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+/// The public holidays of `year` in `state` as a list of `{ day, month, name }` dicts.
+#[pyfunction]
+#[pyo3(signature = (year, state = "BY"))]
+fn holidays<'py>(py: Python<'py>, year: u32, state: &str) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let state: State = state.parse().map_err(value_error)?;
+    Year::new(year)
+        .holidays(state)
+        .into_iter()
+        .map(|holiday| {
+            let dict = PyDict::new(py);
+            dict.set_item("day", holiday.date.day.get())?;
+            dict.set_item("month", holiday.date.month.get())?;
+            dict.set_item("name", holiday.name)?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+#[pymodule]
+fn stundenzettel(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(generate_timesheet, m)?)?;
+    m.add_function(wrap_pyfunction!(holidays, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+//! Compares the archived hours of a year with the working hours of the contract.
+
+use std::fmt;
+use std::num::NonZeroU32;
+
+use serde::Serialize;
+
+use crate::locale::{Locale, Localize};
+use crate::timesheet::Timesheet;
+
+/// The hours of one month of a [`Report`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct MonthBalance {
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The hours of the timesheet of this month, `None` if there is none.
+    pub hours: Option<u32>,
+    /// The hours of the contract in this month.
+    pub target: u32,
+    /// The surplus of all timesheets up to and including this month, months without a timesheet
+    /// don't change it.
+    pub balance: i64,
+}
+
+/// The balance of the timesheets of a year.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Report {
+    /// The year of the report.
+    pub year: u32,
+    /// All months of the year, in order.
+    pub months: Vec<MonthBalance>,
+    /// The hours of all timesheets.
+    pub hours: u32,
+    /// The hours of the contract in the whole year that aren't covered by timesheets yet, negative
+    /// if more were worked.
+    pub remaining: i64,
+}
+
+impl Report {
+    /// Compares the `sheets` of `year` with a contract of `target` hours per month, sheets of
+    /// other years are ignored.
+    pub fn new(year: u32, target: u32, sheets: &[Timesheet]) -> Self {
+        let mut balance = 0;
+        let months = (1..=12)
+            .filter_map(NonZeroU32::new)
+            .map(|month| {
+                let hours = sheets
+                    .iter()
+                    .find(|sheet| sheet.year == year && sheet.month == month)
+                    .map(Timesheet::hours);
+                if let Some(hours) = hours {
+                    balance += i64::from(hours) - i64::from(target);
+                }
+                MonthBalance {
+                    month,
+                    hours,
+                    target,
+                    balance,
+                }
+            })
+            .collect::<Vec<_>>();
+        let hours = months.iter().filter_map(|month| month.hours).sum();
+        Report {
+            year,
+            remaining: 12 * i64::from(target) - i64::from(hours),
+            months,
+            hours,
+        }
+    }
+}
+
+/// A table with a row per month followed by the totals.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_localized(f, Locale::default())
+    }
+}
+
+impl Localize for Report {
+    fn fmt_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let integer = |value: u32| locale.integer(value.into());
+        writeln!(
+            f,
+            "{:<10}{:>5}  {:>6}  {:>7}",
+            "Month", "Hours", "Target", "Balance"
+        )?;
+        for month in &self.months {
+            let date = format!("{:02}/{}", month.month, self.year);
+            let hours = month.hours.map_or("-".into(), integer);
+            let sign = if month.balance < 0 { "" } else { "+" };
+            writeln!(
+                f,
+                "{:<10}{:>5}  {:>6}  {:>7}",
+                date,
+                hours,
+                integer(month.target),
+                format!("{}{}", sign, locale.integer(month.balance))
+            )?;
+        }
+        let target: u32 = self.months.iter().map(|month| month.target).sum();
+        writeln!(
+            f,
+            "{:<10}{:>5}  {:>6}",
+            "Total",
+            integer(self.hours),
+            integer(target)
+        )?;
+        writeln!(
+            f,
+            "{:<10}{:>5}",
+            "Remaining",
+            locale.integer(self.remaining)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::locale::Locale;
+    use crate::report::Report;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_report() {
+        let sheets: Vec<_> = [(2023, 12, 40), (2024, 1, 50), (2024, 3, 20)]
+            .into_iter()
+            .map(|(year, month, hours)| {
+                let year = Year::new(year);
+                let month = Month::new(NonZeroU32::new(month).unwrap(), &year).unwrap();
+                let parameters = SheetParameters {
+                    state: State::Bavaria,
+                    city: None,
+                    hours,
+                    from: 8,
+                    to: 20,
+                    max_per_day: 8,
+                };
+                generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap()
+            })
+            .collect();
+        let report = Report::new(2024, 40, &sheets);
+        let balances: Vec<_> = report
+            .months
+            .iter()
+            .map(|month| (month.hours, month.balance))
+            .collect();
+        assert_eq!(
+            balances[..4],
+            [(Some(50), 10), (None, 10), (Some(20), -10), (None, -10)]
+        );
+        assert_eq!(report.hours, 70);
+        assert_eq!(report.remaining, 410);
+        insta::assert_snapshot!(report.to_string());
+
+        let german = Locale::DeDe
+            .display(&Report::new(2024, 160, &sheets))
+            .to_string();
+        assert!(german.contains("Total        70   1.920"), "{}", german);
+        assert!(german.contains("Remaining 1.850"), "{}", german);
+    }
+}
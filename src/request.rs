@@ -0,0 +1,262 @@
+//! The JSON parameters of a timesheet, read by `generate --stdin` and the HTTP server, and the
+//! [`Reproduction`] of a generated one.
+
+use std::num::NonZeroU32;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::breaks::BreakRule;
+use crate::calendar::{City, State};
+use crate::constraints::{parse_constraints, Blackout, DateConstraint};
+use crate::error::Result;
+use crate::notes::{Activity, Rotation};
+use crate::rounding::RoundingPolicy;
+use crate::timesheet::{FreeDays, Remainder, SheetParameters, Strategy, WeeklyCap};
+
+/// The parameters of a timesheet, the optional fields default to the values of the CLI. The hours
+/// are checked with [`SheetParameters::check`] like the options of the CLI.
+///
+/// ```json
+/// { "month": 2, "year": 2024, "hours": 40, "state": "NW", "seed": 1 }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GenerateRequest {
+    /// The month, starting at 1.
+    #[schemars(range(max = 12))]
+    pub month: NonZeroU32,
+    /// The year.
+    pub year: u32,
+    /// Hours to assign.
+    pub hours: u32,
+    /// Maximum assignable hours per day.
+    #[serde(default = "default_max_per_day")]
+    #[schemars(range(max = 24))]
+    pub max_per_day: u32,
+    /// Earliest assignable starting hour.
+    #[serde(default = "default_earliest")]
+    #[schemars(range(max = 24))]
+    pub earliest: u32,
+    /// Latest assignable stopping hour.
+    #[serde(default = "default_latest")]
+    #[schemars(range(max = 24))]
+    pub latest: u32,
+    /// The state whose holidays aren't worked on.
+    #[serde(default)]
+    pub state: State,
+    /// The city within `state` whose holidays aren't worked on either, e.g. `Augsburg`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<City>,
+    /// Seed for a reproducible timesheet, a random one is generated if missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+pub(crate) fn default_max_per_day() -> u32 {
+    8
+}
+
+pub(crate) fn default_earliest() -> u32 {
+    8
+}
+
+pub(crate) fn default_latest() -> u32 {
+    20
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+impl GenerateRequest {
+    /// The constraints for [`generate_timesheet`](crate::timesheet::generate_timesheet).
+    pub fn parameters(&self) -> SheetParameters {
+        SheetParameters {
+            state: self.state,
+            city: self.city,
+            hours: self.hours,
+            from: self.earliest,
+            to: self.latest,
+            max_per_day: self.max_per_day,
+        }
+    }
+}
+
+/// Everything a generated timesheet depends on, printed by `generate` after every run with a seed
+/// and replayed exactly by `generate --reproduce`.
+///
+/// The parameters are normalized: the hours are pro-rated by the contract, the window and maximum
+/// are adjusted by the policy and the weekday windows, leave and short days are expressed as
+/// blackouts and constraints, so replaying needs neither the configuration nor any other file.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Reproduction {
+    /// The parameters including the seed.
+    #[serde(flatten)]
+    pub request: GenerateRequest,
+    /// The minutes below a full hour of the hours.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub minutes: u32,
+    /// Where the minutes are worked.
+    #[serde(default)]
+    pub remainder: Remainder,
+    /// The constraints of the month as rows of a constraints file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<String>,
+    /// The blackouts, including those of the weekday windows.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blackouts: Vec<String>,
+    /// Whether single hours were moved between the weeks.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub avoid_round_weeks: bool,
+    /// The most minutes worked a week, including those of the archived neighboring months.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_cap: Option<WeeklyCap>,
+    /// Whether the days were moved to start around the same time.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cluster_starts: bool,
+    /// Where the working days without work were moved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free_days: Option<FreeDays>,
+    /// The most working days in a row without work.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_free_streak: Option<u32>,
+    /// The percentage of the hours moved onto weekends and holidays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub off_days: Option<u32>,
+    /// The shape of the hours of the month.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<Strategy>,
+    /// The archived month `YYYY-MM` whose times were copied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub like: Option<String>,
+    /// The most minutes the copied starts were moved by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<u32>,
+    /// The rounding applied to the times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<RoundingPolicy>,
+    /// The breaks deducted from the printed times.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breaks: Vec<BreakRule>,
+    /// The highest audit score the timesheet was generated again until.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<u32>,
+    /// The country or region whose holidays were taken from the Nager.Date API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holidays_from_api: Option<String>,
+    /// How the activities were drawn for the days without a note.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Rotation>,
+    /// The activities the notes were drawn from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activities: Vec<Activity>,
+}
+
+impl Reproduction {
+    /// Records `constraints` and `blackouts` in addition to the other fields, without repeating
+    /// equal constraints.
+    pub fn new(
+        request: GenerateRequest,
+        constraints: &[DateConstraint],
+        blackouts: &[Blackout],
+    ) -> Self {
+        let mut rows: Vec<String> = Vec::new();
+        for constraint in constraints {
+            let row = constraint.to_string();
+            if !rows.contains(&row) {
+                rows.push(row);
+            }
+        }
+        Reproduction {
+            request,
+            minutes: 0,
+            remainder: Remainder::default(),
+            constraints: rows,
+            blackouts: blackouts.iter().map(Blackout::to_string).collect(),
+            avoid_round_weeks: false,
+            weekly_cap: None,
+            cluster_starts: false,
+            free_days: None,
+            max_free_streak: None,
+            off_days: None,
+            distribution: None,
+            like: None,
+            jitter: None,
+            rounding: None,
+            breaks: Vec::new(),
+            max_score: None,
+            holidays_from_api: None,
+            rotation: None,
+            activities: Vec::new(),
+        }
+    }
+
+    /// The recorded constraints, each row is read on its own so that rows don't conflict.
+    pub fn date_constraints(&self) -> Result<Vec<DateConstraint>> {
+        let mut constraints = Vec::new();
+        for row in &self.constraints {
+            constraints.extend(parse_constraints(row)?);
+        }
+        Ok(constraints)
+    }
+
+    /// The recorded blackouts.
+    pub fn blackouts(&self) -> Result<Vec<Blackout>> {
+        self.blackouts
+            .iter()
+            .map(|blackout| blackout.parse())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::State;
+    use crate::constraints::parse_constraints;
+    use crate::request::{GenerateRequest, Reproduction};
+
+    #[test]
+    fn test_defaults() {
+        let request: GenerateRequest =
+            serde_json::from_str(r#"{ "month": 2, "year": 2024, "hours": 40 }"#).unwrap();
+        assert_eq!(
+            request,
+            GenerateRequest {
+                month: NonZeroU32::new(2).unwrap(),
+                year: 2024,
+                hours: 40,
+                max_per_day: 8,
+                earliest: 8,
+                latest: 20,
+                state: State::Bavaria,
+                city: None,
+                seed: None,
+            }
+        );
+        assert!(
+            serde_json::from_str::<GenerateRequest>(r#"{ "month": 2, "year": 2024 }"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_reproduction() {
+        let request: GenerateRequest =
+            serde_json::from_str(r#"{ "month": 5, "year": 2024, "hours": 40, "seed": 1 }"#)
+                .unwrap();
+        let constraints = parse_constraints("13.5.2024,cap,3\n14.5.2024,fill,9:00,13:00").unwrap();
+        let blackouts = ["tue=12:00-13:30".parse().unwrap()];
+        let repeated = [constraints.clone(), constraints.clone()].concat();
+        let reproduction = Reproduction::new(request, &repeated, &blackouts);
+        let blob = serde_json::to_string(&reproduction).unwrap();
+        assert_eq!(
+            blob,
+            r#"{"month":5,"year":2024,"hours":40,"max_per_day":8,"earliest":8,"latest":20,"state":"BY","seed":1,"remainder":"spread","constraints":["13.5.2024,cap,3","14.5.2024,fill,9:00,13:00"],"blackouts":["tue=12:00-13:30"]}"#
+        );
+        let read: Reproduction = serde_json::from_str(&blob).unwrap();
+        assert_eq!(read, reproduction);
+        assert_eq!(read.date_constraints(), Ok(constraints));
+        assert_eq!(read.blackouts(), Ok(blackouts.to_vec()));
+    }
+}
@@ -0,0 +1,98 @@
+//! Construction of the random number generators used for generating times.
+//!
+//! Only the seeded generator is available without the `os-rng` feature, e.g. on WebAssembly.
+//!
+//! # Stability
+//!
+//! A seed yields the same timesheet on every platform and with every version of `rand`, so the
+//! [`crate::request::Reproduction`] of a timesheet replays it later and elsewhere:
+//! - [`seeded`] is pinned to [`ChaCha12Rng`], whose stream is fixed by its algorithm, not the
+//!   [`rand::rngs::StdRng`] that may change with a new `rand`. It is the generator `StdRng` was
+//!   when the first reproductions were printed, so those stay valid.
+//! - Indices are drawn with [`index`] as 64 bit numbers, the `usize` ranges of `rand` consume a
+//!   different amount of randomness on 32 bit targets like WebAssembly.
+//!
+//! Changing the times generated for a seed, by another generator or by drawing differently, is a
+//! breaking change of the generator and needs a new major version.
+
+#[cfg(feature = "os-rng")]
+use rand::{rngs::OsRng, thread_rng};
+use rand::{Rng, RngCore, SeedableRng};
+pub use rand_chacha::ChaCha12Rng;
+
+/// Where the randomness for generating times comes from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RngSource {
+    /// The lazily initialized generator of the current thread.
+    #[cfg(feature = "os-rng")]
+    Thread,
+    /// A deterministic generator, the same seed always yields the same times.
+    Seeded(u64),
+    /// The random number generator of the operating system.
+    #[cfg(feature = "os-rng")]
+    Os,
+}
+
+/// A generator to pick for a [`RngSource`], the seeded one needs a seed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Generator {
+    /// The generator of the current thread.
+    Thread,
+    /// The deterministic generator.
+    Seeded,
+    /// The generator of the operating system.
+    Os,
+}
+
+/// A deterministic generator seeded with `seed`.
+pub fn seeded(seed: u64) -> ChaCha12Rng {
+    ChaCha12Rng::seed_from_u64(seed)
+}
+
+/// A uniformly random index below `len`, the same on every platform.
+///
+/// Panics if `len` is zero.
+pub fn index<R: Rng + ?Sized>(r: &mut R, len: usize) -> usize {
+    r.gen_range(0..len as u64) as usize
+}
+
+/// Creates the generator described by `source`.
+pub fn make_rng(source: RngSource) -> Box<dyn RngCore> {
+    match source {
+        #[cfg(feature = "os-rng")]
+        RngSource::Thread => Box::new(thread_rng()),
+        RngSource::Seeded(seed) => Box::new(seeded(seed)),
+        #[cfg(feature = "os-rng")]
+        RngSource::Os => Box::new(OsRng),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::rng::{index, make_rng, seeded, RngSource};
+
+    #[test]
+    fn test_seeded_is_deterministic() {
+        let mut a = make_rng(RngSource::Seeded(42));
+        let mut b = make_rng(RngSource::Seeded(42));
+        let a: Vec<u32> = (0..16).map(|_| a.gen()).collect();
+        let b: Vec<u32> = (0..16).map(|_| b.gen()).collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_seeded_is_stable() {
+        let mut rng = seeded(42);
+        let values: Vec<u32> = (0..4).map(|_| rng.gen()).collect();
+        assert_eq!(values, [572990626, 2261546851, 1068323197, 2330987027]);
+        let indices: Vec<_> = (0..8).map(|_| index(&mut rng, 7)).collect();
+        assert_eq!(indices, [4, 2, 0, 5, 0, 6, 3, 2]);
+
+        let mut std = StdRng::seed_from_u64(7);
+        let mut rng = seeded(7);
+        assert!((0..64).all(|_| std.gen::<u64>() == rng.gen::<u64>()));
+    }
+}
@@ -0,0 +1,144 @@
+//! Rounds the times of timesheets after the rules of a company.
+//!
+//! A [`RoundingPolicy`] is the last pass before a timesheet is written, both for generated
+//! timesheets and for the punches cleaned by [`crate::smooth`].
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::generate::TimeSpan;
+use crate::timesheet::{Timesheet, GRANULARITY};
+
+/// How the times are rounded to the step of a [`RoundingPolicy`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rounding {
+    /// Both ends to the nearest step, halves upwards.
+    #[default]
+    Nearest,
+    /// Starts upwards and ends downwards, the shorter time.
+    Employer,
+    /// Starts downwards and ends upwards, the longer time.
+    Employee,
+    /// The start to the nearest step and the time worked on the day to the nearest step.
+    Daily,
+}
+
+/// The rounding rules of a company.
+///
+/// ```toml
+/// [rounding]
+/// step = 15
+/// mode = "employee"
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoundingPolicy {
+    /// The minutes the times are rounded to, at least one.
+    pub step: u32,
+    /// How the times are rounded.
+    pub mode: Rounding,
+}
+
+impl Default for RoundingPolicy {
+    /// Rounds to the nearest [`GRANULARITY`].
+    fn default() -> Self {
+        RoundingPolicy {
+            step: GRANULARITY,
+            mode: Rounding::default(),
+        }
+    }
+}
+
+impl RoundingPolicy {
+    fn round_minutes(&self, minutes: u32, upwards: Option<bool>) -> u32 {
+        let step = self.step.max(1);
+        let steps = match upwards {
+            None => (minutes + step / 2) / step,
+            Some(true) => minutes.div_ceil(step),
+            Some(false) => minutes / step,
+        };
+        (steps * step).min(24 * 60)
+    }
+
+    /// `time` rounded after this policy, `None` if nothing is left of it.
+    pub fn round(&self, time: TimeSpan) -> Option<TimeSpan> {
+        let (from, to) = match self.mode {
+            Rounding::Nearest => (
+                self.round_minutes(time.from, None),
+                self.round_minutes(time.to, None),
+            ),
+            Rounding::Employer => (
+                self.round_minutes(time.from, Some(true)),
+                self.round_minutes(time.to, Some(false)),
+            ),
+            Rounding::Employee => (
+                self.round_minutes(time.from, Some(false)),
+                self.round_minutes(time.to, Some(true)),
+            ),
+            Rounding::Daily => {
+                let from = self.round_minutes(time.from, None);
+                let duration = self.round_minutes(time.duration(), None);
+                (from, (from + duration).min(24 * 60))
+            }
+        };
+        (from < to).then_some(TimeSpan { from, to })
+    }
+
+    /// Rounds the times of all entries of `sheet`, the entries that are rounded away are removed.
+    pub fn apply(&self, sheet: &mut Timesheet) {
+        sheet
+            .entries
+            .retain_mut(|entry| match self.round(entry.time) {
+                Some(time) => {
+                    entry.time = time;
+                    true
+                }
+                None => false,
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generate::TimeSpan;
+    use crate::rounding::{Rounding, RoundingPolicy};
+
+    #[test]
+    fn test_round() {
+        let time = TimeSpan {
+            from: 8 * 60 + 7,
+            to: 16 * 60 + 52,
+        };
+        let round = |step, mode| RoundingPolicy { step, mode }.round(time);
+        let span = |from, to| Some(TimeSpan { from, to });
+        assert_eq!(round(15, Rounding::Nearest), span(8 * 60, 16 * 60 + 45));
+        assert_eq!(
+            round(15, Rounding::Employer),
+            span(8 * 60 + 15, 16 * 60 + 45)
+        );
+        assert_eq!(round(15, Rounding::Employee), span(8 * 60, 17 * 60));
+        // 8:45 worked starting at 8:00
+        assert_eq!(round(15, Rounding::Daily), span(8 * 60, 16 * 60 + 45));
+        assert_eq!(round(60, Rounding::Daily), span(8 * 60, 17 * 60));
+
+        let short = TimeSpan {
+            from: 10 * 60 + 1,
+            to: 10 * 60 + 10,
+        };
+        let policy = RoundingPolicy {
+            step: 15,
+            mode: Rounding::Employer,
+        };
+        assert_eq!(policy.round(short), None);
+        let late = TimeSpan {
+            from: 23 * 60 + 20,
+            to: 23 * 60 + 59,
+        };
+        let policy = RoundingPolicy {
+            step: 30,
+            mode: Rounding::Employee,
+        };
+        assert_eq!(policy.round(late), span(23 * 60, 24 * 60));
+    }
+}
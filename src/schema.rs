@@ -0,0 +1,294 @@
+//! JSON Schemas of the machine readable formats, printed by the `schema` subcommand.
+//!
+//! The schemas are derived from the serde types they describe, `schemas/` contains them for
+//! editors and other tools and the tests keep it up to date. The configuration is TOML, but
+//! editors validate TOML against JSON Schemas just as well. [`Schema::validate`] checks a value at
+//! runtime, like `config validate` does with the configuration.
+
+use std::fmt;
+
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::constraints::DateConstraint;
+use crate::output::JsonTimesheet;
+use crate::request::GenerateRequest;
+
+/// A format with a JSON Schema.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Schema {
+    /// The JSON of `generate --format json` including the header, also read by `validate`.
+    Timesheet,
+    /// The [`crate::request::GenerateRequest`] of `generate --stdin` and the HTTP server.
+    Request,
+    /// The rows of a constraints file, see [`crate::constraints`].
+    Constraints,
+    /// The [`crate::config::Config`].
+    Config,
+}
+
+impl Schema {
+    /// All schemas.
+    pub const ALL: [Schema; 4] = [
+        Schema::Timesheet,
+        Schema::Request,
+        Schema::Constraints,
+        Schema::Config,
+    ];
+
+    /// The name of the format, like `timesheet`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Schema::Timesheet => "timesheet",
+            Schema::Request => "request",
+            Schema::Constraints => "constraints",
+            Schema::Config => "config",
+        }
+    }
+
+    /// The schema as a JSON value.
+    pub fn value(&self) -> Value {
+        let (title, description) = match self {
+            Schema::Timesheet => (
+                "Timesheet",
+                "A timesheet as written by `generate --format json` and read by `validate`, the \
+                 header is left out with `--no-header`.",
+            ),
+            Schema::Request => (
+                "Generate request",
+                "The parameters of a timesheet as read by `generate --stdin` and the HTTP server.",
+            ),
+            Schema::Constraints => (
+                "Constraints",
+                "The rows of a constraints file as read by `generate --constraints` and recorded \
+                 in the `constraints` of a reproduction. Rows of a file that are empty or start \
+                 with `#` are skipped.",
+            ),
+            Schema::Config => (
+                "Configuration",
+                "The user configuration in `~/.config/stundenzettel/config.toml`, every section is \
+                 optional. TOML dates are strings like `2024-03-15` to JSON Schema validators.",
+            ),
+        };
+        let mut schema = match self {
+            Schema::Timesheet => schema_for!(JsonTimesheet<'static>),
+            Schema::Request => schema_for!(GenerateRequest),
+            Schema::Constraints => schema_for!(Vec<DateConstraint>),
+            Schema::Config => schema_for!(Config),
+        };
+        schema.insert("title".into(), title.into());
+        schema.insert("description".into(), description.into());
+        if *self == Schema::Config {
+            // serde reads the former name of the time zone as well
+            let timezone = schema
+                .get("properties")
+                .and_then(|properties| properties.get("timezone"))
+                .cloned()
+                .expect("the configuration has a time zone");
+            schema.ensure_object()["properties"]["utc_offset"] = timezone;
+            // the contracts are read with what they inherit filled in
+            let contract = &mut schema.ensure_object()["$defs"]["Contract"];
+            contract["properties"]["inherits"] = json!({
+                "description": "The employer of the contract the settings left out are taken from.",
+                "type": "string",
+            });
+            let contract = contract.as_object_mut().expect("the contract is an object");
+            contract.remove("required");
+            contract.insert(
+                "anyOf".into(),
+                json!([{ "required": ["weekly_hours"] }, { "required": ["inherits"] }]),
+            );
+        }
+        schema.to_value()
+    }
+
+    /// The schema as pretty printed JSON.
+    pub fn json(&self) -> String {
+        let json = serde_json::to_string_pretty(&self.value()).expect("the schemas are JSON");
+        format!("{}\n", json)
+    }
+
+    /// Checks `value` against the schema, an error naming the path for each mismatch.
+    pub fn validate(&self, value: &Value) -> Vec<String> {
+        let validator = jsonschema::validator_for(&self.value()).expect("the schemas are valid");
+        validator
+            .iter_errors(value)
+            .map(|error| format!("{}: {}", error.instance_path(), error))
+            .collect()
+    }
+}
+
+/// `value` as JSON, TOML dates become strings like validators of TOML treat them.
+pub fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => i.into(),
+        toml::Value::Float(f) => f.into(),
+        toml::Value::Boolean(b) => b.into(),
+        toml::Value::Datetime(datetime) => datetime.to_string().into(),
+        toml::Value::Array(values) => values.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, toml_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::num::NonZeroU32;
+
+    use serde_json::Value;
+
+    use crate::breaks::{deduct_breaks, BreakRule};
+    use crate::calendar::{Month, State, Year};
+    use crate::config::Config;
+    use crate::output::{Format, Header};
+    use crate::request::GenerateRequest;
+    use crate::rng::seeded;
+    use crate::schema::{toml_to_json, Schema};
+    use crate::timesheet::{generate_timesheet, SheetParameters, Surcharge, Timesheet};
+
+    fn assert_valid(schema: Schema, value: &Value) {
+        let errors = schema.validate(value);
+        assert!(errors.is_empty(), "{} of {}: {:#?}", schema, value, errors);
+    }
+
+    fn assert_invalid(schema: Schema, value: &Value) {
+        assert!(
+            !schema.validate(value).is_empty(),
+            "{} accepts {}",
+            schema,
+            value
+        );
+    }
+
+    /// `schemas/` has the derived schemas, `UPDATE_SCHEMAS=1` rewrites it.
+    #[test]
+    fn test_files() {
+        for schema in Schema::ALL {
+            let path = format!("schemas/{}.schema.json", schema);
+            if std::env::var_os("UPDATE_SCHEMAS").is_some() {
+                fs::write(&path, schema.json()).unwrap();
+            }
+            let file = fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                file,
+                schema.json(),
+                "{} is outdated, run with UPDATE_SCHEMAS=1",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_timesheet() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 80,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let mut sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+        let header = Header {
+            employee: Some("Erika Mustermann".into()),
+            seed: Some(1),
+            signature: Some("c2lnbmF0dXJl".into()),
+            ..Header::new(&sheet, 80, 1_714_521_600)
+        };
+        let render = |sheet: &Timesheet| {
+            let mut output = Vec::new();
+            Format::Json
+                .formatter()
+                .write_with_header(&header, sheet, &mut output)
+                .unwrap();
+            serde_json::from_slice::<Value>(&output).unwrap()
+        };
+        assert_valid(Schema::Timesheet, &render(&sheet));
+
+        deduct_breaks(&mut sheet, &BreakRule::STATUTORY);
+        sheet.entries[0].surcharge = Some(Surcharge::Holiday);
+        sheet.entries[1].note = Some("Server migration".into());
+        sheet.rate = Some(1550);
+        sheet.timezone = Some("Europe/Berlin".parse().unwrap());
+        let json = render(&sheet);
+        assert!(json["entries"][0]["start"].is_string());
+        assert_valid(Schema::Timesheet, &json);
+
+        let mut json = json;
+        json["entries"][0]["time"]["from"] = "9 Uhr".into();
+        assert_invalid(Schema::Timesheet, &json);
+    }
+
+    #[test]
+    fn test_request() {
+        let request: GenerateRequest = serde_json::from_str(
+            r#"{ "month": 2, "year": 2024, "hours": 40, "state": "NW", "seed": 1 }"#,
+        )
+        .unwrap();
+        assert_valid(Schema::Request, &serde_json::to_value(request).unwrap());
+        assert_invalid(
+            Schema::Request,
+            &serde_json::json!({ "month": 13, "year": 2024 }),
+        );
+    }
+
+    #[test]
+    fn test_constraints() {
+        let file = fs::read_to_string("tests/golden/2024-05-constraints.csv").unwrap();
+        let rows: Vec<_> = file
+            .lines()
+            .filter(|row| !row.trim().is_empty() && !row.starts_with('#'))
+            .collect();
+        assert!(!rows.is_empty());
+        assert_valid(Schema::Constraints, &serde_json::to_value(rows).unwrap());
+        assert_valid(
+            Schema::Constraints,
+            &serde_json::json!(["13.5.2024, cap, 3", "14.5.2024,fill,9:00,13:00"]),
+        );
+        assert_invalid(Schema::Constraints, &serde_json::json!(["2024-05-13,pin"]));
+    }
+
+    #[test]
+    fn test_config() {
+        let source = fs::read_to_string("src/config.rs").unwrap();
+        let example: String = source
+            .lines()
+            .filter_map(|line| line.strip_prefix("//!"))
+            .skip_while(|line| !line.contains("```toml"))
+            .skip(1)
+            .take_while(|line| !line.contains("```"))
+            .map(|line| format!("{}\n", line.strip_prefix(' ').unwrap_or(line)))
+            .collect();
+        Config::parse(&example).unwrap();
+        let mut config = toml_to_json(toml::from_str(&example).unwrap());
+        assert_valid(Schema::Config, &config);
+
+        let contract = "[contract]\nweekly_hours = 20\nstart = 2024-03-15\n";
+        Config::parse(contract).unwrap();
+        assert_valid(
+            Schema::Config,
+            &toml_to_json(toml::from_str(contract).unwrap()),
+        );
+
+        // a job either has its hours or inherits them
+        let job = "[[jobs]]\nemployer = \"A\"\n";
+        assert_invalid(Schema::Config, &toml_to_json(toml::from_str(job).unwrap()));
+
+        config["warnings"]["silence"][0] = "everything".into();
+        assert_invalid(Schema::Config, &config);
+    }
+}
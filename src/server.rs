@@ -0,0 +1,210 @@
+//! A small HTTP server in front of [`generate_timesheet`].
+//!
+//! - `POST /generate` takes a JSON [`GenerateRequest`] and answers with the timesheet as JSON, or
+//!   in any other [`Format`] whose content type the `Accept` header asks for.
+//! - `GET /holidays/{year}?state=BY` answers with the holidays of `year` in `state` as JSON, the
+//!   state defaults to Bavaria.
+//!
+//! Errors are answered with a `{ "error": "..." }` object, bodies larger than [`MAX_BODY`] with
+//! 413.
+
+use std::io::{self, Read};
+
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::calendar::{Month, State, Year};
+use crate::output::{Format, MemorySink};
+pub use crate::request::GenerateRequest;
+use crate::rng::{make_rng, RngSource};
+use crate::timesheet::generate_timesheet;
+
+/// The largest request body that is read, in bytes.
+pub const MAX_BODY: u64 = 64 * 1024;
+
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn json(status: u16, value: &impl serde::Serialize) -> Self {
+        Reply {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_string(value).expect("serializing to a string can't fail"),
+        }
+    }
+
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self::json(status, &json!({ "error": message.to_string() }))
+    }
+}
+
+fn generate(body: &str, accept: &str) -> Reply {
+    let request: GenerateRequest = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return Reply::error(400, e),
+    };
+    if let Err(e) = request.parameters().check() {
+        return Reply::error(400, e);
+    }
+    let year = Year::new(request.year);
+    let month = match Month::new(request.month, &year) {
+        Ok(month) => month,
+        Err(e) => return Reply::error(400, e),
+    };
+    let source = request.seed.map_or(RngSource::Os, RngSource::Seeded);
+    let sheet = generate_timesheet(&year, &month, request.parameters(), &mut *make_rng(source));
+    let sheet = match sheet {
+        Ok(sheet) => sheet,
+        Err(e) => return Reply::error(422, e),
+    };
+
+    let format = Format::ALL
+        .into_iter()
+        .find(|format| accept.contains(format.content_type()));
+    match format {
+        Some(format) => {
+            let mut sink = MemorySink::new();
+            if let Err(e) = format.stream(&sheet, None, &mut sink) {
+                return Reply::error(500, e);
+            }
+            Reply {
+                status: 200,
+                content_type: format.content_type(),
+                body: sink.into_string(),
+            }
+        }
+        None if accept.contains("application/pdf") => {
+            Reply::error(406, "PDF output isn't supported")
+        }
+        None => Reply::json(200, &sheet),
+    }
+}
+
+fn holidays(year: &str, query: &str) -> Reply {
+    let Ok(year) = year.parse() else {
+        return Reply::error(400, format!("`{}` isn't a year", year));
+    };
+    let state = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("state="))
+        .map_or(Ok(State::default()), str::parse);
+    match state {
+        Ok(state) => Reply::json(200, &Year::new(year).holidays(state)),
+        Err(e) => Reply::error(400, e),
+    }
+}
+
+fn respond(method: &Method, url: &str, accept: &str, body: &str) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    match (method, path.strip_prefix("/holidays/")) {
+        (Method::Post, _) if path == "/generate" => generate(body, accept),
+        (Method::Get, Some(year)) => holidays(year, query),
+        (_, Some(_)) => Reply::error(405, "Method not allowed"),
+        _ if path == "/generate" => Reply::error(405, "Method not allowed"),
+        _ => Reply::error(404, "Not found"),
+    }
+}
+
+/// Reads at most [`MAX_BODY`] bytes of a body.
+fn read_body(reader: impl Read) -> Result<String, Reply> {
+    let mut body = String::new();
+    match reader.take(MAX_BODY + 1).read_to_string(&mut body) {
+        Ok(_) if body.len() as u64 > MAX_BODY => Err(Reply::error(
+            413,
+            format!("The body is larger than {} bytes", MAX_BODY),
+        )),
+        Ok(_) => Ok(body),
+        Err(e) => Err(Reply::error(400, e)),
+    }
+}
+
+fn handle(mut request: Request) -> io::Result<()> {
+    let reply = match read_body(request.as_reader()) {
+        Ok(body) => {
+            let accept = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Accept"))
+                .map(|header| header.value.as_str())
+                .unwrap_or_default();
+            respond(request.method(), request.url(), accept, &body)
+        }
+        Err(reply) => reply,
+    };
+    let header = Header::from_bytes("Content-Type", reply.content_type)
+        .expect("content types are valid headers");
+    request.respond(
+        Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header),
+    )
+}
+
+/// Serves requests on `address` until the process is stopped. Failing to answer a request, like
+/// when the client went away, is printed to stderr and doesn't stop the server.
+pub fn serve(address: &str) -> io::Result<()> {
+    let server = Server::http(address).map_err(io::Error::other)?;
+    for request in server.incoming_requests() {
+        if let Err(e) = handle(request) {
+            eprintln!("Failed to answer a request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tiny_http::Method;
+
+    use crate::server::{read_body, respond, MAX_BODY};
+
+    #[test]
+    fn test_respond() {
+        let body = r#"{ "month": 2, "year": 2024, "hours": 40, "seed": 1 }"#;
+        let reply = respond(&Method::Post, "/generate", "text/csv", body);
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.content_type, "text/csv");
+        assert!(reply.body.lines().all(|line| line.ends_with(":00")));
+
+        let reply = respond(&Method::Post, "/generate", "", r#"{ "month": 13 }"#);
+        assert_eq!(reply.status, 400);
+
+        let body = r#"{ "month": 2, "year": 2024, "hours": 40, "latest": 30 }"#;
+        let reply = respond(&Method::Post, "/generate", "", body);
+        assert_eq!(reply.status, 400);
+        assert!(reply
+            .body
+            .contains("The latest hour has to be at most 24, not 30"));
+
+        let body = r#"{ "month": 2, "year": 2024, "hours": 400 }"#;
+        let reply = respond(&Method::Post, "/generate", "", body);
+        assert_eq!(reply.status, 422);
+
+        let reply = respond(&Method::Get, "/holidays/2024?state=BE", "", "");
+        assert_eq!(reply.status, 200);
+        assert!(reply
+            .body
+            .contains(r#"{"date":{"day":1,"month":4},"name":"Ostermontag"}"#));
+        assert_eq!(
+            respond(&Method::Get, "/holidays/2024?state=XX", "", "").status,
+            400
+        );
+
+        assert_eq!(respond(&Method::Get, "/generate", "", "").status, 405);
+        assert_eq!(respond(&Method::Get, "/", "", "").status, 404);
+    }
+
+    #[test]
+    fn test_read_body() {
+        let body = "x".repeat(MAX_BODY as usize);
+        assert_eq!(read_body(body.as_bytes()).unwrap(), body);
+        let reply = read_body(format!("{}x", body).as_bytes()).unwrap_err();
+        assert_eq!(reply.status, 413);
+        assert_eq!(read_body(&[0xff][..]).unwrap_err().status, 400);
+    }
+}
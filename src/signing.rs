@@ -0,0 +1,279 @@
+//! Ed25519 signatures of exported timesheets.
+//!
+//! With a `key` in the `[signing]` section of the configuration, `generate` signs the CSV and JSON
+//! it writes and adds the [`Signature`] to their header. An employer given the [`PublicKey`] checks
+//! with `verify` that a timesheet wasn't altered since. The signature covers the fields of the
+//! header but itself as `label: value` lines, so the employee, the month, the hours and the seed
+//! can't be changed either, followed by the timesheet as `generate --format json --no-header`
+//! writes it like a [`crate::digest::SheetDigest`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+
+use crate::error::{Error, Result};
+use crate::output::{Format, Header};
+use crate::parser::parse_error;
+use crate::timesheet::Timesheet;
+
+/// The label of the signature in the header.
+const LABEL: &str = "Signature";
+
+/// The bytes that are signed of a header with the labels and values `fields` and of `sheet`.
+fn message<'a>(fields: impl IntoIterator<Item = (&'a str, &'a str)>, sheet: &Timesheet) -> String {
+    let mut message = String::new();
+    for (label, value) in fields.into_iter().filter(|(label, _)| *label != LABEL) {
+        message.push_str(&format!("{}: {}\n", label, value.trim()));
+    }
+    message.push('\n');
+    message + &Format::Json.formatter().render(sheet)
+}
+
+/// A private Ed25519 key, written as its seed of 32 bytes in base64.
+pub struct SigningKey {
+    seed: [u8; 32],
+    pair: Ed25519KeyPair,
+}
+
+impl SigningKey {
+    /// A new key from the random numbers of the operating system.
+    pub fn generate() -> Self {
+        let mut seed = [0; 32];
+        SystemRandom::new()
+            .fill(&mut seed)
+            .expect("the operating system has random numbers");
+        SigningKey::from_seed(seed)
+    }
+
+    fn from_seed(seed: [u8; 32]) -> Self {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&seed).expect("every seed is a key");
+        SigningKey { seed, pair }
+    }
+
+    /// The public key to hand out to those checking the signatures.
+    pub fn public_key(&self) -> PublicKey {
+        let bytes = self.pair.public_key().as_ref();
+        PublicKey(bytes.try_into().expect("public keys have 32 bytes"))
+    }
+
+    /// The signature of `sheet` headed by `header`, whose own signature is left out.
+    pub fn sign(&self, header: &Header, sheet: &Timesheet) -> Signature {
+        let fields = header.fields();
+        let fields = fields.iter().map(|(label, value)| (*label, value.as_str()));
+        let signature = self.pair.sign(message(fields, sheet).as_bytes());
+        Signature(
+            signature
+                .as_ref()
+                .try_into()
+                .expect("signatures have 64 bytes"),
+        )
+    }
+}
+
+impl fmt::Display for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&STANDARD.encode(self.seed))
+    }
+}
+
+impl FromStr for SigningKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let seed = decode(s).ok_or_else(|| Error::InvalidKey(s.into()))?;
+        Ok(SigningKey::from_seed(seed))
+    }
+}
+
+/// A public Ed25519 key of 32 bytes in base64.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PublicKey([u8; 32]);
+
+impl PublicKey {
+    /// Whether the signature of `header` is the one of it and `sheet` by the private key of this.
+    pub fn verify(&self, header: &SignedHeader, sheet: &Timesheet) -> bool {
+        let fields = header
+            .fields
+            .iter()
+            .map(|(label, value)| (label.as_str(), value.as_str()));
+        UnparsedPublicKey::new(&ED25519, self.0)
+            .verify(message(fields, sheet).as_bytes(), &header.signature.0)
+            .is_ok()
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&STANDARD.encode(self.0))
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        decode(s)
+            .map(PublicKey)
+            .ok_or_else(|| Error::InvalidKey(s.into()))
+    }
+}
+
+/// An Ed25519 signature of 64 bytes in base64.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Signature([u8; 64]);
+
+/// The header of a signed CSV or JSON timesheet as it was read.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SignedHeader {
+    /// The labels and values of the fields like [`Header::fields`], the signature among them.
+    pub fields: Vec<(String, String)>,
+    /// The signature of the header and the timesheet.
+    pub signature: Signature,
+}
+
+impl SignedHeader {
+    /// The header of a CSV or JSON timesheet, `None` if it isn't signed.
+    pub fn find(input: &str) -> Result<Option<SignedHeader>> {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let fields: Vec<_> = if input.trim_start().starts_with('{') {
+            let mut value: serde_json::Value = serde_json::from_str(input)
+                .map_err(|e| parse_error(e.line().max(1), e.to_string()))?;
+            match value.get_mut("header").map(serde_json::Value::take) {
+                Some(header) => {
+                    let header: Header = serde_json::from_value(header)
+                        .map_err(|e| parse_error(1, format!("the header: {}", e)))?;
+                    header
+                        .fields()
+                        .into_iter()
+                        .map(|(label, value)| (label.to_string(), value))
+                        .collect()
+                }
+                None => Vec::new(),
+            }
+        } else {
+            input
+                .lines()
+                .filter_map(|line| {
+                    let (label, value) = line.strip_prefix('#')?.split_once(':')?;
+                    Some((label.trim().to_string(), value.trim().to_string()))
+                })
+                .collect()
+        };
+        let Some((_, signature)) = fields.iter().find(|(label, _)| label == LABEL) else {
+            return Ok(None);
+        };
+        let signature = signature.parse()?;
+        Ok(Some(SignedHeader { fields, signature }))
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&STANDARD.encode(self.0))
+    }
+}
+
+impl FromStr for Signature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        decode(s)
+            .map(Signature)
+            .ok_or_else(|| Error::InvalidSignature(s.into()))
+    }
+}
+
+/// The `N` bytes `s` is the base64 of.
+fn decode<const N: usize>(s: &str) -> Option<[u8; N]> {
+    STANDARD.decode(s.trim()).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::error::Error;
+    use crate::output::{Format, Header};
+    use crate::rng::seeded;
+    use crate::signing::{PublicKey, SignedHeader, SigningKey};
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_signing() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+        let key = SigningKey::generate();
+        let public_key = key.public_key();
+        assert_eq!(
+            key.to_string().parse::<SigningKey>().unwrap().public_key(),
+            public_key
+        );
+        assert_eq!(public_key.to_string().parse(), Ok(public_key));
+
+        let header = Header {
+            employee: Some("Erika Mustermann".into()),
+            seed: Some(1),
+            ..Header::new(&sheet, 40, 1_714_521_600)
+        };
+        let signature = key.sign(&header, &sheet);
+        let header = Header {
+            signature: Some(signature.to_string()),
+            ..header
+        };
+        for format in [Format::Csv, Format::Json] {
+            let mut output = Vec::new();
+            format
+                .formatter()
+                .write_with_header(&header, &sheet, &mut output)
+                .unwrap();
+            let output = String::from_utf8(output).unwrap();
+            let signed = SignedHeader::find(&output).unwrap().unwrap();
+            assert_eq!(signed.signature, signature);
+            assert!(public_key.verify(&signed, &sheet));
+            assert!(!SigningKey::generate().public_key().verify(&signed, &sheet));
+            assert_eq!(
+                SignedHeader::find(&format.formatter().render(&sheet)),
+                Ok(None)
+            );
+
+            // every field of the header is signed
+            for (field, tampered) in [
+                ("Erika Mustermann", "Max Mustermann"),
+                ("40", "80"),
+                ("2024-05-01T00:00:00Z", "2024-05-02T00:00:00Z"),
+            ] {
+                let tampered = output.replacen(field, tampered, 1);
+                assert_ne!(tampered, output);
+                let signed = SignedHeader::find(&tampered).unwrap().unwrap();
+                assert!(!public_key.verify(&signed, &sheet), "{}", tampered);
+            }
+            let mut tampered = sheet.clone();
+            tampered.entries[0].time.to += 60;
+            assert!(!public_key.verify(&signed, &tampered));
+        }
+
+        assert_eq!(
+            "c2hvcnQ=".parse::<PublicKey>(),
+            Err(Error::InvalidKey("c2hvcnQ=".into()))
+        );
+        assert!(matches!(
+            "not base64!".parse::<SigningKey>(),
+            Err(Error::InvalidKey(_))
+        ));
+        assert!(SignedHeader::find("# Signature: c2hvcnQ=\n1.3.2024,8:00,12:00").is_err());
+    }
+}
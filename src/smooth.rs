@@ -0,0 +1,362 @@
+//! Cleans raw clock-in and clock-out times, like the export of a punch clock, into timesheets.
+//!
+//! [`parse_punches`] reads the rows of such an export and [`smooth`] rounds them after a
+//! [`SmoothingPolicy`], deducts the breaks that weren't taken and merges the punches of each day
+//! into one entry, so that the result renders in every [`crate::output`] format.
+
+use std::num::NonZeroU32;
+
+use crate::breaks::BreakRule;
+use crate::calendar::{DayOfMonth, HolidaySource, Month, State, Year};
+use crate::error::Result;
+use crate::generate::{parse_clock, TimeSpan};
+use crate::parser::{parse_date, parse_error, parse_number};
+use crate::rounding::RoundingPolicy;
+use crate::timesheet::{Entry, Surcharge, Timesheet};
+
+/// A span between clocking in and clocking out.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Punch {
+    /// The year.
+    pub year: u32,
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The day of the month, starting at 1.
+    pub day: NonZeroU32,
+    /// The time between clocking in and out in minutes.
+    pub time: TimeSpan,
+}
+
+/// The rules of a company for cleaning punches.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SmoothingPolicy {
+    /// How the punches are rounded.
+    pub rounding: RoundingPolicy,
+    /// The breaks deducted from the days that didn't take them.
+    pub breaks: Vec<BreakRule>,
+}
+
+impl Default for SmoothingPolicy {
+    /// Rounds to the nearest [`crate::timesheet::GRANULARITY`] and deducts the
+    /// [`BreakRule::STATUTORY`] breaks.
+    fn default() -> Self {
+        SmoothingPolicy {
+            rounding: RoundingPolicy::default(),
+            breaks: BreakRule::STATUTORY.to_vec(),
+        }
+    }
+}
+
+/// Reads the minutes after midnight of `H:MM` or `H:MM:SS`, the seconds are dropped.
+fn parse_punch_clock(line: usize, time: &str) -> Result<u32> {
+    let time = time.trim();
+    let clock = match time.rsplit_once(':') {
+        Some((clock, seconds)) if clock.contains(':') && seconds.len() == 2 => clock,
+        _ => time,
+    };
+    parse_clock(clock).ok_or_else(|| parse_error(line, format!("`{}` isn't a time", time)))
+}
+
+/// Reads a `year-month-day` or `day.month.year` date and checks that the day exists.
+fn parse_punch_date(line: usize, date: &str) -> Result<(u32, NonZeroU32, NonZeroU32)> {
+    let (year, month, day) = match date.trim().split('-').collect::<Vec<_>>()[..] {
+        [year, month, day] => (
+            parse_number(line, year, "year")?,
+            parse_number(line, month, "month")?,
+            parse_number(line, day, "day")?,
+        ),
+        _ => {
+            let (day, month, year) = parse_date(line, date)?;
+            (year, month, day)
+        }
+    };
+    let month_of_year =
+        Month::new(month, &Year::new(year)).map_err(|e| parse_error(line, e.to_string()))?;
+    if day.get() > month_of_year.num_days() {
+        return Err(parse_error(line, format!("`{}` isn't a date", date.trim())));
+    }
+    Ok((year, month, day))
+}
+
+/// Reads rows of `date,in,out` like `2024-03-04,08:03,16:47` or `4.3.2024;8:03:12;16:47:55`.
+///
+/// Columns may be separated by `,` or `;`, further columns are ignored. Empty lines, lines starting
+/// with `#` and a first row that doesn't start with a date like a header are skipped. Fails if a
+/// punch ends before it starts or overlaps another one.
+pub fn parse_punches(input: &str) -> Result<Vec<Punch>> {
+    let mut punches = Vec::new();
+    let mut lines = Vec::new();
+    let rows = input
+        .lines()
+        .enumerate()
+        .map(|(i, row)| (i + 1, row.trim()))
+        .filter(|(_, row)| !row.is_empty() && !row.starts_with('#'));
+    for (index, (line, row)) in rows.enumerate() {
+        let separator = if row.contains(';') { ';' } else { ',' };
+        let columns: Vec<_> = row.split(separator).collect();
+        let [date, from, to, ..] = columns[..] else {
+            return Err(parse_error(line, "expected a date, a start and an end"));
+        };
+        let date = match parse_punch_date(line, date) {
+            Ok(date) => date,
+            Err(_) if index == 0 => continue,
+            Err(e) => return Err(e),
+        };
+        let (from, to) = (parse_punch_clock(line, from)?, parse_punch_clock(line, to)?);
+        if to <= from {
+            return Err(parse_error(line, "the punch ends before it starts"));
+        }
+        let (year, month, day) = date;
+        punches.push(Punch {
+            year,
+            month,
+            day,
+            time: TimeSpan { from, to },
+        });
+        lines.push(line);
+    }
+
+    let mut order: Vec<_> = (0..punches.len()).collect();
+    let key = |i: &usize| {
+        let punch = &punches[*i];
+        (punch.year, punch.month, punch.day, punch.time.from)
+    };
+    order.sort_by_key(key);
+    for pair in order.windows(2) {
+        let (first, second) = (&punches[pair[0]], &punches[pair[1]]);
+        let same_day =
+            (first.year, first.month, first.day) == (second.year, second.month, second.day);
+        if same_day && first.time.overlaps(&second.time) {
+            let (earlier, later) = (
+                lines[pair[0]].min(lines[pair[1]]),
+                lines[pair[0]].max(lines[pair[1]]),
+            );
+            return Err(parse_error(
+                later,
+                format!("the punch overlaps the one of line {}", earlier),
+            ));
+        }
+    }
+    Ok(punches)
+}
+
+/// Rounds the `punches` after `policy` and merges those of each day into an entry, the timesheets
+/// of the months with punches in order.
+///
+/// The rounded punches of a day are merged where they overlap. If the pauses between them are
+/// shorter than the longest break due, the rest of it is deducted. The entry starts with the
+/// first punch of the day and lasts the rest of the time worked, days that are rounded away are
+/// left out. Days on weekends and holidays of `holidays` carry their [`Surcharge`], the sheets
+/// belong to `state`.
+pub fn smooth(
+    punches: &[Punch],
+    policy: &SmoothingPolicy,
+    state: State,
+    holidays: &dyn HolidaySource,
+) -> Vec<Timesheet> {
+    let mut rounded: Vec<_> = punches
+        .iter()
+        .filter_map(|punch| {
+            let time = policy.rounding.round(punch.time)?;
+            Some(Punch { time, ..*punch })
+        })
+        .collect();
+    rounded.sort_by_key(|punch| (punch.year, punch.month, punch.day, punch.time.from));
+
+    let mut sheets: Vec<Timesheet> = Vec::new();
+    for day in rounded.chunk_by(|a, b| (a.year, a.month, a.day) == (b.year, b.month, b.day)) {
+        let mut spans: Vec<TimeSpan> = Vec::new();
+        for punch in day {
+            match spans.last_mut() {
+                Some(last) if punch.time.from <= last.to => last.to = last.to.max(punch.time.to),
+                _ => spans.push(punch.time),
+            }
+        }
+        let worked: u32 = spans.iter().map(TimeSpan::duration).sum();
+        let (start, end) = (spans[0].from, spans[spans.len() - 1].to);
+        let taken = end - start - worked;
+        let due = BreakRule::due(&policy.breaks, worked);
+        let net = worked.saturating_sub(due.saturating_sub(taken));
+        if net == 0 {
+            continue;
+        }
+
+        let Punch {
+            year, month, day, ..
+        } = day[0];
+        if sheets
+            .last()
+            .is_none_or(|sheet| (sheet.year, sheet.month) != (year, month))
+        {
+            sheets.push(Timesheet {
+                year,
+                month,
+                state,
+                rate: None,
+                timezone: None,
+                entries: Vec::new(),
+            });
+        }
+        let year_of = Year::new(year);
+        let month_of = Month::new(month, &year_of).expect("parsed months exist");
+        let day = DayOfMonth {
+            day_of_week: month_of.day_of_week(day.get()),
+            day_of_month: day,
+        };
+        let sheet = sheets.last_mut().expect("a sheet was pushed");
+        sheet.entries.push(Entry {
+            day: day.day_of_month,
+            day_of_week: day.day_of_week,
+            time: TimeSpan {
+                from: start,
+                to: start + net,
+            },
+            surcharge: Surcharge::of(&year_of, &month_of, &day, holidays),
+            break_minutes: 0,
+            note: None,
+        });
+    }
+    sheets
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::breaks::BreakRule;
+    use crate::calendar::State;
+    use crate::error::Error;
+    use crate::generate::TimeSpan;
+    use crate::rounding::{Rounding, RoundingPolicy};
+    use crate::smooth::{parse_punches, smooth, Punch, SmoothingPolicy};
+    use crate::timesheet::Surcharge;
+
+    #[test]
+    fn test_parse_punches() {
+        let punches = parse_punches(
+            "Datum;Kommen;Gehen\n2024-03-04;08:03:12;12:01:40\n\n4.3.2024;12:31;17:02\n",
+        )
+        .unwrap();
+        assert_eq!(
+            punches,
+            [
+                Punch {
+                    year: 2024,
+                    month: NonZeroU32::new(3).unwrap(),
+                    day: NonZeroU32::new(4).unwrap(),
+                    time: TimeSpan {
+                        from: 8 * 60 + 3,
+                        to: 12 * 60 + 1
+                    },
+                },
+                Punch {
+                    year: 2024,
+                    month: NonZeroU32::new(3).unwrap(),
+                    day: NonZeroU32::new(4).unwrap(),
+                    time: TimeSpan {
+                        from: 12 * 60 + 31,
+                        to: 17 * 60 + 2
+                    },
+                },
+            ]
+        );
+
+        assert_eq!(
+            parse_punches("2024-03-04,9:00,12:00\n2024-03-04,8:00,12:00"),
+            Err(Error::Parse {
+                line: 2,
+                message: "the punch overlaps the one of line 1".into()
+            })
+        );
+        assert!(parse_punches("2024-03-04,12:00,8:00").is_err());
+        assert!(parse_punches("2024-02-28,8:00,12:00\n2024-02-30,8:00,12:00").is_err());
+        assert!(parse_punches("2024-03-04,8:00,12:00\nday,in,out").is_err());
+    }
+
+    #[test]
+    fn test_smooth() {
+        // a Friday and a Saturday, Good Friday on 29.3.2024
+        let punches = parse_punches(
+            "2024-03-28,07:53,12:08\n\
+             2024-03-28,12:22,17:07\n\
+             2024-03-29,08:07,14:38\n\
+             2024-03-30,09:58,10:05\n\
+             2024-04-02,08:00,18:00\n",
+        )
+        .unwrap();
+        let sheets = smooth(
+            &punches,
+            &SmoothingPolicy::default(),
+            State::Bavaria,
+            &State::Bavaria,
+        );
+        assert_eq!(sheets.len(), 2);
+        let times: Vec<_> = sheets
+            .iter()
+            .flat_map(|sheet| &sheet.entries)
+            .map(|entry| (entry.day.get(), entry.time, entry.surcharge))
+            .collect();
+        assert_eq!(
+            times,
+            [
+                // 8:00-12:15 and 12:15-17:00 are merged, 9 hours without a pause lose 30 minutes
+                (
+                    28,
+                    TimeSpan {
+                        from: 8 * 60,
+                        to: 16 * 60 + 30
+                    },
+                    None
+                ),
+                // 8:00-14:45 loses 30 minutes as well
+                (
+                    29,
+                    TimeSpan {
+                        from: 8 * 60,
+                        to: 14 * 60 + 15
+                    },
+                    Some(Surcharge::Holiday)
+                ),
+                // 9:58 to 10:05 is rounded away, 10 hours minus 45 minutes
+                (
+                    2,
+                    TimeSpan {
+                        from: 8 * 60,
+                        to: 17 * 60 + 15
+                    },
+                    None
+                ),
+            ]
+        );
+
+        let policy = SmoothingPolicy {
+            rounding: RoundingPolicy {
+                step: 30,
+                mode: Rounding::Employer,
+            },
+            breaks: vec![BreakRule {
+                after: 0,
+                deduct: 60,
+            }],
+        };
+        let sheets = smooth(&punches[..2], &policy, State::Bavaria, &State::Bavaria);
+        // 8:00-12:00 and 12:30-17:00 with a pause of 30 minutes
+        assert_eq!(sheets[0].entries[0].time, TimeSpan::hours(8, 16));
+        let policy = SmoothingPolicy {
+            rounding: RoundingPolicy {
+                step: 30,
+                mode: Rounding::Employee,
+            },
+            ..policy
+        };
+        let sheets = smooth(&punches[..2], &policy, State::Bavaria, &State::Bavaria);
+        // 7:30-12:30 and 12:00-17:30 are merged
+        assert_eq!(
+            sheets[0].entries[0].time,
+            TimeSpan {
+                from: 7 * 60 + 30,
+                to: 16 * 60 + 30
+            }
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! How the hours of many generated timesheets of a month distribute over its weekdays and weeks.
+//!
+//! Generating the same month with many seeds and comparing the mean hours per working day of each
+//! weekday shows whether the generator prefers some days, e.g. overloads Mondays. A [`Simulation`]
+//! instead counts how many days were worked and how long the longest day was, to see whether a set
+//! of constraints always leads to plausible timesheets.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::calendar::{DayOfMonth, DayOfWeek, Month};
+use crate::error::Result;
+use crate::locale::{Locale, Localize};
+use crate::timesheet::Timesheet;
+
+/// The days of a week starting on Monday.
+const WEEK: [DayOfWeek; 7] = [
+    DayOfWeek::Monday,
+    DayOfWeek::Tuesday,
+    DayOfWeek::Wednesday,
+    DayOfWeek::Thursday,
+    DayOfWeek::Friday,
+    DayOfWeek::Saturday,
+    DayOfWeek::Sunday,
+];
+
+/// The hours of one weekday or week of a [`Distribution`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Share {
+    /// The number of working days.
+    pub days: u32,
+    /// The mean hours of all timesheets.
+    pub hours: f64,
+}
+
+impl Share {
+    /// The mean hours per working day, `None` without any.
+    pub fn per_day(&self) -> Option<f64> {
+        (self.days > 0).then(|| self.hours / self.days as f64)
+    }
+}
+
+/// The [`Share`] of a weekday.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekdayShare {
+    /// The weekday.
+    pub day_of_week: DayOfWeek,
+    /// Its working days and hours.
+    #[serde(flatten)]
+    pub share: Share,
+}
+
+/// The [`Share`] of a week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekShare {
+    /// The week of the month, starting at 1.
+    pub week: u32,
+    /// Its working days and hours.
+    #[serde(flatten)]
+    pub share: Share,
+}
+
+/// The mean hours of many timesheets of the same month per weekday and per week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Distribution {
+    /// The number of timesheets.
+    pub samples: u32,
+    /// The weekdays from Monday to Sunday that are working days or were worked on.
+    pub weekdays: Vec<WeekdayShare>,
+    /// The weeks of the month starting on Mondays, the first one may be partial.
+    pub weeks: Vec<WeekShare>,
+}
+
+/// The week of `day` within `month`, starting at 1 with the week of the first day.
+pub(crate) fn week_of(month: &Month, day: u32) -> u32 {
+    let first = month.day_of_week(1);
+    let offset = WEEK.iter().position(|&d| d == first).expect("all days") as u32;
+    (day - 1 + offset) / 7 + 1
+}
+
+impl Distribution {
+    /// Sums up the `sheets` of `month`, whose working days are `working_days`.
+    pub fn new(month: &Month, working_days: &[DayOfMonth], sheets: &[Timesheet]) -> Self {
+        let samples = sheets.len() as u32;
+        let mut weekdays: Vec<_> = WEEK
+            .iter()
+            .map(|&day_of_week| (day_of_week, 0, 0))
+            .collect();
+        let weeks = week_of(month, month.num_days());
+        let mut by_week: Vec<_> = (1..=weeks).map(|week| (week, 0, 0)).collect();
+        for day in working_days {
+            let weekday = WEEK.iter().position(|&d| d == day.day_of_week).unwrap();
+            weekdays[weekday].1 += 1;
+            by_week[week_of(month, day.day_of_month.get()) as usize - 1].1 += 1;
+        }
+        for entry in sheets.iter().flat_map(|sheet| &sheet.entries) {
+            let weekday = WEEK.iter().position(|&d| d == entry.day_of_week).unwrap();
+            weekdays[weekday].2 += entry.time.duration();
+            by_week[week_of(month, entry.day.get()) as usize - 1].2 += entry.time.duration();
+        }
+        let share = |days, minutes: u32| Share {
+            days,
+            hours: minutes as f64 / 60.0 / samples.max(1) as f64,
+        };
+        Distribution {
+            samples,
+            weekdays: weekdays
+                .into_iter()
+                .filter(|&(_, days, minutes)| days > 0 || minutes > 0)
+                .map(|(day_of_week, days, minutes)| WeekdayShare {
+                    day_of_week,
+                    share: share(days, minutes),
+                })
+                .collect(),
+            weeks: by_week
+                .into_iter()
+                .map(|(week, days, minutes)| WeekShare {
+                    week,
+                    share: share(days, minutes),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A table of the weekdays followed by one of the weeks.
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_localized(f, Locale::default())
+    }
+}
+
+impl Localize for Distribution {
+    fn fmt_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter<'_>, label: &str, share: &Share| {
+            let per_day = share
+                .per_day()
+                .map_or("-".into(), |per_day| locale.decimal(per_day, 2));
+            writeln!(
+                f,
+                "{:<10}{:>4}  {:>6}  {:>7}",
+                label,
+                share.days,
+                locale.decimal(share.hours, 2),
+                per_day
+            )
+        };
+        writeln!(f, "Mean of {} timesheets", self.samples)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<10}{:>4}  {:>6}  {:>7}",
+            "Weekday", "Days", "Hours", "Per day"
+        )?;
+        for weekday in &self.weekdays {
+            row(f, &format!("{:?}", weekday.day_of_week), &weekday.share)?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<10}{:>4}  {:>6}  {:>7}",
+            "Week", "Days", "Hours", "Per day"
+        )?;
+        for week in &self.weeks {
+            row(f, &week.week.to_string(), &week.share)?;
+        }
+        Ok(())
+    }
+}
+
+/// How many runs of a [`Simulation`] ended up with a value.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Bucket {
+    /// The number of worked days or the hours of the longest day.
+    pub value: u32,
+    /// The number of timesheets with this value.
+    pub runs: u32,
+}
+
+/// The worked days and the longest days of many generated timesheets of the same month.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Simulation {
+    /// The number of runs, including the failed ones.
+    pub runs: u32,
+    /// The runs that didn't produce a timesheet.
+    pub failed: u32,
+    /// The number of worked days in ascending order.
+    pub worked_days: Vec<Bucket>,
+    /// The hours of the longest day in ascending order, started hours count as full ones.
+    pub longest_day: Vec<Bucket>,
+}
+
+/// The sorted buckets of `counts`.
+fn buckets(counts: BTreeMap<u32, u32>) -> Vec<Bucket> {
+    counts
+        .into_iter()
+        .map(|(value, runs)| Bucket { value, runs })
+        .collect()
+}
+
+impl Simulation {
+    /// Counts the outcomes of generating a timesheet once per run.
+    pub fn new<'a>(results: impl IntoIterator<Item = &'a Result<Timesheet>>) -> Self {
+        let (mut runs, mut failed) = (0, 0);
+        let mut worked_days = BTreeMap::new();
+        let mut longest_day = BTreeMap::new();
+        for result in results {
+            runs += 1;
+            let Ok(sheet) = result else {
+                failed += 1;
+                continue;
+            };
+            let mut days = BTreeMap::new();
+            for entry in &sheet.entries {
+                *days.entry(entry.day).or_insert(0) += entry.time.duration();
+            }
+            *worked_days.entry(days.len() as u32).or_insert(0) += 1;
+            let longest = days
+                .values()
+                .max()
+                .map_or(0, |minutes: &u32| minutes.div_ceil(60));
+            *longest_day.entry(longest).or_insert(0) += 1;
+        }
+        Simulation {
+            runs,
+            failed,
+            worked_days: buckets(worked_days),
+            longest_day: buckets(longest_day),
+        }
+    }
+
+    /// The share of all runs that `bucket` makes up.
+    pub fn share(&self, bucket: &Bucket) -> f64 {
+        bucket.runs as f64 / self.runs.max(1) as f64
+    }
+}
+
+/// A table of the worked days followed by one of the longest days.
+impl fmt::Display for Simulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_localized(f, Locale::default())
+    }
+}
+
+impl Localize for Simulation {
+    fn fmt_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        writeln!(f, "{} runs, {} failed", self.runs, self.failed)?;
+        for (label, buckets) in [
+            ("Worked days", &self.worked_days),
+            ("Longest day", &self.longest_day),
+        ] {
+            writeln!(f)?;
+            writeln!(f, "{:>11}  {:>6}  {:>6}", label, "Runs", "Share")?;
+            for bucket in buckets {
+                writeln!(
+                    f,
+                    "{:>11}  {:>6}  {:>5}%",
+                    bucket.value,
+                    bucket.runs,
+                    locale.decimal(self.share(bucket) * 100.0, 1)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{non_holidays_of_month, DayOfWeek, Month, State, Year};
+    use crate::error::Error;
+    use crate::locale::Locale;
+    use crate::rng::seeded;
+    use crate::stats::{Bucket, Distribution, Simulation};
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+
+    #[test]
+    fn test_distribution() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 60,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let sheets: Vec<_> = (0..500)
+            .map(|seed| generate_timesheet(&year, &month, parameters, &mut seeded(seed)).unwrap())
+            .collect();
+        let days = non_holidays_of_month(&month, &year, &State::Bavaria);
+        let distribution = Distribution::new(&month, &days, &sheets);
+
+        assert_eq!(distribution.samples, 500);
+        // 1.5.2024 is a Wednesday, Himmelfahrt a Thursday and Pfingstmontag and Fronleichnam are on
+        // a Monday and a Thursday
+        let days: Vec<_> = distribution
+            .weekdays
+            .iter()
+            .map(|weekday| (weekday.day_of_week, weekday.share.days))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (DayOfWeek::Monday, 3),
+                (DayOfWeek::Tuesday, 4),
+                (DayOfWeek::Wednesday, 4),
+                (DayOfWeek::Thursday, 3),
+                (DayOfWeek::Friday, 5),
+            ]
+        );
+        let weeks: Vec<_> = distribution
+            .weeks
+            .iter()
+            .map(|week| week.share.days)
+            .collect();
+        assert_eq!(weeks, [2, 4, 5, 4, 4]);
+
+        let weekdays: f64 = distribution.weekdays.iter().map(|w| w.share.hours).sum();
+        let weeks: f64 = distribution.weeks.iter().map(|w| w.share.hours).sum();
+        assert!((weekdays - 60.0).abs() < 1e-9 && (weeks - 60.0).abs() < 1e-9);
+        for weekday in &distribution.weekdays {
+            let per_day = weekday.share.per_day().unwrap();
+            assert!((per_day - 60.0 / 19.0).abs() < 0.25, "{}", distribution);
+        }
+
+        let table = distribution.to_string();
+        let german = Locale::DeDe.display(&distribution).to_string();
+        assert_eq!(german, table.replace('.', ","));
+    }
+
+    #[test]
+    fn test_simulation() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 60,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let mut results: Vec<_> = (0..200)
+            .map(|seed| generate_timesheet(&year, &month, parameters, &mut seeded(seed)))
+            .collect();
+        results.push(Err(Error::InvalidWindow { from: 8, to: 6 }));
+        let simulation = Simulation::new(&results);
+
+        assert_eq!((simulation.runs, simulation.failed), (201, 1));
+        for buckets in [&simulation.worked_days, &simulation.longest_day] {
+            assert_eq!(buckets.iter().map(|b| b.runs).sum::<u32>(), 200);
+            assert!(buckets.windows(2).all(|w| w[0].value < w[1].value));
+        }
+        // 60 hours need at least 8 days of at most 8 hours, May 2024 has 19 working days in Bavaria
+        assert!(simulation
+            .worked_days
+            .iter()
+            .all(|b| (8..=19).contains(&b.value)));
+        assert!(simulation.longest_day.iter().all(|b| b.value <= 8));
+
+        let single = Simulation::new(&results[..1]);
+        let sheet = results[0].as_ref().unwrap();
+        assert_eq!(
+            single.worked_days,
+            [Bucket {
+                value: sheet.entries.len() as u32,
+                runs: 1
+            }]
+        );
+        assert!((single.share(&single.worked_days[0]) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,2208 @@
+//! Combines the working days of a month with generated times.
+
+use std::fmt;
+use std::num::NonZeroU32;
+
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::ROUND_WEEK;
+use crate::calendar::{
+    non_holidays_of_month, City, DateOfYear, DayOfMonth, DayOfWeek, HolidaySource, Month, Region,
+    State, Year,
+};
+use crate::constraints::{Blackout, Constraint, DateConstraint};
+use crate::distribute::{BackLoaded, Distribution, Even, FrontLoaded, Shape, Uniform, Weighted};
+use crate::error::{Error, Result};
+use crate::generate::{generate_times, generate_times_with, DayCapacity, Parameters, TimeSpan};
+use crate::output::{clock, MONTH_NAMES, WEEKDAYS};
+use crate::rng::index;
+use crate::stats::week_of;
+use crate::timezone::Timezone;
+use crate::trace::trace;
+
+/// How often [`avoid_round_weeks`] tries to move an hour between weeks.
+const ROUGHEN_ATTEMPTS: u32 = 200;
+
+/// The finest granularity of the remainder of [`place_remainder`] in minutes.
+pub const GRANULARITY: u32 = 15;
+
+/// A working day of a [`Timesheet`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Entry {
+    /// The day of the month, starting at 1.
+    pub day: NonZeroU32,
+    /// The day of the week of `day`.
+    pub day_of_week: DayOfWeek,
+    /// The time worked on this day.
+    pub time: TimeSpan,
+    /// The premium paid for working on this day, see [`work_off_days`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surcharge: Option<Surcharge>,
+    /// The minutes of break deducted from the time, see [`crate::breaks::deduct_breaks`].
+    #[serde(default, rename = "break", skip_serializing_if = "is_zero")]
+    pub break_minutes: u32,
+    /// The description of the activity on this day, see [`crate::notes`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+impl Entry {
+    /// The minutes on the clock on this day without the break, see [`Timesheet::net_minutes_of`]
+    /// for the minutes that pass.
+    pub fn net_minutes(&self) -> u32 {
+        self.time.duration().saturating_sub(self.break_minutes)
+    }
+}
+
+/// Why the work on a day is paid a premium.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Surcharge {
+    /// Work on a Saturday that isn't a holiday.
+    Saturday,
+    /// Work on a Sunday that isn't a holiday.
+    Sunday,
+    /// Work on a public holiday.
+    Holiday,
+}
+
+impl Surcharge {
+    /// All surcharges.
+    pub const ALL: [Surcharge; 3] = [Surcharge::Saturday, Surcharge::Sunday, Surcharge::Holiday];
+
+    /// The code of the premium in the rendered timesheets.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Surcharge::Saturday => "saturday",
+            Surcharge::Sunday => "sunday",
+            Surcharge::Holiday => "holiday",
+        }
+    }
+
+    /// The premium for working on `day` of `month`, `None` on a regular working day.
+    pub fn of(
+        year: &Year,
+        month: &Month,
+        day: &DayOfMonth,
+        holidays: &dyn HolidaySource,
+    ) -> Option<Self> {
+        let date = DateOfYear::new(day.day_of_month, month.month());
+        if holidays
+            .holidays(year)
+            .iter()
+            .any(|holiday| holiday.date == date)
+        {
+            Some(Surcharge::Holiday)
+        } else {
+            match day.day_of_week {
+                DayOfWeek::Saturday => Some(Surcharge::Saturday),
+                DayOfWeek::Sunday => Some(Surcharge::Sunday),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// The generated times of all days worked in a month.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Timesheet {
+    /// The year of the month.
+    pub year: u32,
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The state whose holidays were excluded.
+    pub state: State,
+    /// The hourly rate in cents, the formats add the pay of each entry if it is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<u32>,
+    /// The zone of the times, JSON adds the start and end of each entry as timestamps if it is
+    /// set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Timezone>,
+    /// The days worked, in order.
+    pub entries: Vec<Entry>,
+}
+
+impl Timesheet {
+    /// The total number of minutes of all entries.
+    pub fn minutes(&self) -> u32 {
+        self.entries.iter().map(|entry| entry.time.duration()).sum()
+    }
+
+    /// The total number of full hours of all entries.
+    pub fn hours(&self) -> u32 {
+        self.minutes() / 60
+    }
+
+    /// The total number of minutes of all entries without their breaks, see
+    /// [`Timesheet::net_minutes_of`].
+    pub fn net_minutes(&self) -> u32 {
+        self.entries
+            .iter()
+            .map(|entry| self.net_minutes_of(entry))
+            .sum()
+    }
+
+    /// The minutes that pass during `entry`. Without a time zone or outside the nights the clocks
+    /// change these are the minutes on the clock, see [`Timezone::elapsed`].
+    pub fn gross_minutes_of(&self, entry: &Entry) -> u32 {
+        match self.timezone {
+            Some(timezone) => timezone.elapsed(self.year, self.month, entry.day.get(), entry.time),
+            None => entry.time.duration(),
+        }
+    }
+
+    /// The minutes worked during `entry` without its break.
+    pub fn net_minutes_of(&self, entry: &Entry) -> u32 {
+        self.gross_minutes_of(entry)
+            .saturating_sub(entry.break_minutes)
+    }
+
+    /// The minutes of `entry` within the night, the hour the clocks skip or repeat is always part
+    /// of it.
+    pub fn night_minutes_of(&self, entry: &Entry) -> u32 {
+        (entry.time.night_minutes() + self.gross_minutes_of(entry))
+            .saturating_sub(entry.time.duration())
+    }
+
+    /// Whether any entry has a break.
+    pub fn has_breaks(&self) -> bool {
+        self.entries.iter().any(|entry| entry.break_minutes > 0)
+    }
+
+    /// Whether any entry has a [`Surcharge`].
+    pub fn has_surcharges(&self) -> bool {
+        self.entries.iter().any(|entry| entry.surcharge.is_some())
+    }
+
+    /// Whether any entry has a note.
+    pub fn has_notes(&self) -> bool {
+        self.entries.iter().any(|entry| entry.note.is_some())
+    }
+
+    /// Whether any entry is worked during the night, see [`TimeSpan::night_minutes`].
+    pub fn has_night(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.time.night_minutes() > 0)
+    }
+}
+
+/// The constraints for [`generate_timesheet`].
+#[derive(Debug, Copy, Clone)]
+pub struct SheetParameters {
+    /// The state whose holidays aren't worked on.
+    pub state: State,
+    /// The city within `state` whose holidays aren't worked on either.
+    pub city: Option<City>,
+    /// The total number of hours to distribute.
+    pub hours: u32,
+    /// The earliest starting hour.
+    pub from: u32,
+    /// The latest stopping hour.
+    pub to: u32,
+    /// The maximum number of hours per day.
+    pub max_per_day: u32,
+}
+
+impl SheetParameters {
+    /// Checks that the earliest and the latest hour and the maximum per day fit into a day, like
+    /// the options of the CLI do. [`generate_timesheet`] would write times after midnight otherwise.
+    pub fn check(&self) -> Result<()> {
+        let settings = [
+            ("The earliest hour", self.from),
+            ("The latest hour", self.to),
+            ("The maximum of hours per day", self.max_per_day),
+        ];
+        match settings.into_iter().find(|(_, value)| *value > 24) {
+            Some((setting, value)) => Err(Error::AboveDay {
+                setting: setting.into(),
+                value,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The shape of the hours of a month, see [`generate_distributed_timesheet`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// Every distribution of the hours is equally likely.
+    #[default]
+    Uniform,
+    /// The days are as equally long as possible.
+    Even,
+    /// The days are weighted by their day of the week, starting with Monday.
+    Weighted([u32; 7]),
+    /// The days at the start of the month are longer.
+    FrontLoaded,
+    /// The days at the end of the month are longer.
+    BackLoaded,
+    /// Uniformly random, but biased towards a part of the month.
+    Shaped(Shape),
+}
+
+impl Strategy {
+    /// The distribution of the hours onto the `days` in order.
+    pub fn distribution(&self, days: &[DayOfWeek]) -> Box<dyn Distribution> {
+        match self {
+            Strategy::Uniform => Box::new(Uniform),
+            Strategy::Even => Box::new(Even),
+            Strategy::Weighted(weights) => Box::new(Weighted {
+                weights: days
+                    .iter()
+                    .map(|&day| weights[(day as usize + 6) % 7])
+                    .collect(),
+            }),
+            Strategy::FrontLoaded => Box::new(FrontLoaded),
+            Strategy::BackLoaded => Box::new(BackLoaded),
+            Strategy::Shaped(shape) => Box::new(*shape),
+        }
+    }
+}
+
+/// Distributes the hours of `parameters` onto the working days of `month`.
+pub fn generate_timesheet<R: Rng + ?Sized>(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    r: &mut R,
+) -> Result<Timesheet> {
+    let region = Region::new(parameters.state, parameters.city)?;
+    plan_plain(year, month, parameters, &region).execute(Strategy::Uniform, r)
+}
+
+/// The plan of `month` without constraints or blackouts, every day is a [`DayCapacity`].
+fn plan_plain(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    holidays: &dyn HolidaySource,
+) -> SheetPlan {
+    let SheetParameters {
+        state,
+        hours,
+        from,
+        to,
+        max_per_day,
+        ..
+    } = parameters;
+    SheetPlan {
+        year: year.year(),
+        month: month.month(),
+        state,
+        hours,
+        filled: Vec::new(),
+        days: DayCapacity::of_month(
+            month,
+            year,
+            holidays,
+            max_per_day,
+            TimeSpan::hours(from, to),
+        ),
+    }
+}
+
+/// Like [`generate_timesheet`], but also respects the `constraints` of dates in `month` and never
+/// works during the `blackouts`.
+///
+/// Pinned and filled dates are worked even on weekends and holidays, the filled times count towards
+/// the hours of `parameters`. Constraints of other months are ignored.
+pub fn generate_constrained_timesheet<R: Rng + ?Sized>(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<Timesheet> {
+    let region = Region::new(parameters.state, parameters.city)?;
+    generate_timesheet_with(year, month, parameters, &region, constraints, blackouts, r)
+}
+
+/// Like [`generate_constrained_timesheet`], but the days off are the holidays of `holidays`
+/// instead of those of the state and city of `parameters`.
+pub fn generate_timesheet_with<R: Rng + ?Sized>(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<Timesheet> {
+    generate_distributed_timesheet(
+        year,
+        month,
+        parameters,
+        holidays,
+        constraints,
+        blackouts,
+        Strategy::Uniform,
+        r,
+    )
+}
+
+/// Like [`generate_timesheet_with`], but the hours are shaped after `strategy`.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_distributed_timesheet<R: Rng + ?Sized>(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    strategy: Strategy,
+    r: &mut R,
+) -> Result<Timesheet> {
+    plan_timesheet(year, month, parameters, holidays, constraints, blackouts)?.execute(strategy, r)
+}
+
+/// The [`SheetPlan`] of [`generate_distributed_timesheet`], resolving the days of `month` that can
+/// be worked and how many hours each of them can hold without drawing any time.
+///
+/// Fails if the filled dates already take more than the hours of `parameters`.
+pub fn plan_timesheet(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+) -> Result<SheetPlan> {
+    let constraints: Vec<_> = constraints
+        .iter()
+        .filter(|c| c.year == year.year() && c.month == month.month())
+        .collect();
+    if constraints.is_empty() && blackouts.is_empty() {
+        return Ok(plan_plain(year, month, parameters, holidays));
+    }
+    plan_around(
+        year,
+        month,
+        parameters,
+        holidays,
+        &constraints,
+        blackouts,
+        &[],
+    )
+}
+
+/// Generates a timesheet for each of `jobs` with their own constraints, the times of the jobs
+/// never overlap on any day and none of them is worked during the `blackouts`.
+///
+/// The jobs are generated in order, each one is fitted around the times of the previous ones.
+pub fn generate_jobs<R: Rng + ?Sized>(
+    year: &Year,
+    month: &Month,
+    jobs: &[(SheetParameters, Vec<DateConstraint>)],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<Vec<Timesheet>> {
+    let mut busy = Vec::new();
+    let mut sheets = Vec::new();
+    for (parameters, constraints) in jobs {
+        let constraints: Vec<_> = constraints
+            .iter()
+            .filter(|c| c.year == year.year() && c.month == month.month())
+            .collect();
+        let region = Region::new(parameters.state, parameters.city)?;
+        let sheet = plan_around(
+            year,
+            month,
+            *parameters,
+            &region,
+            &constraints,
+            blackouts,
+            &busy,
+        )?
+        .execute(Strategy::Uniform, r)?;
+        busy.extend(sheet.entries.iter().cloned());
+        sheets.push(sheet);
+    }
+    Ok(sheets)
+}
+
+/// The plan of `month` with the `constraints` of its dates, the times don't overlap the
+/// `blackouts` or the `busy` entries of the same day.
+fn plan_around(
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[&DateConstraint],
+    blackouts: &[Blackout],
+    busy: &[Entry],
+) -> Result<SheetPlan> {
+    let SheetParameters {
+        state,
+        hours,
+        from,
+        to,
+        max_per_day,
+        ..
+    } = parameters;
+
+    let mut days = non_holidays_of_month(month, year, holidays);
+    for c in constraints {
+        let worked = matches!(c.constraint, Constraint::Pin | Constraint::Fill(_));
+        if worked && !days.iter().any(|day| day.day_of_month == c.day) {
+            days.push(DayOfMonth {
+                day_of_week: month.day_of_week(c.day.get()),
+                day_of_month: c.day,
+            });
+        }
+    }
+    days.sort_by_key(|day| day.day_of_month);
+
+    let mut entries = Vec::new();
+    let mut free_days = Vec::new();
+    for day in days {
+        let mut capacity = DayCapacity {
+            day: day.day_of_month,
+            day_of_week: day.day_of_week,
+            min: 0,
+            max: max_per_day,
+            window: TimeSpan::hours(from, to),
+            blackouts: Vec::new(),
+        };
+        let mut free = true;
+        for c in constraints.iter().filter(|c| c.day == day.day_of_month) {
+            match c.constraint {
+                Constraint::Pin => capacity.min = 1,
+                Constraint::Cap(cap) => capacity.max = capacity.max.min(cap),
+                Constraint::Exclude => free = false,
+                Constraint::Fill(time) => {
+                    free = false;
+                    entries.push(Entry {
+                        day: day.day_of_month,
+                        day_of_week: day.day_of_week,
+                        time,
+                        surcharge: None,
+                        break_minutes: 0,
+                        note: None,
+                    });
+                }
+            }
+        }
+        if free {
+            let busy = busy.iter().filter(|entry| entry.day == day.day_of_month);
+            let blackouts = blackouts
+                .iter()
+                .filter(|blackout| blackout.applies_to(day.day_of_week));
+            capacity.blackouts = busy
+                .map(|entry| entry.time)
+                .chain(blackouts.map(|blackout| blackout.time))
+                .collect();
+            free_days.push(capacity);
+        }
+    }
+
+    let filled: u32 = entries
+        .iter()
+        .map(|entry: &Entry| entry.time.duration())
+        .sum::<u32>()
+        / 60;
+    let remaining = hours.checked_sub(filled).ok_or(Error::Infeasible {
+        hours,
+        days: entries.len() as u32,
+        max_per_day,
+    })?;
+    Ok(SheetPlan {
+        year: year.year(),
+        month: month.month(),
+        state,
+        hours: remaining,
+        filled: entries,
+        days: free_days,
+    })
+}
+
+/// The days of a month a timesheet is generated on, resolved from the calendar, the constraints
+/// and the blackouts before any time is drawn, see [`plan_timesheet`].
+///
+/// The dates filled by a constraint are already entries, every other day is a [`DayCapacity`]
+/// that holds no hours if it isn't worked. Printed as a row per day of the month.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct SheetPlan {
+    /// The year of the month.
+    pub year: u32,
+    /// The month, starting at 1.
+    pub month: NonZeroU32,
+    /// The state whose holidays are excluded.
+    pub state: State,
+    /// The hours distributed onto the `days`, without those of the `filled` entries.
+    pub hours: u32,
+    /// The entries of the filled dates.
+    pub filled: Vec<Entry>,
+    /// The days the hours are distributed onto in order.
+    pub days: Vec<DayCapacity>,
+}
+
+impl SheetPlan {
+    /// The most hours all days can hold together.
+    pub fn capacity(&self) -> u32 {
+        self.days.iter().map(DayCapacity::room).sum()
+    }
+
+    /// Draws the times of the plan with the hours shaped after `strategy`.
+    pub fn execute<R: Rng + ?Sized>(&self, strategy: Strategy, r: &mut R) -> Result<Timesheet> {
+        let weekdays: Vec<_> = self
+            .days
+            .iter()
+            .filter(|day| day.max > 0)
+            .map(|day| day.day_of_week)
+            .collect();
+        let parameters = Parameters {
+            hours: self.hours,
+            days: self.days.clone(),
+        };
+        let times = generate_times_with(&parameters, strategy.distribution(&weekdays).as_ref(), r)?;
+        let mut entries = self.filled.clone();
+        entries.extend(
+            times
+                .into_iter()
+                .zip(parameters.days)
+                .filter_map(|(time, day)| {
+                    Some(Entry {
+                        day: day.day,
+                        day_of_week: day.day_of_week,
+                        time: time?,
+                        surcharge: None,
+                        break_minutes: 0,
+                        note: None,
+                    })
+                }),
+        );
+        entries.sort_by_key(|entry| entry.day);
+        Ok(Timesheet {
+            year: self.year,
+            month: self.month,
+            state: self.state,
+            rate: None,
+            timezone: None,
+            entries,
+        })
+    }
+}
+
+/// A line with the hours and a row per day like `Tue 14.  fill 09:00-13:00` or
+/// `Wed 15.  0-8 hours in 08:00-20:00 except 12:00-13:00`.
+impl fmt::Display for SheetPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filled: u32 = self.filled.iter().map(|entry| entry.time.duration()).sum();
+        writeln!(
+            f,
+            "{} {}: {} hours onto {} days holding at most {}, {} filled",
+            MONTH_NAMES[self.month.get() as usize - 1],
+            self.year,
+            self.hours,
+            self.days.iter().filter(|day| day.room() > 0).count(),
+            self.capacity(),
+            clock(filled)
+        )?;
+        let year = Year::new(self.year);
+        let month = Month::new(self.month, &year).expect("plans have valid months");
+        for day in month.days() {
+            let name = WEEKDAYS[(day.day_of_week as usize + 6) % 7];
+            write!(f, "{} {:>2}.  ", name, day.day_of_month)?;
+            let fill = self
+                .filled
+                .iter()
+                .find(|entry| entry.day == day.day_of_month);
+            let capacity = self
+                .days
+                .iter()
+                .find(|capacity| capacity.day == day.day_of_month);
+            match (fill, capacity) {
+                (Some(entry), _) => writeln!(f, "fill {}", entry.time)?,
+                (None, Some(capacity)) if capacity.room() > 0 => {
+                    write!(
+                        f,
+                        "{}-{} hours in {}",
+                        capacity.min,
+                        capacity.room(),
+                        capacity.window
+                    )?;
+                    if !capacity.blackouts.is_empty() {
+                        let blackouts: Vec<_> =
+                            capacity.blackouts.iter().map(ToString::to_string).collect();
+                        write!(f, " except {}", blackouts.join(", "))?;
+                    }
+                    writeln!(f)?;
+                }
+                _ => writeln!(f, "not worked")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How suspicious the weekly totals in minutes look: a point per round week and one per week if
+/// they are all the same.
+fn roundness(weeks: &[u32]) -> usize {
+    let round = weeks
+        .iter()
+        .filter(|&&minutes| minutes % ROUND_WEEK == 0)
+        .count();
+    let equal = weeks.len() > 1 && weeks.iter().all(|&minutes| minutes == weeks[0]);
+    round + if equal { weeks.len() } else { 0 }
+}
+
+/// Moves single hours of `sheet` between its weeks until the weekly totals are neither all the
+/// same nor multiples of five hours, as far as the `parameters` and `blackouts` allow.
+///
+/// Days with any of the `constraints` are left as they are. Each move shortens a day of one week
+/// and lengthens a day of another, so the total of the month stays exact.
+pub fn avoid_round_weeks<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    parameters: &SheetParameters,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<()> {
+    let month = Month::new(sheet.month, &Year::new(sheet.year))?;
+    let week = |entry: &Entry| week_of(&month, entry.day.get()) as usize - 1;
+    let weeks = sheet
+        .entries
+        .iter()
+        .map(week)
+        .max()
+        .map_or(0, |last| last + 1);
+    let movable: Vec<usize> = (0..sheet.entries.len())
+        .filter(|&i| {
+            let entry = &sheet.entries[i];
+            !constraints
+                .iter()
+                .any(|c| c.year == sheet.year && c.month == sheet.month && c.day == entry.day)
+        })
+        .collect();
+    if weeks < 2 || movable.is_empty() {
+        return Ok(());
+    }
+    let mut totals = vec![0; weeks];
+    for entry in &sheet.entries {
+        totals[week(entry)] += entry.time.duration();
+    }
+
+    for attempt in 1..=ROUGHEN_ATTEMPTS {
+        if roundness(&totals) == 0 {
+            break;
+        }
+        let shorter = movable[index(r, movable.len())];
+        let longer = movable[index(r, movable.len())];
+        let (a, b) = (&sheet.entries[shorter], &sheet.entries[longer]);
+        let outcome = |outcome: &str| {
+            trace!(
+                "round weeks, attempt {}: an hour from the {}. to the {}. {}",
+                attempt,
+                a.day,
+                b.day,
+                outcome
+            )
+        };
+        if week(a) == week(b) || a.time.duration() <= 60 {
+            outcome("rejected, same week or too short");
+            continue;
+        }
+        let shortened = TimeSpan {
+            from: a.time.from,
+            to: a.time.to - 60,
+        };
+        let Some(lengthened) = lengthen(b, 60, parameters, blackouts) else {
+            outcome("rejected, it can't be longer");
+            continue;
+        };
+        let mut moved = totals.clone();
+        moved[week(a)] -= 60;
+        moved[week(b)] += 60;
+        if roundness(&moved) < roundness(&totals) {
+            outcome("taken");
+            totals = moved;
+            sheet.entries[shorter].time = shortened;
+            sheet.entries[longer].time = lengthened;
+        } else {
+            outcome("rejected, not less round");
+        }
+    }
+    Ok(())
+}
+
+/// The most minutes worked in a week from Monday to Sunday, see [`cap_weeks`].
+///
+/// The weeks that straddle the boundaries of the month also hold the minutes the neighboring
+/// months worked in them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyCap {
+    /// The most minutes worked a week.
+    pub max: u32,
+    /// The minutes the previous month worked in the week of the first day.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub before: u32,
+    /// The minutes the next month worked in the week of the last day.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub after: u32,
+}
+
+impl WeeklyCap {
+    /// A cap of `max` minutes a week in `month` that counts the times of the `previous` and the
+    /// `next` month, if known, in the weeks they share with it.
+    pub fn new(
+        max: u32,
+        month: &Month,
+        previous: Option<&Timesheet>,
+        next: Option<&Timesheet>,
+    ) -> Self {
+        // the days since Monday of the first day and until Sunday of the last day
+        let leading = (month.day_of_week(1) as u32 + 6) % 7;
+        let trailing = 6 - (month.day_of_week(month.num_days()) as u32 + 6) % 7;
+        let before = previous.map_or(0, |sheet| {
+            let days = Year::new(sheet.year).days_of_month(sheet.month);
+            sheet
+                .entries
+                .iter()
+                .filter(|entry| entry.day.get() + leading > days)
+                .map(|entry| entry.time.duration())
+                .sum()
+        });
+        let after = next.map_or(0, |sheet| {
+            sheet
+                .entries
+                .iter()
+                .filter(|entry| entry.day.get() <= trailing)
+                .map(|entry| entry.time.duration())
+                .sum()
+        });
+        WeeklyCap { max, before, after }
+    }
+}
+
+/// Moves time of `sheet` out of the weeks that hold more than `cap` into weeks below it, an hour
+/// or else a quarter at a time, as far as the `parameters` and `blackouts` allow.
+///
+/// Days with any of the `constraints` are left as they are and the total of the month stays
+/// exact. Fails if a week can't be brought below the cap.
+pub fn cap_weeks<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    cap: &WeeklyCap,
+    parameters: &SheetParameters,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<()> {
+    let month = Month::new(sheet.month, &Year::new(sheet.year))?;
+    let week = |entry: &Entry| week_of(&month, entry.day.get()) as usize - 1;
+    let weeks = week_of(&month, month.num_days()) as usize;
+    let mut totals = vec![0; weeks];
+    totals[0] += cap.before;
+    totals[weeks - 1] += cap.after;
+    for entry in &sheet.entries {
+        totals[week(entry)] += entry.time.duration();
+    }
+    let movable = |entry: &Entry| {
+        !constraints
+            .iter()
+            .any(|c| c.year == sheet.year && c.month == sheet.month && c.day == entry.day)
+    };
+
+    while let Some(over) = (0..weeks).find(|&i| totals[i] > cap.max) {
+        let steps = if totals[over] - cap.max >= 60 {
+            &[60, GRANULARITY][..]
+        } else {
+            &[GRANULARITY][..]
+        };
+        let moves = steps.iter().find_map(|&step| {
+            let (shorter, longer): (Vec<_>, Vec<_>) = (0..sheet.entries.len())
+                .filter(|&i| movable(&sheet.entries[i]))
+                .partition(|&i| week(&sheet.entries[i]) == over);
+            let shorter: Vec<_> = shorter
+                .into_iter()
+                .filter(|&i| sheet.entries[i].time.duration() > step)
+                .collect();
+            let longer: Vec<_> = longer
+                .into_iter()
+                .filter(|&i| totals[week(&sheet.entries[i])] + step <= cap.max)
+                .filter_map(|i| {
+                    lengthen(&sheet.entries[i], step, parameters, blackouts).map(|time| (i, time))
+                })
+                .collect();
+            (!shorter.is_empty() && !longer.is_empty()).then_some((step, shorter, longer))
+        });
+        let Some((step, shorter, longer)) = moves else {
+            return Err(Error::WeeklyCapExceeded {
+                week: over as u32 + 1,
+                minutes: totals[over],
+                cap: cap.max,
+            });
+        };
+        let a = shorter[index(r, shorter.len())];
+        let (b, lengthened) = longer[index(r, longer.len())];
+        sheet.entries[a].time.to -= step;
+        sheet.entries[b].time = lengthened;
+        totals[over] -= step;
+        totals[week(&sheet.entries[b])] += step;
+    }
+    Ok(())
+}
+
+/// Moves the days of `sheet` to start within half an hour of the median start, like a habit of
+/// arriving at the same time, as far as the `parameters` and `blackouts` allow.
+///
+/// The days keep their length and days with any of the `constraints` are left as they are.
+pub fn cluster_starts<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    parameters: &SheetParameters,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) {
+    let (year, month) = (sheet.year, sheet.month);
+    let mut movable: Vec<_> = sheet
+        .entries
+        .iter_mut()
+        .filter(|entry| {
+            !constraints
+                .iter()
+                .any(|c| c.year == year && c.month == month && c.day == entry.day)
+        })
+        .collect();
+    if movable.is_empty() {
+        return;
+    }
+    let mut starts: Vec<_> = movable.iter().map(|entry| entry.time.from).collect();
+    starts.sort_unstable();
+    let median = starts[starts.len() / 2] / GRANULARITY * GRANULARITY;
+    let (from, to) = (parameters.from * 60, parameters.to * 60);
+    for entry in &mut movable {
+        let start = (median + r.gen_range(0..=4) * GRANULARITY).saturating_sub(2 * GRANULARITY);
+        let time = TimeSpan {
+            from: start,
+            to: start + entry.time.duration(),
+        };
+        let free = from <= time.from
+            && time.to <= to
+            && !blackouts.iter().any(|blackout| {
+                blackout.applies_to(entry.day_of_week) && blackout.time.overlaps(&time)
+            });
+        if free {
+            entry.time = time;
+        }
+    }
+}
+
+/// Where [`place_remainder`] puts the minutes that don't make up a full hour.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Remainder {
+    /// A quarter of an hour each on different random days.
+    #[default]
+    Spread,
+    /// All of it on the last day that can take it.
+    LastDay,
+    /// All of it on one random day that can take it.
+    RandomDay,
+}
+
+/// The span of `entry` lengthened by `minutes` at its end or else at its start, `None` if neither
+/// stays within the `parameters` or avoids the `blackouts`.
+fn lengthen(
+    entry: &Entry,
+    minutes: u32,
+    parameters: &SheetParameters,
+    blackouts: &[Blackout],
+) -> Option<TimeSpan> {
+    if entry.time.duration() + minutes > parameters.max_per_day * 60 {
+        return None;
+    }
+    widen(entry, minutes, parameters, blackouts)
+}
+
+/// The span of `entry` lengthened by `minutes` like [`lengthen`], but regardless of the maximum
+/// hours per day of the `parameters`.
+pub(crate) fn widen(
+    entry: &Entry,
+    minutes: u32,
+    parameters: &SheetParameters,
+    blackouts: &[Blackout],
+) -> Option<TimeSpan> {
+    let (from, to) = (parameters.from * 60, parameters.to * 60);
+    let free = |time: &TimeSpan| {
+        from <= time.from
+            && time.to <= to
+            && !blackouts.iter().any(|blackout| {
+                blackout.applies_to(entry.day_of_week) && blackout.time.overlaps(time)
+            })
+    };
+    let later = TimeSpan {
+        from: entry.time.from,
+        to: entry.time.to + minutes,
+    };
+    let earlier = entry.time.from.checked_sub(minutes).map(|start| TimeSpan {
+        from: start,
+        to: entry.time.to,
+    });
+    [Some(later), earlier].into_iter().flatten().find(free)
+}
+
+/// Adds `minutes` below a full hour to the days of `sheet` as `remainder` says, in steps of
+/// [`GRANULARITY`].
+///
+/// Days with any of the `constraints` are left as they are, the lengthened days stay within the
+/// `parameters` and avoid the `blackouts`. Fails if no day can take the minutes.
+pub fn place_remainder<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    minutes: u32,
+    remainder: Remainder,
+    parameters: &SheetParameters,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<()> {
+    let unfit = Error::RemainderDoesNotFit { minutes };
+    if !minutes.is_multiple_of(GRANULARITY) || minutes >= 60 {
+        return Err(unfit);
+    }
+    let constrained = |entry: &Entry| {
+        constraints
+            .iter()
+            .any(|c| c.year == sheet.year && c.month == sheet.month && c.day == entry.day)
+    };
+    let fits = |minutes| {
+        (0..sheet.entries.len())
+            .filter(|&i| {
+                let entry = &sheet.entries[i];
+                !constrained(entry) && lengthen(entry, minutes, parameters, blackouts).is_some()
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut lengthened = Vec::new();
+    match remainder {
+        Remainder::Spread => {
+            let mut candidates = fits(GRANULARITY);
+            if candidates.len() < (minutes / GRANULARITY) as usize {
+                return Err(unfit);
+            }
+            for _ in 0..minutes / GRANULARITY {
+                let i = candidates.swap_remove(index(r, candidates.len()));
+                lengthened.push((i, GRANULARITY));
+            }
+        }
+        Remainder::LastDay => {
+            let &i = fits(minutes).last().ok_or(unfit)?;
+            lengthened.push((i, minutes));
+        }
+        Remainder::RandomDay => {
+            let candidates = fits(minutes);
+            if candidates.is_empty() {
+                return Err(unfit);
+            }
+            lengthened.push((candidates[index(r, candidates.len())], minutes));
+        }
+    }
+    for (i, minutes) in lengthened {
+        let time = lengthen(&sheet.entries[i], minutes, parameters, blackouts);
+        sheet.entries[i].time = time.expect("only days that fit are lengthened");
+    }
+    Ok(())
+}
+
+/// Which working days [`place_free_days`] leaves without work.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FreeDays {
+    /// This day of the week first, further free days at random.
+    Weekday(DayOfWeek),
+    /// Random days.
+    Random,
+    /// Days evenly spread over the month.
+    Spread,
+}
+
+/// Moves the times of `sheet` so that the working days of `holidays` it leaves free fall as
+/// `free_days` says, the number of free days and the times stay the same.
+///
+/// Days with any of the `constraints` are left as they are. A moved time keeps its start if the
+/// `blackouts` of its new weekday allow, otherwise it starts as close to it as the `parameters`
+/// allow. Times that fit nowhere stay on their day.
+pub fn place_free_days<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    free_days: FreeDays,
+    parameters: &SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<()> {
+    let year = Year::new(sheet.year);
+    let month = Month::new(sheet.month, &year)?;
+    let days: Vec<_> = non_holidays_of_month(&month, &year, holidays)
+        .into_iter()
+        .filter(|day| {
+            !constraints.iter().any(|c| {
+                c.year == sheet.year && c.month == sheet.month && c.day == day.day_of_month
+            })
+        })
+        .collect();
+    let worked = |day: &DayOfMonth| {
+        sheet
+            .entries
+            .iter()
+            .any(|entry| entry.day == day.day_of_month)
+    };
+    let count = days.iter().filter(|day| !worked(day)).count();
+    if count == 0 || count == days.len() {
+        return Ok(());
+    }
+
+    let mut free: Vec<usize> = match free_days {
+        FreeDays::Weekday(day_of_week) => {
+            let (mut first, mut rest): (Vec<_>, Vec<_>) =
+                (0..days.len()).partition(|&i| days[i].day_of_week == day_of_week);
+            while first.len() > count {
+                first.swap_remove(index(r, first.len()));
+            }
+            while first.len() < count {
+                first.push(rest.swap_remove(index(r, rest.len())));
+            }
+            first
+        }
+        FreeDays::Random => {
+            let mut all: Vec<_> = (0..days.len()).collect();
+            (0..count)
+                .map(|_| all.swap_remove(index(r, all.len())))
+                .collect()
+        }
+        FreeDays::Spread => (0..count)
+            .map(|i| (2 * i + 1) * days.len() / (2 * count))
+            .collect(),
+    };
+    free.sort_unstable();
+    let (to_free, to_work): (Vec<_>, Vec<_>) = (0..days.len())
+        .filter(|&i| free.contains(&i) == worked(&days[i]))
+        .partition(|&i| free.contains(&i));
+
+    let (from, to) = (parameters.from * 60, parameters.to * 60);
+    for (a, b) in to_free.into_iter().zip(to_work) {
+        let (a, b) = (&days[a], &days[b]);
+        let entry = sheet
+            .entries
+            .iter_mut()
+            .find(|entry| entry.day == a.day_of_month)
+            .expect("days to free are worked");
+        let duration = entry.time.duration();
+        let fits = |time: &TimeSpan| {
+            !blackouts
+                .iter()
+                .any(|blackout| blackout.applies_to(b.day_of_week) && blackout.time.overlaps(time))
+        };
+        let mut starts: Vec<_> = (from..=to.saturating_sub(duration))
+            .step_by(GRANULARITY as usize)
+            .collect();
+        starts.sort_by_key(|&start| start.abs_diff(entry.time.from));
+        let time = [entry.time]
+            .into_iter()
+            .filter(|time| from <= time.from && time.to <= to)
+            .chain(starts.into_iter().map(|start| TimeSpan {
+                from: start,
+                to: start + duration,
+            }))
+            .find(fits);
+        if let Some(time) = time {
+            entry.day = b.day_of_month;
+            entry.day_of_week = b.day_of_week;
+            entry.time = time;
+        }
+    }
+    sheet.entries.sort_by_key(|entry| entry.day);
+    Ok(())
+}
+
+/// Moves single hours of `sheet` onto the working days of `holidays` that end runs of more than
+/// `max` working days in a row without work.
+///
+/// The hours are taken from the longest days, the new days stay within the `parameters` and avoid
+/// the `blackouts`. Days with any of the `constraints` are left as they are and end a run. Fails if
+/// no day can spare an hour.
+#[allow(clippy::too_many_arguments)]
+pub fn limit_free_streaks<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    max: u32,
+    parameters: &SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<()> {
+    let year = Year::new(sheet.year);
+    let month = Month::new(sheet.month, &year)?;
+    let constrained = |day: NonZeroU32| {
+        constraints
+            .iter()
+            .any(|c| c.year == sheet.year && c.month == sheet.month && c.day == day)
+    };
+    let days: Vec<_> = non_holidays_of_month(&month, &year, holidays)
+        .into_iter()
+        .map(|day| {
+            (
+                day.day_of_month,
+                day.day_of_week,
+                constrained(day.day_of_month),
+            )
+        })
+        .collect();
+    let (from, to) = (parameters.from * 60, parameters.to * 60);
+
+    loop {
+        // the free days in a row, ended by worked or constrained days
+        let mut runs: Vec<Vec<_>> = vec![Vec::new()];
+        for &(day, day_of_week, constrained) in &days {
+            if constrained || sheet.entries.iter().any(|entry| entry.day == day) {
+                runs.push(Vec::new());
+            } else {
+                runs.last_mut()
+                    .expect("never empty")
+                    .push((day, day_of_week));
+            }
+        }
+        let Some(run) = runs.into_iter().find(|run| run.len() as u32 > max) else {
+            return Ok(());
+        };
+        let streak = run.len() as u32;
+        let (day, day_of_week) = run[max as usize];
+        let unfit = Error::FreeStreakTooLong { days: streak, max };
+
+        let starts: Vec<_> = (from..to.saturating_sub(59))
+            .step_by(GRANULARITY as usize)
+            .map(|start| TimeSpan {
+                from: start,
+                to: start + 60,
+            })
+            .filter(|time| {
+                !blackouts.iter().any(|blackout| {
+                    blackout.applies_to(day_of_week) && blackout.time.overlaps(time)
+                })
+            })
+            .collect();
+        let longest = (0..sheet.entries.len())
+            .filter(|&i| {
+                let entry = &sheet.entries[i];
+                !constrained(entry.day) && entry.time.duration() > 60
+            })
+            .max_by_key(|&i| sheet.entries[i].time.duration());
+        let (Some(longest), false) = (longest, starts.is_empty()) else {
+            return Err(unfit);
+        };
+        sheet.entries[longest].time.to -= 60;
+        sheet.entries.push(Entry {
+            day,
+            day_of_week,
+            time: starts[index(r, starts.len())],
+            surcharge: None,
+            break_minutes: 0,
+            note: None,
+        });
+        sheet.entries.sort_by_key(|entry| entry.day);
+    }
+}
+
+/// Moves `percent` of the hours of `sheet` onto the weekends and holidays of `holidays` and marks
+/// every entry on such a day with its [`Surcharge`].
+///
+/// The moved hours are taken from days without any of the `constraints` an hour at a time and put
+/// onto days off that aren't constrained either, within the `parameters` and around the
+/// `blackouts`. Fails if the days off can't hold them.
+pub fn work_off_days<R: Rng + ?Sized>(
+    sheet: &mut Timesheet,
+    percent: u32,
+    parameters: &SheetParameters,
+    holidays: &dyn HolidaySource,
+    constraints: &[DateConstraint],
+    blackouts: &[Blackout],
+    r: &mut R,
+) -> Result<()> {
+    let year = Year::new(sheet.year);
+    let month = Month::new(sheet.month, &year)?;
+    let constrained = |day: NonZeroU32| {
+        constraints
+            .iter()
+            .any(|c| c.year == sheet.year && c.month == sheet.month && c.day == day)
+    };
+    let off_days: Vec<_> = month
+        .days()
+        .filter(|day| {
+            Surcharge::of(&year, &month, day, holidays).is_some()
+                && !constrained(day.day_of_month)
+                && !sheet
+                    .entries
+                    .iter()
+                    .any(|entry| entry.day == day.day_of_month)
+        })
+        .collect();
+    let hours = (sheet.minutes() * percent.min(100) / 100 + 30) / 60;
+
+    let days = off_days
+        .iter()
+        .map(|day| DayCapacity {
+            day: day.day_of_month,
+            day_of_week: day.day_of_week,
+            min: 0,
+            max: parameters.max_per_day,
+            window: TimeSpan::hours(parameters.from, parameters.to),
+            blackouts: blackouts
+                .iter()
+                .filter(|blackout| blackout.applies_to(day.day_of_week))
+                .map(|blackout| blackout.time)
+                .collect(),
+        })
+        .collect();
+    let times = generate_times(&Parameters { hours, days }, r)?;
+
+    for _ in 0..hours {
+        let candidates: Vec<_> = (0..sheet.entries.len())
+            .filter(|&i| {
+                let entry = &sheet.entries[i];
+                !constrained(entry.day) && entry.time.duration() >= 60
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(Error::Infeasible {
+                hours,
+                days: off_days.len() as u32,
+                max_per_day: parameters.max_per_day,
+            });
+        }
+        let i = candidates[index(r, candidates.len())];
+        let time = &mut sheet.entries[i].time;
+        time.to -= 60;
+        if time.duration() == 0 {
+            sheet.entries.remove(i);
+        }
+    }
+    sheet
+        .entries
+        .extend(times.into_iter().zip(off_days).filter_map(|(time, day)| {
+            Some(Entry {
+                day: day.day_of_month,
+                day_of_week: day.day_of_week,
+                time: time?,
+                surcharge: None,
+                break_minutes: 0,
+                note: None,
+            })
+        }));
+    sheet.entries.sort_by_key(|entry| entry.day);
+    for entry in &mut sheet.entries {
+        let day = DayOfMonth {
+            day_of_week: entry.day_of_week,
+            day_of_month: entry.day,
+        };
+        entry.surcharge = Surcharge::of(&year, &month, &day, holidays);
+    }
+    Ok(())
+}
+
+/// Generates a timesheet of `month` that resembles `template`, like the sheet of the previous
+/// month.
+///
+/// Each working day takes the time of the day of `template` with the same position among the
+/// days of its weekday, the second Tuesday that of the second Tuesday, or else of the last one
+/// before it. The days are lengthened or shortened an hour at a time until they sum up to the
+/// hours of the `parameters`, days off are only worked once the worked days are full. Every start is moved by up to `jitter` minutes in steps of
+/// [`GRANULARITY`], within the window of the `parameters`. Fails if the hours don't fit into the
+/// working days.
+pub fn generate_like<R: Rng + ?Sized>(
+    template: &Timesheet,
+    year: &Year,
+    month: &Month,
+    parameters: SheetParameters,
+    holidays: &dyn HolidaySource,
+    jitter: u32,
+    r: &mut R,
+) -> Result<Timesheet> {
+    let SheetParameters {
+        state,
+        hours,
+        from,
+        to,
+        max_per_day,
+        ..
+    } = parameters;
+    let position = |day: NonZeroU32| (day.get() - 1) / 7;
+    let days = non_holidays_of_month(month, year, holidays);
+    let templates: Vec<_> = days
+        .iter()
+        .map(|day| {
+            template
+                .entries
+                .iter()
+                .filter(|entry| entry.day_of_week == day.day_of_week)
+                .filter(|entry| position(entry.day) <= position(day.day_of_month))
+                .max_by_key(|entry| entry.day)
+                .map(|entry| entry.time)
+        })
+        .collect();
+
+    let mut durations: Vec<_> = templates
+        .iter()
+        .map(|time| time.map_or(0, |time| (time.duration() / 60).min(max_per_day)))
+        .collect();
+    let infeasible = Error::Infeasible {
+        hours,
+        days: days.len() as u32,
+        max_per_day,
+    };
+    let mut total: u32 = durations.iter().sum();
+    while total != hours {
+        let longer = total < hours;
+        let fitting = |worked: bool| -> Vec<_> {
+            (0..durations.len())
+                .filter(|&i| {
+                    if longer {
+                        durations[i] < max_per_day && (!worked || durations[i] > 0)
+                    } else {
+                        durations[i] > 0
+                    }
+                })
+                .collect()
+        };
+        // days off of the template only start to be worked if the worked days are full
+        let mut candidates = fitting(true);
+        if candidates.is_empty() && longer {
+            candidates = fitting(false);
+        }
+        if candidates.is_empty() {
+            return Err(infeasible);
+        }
+        let i = candidates[index(r, candidates.len())];
+        if longer {
+            durations[i] += 1;
+            total += 1;
+        } else {
+            durations[i] -= 1;
+            total -= 1;
+        }
+    }
+
+    let steps = (jitter / GRANULARITY) as i32;
+    let entries = days
+        .iter()
+        .zip(templates)
+        .zip(durations)
+        .filter(|(_, duration)| *duration > 0)
+        .map(|((day, time), duration)| {
+            let duration = duration * 60;
+            let latest = to * 60 - duration;
+            let start = match time {
+                Some(time) => {
+                    let shift = r.gen_range(-steps..=steps) * GRANULARITY as i32;
+                    time.from
+                        .saturating_add_signed(shift)
+                        .clamp(from * 60, latest)
+                }
+                None => r.gen_range(from..=latest / 60) * 60,
+            };
+            Entry {
+                day: day.day_of_month,
+                day_of_week: day.day_of_week,
+                time: TimeSpan {
+                    from: start,
+                    to: start + duration,
+                },
+                surcharge: None,
+                break_minutes: 0,
+                note: None,
+            }
+        })
+        .collect();
+    Ok(Timesheet {
+        year: year.year(),
+        month: month.month(),
+        state,
+        rate: None,
+        timezone: None,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::audit::Audit;
+    use crate::calendar::{non_holidays_of_month, DayOfWeek, Month, Region, State, Year};
+    use crate::constraints::parse_constraints;
+    use crate::error::Error;
+    use crate::generate::TimeSpan;
+    use crate::rng::seeded;
+    use crate::stats::week_of;
+    use crate::timesheet::{
+        avoid_round_weeks, cap_weeks, cluster_starts, generate_constrained_timesheet,
+        generate_jobs, generate_like, generate_timesheet, limit_free_streaks, place_free_days,
+        place_remainder, plan_timesheet, work_off_days, Entry, FreeDays, Remainder,
+        SheetParameters, Strategy, Surcharge, Timesheet, WeeklyCap,
+    };
+
+    #[test]
+    fn test_generate_timesheet() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(2).unwrap(), &year).unwrap();
+        let sheet = generate_timesheet(
+            &year,
+            &month,
+            SheetParameters {
+                state: State::Bavaria,
+                city: None,
+                hours: 40,
+                from: 8,
+                to: 20,
+                max_per_day: 8,
+            },
+            &mut seeded(1),
+        )
+        .unwrap();
+        assert_eq!(sheet.hours(), 40);
+        assert!(sheet
+            .entries
+            .iter()
+            .all(|entry| !entry.day_of_week.is_weekend()));
+    }
+
+    #[test]
+    fn test_check_parameters() {
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 0,
+            to: 24,
+            max_per_day: 24,
+        };
+        assert_eq!(parameters.check(), Ok(()));
+        let latest = SheetParameters {
+            to: 30,
+            ..parameters
+        };
+        assert_eq!(
+            latest.check(),
+            Err(Error::AboveDay {
+                setting: "The latest hour".into(),
+                value: 30
+            })
+        );
+        let max_per_day = SheetParameters {
+            max_per_day: 25,
+            ..parameters
+        };
+        assert!(max_per_day.check().is_err());
+    }
+
+    #[test]
+    fn test_generate_constrained_timesheet() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let constraints = parse_constraints(
+            "4.5.2024,pin\n4.5.2024,cap,2\n6.5.2024,exclude\n7.5.2024,cap,1\n8.5.2024,fill,9:00,13:00\n1.6.2024,exclude",
+        )
+        .unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 100,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        for seed in 0..20 {
+            let sheet = generate_constrained_timesheet(
+                &year,
+                &month,
+                parameters,
+                &constraints,
+                &[],
+                &mut seeded(seed),
+            )
+            .unwrap();
+            assert_eq!(sheet.hours(), 100);
+            let time = |day| {
+                sheet
+                    .entries
+                    .iter()
+                    .find(|entry| entry.day.get() == day)
+                    .map(|entry| entry.time)
+            };
+            assert!(time(4).is_some_and(|time| time.duration() <= 2 * 60));
+            assert_eq!(time(5), None);
+            assert_eq!(time(6), None);
+            assert!(time(7).is_none_or(|time| time.duration() <= 60));
+            assert_eq!(time(8), Some(TimeSpan::hours(9, 13)));
+            assert!(sheet
+                .entries
+                .windows(2)
+                .all(|pair| pair[0].day < pair[1].day));
+        }
+
+        let unconstrained = generate_timesheet(&year, &month, parameters, &mut seeded(1));
+        let other_month = parse_constraints("1.6.2024,exclude").unwrap();
+        let constrained = generate_constrained_timesheet(
+            &year,
+            &month,
+            parameters,
+            &other_month,
+            &[],
+            &mut seeded(1),
+        );
+        assert_eq!(constrained, unconstrained);
+    }
+
+    #[test]
+    fn test_plan_timesheet() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let region = Region::new(State::Bavaria, None).unwrap();
+        let constraints =
+            parse_constraints("4.5.2024,pin\n7.5.2024,cap,3\n8.5.2024,fill,9:00,13:00").unwrap();
+        let blackouts = ["tue=12:00-13:00".parse().unwrap()];
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 100,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let plan =
+            plan_timesheet(&year, &month, parameters, &region, &constraints, &blackouts).unwrap();
+        assert_eq!(plan.hours, 96);
+        assert_eq!(plan.filled.len(), 1);
+        // 19 working days and the pinned Saturday, less the filled day, the other Tuesdays hold 7
+        // hours after the blackout
+        assert_eq!(plan.days.len(), 19);
+        assert_eq!(plan.capacity(), 15 * 8 + 3 * 7 + 3);
+        let text = plan.to_string();
+        assert!(
+            text.starts_with("May 2024: 96 hours onto 19 days holding at most 144, 4:00 filled\n")
+        );
+        for row in [
+            "Wed  1.  not worked\n",
+            "Sat  4.  1-8 hours in 08:00-20:00\n",
+            "Sun  5.  not worked\n",
+            "Tue  7.  0-3 hours in 08:00-20:00 except 12:00-13:00\n",
+            "Wed  8.  fill 09:00-13:00\n",
+        ] {
+            assert!(text.contains(row), "{} is missing from {}", row, text);
+        }
+
+        // executing the plan draws the same times as generating the timesheet at once
+        let sheet = plan.execute(Strategy::Uniform, &mut seeded(1)).unwrap();
+        let generated = generate_constrained_timesheet(
+            &year,
+            &month,
+            parameters,
+            &constraints,
+            &blackouts,
+            &mut seeded(1),
+        )
+        .unwrap();
+        assert_eq!(sheet, generated);
+        assert_eq!(sheet.hours(), 100);
+
+        let full = parse_constraints("8.5.2024,fill,8:00,20:00").unwrap();
+        let few = SheetParameters {
+            hours: 10,
+            ..parameters
+        };
+        assert!(matches!(
+            plan_timesheet(&year, &month, few, &region, &full, &[]),
+            Err(Error::Infeasible { .. })
+        ));
+    }
+
+    #[test]
+    fn test_generate_jobs() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let job = |hours, from, to| SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours,
+            from,
+            to,
+            max_per_day: 6,
+        };
+        let jobs = [
+            (job(60, 8, 16), Vec::new()),
+            (
+                job(40, 10, 20),
+                parse_constraints("2.5.2024,exclude").unwrap(),
+            ),
+        ];
+        let blackouts = ["12:00-13:00".parse().unwrap()];
+        for seed in 0..20 {
+            let sheets =
+                generate_jobs(&year, &month, &jobs, &blackouts, &mut seeded(seed)).unwrap();
+            assert_eq!(sheets[0].hours(), 60);
+            assert_eq!(sheets[1].hours(), 40);
+            assert!(sheets[1].entries.iter().all(|entry| entry.day.get() != 2));
+            assert!(sheets
+                .iter()
+                .flat_map(|sheet| &sheet.entries)
+                .all(|entry| !entry.time.overlaps(&TimeSpan::hours(12, 13))));
+            for a in &sheets[0].entries {
+                for b in sheets[1].entries.iter().filter(|b| b.day == a.day) {
+                    assert!(!a.time.overlaps(&b.time));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_avoid_round_weeks() {
+        // the second and third week of May 2024, always from 9 to 13
+        let days = [
+            DayOfWeek::Monday,
+            DayOfWeek::Tuesday,
+            DayOfWeek::Wednesday,
+            DayOfWeek::Thursday,
+            DayOfWeek::Friday,
+        ];
+        let sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(5).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: (6..11)
+                .chain(13..18)
+                .zip(days.iter().cycle())
+                .map(|(day, &day_of_week)| Entry {
+                    day: NonZeroU32::new(day).unwrap(),
+                    day_of_week,
+                    time: TimeSpan::hours(9, 13),
+                    surcharge: None,
+                    break_minutes: 0,
+                    note: None,
+                })
+                .collect(),
+        };
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let constraints = parse_constraints("6.5.2024,pin").unwrap();
+        let blackouts = ["13:00-14:00".parse().unwrap()];
+        for seed in 0..20 {
+            let mut roughened = sheet.clone();
+            avoid_round_weeks(
+                &mut roughened,
+                &parameters,
+                &constraints,
+                &blackouts,
+                &mut seeded(seed),
+            )
+            .unwrap();
+            assert_eq!(roughened.hours(), 40);
+            assert_eq!(Audit::new(&roughened, 8).round_weeks, 0);
+            assert_eq!(roughened.entries[0], sheet.entries[0]);
+            assert!(roughened.entries.iter().all(|entry| {
+                let time = entry.time;
+                8 * 60 <= time.from
+                    && time.to <= 20 * 60
+                    && time.duration() <= 8 * 60
+                    && !time.overlaps(&TimeSpan::hours(13, 14))
+            }));
+        }
+    }
+
+    #[test]
+    fn test_cap_weeks() {
+        // April 2024 ends on a Tuesday, May starts on a Wednesday and ends on a Friday
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let sheet = |month, days: &[u32]| Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(month).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: days
+                .iter()
+                .map(|&day| Entry {
+                    day: NonZeroU32::new(day).unwrap(),
+                    day_of_week: DayOfWeek::Monday,
+                    time: TimeSpan::hours(8, 16),
+                    surcharge: None,
+                    break_minutes: 0,
+                    note: None,
+                })
+                .collect(),
+        };
+        let april = sheet(4, &[26, 29, 30]);
+        let june = sheet(6, &[1, 3]);
+        let cap = WeeklyCap::new(20 * 60, &month, Some(&april), Some(&june));
+        assert_eq!(
+            cap,
+            WeeklyCap {
+                max: 20 * 60,
+                before: 16 * 60,
+                after: 8 * 60,
+            }
+        );
+        assert_eq!(WeeklyCap::new(60, &month, None, None).before, 0);
+
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 60,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        for seed in 0..20 {
+            let mut rng = seeded(seed);
+            let mut sheet = generate_timesheet(&year, &month, parameters, &mut rng).unwrap();
+            cap_weeks(&mut sheet, &cap, &parameters, &[], &[], &mut rng).unwrap();
+            assert_eq!(sheet.hours(), 60);
+            let mut weeks = [0; 5];
+            for entry in &sheet.entries {
+                weeks[week_of(&month, entry.day.get()) as usize - 1] += entry.time.duration();
+                assert!(entry.time.duration() <= 8 * 60);
+            }
+            assert!(weeks[0] <= 4 * 60);
+            assert!(weeks[1..4].iter().all(|&minutes| minutes <= 20 * 60));
+            assert!(weeks[4] <= 12 * 60);
+        }
+
+        let mut sheet = generate_timesheet(&year, &month, parameters, &mut seeded(0)).unwrap();
+        let cap = WeeklyCap {
+            max: 10 * 60,
+            ..cap
+        };
+        assert!(matches!(
+            cap_weeks(&mut sheet, &cap, &parameters, &[], &[], &mut seeded(0)),
+            Err(Error::WeeklyCapExceeded { cap: 600, .. })
+        ));
+    }
+
+    #[test]
+    fn test_cluster_starts() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 80,
+            from: 7,
+            to: 20,
+            max_per_day: 8,
+        };
+        let constraints = parse_constraints("6.5.2024,pin").unwrap();
+        let blackouts = ["fri=15:00-20:00".parse().unwrap()];
+        for seed in 0..20 {
+            let mut rng = seeded(seed);
+            let sheet = generate_timesheet(&year, &month, parameters, &mut rng).unwrap();
+            let mut clustered = sheet.clone();
+            cluster_starts(
+                &mut clustered,
+                &parameters,
+                &constraints,
+                &blackouts,
+                &mut rng,
+            );
+            let spread = |sheet: &Timesheet| {
+                let starts = sheet.entries.iter().map(|entry| entry.time.from);
+                starts.clone().max().unwrap() - starts.min().unwrap()
+            };
+            assert!(spread(&clustered) <= spread(&sheet));
+            for (a, b) in sheet.entries.iter().zip(&clustered.entries) {
+                assert_eq!(a.time.duration(), b.time.duration());
+                assert!(7 * 60 <= b.time.from && b.time.to <= 20 * 60);
+                if b.day_of_week == DayOfWeek::Friday {
+                    assert!(b.time.to <= 15 * 60 || a == b);
+                }
+            }
+            let pinned = sheet.entries.iter().position(|entry| entry.day.get() == 6);
+            if let Some(i) = pinned {
+                assert_eq!(clustered.entries[i], sheet.entries[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_place_free_days() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let region = Region::new(State::Bavaria, None).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 40,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        // Mondays are the 6th, 13th and 27th, the 20th is Whit Monday
+        let mondays = [6, 13, 27];
+        let blackouts = ["mon=08:00-10:00".parse().unwrap()];
+        let free = |sheet: &Timesheet| {
+            non_holidays_of_month(&month, &year, &region)
+                .into_iter()
+                .map(|day| day.day_of_month.get())
+                .filter(|&day| sheet.entries.iter().all(|entry| entry.day.get() != day))
+                .collect::<Vec<_>>()
+        };
+        for seed in 0..20 {
+            let mut rng = seeded(seed);
+            let sheet = generate_constrained_timesheet(
+                &year,
+                &month,
+                parameters,
+                &[],
+                &blackouts,
+                &mut rng,
+            )
+            .unwrap();
+            for free_days in [
+                FreeDays::Weekday(DayOfWeek::Monday),
+                FreeDays::Random,
+                FreeDays::Spread,
+            ] {
+                let mut placed = sheet.clone();
+                place_free_days(
+                    &mut placed,
+                    free_days,
+                    &parameters,
+                    &region,
+                    &[],
+                    &blackouts,
+                    &mut rng,
+                )
+                .unwrap();
+                assert_eq!(placed.minutes(), sheet.minutes());
+                assert_eq!(free(&placed).len(), free(&sheet).len());
+                assert!(placed.entries.windows(2).all(|w| w[0].day < w[1].day));
+                assert!(placed.entries.iter().all(|entry| {
+                    entry.day_of_week != DayOfWeek::Monday || entry.time.from >= 10 * 60
+                }));
+                if free_days == FreeDays::Weekday(DayOfWeek::Monday) {
+                    let free = free(&placed);
+                    assert!(
+                        free.iter().all(|day| mondays.contains(day))
+                            || mondays.iter().all(|day| free.contains(day))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_limit_free_streaks() {
+        let region = Region::new(State::Bavaria, None).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 24,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let entry = |day, day_of_week, from, to| Entry {
+            day: NonZeroU32::new(day).unwrap(),
+            day_of_week,
+            time: TimeSpan::hours(from, to),
+            surcharge: None,
+            break_minutes: 0,
+            note: None,
+        };
+        let sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(5).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: vec![
+                entry(2, DayOfWeek::Thursday, 13, 20),
+                entry(3, DayOfWeek::Friday, 13, 20),
+                entry(31, DayOfWeek::Friday, 13, 20),
+            ],
+        };
+        // the 13th to the 17th are off
+        let constraints = parse_constraints(
+            "13.5.2024,exclude\n14.5.2024,exclude\n15.5.2024,exclude\n16.5.2024,exclude\n\
+             17.5.2024,exclude",
+        )
+        .unwrap();
+        let blackouts = ["12:00-13:00".parse().unwrap()];
+        for seed in 0..10 {
+            let mut limited = sheet.clone();
+            limit_free_streaks(
+                &mut limited,
+                3,
+                &parameters,
+                &region,
+                &constraints,
+                &blackouts,
+                &mut seeded(seed),
+            )
+            .unwrap();
+            assert_eq!(limited.minutes(), sheet.minutes());
+            let worked: Vec<_> = limited
+                .entries
+                .iter()
+                .map(|entry| entry.day.get())
+                .collect();
+            assert_eq!(worked, [2, 3, 10, 24, 31]);
+            assert!(limited
+                .entries
+                .iter()
+                .all(|entry| !entry.time.overlaps(&TimeSpan::hours(12, 13))));
+        }
+
+        let mut short = Timesheet {
+            entries: vec![entry(2, DayOfWeek::Thursday, 8, 9)],
+            ..sheet
+        };
+        assert_eq!(
+            limit_free_streaks(
+                &mut short,
+                3,
+                &parameters,
+                &region,
+                &[],
+                &[],
+                &mut seeded(0)
+            ),
+            Err(Error::FreeStreakTooLong { days: 18, max: 3 })
+        );
+    }
+
+    #[test]
+    fn test_place_remainder() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 60,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+        let constraints = parse_constraints("31.5.2024,pin").unwrap();
+        let place = |minutes, remainder| {
+            let mut placed = sheet.clone();
+            place_remainder(
+                &mut placed,
+                minutes,
+                remainder,
+                &parameters,
+                &constraints,
+                &[],
+                &mut seeded(0),
+            )
+            .map(|()| placed)
+        };
+        let changed = |placed: &Timesheet| {
+            placed
+                .entries
+                .iter()
+                .zip(&sheet.entries)
+                .filter(|(a, b)| a != b)
+                .map(|(a, _)| a.day.get())
+                .collect::<Vec<_>>()
+        };
+
+        let spread = place(45, Remainder::Spread).unwrap();
+        assert_eq!(spread.minutes(), 60 * 60 + 45);
+        assert_eq!(changed(&spread).len(), 3);
+
+        let last = place(30, Remainder::LastDay).unwrap();
+        assert_eq!(last.minutes(), 60 * 60 + 30);
+        let last_day = sheet
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.day.get() != 31);
+        assert_eq!(changed(&last), [last_day.unwrap().day.get()]);
+
+        let random = place(15, Remainder::RandomDay).unwrap();
+        assert_eq!(random.minutes(), 60 * 60 + 15);
+        assert_eq!(changed(&random).len(), 1);
+        assert!(random.entries.iter().all(|entry| {
+            8 * 60 <= entry.time.from && entry.time.to <= 20 * 60 && entry.time.duration() <= 8 * 60
+        }));
+
+        assert_eq!(
+            place(20, Remainder::Spread),
+            Err(Error::RemainderDoesNotFit { minutes: 20 })
+        );
+    }
+
+    #[test]
+    fn test_work_off_days() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 80,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        // Christi Himmelfahrt on Thursday 9.5. and the weekend of 11.5. are constrained
+        let constraints = parse_constraints("9.5.2024,exclude\n11.5.2024,cap,2").unwrap();
+        for seed in 0..20 {
+            let mut rng = seeded(seed);
+            let mut sheet = generate_constrained_timesheet(
+                &year,
+                &month,
+                parameters,
+                &constraints,
+                &[],
+                &mut rng,
+            )
+            .unwrap();
+            work_off_days(
+                &mut sheet,
+                25,
+                &parameters,
+                &State::Bavaria,
+                &constraints,
+                &[],
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(sheet.hours(), 80);
+            let off: u32 = sheet
+                .entries
+                .iter()
+                .filter(|entry| entry.surcharge.is_some())
+                .map(|entry| entry.time.duration())
+                .sum();
+            assert_eq!(off, 20 * 60);
+            for entry in &sheet.entries {
+                let expected = match (entry.day.get(), entry.day_of_week) {
+                    (1 | 9 | 20 | 30, _) => Some(Surcharge::Holiday),
+                    (_, DayOfWeek::Saturday) => Some(Surcharge::Saturday),
+                    (_, DayOfWeek::Sunday) => Some(Surcharge::Sunday),
+                    _ => None,
+                };
+                assert_eq!(entry.surcharge, expected, "{:?}", entry);
+                assert!(![9, 11].contains(&entry.day.get()));
+                assert!(entry.time.duration() <= 8 * 60);
+            }
+            assert!(sheet
+                .entries
+                .windows(2)
+                .all(|pair| pair[0].day < pair[1].day));
+        }
+    }
+
+    #[test]
+    fn test_generate_like() {
+        let year = Year::new(2024);
+        let february = Month::new(NonZeroU32::new(2).unwrap(), &year).unwrap();
+        let march = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let mut parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 0,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        // four hours from 9:00 on the Mondays and Wednesdays of February
+        let template = Timesheet {
+            year: 2024,
+            month: february.month(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: [5, 7, 12, 14, 19, 21, 26, 28]
+                .into_iter()
+                .map(|day| Entry {
+                    day: NonZeroU32::new(day).unwrap(),
+                    day_of_week: february.day_of_week(day),
+                    time: TimeSpan::hours(9, 13),
+                    surcharge: None,
+                    break_minutes: 0,
+                    note: None,
+                })
+                .collect(),
+        };
+        let mut rng = seeded(0);
+        let worked = |sheet: &Timesheet| {
+            sheet
+                .entries
+                .iter()
+                .all(|entry| matches!(entry.day_of_week, DayOfWeek::Monday | DayOfWeek::Wednesday))
+        };
+
+        parameters.hours = 32;
+        let sheet = generate_like(
+            &template,
+            &year,
+            &march,
+            parameters,
+            &State::Bavaria,
+            0,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(sheet.hours(), 32);
+        assert!(worked(&sheet));
+        assert!(sheet
+            .entries
+            .iter()
+            .all(|entry| entry.time == TimeSpan::hours(9, 13)));
+
+        parameters.hours = 44;
+        let sheet = generate_like(
+            &template,
+            &year,
+            &march,
+            parameters,
+            &State::Bavaria,
+            60,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(sheet.hours(), 44);
+        assert!(worked(&sheet));
+        assert!(sheet
+            .entries
+            .iter()
+            .all(|entry| (8 * 60..=10 * 60).contains(&entry.time.from)));
+
+        parameters.hours = 200;
+        assert_eq!(
+            generate_like(
+                &template,
+                &year,
+                &march,
+                parameters,
+                &State::Bavaria,
+                0,
+                &mut rng
+            ),
+            Err(Error::Infeasible {
+                hours: 200,
+                days: 20,
+                max_per_day: 8
+            })
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! The time zone of the local times of a timesheet.
+//!
+//! The times of a timesheet are local times without a zone. Exports that other programs import
+//! as points in time, the JSON of [`crate::output::Json`] and the requests of `push`, need the
+//! offset to UTC in effect on each day. A [`Timezone`] is either a fixed offset like `+01:00` or
+//! one of the European zones, which switch to summer time at 1:00 UTC on the last Sunday of March
+//! and back at 1:00 UTC on the last Sunday of October. The entries worked during these nights last
+//! an hour less or more than their times say, see [`Timezone::elapsed`].
+//!
+//! These are the rules of the European Union, which all of the zones follow since
+//! [`FIRST_YEAR`]. Earlier years changed on other days or not at all, [`Timezone::check`] rejects
+//! them instead of getting their offsets wrong. Zones outside of Europe can only be given as their
+//! offset.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::calendar::{DayOfWeek, Month, Year};
+use crate::error::{Error, Result};
+use crate::generate::TimeSpan;
+
+/// The first year in which all of the [`ZONES`] switch as the European Union rules.
+pub const FIRST_YEAR: u32 = 1996;
+
+/// The European zones of the IANA database with their standard offsets to UTC in minutes.
+const ZONES: [(&str, i32); 27] = [
+    ("Europe/Amsterdam", 60),
+    ("Europe/Athens", 120),
+    ("Europe/Berlin", 60),
+    ("Europe/Bratislava", 60),
+    ("Europe/Brussels", 60),
+    ("Europe/Bucharest", 120),
+    ("Europe/Budapest", 60),
+    ("Europe/Copenhagen", 60),
+    ("Europe/Dublin", 0),
+    ("Europe/Helsinki", 120),
+    ("Europe/Lisbon", 0),
+    ("Europe/Ljubljana", 60),
+    ("Europe/London", 0),
+    ("Europe/Luxembourg", 60),
+    ("Europe/Madrid", 60),
+    ("Europe/Oslo", 60),
+    ("Europe/Paris", 60),
+    ("Europe/Prague", 60),
+    ("Europe/Riga", 120),
+    ("Europe/Rome", 60),
+    ("Europe/Sofia", 120),
+    ("Europe/Stockholm", 60),
+    ("Europe/Tallinn", 120),
+    ("Europe/Vienna", 60),
+    ("Europe/Vilnius", 120),
+    ("Europe/Warsaw", 60),
+    ("Europe/Zurich", 60),
+];
+
+/// The zone of the local times, written as `Europe/Berlin`, `+01:00` or `Z`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Timezone {
+    /// A fixed offset to UTC in minutes without daylight saving time.
+    Fixed(i32),
+    /// A European zone with its standard offset to UTC in minutes, an hour more in summer.
+    European {
+        /// The IANA name like `Europe/Berlin`.
+        name: &'static str,
+        /// The offset to UTC in winter.
+        standard: i32,
+    },
+}
+
+impl Default for Timezone {
+    /// UTC.
+    fn default() -> Self {
+        Timezone::Fixed(0)
+    }
+}
+
+/// The day of the year of `day` in `month`, starting at 0.
+fn day_of_year(year: &Year, month: NonZeroU32, day: u32) -> i64 {
+    let before: u32 = (1..month.get())
+        .filter_map(NonZeroU32::new)
+        .map(|month| year.days_of_month(month))
+        .sum();
+    i64::from(before + day - 1)
+}
+
+/// The minute of the year at which summer time starts or ends in `month`, 1:00 UTC on its last
+/// Sunday.
+fn transition(year: &Year, month: u32) -> i64 {
+    let month = NonZeroU32::new(month).expect("transitions are in March and October");
+    let days = Month::new(month, year).expect("March and October exist");
+    let sunday = (1..=days.num_days())
+        .rev()
+        .find(|&day| days.day_of_week(day) == DayOfWeek::Sunday)
+        .expect("every month has a Sunday");
+    day_of_year(year, month, sunday) * 1440 + 60
+}
+
+impl Timezone {
+    /// The IANA name of the zone, `None` for fixed offsets.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Timezone::Fixed(_) => None,
+            Timezone::European { name, .. } => Some(name),
+        }
+    }
+
+    /// Fails for a European zone before [`FIRST_YEAR`], whose offsets aren't known.
+    pub fn check(&self, year: u32) -> Result<()> {
+        match *self {
+            Timezone::European { name, .. } if year < FIRST_YEAR => Err(Error::ZoneBeforeRules {
+                zone: name.into(),
+                year,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The offset to UTC in minutes at the local time `minute` minutes after midnight on `day` of
+    /// `month` in `year`.
+    ///
+    /// The hour skipped in March has the offset of winter, the hour repeated in October the one of
+    /// summer. Years before [`FIRST_YEAR`] follow the same rules, see [`Timezone::check`].
+    pub fn offset(&self, year: u32, month: NonZeroU32, day: u32, minute: u32) -> i32 {
+        let standard = match *self {
+            Timezone::Fixed(offset) => return offset,
+            Timezone::European { standard, .. } => standard,
+        };
+        let year = Year::new(year);
+        let local = day_of_year(&year, month, day) * 1440 + i64::from(minute);
+        let summer = local - i64::from(standard) - 60;
+        if (transition(&year, 3)..transition(&year, 10)).contains(&summer) {
+            standard + 60
+        } else {
+            standard
+        }
+    }
+
+    /// The minutes that pass during `time` on `day` of `month` in `year`, an hour less than on the
+    /// clock if it spans the change to summer time and an hour more if it spans the change back.
+    pub fn elapsed(&self, year: u32, month: NonZeroU32, day: u32, time: TimeSpan) -> u32 {
+        let utc =
+            |minute: u32| i64::from(minute) - i64::from(self.offset(year, month, day, minute));
+        u32::try_from(utc(time.to) - utc(time.from)).unwrap_or_default()
+    }
+
+    /// The local time `minute` minutes after midnight on `day` of `month` in `year` as an RFC 3339
+    /// timestamp like `2024-03-04T09:00:00+01:00`. Midnight at the end of a day is written as the
+    /// start of the next.
+    pub fn timestamp(&self, year: u32, month: NonZeroU32, day: u32, minute: u32) -> String {
+        let offset = self.offset(year, month, day, minute);
+        let (mut year, mut month, mut day, mut minute) = (year, month.get(), day, minute);
+        if minute >= 1440 {
+            minute -= 1440;
+            day += 1;
+            let days =
+                Year::new(year).days_of_month(NonZeroU32::new(month).expect("months start at 1"));
+            if day > days {
+                day = 1;
+                month += 1;
+                if month > 12 {
+                    month = 1;
+                    year += 1;
+                }
+            }
+        }
+        format!(
+            "{}-{:02}-{:02}T{:02}:{:02}:00{}",
+            year,
+            month,
+            day,
+            minute / 60,
+            minute % 60,
+            Offset(offset)
+        )
+    }
+}
+
+/// An offset to UTC in minutes as `Z` or `+01:00`.
+struct Offset(i32);
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => f.write_str("Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Timezone::Fixed(offset) => Offset(offset).fmt(f),
+            Timezone::European { name, .. } => f.write_str(name),
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "`{}` isn't a time zone, expected a European zone like `Europe/Berlin` or an \
+                 offset like `+01:00`",
+                s
+            )
+        };
+        if ["Z", "UTC", "Etc/UTC"]
+            .iter()
+            .any(|utc| utc.eq_ignore_ascii_case(s))
+        {
+            return Ok(Timezone::Fixed(0));
+        }
+        if let Some(&(name, standard)) = ZONES.iter().find(|(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(Timezone::European { name, standard });
+        }
+        let (sign, offset) = match s.as_bytes().first() {
+            Some(b'+') => (1, &s[1..]),
+            Some(b'-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = offset.split_once(':').ok_or_else(invalid)?;
+        if hours.len() != 2 || minutes.len() != 2 {
+            return Err(invalid());
+        }
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+        Ok(Timezone::Fixed(sign * (hours * 60 + minutes)))
+    }
+}
+
+impl Serialize for Timezone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timezone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A string like `Europe/Berlin` or `+01:00`.
+impl JsonSchema for Timezone {
+    fn schema_name() -> Cow<'static, str> {
+        "Timezone".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "The zone of the local times like `Europe/Berlin` or a fixed offset to UTC like `+01:00`.",
+            "type": "string",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::error::Error;
+    use crate::generate::TimeSpan;
+    use crate::timezone::Timezone;
+
+    #[test]
+    fn test_timezone() {
+        let berlin: Timezone = "Europe/Berlin".parse().unwrap();
+        assert_eq!(berlin.name(), Some("Europe/Berlin"));
+        assert_eq!(berlin.to_string(), "Europe/Berlin");
+        assert_eq!("europe/berlin".parse(), Ok(berlin));
+        assert_eq!("+05:30".parse(), Ok(Timezone::Fixed(330)));
+        assert_eq!("-03:00".parse::<Timezone>().unwrap().to_string(), "-03:00");
+        assert_eq!("UTC".parse(), Ok(Timezone::Fixed(0)));
+        assert!("Mars/Olympus".parse::<Timezone>().is_err());
+        assert!("+1:00".parse::<Timezone>().is_err());
+        assert!("+01:60".parse::<Timezone>().is_err());
+        assert!("America/New_York".parse::<Timezone>().is_err());
+        assert_eq!(berlin.check(1996), Ok(()));
+        assert_eq!(
+            berlin.check(1995),
+            Err(Error::ZoneBeforeRules {
+                zone: "Europe/Berlin".into(),
+                year: 1995
+            })
+        );
+        assert_eq!(Timezone::Fixed(60).check(1990), Ok(()));
+
+        let month = |month| NonZeroU32::new(month).unwrap();
+        let timestamp = |number, day, minute| berlin.timestamp(2024, month(number), day, minute);
+        assert_eq!(timestamp(3, 4, 9 * 60), "2024-03-04T09:00:00+01:00");
+        assert_eq!(timestamp(7, 1, 9 * 60), "2024-07-01T09:00:00+02:00");
+        // summer time starts on 31.3.2024 at 2:00 and ends on 27.10.2024 at 3:00
+        assert_eq!(timestamp(3, 31, 60 + 59), "2024-03-31T01:59:00+01:00");
+        assert_eq!(timestamp(3, 31, 3 * 60), "2024-03-31T03:00:00+02:00");
+        assert_eq!(timestamp(10, 27, 2 * 60 + 30), "2024-10-27T02:30:00+02:00");
+        assert_eq!(timestamp(10, 27, 3 * 60), "2024-10-27T03:00:00+01:00");
+        assert_eq!(timestamp(4, 30, 24 * 60), "2024-05-01T00:00:00+02:00");
+        assert_eq!(
+            Timezone::Fixed(0).timestamp(2023, month(12), 31, 24 * 60),
+            "2024-01-01T00:00:00Z"
+        );
+
+        let london: Timezone = "Europe/London".parse().unwrap();
+        assert_eq!(london.offset(2024, month(3), 31, 59), 0);
+        assert_eq!(london.offset(2024, month(3), 31, 2 * 60), 60);
+        assert_eq!(serde_json::to_string(&london).unwrap(), "\"Europe/London\"");
+    }
+
+    #[test]
+    fn test_elapsed() {
+        let berlin: Timezone = "Europe/Berlin".parse().unwrap();
+        let month = |month| NonZeroU32::new(month).unwrap();
+        let elapsed = |number, day, from, to| {
+            berlin.elapsed(2024, month(number), day, TimeSpan::hours(from, to))
+        };
+        // the nights of 30./31.3.2024 and 26./27.10.2024
+        assert_eq!(elapsed(3, 31, 0, 6), 5 * 60);
+        assert_eq!(elapsed(3, 31, 3, 6), 3 * 60);
+        assert_eq!(elapsed(3, 31, 0, 2), 2 * 60);
+        assert_eq!(elapsed(10, 27, 0, 6), 7 * 60);
+        assert_eq!(elapsed(10, 27, 3, 6), 3 * 60);
+        assert_eq!(elapsed(10, 27, 22, 24), 2 * 60);
+        // the weekends before and after
+        assert_eq!(elapsed(3, 24, 0, 6), 6 * 60);
+        assert_eq!(elapsed(4, 7, 0, 6), 6 * 60);
+        assert_eq!(elapsed(10, 20, 0, 6), 6 * 60);
+        // 30.3.2025 and 26.10.2025
+        let span = TimeSpan::hours(1, 4);
+        assert_eq!(berlin.elapsed(2025, month(3), 30, span), 2 * 60);
+        assert_eq!(berlin.elapsed(2025, month(10), 26, span), 4 * 60);
+        assert_eq!(
+            Timezone::Fixed(60).elapsed(2025, month(10), 26, span),
+            3 * 60
+        );
+        // London changes at 1:00 local time
+        let london: Timezone = "Europe/London".parse().unwrap();
+        assert_eq!(
+            london.elapsed(2024, month(3), 31, TimeSpan::hours(0, 3)),
+            2 * 60
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Traces the random decisions made while generating a timesheet.
+//!
+//! `generate --trace` writes a line per decision to stderr or a file: how the hours were split onto
+//! the days, which start each day drew among the positions its window and blackouts leave, and
+//! which attempts of the passes afterwards were rejected. Tracing is off until [`start`] sets a
+//! sink for the current thread, so an untraced decision costs a single check.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+thread_local! {
+    static SINK: RefCell<Option<Box<dyn Write>>> = const { RefCell::new(None) };
+}
+
+/// Writes the decisions made on this thread into `sink` until [`stop`], a line each.
+pub fn start(sink: Box<dyn Write>) {
+    SINK.with(|current| *current.borrow_mut() = Some(sink));
+}
+
+/// Stops tracing on this thread and flushes the sink.
+pub fn stop() {
+    if let Some(mut sink) = SINK.with(|current| current.borrow_mut().take()) {
+        let _ = sink.flush();
+    }
+}
+
+/// Whether decisions are traced on this thread, to skip preparing the messages otherwise.
+pub fn enabled() -> bool {
+    SINK.with(|current| current.borrow().is_some())
+}
+
+/// Writes `message` as a line into the sink of this thread, if there is one. Failing to write
+/// doesn't fail generating, the line is lost.
+pub fn record(message: fmt::Arguments) {
+    SINK.with(|current| {
+        if let Some(sink) = current.borrow_mut().as_mut() {
+            let _ = writeln!(sink, "{}", message);
+        }
+    });
+}
+
+/// Records a decision like [`format!`] if tracing is on.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::trace::enabled() {
+            $crate::trace::record(format_args!($($arg)*))
+        }
+    };
+}
+pub(crate) use trace;
+
+/// A sink appending to a shared buffer.
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `f` with tracing into a string and returns what it returned together with the trace.
+/// Replaces a sink set with [`start`] and stops tracing afterwards.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let buffer = Rc::new(RefCell::new(Vec::new()));
+    start(Box::new(Buffer(buffer.clone())));
+    let result = f();
+    stop();
+    let trace = String::from_utf8(buffer.take()).expect("traces are UTF-8");
+    (result, trace)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::trace::{capture, enabled};
+
+    #[test]
+    fn test_capture() {
+        assert!(!enabled());
+        trace!("lost");
+        let (result, trace) = capture(|| {
+            assert!(enabled());
+            trace!("day {}: {} hours", 2, 6);
+            trace!("done");
+            42
+        });
+        assert_eq!(result, 42);
+        assert_eq!(trace, "day 2: 6 hours\ndone\n");
+        assert!(!enabled());
+    }
+}
@@ -0,0 +1,165 @@
+//! Checks whether a newer release than the running binary is published on GitHub.
+//!
+//! Nothing is downloaded or replaced, `self check-update` only names the newer version and the page
+//! of its release. The releases of a fork or mirror are checked by setting [`URL_VARIABLE`] to the
+//! URL of its latest release in the GitHub API.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// The latest release of this repository in the GitHub API.
+pub const RELEASES: &str = "https://api.github.com/repos/jwiesler/stundenzettel-rs/releases/latest";
+/// The environment variable overriding [`RELEASES`].
+pub const URL_VARIABLE: &str = "STUNDENZETTEL_RELEASES_URL";
+
+/// The reasons checking for a newer release can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateError {
+    /// GitHub couldn't be reached or has no release.
+    #[error("Fetching the latest release failed: {0}")]
+    Http(#[from] ureq::Error),
+    /// The answer of GitHub isn't a release.
+    #[error("The latest release can't be read: {0}")]
+    Parse(#[from] serde_json::Error),
+    /// The tag of the release isn't a version.
+    #[error("`{0}` isn't a version like `v1.2.3`")]
+    InvalidVersion(String),
+}
+
+/// A version like `1.2.3`, ordered by its numbers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Version {
+    /// Incompatible changes.
+    pub major: u32,
+    /// Added features.
+    pub minor: u32,
+    /// Fixes.
+    pub patch: u32,
+}
+
+impl Version {
+    /// The version of the running binary.
+    pub fn current() -> Self {
+        env!("CARGO_PKG_VERSION")
+            .parse()
+            .expect("the package version is a version")
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = UpdateError;
+
+    /// Reads tags like `v1.2.3` as well, a suffix like `-rc.1` or `+build` is ignored.
+    fn from_str(s: &str) -> Result<Self, UpdateError> {
+        let invalid = || UpdateError::InvalidVersion(s.into());
+        let version = s.trim().strip_prefix('v').unwrap_or(s.trim());
+        let version = version.split(['-', '+']).next().unwrap_or_default();
+        let numbers: Vec<u32> = version
+            .split('.')
+            .map(|number| number.parse().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        let [major, minor, patch] = numbers[..] else {
+            return Err(invalid());
+        };
+        Ok(Version {
+            major,
+            minor,
+            patch,
+        })
+    }
+}
+
+/// A release as returned by the API.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct Release {
+    /// The tag like `v1.2.3`.
+    pub tag_name: String,
+    /// The page of the release with its binaries.
+    pub html_url: String,
+}
+
+impl Release {
+    /// Reads the answer of the API.
+    pub fn parse(json: &str) -> Result<Self, UpdateError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The latest release at [`RELEASES`] or the URL in [`URL_VARIABLE`].
+    pub fn fetch_latest() -> Result<Self, UpdateError> {
+        let url = env::var(URL_VARIABLE).unwrap_or_else(|_| RELEASES.into());
+        let json = ureq::get(url)
+            .header("Accept", "application/vnd.github+json")
+            .call()?
+            .body_mut()
+            .read_to_string()?;
+        Self::parse(&json)
+    }
+
+    /// The version of the release if it is newer than `current`.
+    pub fn newer_than(&self, current: Version) -> Result<Option<Version>, UpdateError> {
+        let version: Version = self.tag_name.parse()?;
+        Ok((version > current).then_some(version))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::update::{Release, UpdateError, Version};
+
+    fn version(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!("1.2.3".parse::<Version>().unwrap(), version(1, 2, 3));
+        assert_eq!("v0.10.0".parse::<Version>().unwrap(), version(0, 10, 0));
+        assert_eq!("v2.0.0-rc.1".parse::<Version>().unwrap(), version(2, 0, 0));
+        assert_eq!(version(0, 10, 0).to_string(), "0.10.0");
+        assert!(version(0, 10, 0) > version(0, 9, 12));
+        assert!(version(1, 0, 0) > version(0, 99, 99));
+        for invalid in ["", "v1.2", "1.2.3.4", "latest", "v1.x.0"] {
+            assert!(matches!(
+                invalid.parse::<Version>(),
+                Err(UpdateError::InvalidVersion(_))
+            ));
+        }
+        assert_eq!(Version::current().to_string(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_release() {
+        let json = r#"{
+            "tag_name": "v0.3.1",
+            "name": "0.3.1",
+            "html_url": "https://github.com/jwiesler/stundenzettel-rs/releases/tag/v0.3.1",
+            "prerelease": false,
+            "assets": []
+        }"#;
+        let release = Release::parse(json).unwrap();
+        assert_eq!(release.tag_name, "v0.3.1");
+        assert_eq!(
+            release.newer_than(version(0, 3, 0)).unwrap(),
+            Some(version(0, 3, 1))
+        );
+        assert_eq!(release.newer_than(version(0, 3, 1)).unwrap(), None);
+        assert_eq!(release.newer_than(version(1, 0, 0)).unwrap(), None);
+        assert!(matches!(
+            Release::parse(r#"{ "message": "Not Found" }"#),
+            Err(UpdateError::Parse(_))
+        ));
+    }
+}
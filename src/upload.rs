@@ -0,0 +1,141 @@
+//! Uploads rendered timesheets to a WebDAV share such as a Nextcloud folder.
+//!
+//! `webdav://host/path/` is uploaded to via HTTPS, `http://` and `https://` URLs are used as they
+//! are. A URL ending in `/` is a folder, the timesheet is stored in it as
+//! `stundenzettel-<year>-<month>.<extension>`. PDF and XLSX output isn't supported, the timesheet
+//! is uploaded in one of the [`Format`]s.
+
+use std::env;
+
+use base64::Engine;
+
+use crate::config::WebdavConfig;
+use crate::output::Format;
+use crate::timesheet::Timesheet;
+
+/// The environment variable overriding [`WebdavConfig::username`].
+pub const USERNAME_VARIABLE: &str = "STUNDENZETTEL_WEBDAV_USERNAME";
+/// The environment variable overriding [`WebdavConfig::password`].
+pub const PASSWORD_VARIABLE: &str = "STUNDENZETTEL_WEBDAV_PASSWORD";
+
+/// The reasons uploading a timesheet can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    /// The URL has an unsupported scheme.
+    #[error("`{0}` isn't a webdav://, http:// or https:// URL")]
+    InvalidUrl(String),
+    /// The server couldn't be reached or rejected the upload.
+    #[error("Uploading failed: {0}")]
+    Http(#[from] ureq::Error),
+}
+
+/// The user and password to log in with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Credentials {
+    /// The user name.
+    pub username: String,
+    /// The password or app password.
+    pub password: String,
+}
+
+impl Credentials {
+    /// The credentials of `config`, each overridden by its environment variable if set.
+    ///
+    /// `None` if there is no user name.
+    pub fn resolve(config: Option<&WebdavConfig>) -> Option<Self> {
+        let username = env::var(USERNAME_VARIABLE)
+            .ok()
+            .or_else(|| config?.username.clone())?;
+        let password = env::var(PASSWORD_VARIABLE)
+            .ok()
+            .or_else(|| config?.password.clone())
+            .unwrap_or_default();
+        Some(Credentials { username, password })
+    }
+
+    fn header(&self) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", self.username, self.password));
+        format!("Basic {}", encoded)
+    }
+}
+
+/// The HTTP URL `sheet` is uploaded to for `url`.
+pub fn target(url: &str, sheet: &Timesheet, format: Format) -> Result<String, UploadError> {
+    let url = if let Some(rest) = url.strip_prefix("webdav://") {
+        format!("https://{}", rest)
+    } else if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        return Err(UploadError::InvalidUrl(url.to_string()));
+    };
+    if url.ends_with('/') {
+        Ok(format!(
+            "{}stundenzettel-{}-{:02}.{}",
+            url,
+            sheet.year,
+            sheet.month,
+            format.extension()
+        ))
+    } else {
+        Ok(url)
+    }
+}
+
+/// Uploads `sheet` rendered as `format` to `url` with a `PUT`, replacing an existing file.
+pub fn upload(
+    url: &str,
+    credentials: Option<&Credentials>,
+    sheet: &Timesheet,
+    format: Format,
+) -> Result<String, UploadError> {
+    let target = target(url, sheet, format)?;
+    let mut request = ureq::put(&target).header("Content-Type", format.content_type());
+    if let Some(credentials) = credentials {
+        request = request.header("Authorization", credentials.header());
+    }
+    request.send(format.formatter().render(sheet))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{Month, State, Year};
+    use crate::output::Format;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, SheetParameters};
+    use crate::upload::{target, Credentials};
+
+    #[test]
+    fn test_target() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(3).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 20,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let sheet = generate_timesheet(&year, &month, parameters, &mut seeded(1)).unwrap();
+
+        assert_eq!(
+            target("webdav://cloud.example.com/dav/HR/", &sheet, Format::Csv).unwrap(),
+            "https://cloud.example.com/dav/HR/stundenzettel-2024-03.csv"
+        );
+        assert_eq!(
+            target("http://localhost/sheet.json", &sheet, Format::Json).unwrap(),
+            "http://localhost/sheet.json"
+        );
+        assert!(target("ftp://localhost/", &sheet, Format::Csv).is_err());
+
+        let credentials = Credentials {
+            username: "Aladdin".to_string(),
+            password: "open sesame".to_string(),
+        };
+        assert_eq!(credentials.header(), "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+    }
+}
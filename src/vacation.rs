@@ -0,0 +1,186 @@
+//! A yearly leave budget, kept in the state file `~/.local/share/stundenzettel/state.json` next to
+//! the archive.
+//!
+//! Recorded days of leave are excluded when generating their month, see
+//! [`StateFile::constraints`]. The state file also continues the numbers of the invoices of
+//! [`crate::invoice`].
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{DateOfYear, Month, State, Year};
+use crate::constraints::{Constraint, DateConstraint};
+use crate::error::Result;
+use crate::paths::Paths;
+
+/// The leave of one year.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    /// The number of days of leave.
+    pub entitlement: u32,
+    /// The days taken, in order.
+    pub taken: Vec<DateOfYear>,
+}
+
+fn key(date: &DateOfYear) -> (u32, u32) {
+    (date.month.get(), date.day.get())
+}
+
+impl Budget {
+    /// The days of the entitlement that aren't taken yet, negative if more days were taken.
+    pub fn remaining(&self) -> i64 {
+        self.entitlement as i64 - self.taken.len() as i64
+    }
+
+    /// Records `days` as taken and returns the number of days that weren't recorded before.
+    pub fn take(&mut self, days: impl IntoIterator<Item = DateOfYear>) -> usize {
+        let before = self.taken.len();
+        for day in days {
+            if let Err(index) = self.taken.binary_search_by_key(&key(&day), key) {
+                self.taken.insert(index, day);
+            }
+        }
+        self.taken.len() - before
+    }
+
+    /// Removes the recorded days from `from` until `until` and returns their number.
+    pub fn cancel(&mut self, from: DateOfYear, until: DateOfYear) -> usize {
+        let before = self.taken.len();
+        self.taken
+            .retain(|day| !(key(&from)..=key(&until)).contains(&key(day)));
+        before - self.taken.len()
+    }
+}
+
+/// The working days in `state` from `from` until `until`, both inclusive.
+pub fn working_days(
+    year: &Year,
+    state: State,
+    from: DateOfYear,
+    until: DateOfYear,
+) -> Result<Vec<DateOfYear>> {
+    let holidays = year.holidays(state);
+    let mut days = Vec::new();
+    let mut day = from;
+    // checks `from` for validity even if `until` is before it
+    day.add_days(0, year.is_leap())?;
+    while key(&day) <= key(&until) {
+        let month = Month::new(day.month, year)?;
+        let weekend = month.day_of_week(day.day.get()).is_weekend();
+        if !weekend && !holidays.iter().any(|holiday| holiday.date == day) {
+            days.push(day);
+        }
+        match day.add_days(1, year.is_leap()) {
+            Ok(next) => day = next,
+            Err(_) => break,
+        }
+    }
+    Ok(days)
+}
+
+/// Everything the CLI remembers between runs.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateFile {
+    /// The leave budgets by year.
+    pub vacation: BTreeMap<u32, Budget>,
+    /// The last invoice number of each year, see [`StateFile::next_invoice_number`].
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub invoices: BTreeMap<u32, u32>,
+}
+
+impl StateFile {
+    /// `~/.local/share/stundenzettel/state.json` or [`Paths::state`] on this platform, `None` if
+    /// the home directory is unknown.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(Paths::from_environment()?.state())
+    }
+
+    /// Loads the state in `path`, a missing file is an empty state.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(input) => serde_json::from_str(&input)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stores the state in `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json + "\n")
+    }
+
+    /// Advances the sequence of the invoices of `year` and returns the new number like
+    /// `2024-001`.
+    pub fn next_invoice_number(&mut self, year: u32) -> String {
+        let number = self.invoices.entry(year).or_default();
+        *number += 1;
+        format!("{}-{:03}", year, number)
+    }
+
+    /// Excludes the recorded days of leave in `month` of `year`.
+    pub fn constraints(&self, year: u32, month: &Month) -> Vec<DateConstraint> {
+        self.vacation
+            .get(&year)
+            .into_iter()
+            .flat_map(|budget| &budget.taken)
+            .filter(|day| day.month == month.month())
+            .map(|day| DateConstraint {
+                year,
+                month: day.month,
+                day: day.day,
+                constraint: Constraint::Exclude,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::calendar::{DateOfYear, State, Year};
+    use crate::vacation::{working_days, Budget, StateFile};
+
+    fn date(day: u32, month: u32) -> DateOfYear {
+        DateOfYear::new_checked(day, month).unwrap()
+    }
+
+    #[test]
+    fn test_budget() {
+        let year = Year::new(2024);
+        // Thursday before Easter until the Wednesday after, Good Friday and Easter Monday are
+        // holidays
+        let days = working_days(&year, State::Bavaria, date(28, 3), date(3, 4)).unwrap();
+        assert_eq!(days, vec![date(28, 3), date(2, 4), date(3, 4)]);
+        assert_eq!(
+            working_days(&year, State::Bavaria, date(30, 12), date(31, 12)).unwrap(),
+            vec![date(30, 12), date(31, 12)]
+        );
+        assert!(working_days(&year, State::Bavaria, date(30, 2), date(1, 3)).is_err());
+
+        let mut budget = Budget {
+            entitlement: 30,
+            taken: vec![],
+        };
+        assert_eq!(budget.take(days), 3);
+        assert_eq!(budget.take([date(2, 4), date(2, 1)]), 1);
+        assert_eq!(budget.remaining(), 26);
+        assert_eq!(budget.taken[0], date(2, 1));
+        assert_eq!(budget.cancel(date(1, 4), date(30, 4)), 2);
+        assert_eq!(budget.taken, vec![date(2, 1), date(28, 3)]);
+
+        let mut state = StateFile::default();
+        assert_eq!(state.next_invoice_number(2024), "2024-001");
+        assert_eq!(state.next_invoice_number(2024), "2024-002");
+        assert_eq!(state.next_invoice_number(2025), "2025-001");
+        assert_eq!(state.invoices[&2024], 2);
+    }
+}
@@ -0,0 +1,238 @@
+//! Checks that a timesheet satisfies the constraints it was generated with.
+
+use std::num::NonZeroU32;
+
+use crate::calendar::{non_holidays_of_month, DayOfMonth, Month, Region, Year};
+use crate::timesheet::{SheetParameters, Surcharge, Timesheet};
+
+/// A constraint that a [`Timesheet`] doesn't satisfy.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum Violation {
+    /// The month of the timesheet doesn't exist.
+    #[error("`{0}` isn't a month")]
+    InvalidMonth(NonZeroU32),
+    /// The entries without their breaks don't add up to the requested hours.
+    #[error("The entries add up to {actual} hours instead of {expected}")]
+    Hours {
+        /// The requested hours.
+        expected: u32,
+        /// The sum of all entries without their breaks.
+        actual: u32,
+    },
+    /// An entry doesn't start before it stops.
+    #[error("The entry of the {day}. is empty")]
+    Empty {
+        /// The day of the entry.
+        day: NonZeroU32,
+    },
+    /// An entry without its break is longer than allowed.
+    #[error("The entry of the {day}. is {hours} hours long, at most {max_per_day} are allowed")]
+    TooLong {
+        /// The day of the entry.
+        day: NonZeroU32,
+        /// The length of the entry.
+        hours: u32,
+        /// The maximum number of hours per day.
+        max_per_day: u32,
+    },
+    /// An entry starts too early or stops too late.
+    #[error("The entry of the {day}. is outside of {from}:00-{to}:00")]
+    OutsideWindow {
+        /// The day of the entry.
+        day: NonZeroU32,
+        /// The earliest starting hour.
+        from: u32,
+        /// The latest stopping hour.
+        to: u32,
+    },
+    /// An entry is on a weekend or a holiday without the matching surcharge or doesn't exist in the
+    /// month.
+    #[error("The {day}. isn't a working day")]
+    NotAWorkingDay {
+        /// The day of the entry.
+        day: NonZeroU32,
+    },
+    /// The entries aren't ordered by day or a day has more than one entry.
+    #[error("The entry of the {day}. isn't after the previous one")]
+    Unordered {
+        /// The day of the entry.
+        day: NonZeroU32,
+    },
+}
+
+/// Checks `sheet` against `parameters`, returning every violated constraint.
+///
+/// The hours and the maximum per day apply to the time worked, without the breaks recorded on the
+/// entries. The window applies to the time present.
+pub fn verify(sheet: &Timesheet, parameters: &SheetParameters) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let year = Year::new(sheet.year);
+    let Ok(month) = Month::new(sheet.month, &year) else {
+        return vec![Violation::InvalidMonth(sheet.month)];
+    };
+    let region = Region {
+        state: parameters.state,
+        city: parameters.city,
+    };
+    let working_days = non_holidays_of_month(&month, &year, &region);
+
+    let actual = sheet.net_minutes() / 60;
+    if actual != parameters.hours {
+        violations.push(Violation::Hours {
+            expected: parameters.hours,
+            actual,
+        });
+    }
+
+    let mut previous = None;
+    for entry in &sheet.entries {
+        let day = entry.day;
+        let time = entry.time;
+        if previous.is_some_and(|previous| previous >= day) {
+            violations.push(Violation::Unordered { day });
+        }
+        previous = Some(day);
+
+        if time.to <= time.from {
+            violations.push(Violation::Empty { day });
+        } else if sheet.net_minutes_of(entry) > parameters.max_per_day * 60 {
+            violations.push(Violation::TooLong {
+                day,
+                hours: sheet.net_minutes_of(entry).div_ceil(60),
+                max_per_day: parameters.max_per_day,
+            });
+        }
+        if time.from < parameters.from * 60 || time.to > parameters.to * 60 {
+            violations.push(Violation::OutsideWindow {
+                day,
+                from: parameters.from,
+                to: parameters.to,
+            });
+        }
+        let working_day = working_days
+            .iter()
+            .find(|working_day| working_day.day_of_month == day);
+        let surcharged = entry.surcharge.is_some()
+            && day.get() <= month.num_days()
+            && month.day_of_week(day.get()) == entry.day_of_week
+            && entry.surcharge
+                == Surcharge::of(
+                    &year,
+                    &month,
+                    &DayOfMonth {
+                        day_of_week: entry.day_of_week,
+                        day_of_month: day,
+                    },
+                    &region,
+                );
+        if !surcharged
+            && working_day.is_none_or(|working_day| working_day.day_of_week != entry.day_of_week)
+        {
+            violations.push(Violation::NotAWorkingDay { day });
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use proptest::prelude::*;
+
+    use crate::breaks::{add_breaks, BreakRule};
+    use crate::calendar::{non_holidays_of_month, Month, State, Year};
+    use crate::error::Error;
+    use crate::rng::seeded;
+    use crate::timesheet::{generate_timesheet, work_off_days, SheetParameters, Surcharge};
+    use crate::verify::{verify, Violation};
+
+    fn parameters() -> impl Strategy<Value = (u32, NonZeroU32, SheetParameters)> {
+        let state = prop::sample::select(State::ALL.to_vec());
+        (1970..2100u32, 1..=12u32, state, 0..24u32, 1..=24u32)
+            .prop_filter("window", |(_, _, _, from, to)| from < to)
+            .prop_flat_map(|(year, month, state, from, to)| {
+                let month = NonZeroU32::new(month).unwrap();
+                let days = {
+                    let year = Year::new(year);
+                    let month = Month::new(month, &year).unwrap();
+                    non_holidays_of_month(&month, &year, &state).len() as u32
+                };
+                (1..=to - from).prop_flat_map(move |max_per_day| {
+                    (0..=days * max_per_day).prop_map(move |hours| {
+                        let parameters = SheetParameters {
+                            state,
+                            city: None,
+                            hours,
+                            from,
+                            to,
+                            max_per_day,
+                        };
+                        (year, month, parameters)
+                    })
+                })
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_generated_sheets_verify((year, month, parameters) in parameters(), seed: u64) {
+            let year = Year::new(year);
+            let month = Month::new(month, &year).unwrap();
+            let mut sheet = generate_timesheet(&year, &month, parameters, &mut seeded(seed)).unwrap();
+            prop_assert_eq!(verify(&sheet, &parameters), vec![]);
+
+            let rules = BreakRule::STATUTORY;
+            match add_breaks(&mut sheet, &rules, &parameters, &[], &[]) {
+                Ok(()) => {
+                    prop_assert_eq!(verify(&sheet, &parameters), vec![]);
+                    prop_assert_eq!(sheet.net_minutes(), parameters.hours * 60);
+                    for entry in &sheet.entries {
+                        let due = BreakRule::due(&rules, sheet.gross_minutes_of(entry));
+                        prop_assert_eq!(entry.break_minutes, due);
+                    }
+                }
+                Err(e) => prop_assert!(matches!(e, Error::BreakDoesNotFit { .. }), "{}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_surcharges() {
+        let year = Year::new(2024);
+        let month = Month::new(NonZeroU32::new(5).unwrap(), &year).unwrap();
+        let parameters = SheetParameters {
+            state: State::Bavaria,
+            city: None,
+            hours: 80,
+            from: 8,
+            to: 20,
+            max_per_day: 8,
+        };
+        let mut rng = seeded(1);
+        let mut sheet = generate_timesheet(&year, &month, parameters, &mut rng).unwrap();
+        work_off_days(
+            &mut sheet,
+            25,
+            &parameters,
+            &State::Bavaria,
+            &[],
+            &[],
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(verify(&sheet, &parameters), vec![]);
+
+        let entry = sheet
+            .entries
+            .iter_mut()
+            .find(|entry| entry.surcharge == Some(Surcharge::Holiday))
+            .unwrap();
+        entry.surcharge = Some(Surcharge::Sunday);
+        let day = entry.day;
+        assert_eq!(
+            verify(&sheet, &parameters),
+            vec![Violation::NotAWorkingDay { day }]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Warnings about patterns in timesheets that payroll departments often ask about.
+//!
+//! Unlike the [`crate::audit`] of how plausible a timesheet looks, each [`Warning`] points at
+//! something that may be perfectly legitimate but raises questions, like a long day. `generate`
+//! prints them to stderr, every [`WarningKind`] can be silenced with the `[warnings]` section of
+//! the [`crate::config`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::NonZeroU32;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{DateOfYear, Month, Year};
+use crate::stats::week_of;
+use crate::timesheet::Timesheet;
+
+/// Days with more minutes worked than this, nine hours, are warned about.
+pub const LONG_DAY: u32 = 9 * 60;
+
+/// Weeks with more minutes worked than this, 40 hours, are warned about.
+pub const LONG_WEEK: u32 = 40 * 60;
+
+/// The percentage of the worked days as long as allowed above which they are warned about.
+pub const MAXIMAL_DAYS: u32 = 80;
+
+/// The days usually worked only until noon, Christmas Eve and New Year's Eve.
+const HALF_DAYS: [(u32, u32); 2] = [(24, 12), (31, 12)];
+
+/// What a [`Warning`] is about, the names silence it in the configuration.
+///
+/// ```toml
+/// [warnings]
+/// silence = ["half_days", "long_weeks"]
+/// ```
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// More than [`LONG_DAY`] minutes on a day.
+    LongDays,
+    /// Work on Christmas Eve or New Year's Eve.
+    HalfDays,
+    /// More than [`MAXIMAL_DAYS`] percent of the days as long as allowed.
+    MaximalDays,
+    /// More than [`LONG_WEEK`] minutes in a week.
+    LongWeeks,
+}
+
+/// A pattern of a timesheet that may be questioned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Warning {
+    /// A day with more than [`LONG_DAY`] minutes.
+    LongDay {
+        /// The day of the month.
+        day: NonZeroU32,
+        /// The minutes worked without breaks.
+        minutes: u32,
+    },
+    /// Work on a half-day.
+    HalfDay {
+        /// The day of the month.
+        day: NonZeroU32,
+    },
+    /// More than [`MAXIMAL_DAYS`] percent of the worked days are as long as allowed.
+    MaximalDays {
+        /// The percentage of the worked days, rounded down.
+        percent: u32,
+        /// The maximum hours per day.
+        max_per_day: u32,
+    },
+    /// A week with more than [`LONG_WEEK`] minutes.
+    LongWeek {
+        /// The week within the month, starting at 1.
+        week: u32,
+        /// The minutes worked in the days of the week within the month without breaks.
+        minutes: u32,
+    },
+}
+
+impl Warning {
+    /// What the warning is about.
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            Warning::LongDay { .. } => WarningKind::LongDays,
+            Warning::HalfDay { .. } => WarningKind::HalfDays,
+            Warning::MaximalDays { .. } => WarningKind::MaximalDays,
+            Warning::LongWeek { .. } => WarningKind::LongWeeks,
+        }
+    }
+}
+
+/// `minutes` like `9:30`.
+fn clock(minutes: u32) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::LongDay { day, minutes } => write!(
+                f,
+                "The {}. has {} hours of work, more than {} hours a day",
+                day,
+                clock(*minutes),
+                LONG_DAY / 60
+            ),
+            Warning::HalfDay { day } => {
+                write!(f, "The {}. is worked, it is usually a half-day", day)
+            }
+            Warning::MaximalDays {
+                percent,
+                max_per_day,
+            } => write!(
+                f,
+                "{}% of the worked days have {} hours, the most allowed",
+                percent, max_per_day
+            ),
+            Warning::LongWeek { week, minutes } => write!(
+                f,
+                "Week {} has {} hours of work, more than {} hours a week",
+                week,
+                clock(*minutes),
+                LONG_WEEK / 60
+            ),
+        }
+    }
+}
+
+/// The warnings about `sheet`, which was generated with at most `max_per_day` hours per day,
+/// without those of the `silenced` kinds. The times are counted without breaks.
+pub fn warnings(sheet: &Timesheet, max_per_day: u32, silenced: &[WarningKind]) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for entry in &sheet.entries {
+        let minutes = sheet.net_minutes_of(entry);
+        if minutes > LONG_DAY {
+            warnings.push(Warning::LongDay {
+                day: entry.day,
+                minutes,
+            });
+        }
+        let date = DateOfYear::new(entry.day, sheet.month);
+        if HALF_DAYS
+            .iter()
+            .any(|&(day, month)| DateOfYear::new_checked(day, month) == Some(date))
+        {
+            warnings.push(Warning::HalfDay { day: entry.day });
+        }
+    }
+
+    let maximal = sheet
+        .entries
+        .iter()
+        .filter(|entry| entry.time.duration() >= max_per_day * 60)
+        .count();
+    let percent = (maximal * 100)
+        .checked_div(sheet.entries.len())
+        .unwrap_or_default() as u32;
+    if percent > MAXIMAL_DAYS {
+        warnings.push(Warning::MaximalDays {
+            percent,
+            max_per_day,
+        });
+    }
+
+    if let Ok(month) = Month::new(sheet.month, &Year::new(sheet.year)) {
+        let mut weeks = BTreeMap::new();
+        for entry in &sheet.entries {
+            *weeks.entry(week_of(&month, entry.day.get())).or_insert(0) +=
+                sheet.net_minutes_of(entry);
+        }
+        warnings.extend(
+            weeks
+                .into_iter()
+                .filter(|&(_, minutes)| minutes > LONG_WEEK)
+                .map(|(week, minutes)| Warning::LongWeek { week, minutes }),
+        );
+    }
+
+    warnings.retain(|warning| !silenced.contains(&warning.kind()));
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use crate::calendar::{DayOfWeek, State};
+    use crate::generate::TimeSpan;
+    use crate::timesheet::{Entry, Timesheet};
+    use crate::warnings::{warnings, Warning, WarningKind};
+
+    #[test]
+    fn test_warnings() {
+        let entry = |day, from, to| Entry {
+            day: NonZeroU32::new(day).unwrap(),
+            day_of_week: DayOfWeek::Monday,
+            time: TimeSpan::hours(from, to),
+            surcharge: None,
+            break_minutes: 0,
+            note: None,
+        };
+        // the 2. to the 6.12.2024 are a week from Monday to Friday
+        let mut sheet = Timesheet {
+            year: 2024,
+            month: NonZeroU32::new(12).unwrap(),
+            state: State::Bavaria,
+            rate: None,
+            timezone: None,
+            entries: vec![
+                entry(2, 7, 17),
+                entry(3, 8, 17),
+                entry(4, 8, 17),
+                entry(5, 8, 17),
+                entry(6, 8, 13),
+                entry(24, 8, 12),
+            ],
+        };
+        let day = |day| NonZeroU32::new(day).unwrap();
+        assert_eq!(
+            warnings(&sheet, 10, &[]),
+            [
+                Warning::LongDay {
+                    day: day(2),
+                    minutes: 600
+                },
+                Warning::HalfDay { day: day(24) },
+                Warning::LongWeek {
+                    week: 2,
+                    minutes: 42 * 60
+                },
+            ]
+        );
+        sheet.entries.pop();
+        sheet.entries[0].break_minutes = 60;
+        sheet.entries[4].time = TimeSpan::hours(8, 17);
+        assert_eq!(
+            warnings(&sheet, 9, &[WarningKind::LongWeeks]),
+            [Warning::MaximalDays {
+                percent: 100,
+                max_per_day: 9
+            }]
+        );
+        assert_eq!(
+            warnings(
+                &sheet,
+                9,
+                &[WarningKind::LongWeeks, WarningKind::MaximalDays]
+            ),
+            []
+        );
+        assert_eq!(
+            Warning::LongWeek {
+                week: 2,
+                minutes: 42 * 60 + 30
+            }
+            .to_string(),
+            "Week 2 has 42:30 hours of work, more than 40 hours a week"
+        );
+    }
+}
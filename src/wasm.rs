@@ -0,0 +1,63 @@
+//! JavaScript bindings for generating timesheets in the browser.
+//!
+//! There is no operating system randomness on `wasm32-unknown-unknown`, so every call takes the
+//! seed of the generator. A front end can pass e.g. `BigInt(Date.now())`.
+
+use std::num::NonZeroU32;
+
+use wasm_bindgen::prelude::*;
+
+use crate::calendar::{Month, State, Year};
+use crate::error::Error;
+use crate::rng::seeded;
+use crate::timesheet::{generate_timesheet, SheetParameters};
+
+fn month_of(month: u32, year: &Year) -> Result<Month, Error> {
+    let month = NonZeroU32::new(month).ok_or(Error::InvalidMonth(month))?;
+    Month::new(month, year)
+}
+
+/// Generates the timesheet of `month` in `year` for the state with the abbreviation `state`, see
+/// [`generate_timesheet`](crate::timesheet::generate_timesheet).
+///
+/// Returns the timesheet as a plain object or throws an `Error` if the constraints can't be met.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn generate(
+    month: u32,
+    year: u32,
+    hours: u32,
+    max_per_day: u32,
+    earliest: u32,
+    latest: u32,
+    state: &str,
+    seed: u64,
+) -> Result<JsValue, JsError> {
+    let state: State = state.parse()?;
+    let year = Year::new(year);
+    let month = month_of(month, &year)?;
+    let sheet = generate_timesheet(
+        &year,
+        &month,
+        SheetParameters {
+            state,
+            city: None,
+            hours,
+            from: earliest,
+            to: latest,
+            max_per_day,
+        },
+        &mut seeded(seed),
+    )?;
+    Ok(serde_wasm_bindgen::to_value(&sheet)?)
+}
+
+/// The public holidays of `year` in `state` as an array of `{ date: { day, month }, name }`
+/// objects.
+#[wasm_bindgen]
+pub fn holidays(year: u32, state: &str) -> Result<JsValue, JsError> {
+    let state: State = state.parse()?;
+    Ok(serde_wasm_bindgen::to_value(
+        &Year::new(year).holidays(state),
+    )?)
+}
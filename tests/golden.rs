@@ -0,0 +1,2488 @@
+//! Runs the CLI with fixed seeds and compares its output against the files in `tests/golden`.
+//!
+//! Run with `UPDATE_GOLDEN=1` to rewrite the golden files after a deliberate change.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// `(golden file, arguments)`
+const CASES: [(&str, &[&str]); 11] = [
+    (
+        "2024-02-by",
+        &["generate", "2", "2024", "40", "--seed", "1"],
+    ),
+    (
+        "2024-05-nw-csv",
+        &[
+            "generate", "5", "2024", "60", "--state", "NW", "--seed", "2", "--csv",
+        ],
+    ),
+    (
+        "2023-10-sn-markdown",
+        &[
+            "generate", "10", "2023", "30", "--state", "SN", "--seed", "3", "--format", "markdown",
+        ],
+    ),
+    (
+        "2023-12-be-json",
+        &[
+            "generate", "12", "2023", "90", "6", "9", "17", "--state", "BE", "--seed", "4",
+            "--format", "json",
+        ],
+    ),
+    (
+        "2024-03-th-dense",
+        &[
+            "generate", "3", "2024", "150", "--state", "TH", "--seed", "5",
+        ],
+    ),
+    (
+        "2024-05-by-constraints",
+        &[
+            "generate",
+            "5",
+            "2024",
+            "80",
+            "--seed",
+            "6",
+            "--constraints",
+            "tests/golden/2024-05-constraints.csv",
+        ],
+    ),
+    (
+        "2024-03-by-weekly",
+        &[
+            "generate", "3", "2024", "40", "--seed", "1", "--layout", "weekly",
+        ],
+    ),
+    (
+        "2024-05-by-calendar",
+        &[
+            "generate", "5", "2024", "40", "--seed", "3", "--layout", "calendar",
+        ],
+    ),
+    (
+        "2024-03-by-pauses",
+        &[
+            "generate", "3", "2024", "120", "--seed", "3", "--break", "6h=30m", "--layout",
+            "pauses",
+        ],
+    ),
+    (
+        "2024-03-by-pauses-csv",
+        &[
+            "generate", "3", "2024", "120", "--seed", "3", "--break", "6h=30m", "--break",
+            "9h=45m", "--layout", "pauses", "--csv",
+        ],
+    ),
+    (
+        "2024-03-smooth-csv",
+        &["smooth", "tests/golden/2024-03-punches.csv", "--csv"],
+    ),
+];
+
+/// The binary with `home` as the home directory and without the XDG directories of the
+/// environment, so that it neither reads nor writes the files of whoever runs the tests.
+fn stundenzettel(home: &Path) -> Command {
+    let mut command = Command::cargo_bin("stundenzettel").unwrap();
+    command
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_DATA_HOME")
+        .env_remove("XDG_CACHE_HOME");
+    command
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", name))
+}
+
+#[test]
+fn test_golden_sheets() {
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+    let home = tempfile::tempdir().unwrap();
+    for (name, arguments) in CASES {
+        let output = stundenzettel(home.path())
+            .env("SOURCE_DATE_EPOCH", "1714521600")
+            .args(arguments)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let path = golden_path(name);
+        if update {
+            fs::write(&path, &output).unwrap();
+        } else {
+            let expected = fs::read_to_string(&path).unwrap();
+            assert_eq!(output, expected, "{} differs from {}", name, path.display());
+        }
+    }
+}
+
+#[test]
+fn test_infeasible_exit_code() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "--error-format",
+            "json",
+            "generate",
+            "2",
+            "2024",
+            "200",
+            "--no-archive",
+        ])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicates::str::contains(r#""code":"infeasible""#));
+}
+
+#[test]
+fn test_json_errors() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        let mut command = stundenzettel(home.path());
+        command.args(["--error-format", "json"]).args(args);
+        command
+    };
+    for (args, code, exit_code) in [
+        (
+            &["generate", "5", "2024", "40", "--realism-profile", "house"][..],
+            "unknown_profile",
+            4,
+        ),
+        (&["generate", "5", "2024"], "missing_setting", 4),
+        (
+            &["generate", "5", "2024", "40", "--activities", "balanced"],
+            "missing_setting",
+            4,
+        ),
+        (
+            &["generate", "5", "2024", "40", "--rng", "os", "--seed", "1"],
+            "seed_without_seeded",
+            2,
+        ),
+        (
+            &["generate", "5", "2024", "40", "--rng", "seeded"],
+            "missing_seed",
+            2,
+        ),
+        (&["key", "public"], "missing_setting", 4),
+        (&["generate", "--reproduce", "{"][..], "parse", 4),
+    ] {
+        let assert = run(args).assert().code(exit_code).stdout("");
+        let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+        let error: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+        assert_eq!(error["code"], code, "{:?}", args);
+    }
+}
+
+#[test]
+fn test_exit_codes() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        let mut command = stundenzettel(home.path());
+        command.args(args);
+        command
+    };
+    // invalid arguments and constraints that can't be met
+    run(&["generate", "13", "2024", "40"]).assert().code(2);
+    run(&["generate", "2", "2024", "200", "--no-archive"])
+        .assert()
+        .code(2);
+    // reading failed
+    run(&[
+        "generate",
+        "2",
+        "2024",
+        "40",
+        "--constraints",
+        "missing.csv",
+    ])
+    .assert()
+    .code(3);
+    // a timesheet that doesn't parse, given or archived
+    run(&["validate", "-", "40"])
+        .write_stdin("no timesheet\n")
+        .assert()
+        .code(4);
+    let archived = home.path().join(".local/share/stundenzettel/2024/05.json");
+    fs::create_dir_all(archived.parent().unwrap()).unwrap();
+    fs::write(&archived, "{").unwrap();
+    run(&["--error-format", "json", "report", "2024", "--target", "30"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(r#""code":"invalid_data""#));
+    fs::remove_file(&archived).unwrap();
+    // a configuration that doesn't parse, for every command
+    let config = home.path().join(".config/stundenzettel/config.toml");
+    fs::create_dir_all(config.parent().unwrap()).unwrap();
+    fs::write(&config, "[contract\n").unwrap();
+    run(&["generate", "2", "2024", "40", "--no-archive"])
+        .assert()
+        .code(4);
+    run(&["config", "show"]).assert().code(4);
+    run(&["config", "validate"]).assert().code(4);
+}
+
+#[test]
+fn test_archive() {
+    let home = tempfile::tempdir().unwrap();
+    for arguments in [
+        ["generate", "3", "2024", "40"],
+        ["generate", "12", "2023", "40"],
+    ] {
+        stundenzettel(home.path())
+            .args(arguments)
+            .assert()
+            .success();
+    }
+    stundenzettel(home.path())
+        .args(["generate", "4", "2024", "40", "--no-archive"])
+        .assert()
+        .success();
+
+    let archive = home.path().join(".local/share/stundenzettel");
+    let output = stundenzettel(home.path())
+        .arg("list")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let expected = format!(
+        "2023-12  {}\n2024-03  {}\n",
+        archive.join("2023/12.json").display(),
+        archive.join("2024/03.json").display()
+    );
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+    let output = stundenzettel(home.path())
+        .args(["report", "2024", "--target", "30", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["hours"], 40);
+    assert_eq!(report["remaining"], 320);
+    assert_eq!(report["months"][2]["balance"], 10);
+    assert_eq!(report["months"][3]["hours"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_if_missing() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = || {
+        stundenzettel(home.path())
+            .args(["generate", "3", "2024", "40", "--if-missing"])
+            .assert()
+            .success()
+    };
+    generate().stdout(predicates::str::is_empty().not());
+    generate().stdout("");
+}
+
+#[test]
+fn test_check_only() {
+    let home = tempfile::tempdir().unwrap();
+    let fills = home.path().join("fills.csv");
+    fs::write(&fills, "4.3.2024,fill,6:00,9:00\n").unwrap();
+    let check = |arguments: &[&str]| {
+        stundenzettel(home.path())
+            .args(["generate", "3", "2024", "--seed", "1", "--check-only"])
+            .args(arguments)
+            .assert()
+            .stdout("")
+    };
+    check(&["40"])
+        .success()
+        .stderr(predicate::str::contains("Reproduce").not());
+    assert!(!home.path().join(".local").exists());
+    check(&["400"]).code(2);
+    check(&["40", "--constraints", fills.to_str().unwrap()])
+        .code(4)
+        .stderr("The entry of the 4. is outside of 8:00-20:00\n");
+}
+
+#[test]
+fn test_stdin() {
+    let home = tempfile::tempdir().unwrap();
+    let expected = fs::read_to_string(golden_path("2024-02-by")).unwrap();
+    stundenzettel(home.path())
+        .env("SOURCE_DATE_EPOCH", "1714521600")
+        .args(["generate", "--stdin"])
+        .write_stdin(r#"{ "month": 2, "year": 2024, "hours": 40, "seed": 1 }"#)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    stundenzettel(home.path())
+        .args([
+            "--error-format",
+            "json",
+            "generate",
+            "--stdin",
+            "--no-archive",
+        ])
+        .write_stdin(r#"{ "month": 2 }"#)
+        .assert()
+        .code(4)
+        .stderr(predicates::str::contains(r#""code":"parse""#));
+
+    // the bounds of the options apply to the JSON as well
+    stundenzettel(home.path())
+        .args(["generate", "--stdin", "--no-archive"])
+        .write_stdin(r#"{ "month": 2, "year": 2024, "hours": 40, "latest": 30 }"#)
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "The latest hour has to be at most 24, not 30",
+        ));
+}
+
+#[test]
+fn test_contract() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = || {
+        stundenzettel(home.path())
+            .args([
+                "generate",
+                "3",
+                "2024",
+                "--no-archive",
+                "--seed",
+                "1",
+                "--csv",
+                "--no-header",
+            ])
+            .assert()
+    };
+    generate().code(4);
+
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[contract]\nweekly_hours = 20\nstate = \"NW\"\nstart = 2024-03-15\n",
+    )
+    .unwrap();
+    let output = generate().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let mut hours = 0;
+    for line in output.lines() {
+        let [date, from, to] = line.split(',').collect::<Vec<_>>()[..] else {
+            panic!("`{}` isn't a CSV row", line);
+        };
+        let day: u32 = date.split('.').next().unwrap().parse().unwrap();
+        assert!(day >= 15, "{} is before the contract", date);
+        let hour = |time: &str| time.trim_end_matches(":00").parse::<u32>().unwrap();
+        hours += hour(to) - hour(from);
+    }
+    assert_eq!(hours, 40);
+}
+
+#[test]
+fn test_contract_from() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "3",
+            "2024",
+            "160",
+            "--state",
+            "NW",
+            "--contract-from",
+            "15.03.2024",
+            "--no-archive",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let sheet = stundenzettel::parser::parse_json(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(sheet.hours(), 80);
+    assert!(sheet.entries.iter().all(|entry| entry.day.get() >= 15));
+}
+
+#[test]
+fn test_jobs() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = || {
+        stundenzettel(home.path())
+            .args(["generate", "5", "2024", "--jobs", "--seed", "1", "--csv"])
+            .assert()
+    };
+    generate().code(4);
+
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[jobs]]\nemployer = \"Lehrstuhl\"\nweekly_hours = 10\nlatest = 14\n\n\
+         [[jobs]]\nweekly_hours = 20\nearliest = 10\n",
+    )
+    .unwrap();
+    let output = generate()
+        .success()
+        .stderr(predicate::str::contains("Total").not())
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let (first, second) = output.split_once("\n\n").unwrap();
+    assert!(first.starts_with("Lehrstuhl\n"));
+    assert!(second.starts_with("Job 2\n"));
+    assert!(!home.path().join(".local/share/stundenzettel/2024").exists());
+
+    let weekly = stundenzettel(home.path())
+        .args(["generate", "5", "2024", "--jobs", "--seed", "1", "--csv"])
+        .args(["--layout", "weekly"])
+        .assert()
+        .success();
+    let weekly = String::from_utf8(weekly.get_output().stdout.clone()).unwrap();
+    let rows: Vec<_> = weekly.lines().collect();
+    assert_eq!(rows[0], "Week,Project,Mon,Tue,Wed,Thu,Fri,Sat,Sun,Total");
+    assert!(rows[1].starts_with("2024-W18,\"Lehrstuhl\","));
+    assert!(rows[2].starts_with("2024-W18,\"Job 2\","));
+    // the column names and both jobs in each of the five weeks of May
+    assert_eq!(rows.len(), 1 + 2 * 5);
+
+    // 38 hours at 15.50 €, the second job has no rate
+    fs::write(
+        config.join("config.toml"),
+        "[[jobs]]\nemployer = \"Lehrstuhl\"\nweekly_hours = 10\nlatest = 14\nrate = 15.5\n\n\
+         [[jobs]]\nweekly_hours = 20\nearliest = 10\n",
+    )
+    .unwrap();
+    generate()
+        .success()
+        .stderr(predicate::str::contains("Lehrstuhl"))
+        .stderr(predicate::str::is_match(r"Job 2 +76\.00 +- +-\n").unwrap())
+        .stderr(predicate::str::is_match(r"Total +114\.00 +589\.00 €\n").unwrap());
+}
+
+#[test]
+fn test_blackout() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "60",
+            "--blackout",
+            "12:00-13:30",
+            "--blackout",
+            "wed=8:00-12:00",
+            "--seed",
+            "1",
+            "--no-archive",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let sheet = stundenzettel::parser::parse_json(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(sheet.hours(), 60);
+    for entry in &sheet.entries {
+        assert!(entry.time.to <= 12 * 60 || 14 * 60 <= entry.time.from);
+        if entry.day_of_week == stundenzettel::calendar::DayOfWeek::Wednesday {
+            assert!(entry.time.from >= 14 * 60);
+        }
+    }
+}
+
+#[test]
+fn test_window() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "100",
+            "--window",
+            "fri=8-14",
+            "--window",
+            "mon=6-12",
+            "--seed",
+            "2",
+            "--no-archive",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let sheet = stundenzettel::parser::parse_json(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(sheet.hours(), 100);
+    for entry in &sheet.entries {
+        let (from, to) = match entry.day_of_week {
+            stundenzettel::calendar::DayOfWeek::Monday => (6, 12),
+            stundenzettel::calendar::DayOfWeek::Friday => (8, 14),
+            _ => (8, 20),
+        };
+        assert!(from * 60 <= entry.time.from && entry.time.to <= to * 60);
+    }
+}
+
+#[test]
+fn test_strict() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |strict: bool| {
+        let mut command = stundenzettel(home.path());
+        command.args([
+            "generate",
+            "5",
+            "2024",
+            "60",
+            "8",
+            "8",
+            "14",
+            "--no-archive",
+        ]);
+        if strict {
+            command.arg("--strict");
+        }
+        command.assert()
+    };
+    generate(false)
+        .success()
+        .stdout(predicates::str::contains("Reducing").not())
+        .stderr(predicates::str::contains(
+            "warning: Reducing max hours per day from 8 to 6",
+        ));
+    generate(true)
+        .code(2)
+        .stderr(predicates::str::contains("Can't fit 8 hours per day"));
+}
+
+#[test]
+fn test_stats() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate", "5", "2024", "60", "--stats", "50", "--format", "json",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stats: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(stats["samples"], 50);
+    assert_eq!(stats["weekdays"][0]["day_of_week"], "Monday");
+    assert_eq!(stats["weeks"].as_array().unwrap().len(), 5);
+    assert!(!home.path().join(".local/share/stundenzettel").exists());
+}
+
+#[test]
+fn test_simulate() {
+    let home = tempfile::tempdir().unwrap();
+    let simulate = |hours: &str| {
+        let output = stundenzettel(home.path())
+            .args([
+                "simulate", "5", "2024", hours, "--runs", "50", "--format", "json",
+            ])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+    };
+    let simulation = simulate("60");
+    assert_eq!(simulation["runs"], 50);
+    assert_eq!(simulation["failed"], 0);
+    let runs: u64 = simulation["longest_day"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|bucket| bucket["runs"].as_u64().unwrap())
+        .sum();
+    assert_eq!(runs, 50);
+
+    let simulation = simulate("200");
+    assert_eq!(simulation["failed"], 50);
+    assert_eq!(simulation["worked_days"], serde_json::json!([]));
+}
+
+#[test]
+fn test_audit() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |max_score: &str| {
+        stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "60",
+                "--seed",
+                "3",
+                "--audit",
+                "--max-score",
+                max_score,
+                "--no-archive",
+            ])
+            .assert()
+    };
+    generate("5")
+        .success()
+        .stdout(predicates::str::contains("Score").not())
+        .stderr(predicates::str::contains("Score"));
+    generate("0")
+        .code(2)
+        .stderr(predicates::str::contains("No timesheet of 100 attempts"));
+}
+
+#[test]
+fn test_avoid_round_weeks() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "60",
+            "--seed",
+            "3",
+            "--avoid-round-weeks",
+            "--audit",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Round weeks       0%"));
+}
+
+#[test]
+fn test_max_per_week() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |args: &[&str]| {
+        stundenzettel(home.path())
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .clone()
+    };
+    let minutes = |sheet: &serde_json::Value, days: std::ops::RangeInclusive<u64>| {
+        sheet["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|entry| days.contains(&entry["day"].as_u64().unwrap()))
+            .map(|entry| {
+                let time = |key: &str| {
+                    let (hours, minutes) = entry["time"][key]
+                        .as_str()
+                        .unwrap()
+                        .split_once(':')
+                        .unwrap();
+                    hours.parse::<u64>().unwrap() * 60 + minutes.parse::<u64>().unwrap()
+                };
+                time("to") - time("from")
+            })
+            .sum::<u64>()
+    };
+    // the week of 29.4. to 5.5.2024 straddles April and May
+    let april = generate(&[
+        "generate", "4", "2024", "160", "--seed", "1", "--format", "json",
+    ]);
+    let april: serde_json::Value = serde_json::from_slice(&april.stdout).unwrap();
+    let before = minutes(&april, 29..=30);
+    assert!(before > 0);
+    let may = generate(&[
+        "generate",
+        "5",
+        "2024",
+        "60",
+        "--seed",
+        "1",
+        "--max-per-week",
+        "20",
+        "--format",
+        "json",
+        "--no-archive",
+    ]);
+    assert!(String::from_utf8(may.stderr).unwrap().contains(&format!(
+        r#""weekly_cap":{{"max":1200,"before":{}}}"#,
+        before
+    )));
+    let may: serde_json::Value = serde_json::from_slice(&may.stdout).unwrap();
+    assert!(before + minutes(&may, 1..=5) <= 20 * 60);
+    for week in [6..=12, 13..=19, 20..=26, 27..=31] {
+        assert!(minutes(&may, week) <= 20 * 60);
+    }
+}
+
+#[test]
+fn test_realism() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "100",
+            "--seed",
+            "1",
+            "--realism",
+            "standard",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("break 0:30"))
+        .stderr(predicates::str::contains(
+            r#""blackouts":["mon=08:00-09:00","fri=15:00-20:00"],"cluster_starts":true"#,
+        ));
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "100",
+            "--seed",
+            "1",
+            "--realism",
+            "standard",
+            "--window",
+            "fri=8-13",
+            "--no-breaks",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("break").not())
+        .stderr(predicates::str::contains(
+            r#""blackouts":["mon=08:00-09:00","fri=13:00-20:00"]"#,
+        ));
+
+    // the days are lengthened by their breaks, the time worked stays at the requested hours
+    for seed in ["1", "2", "3"] {
+        let output = stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "100",
+                "--seed",
+                seed,
+                "--realism",
+                "standard",
+                "--csv",
+                "--no-header",
+                "--no-archive",
+            ])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let minutes = |column: &str| {
+            let (hours, minutes) = column.split_once(':').unwrap();
+            hours.parse::<u32>().unwrap() * 60 + minutes.parse::<u32>().unwrap()
+        };
+        let (mut gross, mut net) = (0, 0);
+        for line in output.lines() {
+            let [.., present, _, worked] = line.split(',').collect::<Vec<_>>()[..] else {
+                panic!("`{}` has no breaks", line);
+            };
+            gross += minutes(present);
+            net += minutes(worked);
+        }
+        assert_eq!(net, 100 * 60, "seed {}", seed);
+        assert!(gross > net, "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_realism_profile() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |args: &[&str]| {
+        stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "100",
+                "--seed",
+                "1",
+                "--no-archive",
+            ])
+            .args(args)
+            .assert()
+    };
+    generate(&["--realism-profile", "house"])
+        .code(4)
+        .stderr(predicate::str::contains(
+            "No `[realism.house]` profile in the configuration",
+        ));
+
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[realism.house]\nweights = [3, 3, 2, 2, 1]\nwindows = [\"fri=8-14\"]\n\
+         cluster_starts = true\nbreaks = [{ after = 360, deduct = 30 }]\n",
+    )
+    .unwrap();
+    generate(&["--realism-profile", "house"])
+        .success()
+        .stdout(predicate::str::contains("break 0:30"))
+        .stderr(predicate::str::contains(
+            r#""blackouts":["fri=14:00-20:00"],"cluster_starts":true,"distribution":{"weighted":[3,3,2,2,1,0,0]},"breaks":[{"after":360,"deduct":30}]"#,
+        ));
+    generate(&[
+        "--realism-profile",
+        "house",
+        "--window",
+        "fri=8-12",
+        "--distribution",
+        "even",
+        "--no-breaks",
+    ])
+    .success()
+    .stdout(predicate::str::contains("break").not())
+    .stderr(predicate::str::contains(
+        r#""blackouts":["fri=12:00-20:00"],"cluster_starts":true,"distribution":"even"}"#,
+    ));
+}
+
+#[test]
+fn test_free_days() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |free_days: &str| {
+        stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "40",
+                "--seed",
+                "3",
+                "--free-days",
+                free_days,
+                "--no-archive",
+            ])
+            .assert()
+    };
+    let output = generate("mon")
+        .success()
+        .stderr(predicate::str::contains(
+            r#""free_days":{"weekday":"Monday"}"#,
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    for monday in ["\n6.5.2024", "\n13.5.2024", "\n27.5.2024"] {
+        assert!(!output.contains(monday));
+    }
+    generate("spread")
+        .success()
+        .stderr(predicate::str::contains(r#""free_days":"spread""#));
+    generate("sat").code(2).stderr(predicate::str::contains(
+        "isn't a weekday from `mon` to `fri`, `random` or `spread`",
+    ));
+}
+
+#[test]
+fn test_max_free_streak() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |hours: &str| {
+        stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                hours,
+                "--seed",
+                "3",
+                "--max-free-streak",
+                "2",
+                "--no-archive",
+            ])
+            .assert()
+    };
+    // without the limit the 2nd, 3rd and 6th are free
+    generate("20")
+        .success()
+        .stdout(predicate::str::contains("\n6.5.2024"))
+        .stderr(predicate::str::contains(r#""max_free_streak":2"#));
+    generate("2")
+        .code(2)
+        .stderr(predicate::str::contains("No day can spare an hour for"));
+}
+
+#[test]
+fn test_remainder() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "37:30",
+            "--seed",
+            "1",
+            "--remainder",
+            "last-day",
+            "--format",
+            "csv",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(":30"));
+    stundenzettel(home.path())
+        .args(["generate", "5", "2024", "37:20", "--no-archive"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("multiple of 15"));
+}
+
+#[test]
+fn test_min_per_day() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "80",
+            "--seed",
+            "1",
+            "--window",
+            "fri=8-10",
+            "--min-per-day",
+            "3",
+            "--format",
+            "csv",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("10.5.2024").not())
+        .stdout(predicates::str::contains("17.5.2024").not());
+}
+
+#[test]
+fn test_reproduce() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        let output = stundenzettel(home.path())
+            .env("SOURCE_DATE_EPOCH", "1714521600")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    let (sheet, log) = run(&[
+        "generate",
+        "5",
+        "2024",
+        "37:30",
+        "--blackout",
+        "tue=12:00-13:00",
+        "--avoid-round-weeks",
+        "--no-archive",
+    ]);
+    let blob = log
+        .lines()
+        .find_map(|line| line.strip_prefix("Reproduce with --reproduce '"))
+        .and_then(|line| line.strip_suffix('\''))
+        .unwrap();
+    let (replayed, _) = run(&["generate", "--reproduce", blob, "--no-archive"]);
+    assert_eq!(replayed, sheet);
+}
+
+#[test]
+fn test_locale() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "simulate", "5", "2024", "60", "--runs", "50", "--locale", "de-DE",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match(r"\d,\d%").unwrap())
+        .stdout(predicates::str::contains(".0%").not());
+}
+
+#[test]
+fn test_period() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[contract]\nweekly_hours = 10\nstate = \"NW\"\nstart = 2024-10-01\n",
+    )
+    .unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "--period",
+            "WS24",
+            "--seed",
+            "1",
+            "--csv",
+            "--no-header",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let mut months: Vec<_> = output
+        .lines()
+        .map(|line| {
+            let date = line.split(',').next().unwrap();
+            date.split_once('.').unwrap().1.to_string()
+        })
+        .collect();
+    months.dedup();
+    assert_eq!(
+        months,
+        ["10.2024", "11.2024", "12.2024", "1.2025", "2.2025", "3.2025"]
+    );
+
+    stundenzettel(home.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("2024-12"))
+        .stdout(predicates::str::contains("2025-03"));
+    stundenzettel(home.path())
+        .args(["generate", "--period", "WS24/26"])
+        .assert()
+        .code(2)
+        .stderr(predicates::str::contains("isn't a quarter"));
+}
+
+#[test]
+fn test_bank() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[contract]\nweekly_hours = 10\nstate = \"NW\"\n",
+    )
+    .unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "--period",
+            "Q1-2024",
+            "--bank",
+            "--month-cap",
+            "50",
+            "--seed",
+            "1",
+            "--format",
+            "json",
+            "--no-header",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::is_match(r"Total\s+(\d+)\s+(\d+)").unwrap())
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let mut hours = 0;
+    for sheet in serde_json::Deserializer::from_str(&stdout).into_iter::<serde_json::Value>() {
+        let sheet = stundenzettel::parser::parse_json(&sheet.unwrap().to_string()).unwrap();
+        let banked = stderr
+            .lines()
+            .find(|line| line.starts_with(&format!("{:02}/2024", sheet.month)))
+            .and_then(|line| line.split_whitespace().last())
+            .unwrap();
+        assert_eq!(sheet.hours().to_string(), banked);
+        assert!(sheet.hours() <= 50);
+        hours += sheet.hours();
+    }
+    // 22, 21 and 20 working days in Q1 2024 at 2 hours a day
+    assert_eq!(hours, 126);
+}
+
+#[test]
+fn test_off_days() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "80",
+            "--seed",
+            "1",
+            "--off-days",
+            "25",
+            "--csv",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(",holiday\n"))
+        .get_output()
+        .stdout
+        .clone();
+    stundenzettel(home.path())
+        .args(["validate", "-", "80"])
+        .write_stdin(output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_night() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "40",
+            "6",
+            "18",
+            "24",
+            "--seed",
+            "1",
+            "--csv",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let csv = String::from_utf8(output.clone()).unwrap();
+    for row in csv.lines().filter(|line| !line.starts_with('#')) {
+        let columns: Vec<_> = row.split(',').collect();
+        let (to, night) = (columns[2], columns[3]);
+        assert_eq!(night != "0:00", to.starts_with("24"), "{}", row);
+    }
+    stundenzettel(home.path())
+        .args(["validate", "-", "40", "6", "18", "24"])
+        .write_stdin(output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_distribution() {
+    let home = tempfile::tempdir().unwrap();
+    let hours = |distribution: &str| {
+        let output = stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "80",
+                "--seed",
+                "1",
+                "--distribution",
+                distribution,
+                "--csv",
+                "--no-header",
+                "--no-archive",
+            ])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|row| {
+                let columns: Vec<_> = row.split(',').collect();
+                let clock = |time: &str| {
+                    let (hours, minutes) = time.split_once(':').unwrap();
+                    hours.parse::<u32>().unwrap() * 60 + minutes.parse::<u32>().unwrap()
+                };
+                (clock(columns[2]) - clock(columns[1])) / 60
+            })
+            .collect::<Vec<_>>()
+    };
+    let even = hours("even");
+    assert_eq!(even.iter().sum::<u32>(), 80);
+    assert!(
+        even.iter().all(|&hours| hours == 4 || hours == 5),
+        "{:?}",
+        even
+    );
+
+    let front = hours("front-loaded");
+    let half = front.len() / 2;
+    assert!(front[..half].iter().sum::<u32>() > front[half..].iter().sum::<u32>());
+}
+
+#[test]
+fn test_shape() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "80",
+            "--seed",
+            "1",
+            "--shape",
+            "back",
+            "--csv",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            r#""distribution":{"shaped":"back"}"#,
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    stundenzettel(home.path())
+        .args(["validate", "-", "80"])
+        .write_stdin(output)
+        .assert()
+        .success();
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "80",
+            "--shape",
+            "mid",
+            "--distribution",
+            "even",
+            "--no-archive",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_like() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args(["generate", "3", "2024", "40", "--like", "2024-02"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "No timesheet of 02/2024 is archived",
+        ));
+    stundenzettel(home.path())
+        .args(["generate", "2", "2024", "80", "--seed", "1"])
+        .assert()
+        .success();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "3",
+            "2024",
+            "76",
+            "--seed",
+            "1",
+            "--like",
+            "2024-02",
+            "--jitter",
+            "30m",
+            "--format",
+            "json",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(r#""like":"2024-02","jitter":30"#))
+        .get_output()
+        .stdout
+        .clone();
+    stundenzettel(home.path())
+        .args(["validate", "-", "76"])
+        .write_stdin(output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_rounding() {
+    let home = tempfile::tempdir().unwrap();
+    let smooth = || {
+        stundenzettel(home.path())
+            .args([
+                "smooth",
+                "tests/golden/2024-03-punches.csv",
+                "--csv",
+                "--no-header",
+                "--no-breaks",
+            ])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    };
+    let output = String::from_utf8(smooth()).unwrap();
+    assert!(output.contains(":15") || output.contains(":30") || output.contains(":45"));
+
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[rounding]\nstep = 60\nmode = \"employee\"\n",
+    )
+    .unwrap();
+    let output = String::from_utf8(smooth()).unwrap();
+    for line in output.lines() {
+        let [_, from, to, _] = line.split(',').collect::<Vec<_>>()[..] else {
+            panic!("`{}` isn't a CSV row", line);
+        };
+        assert!(from.ends_with(":00") && to.ends_with(":00"), "{}", line);
+    }
+
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "80",
+            "--seed",
+            "1",
+            "--rounding",
+            "daily",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            r#""rounding":{"step":15,"mode":"daily"}"#,
+        ));
+}
+
+#[test]
+fn test_breaks() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[breaks]]\nafter = 240\ndeduct = 30\n",
+    )
+    .unwrap();
+    let generate = |extra: &[&str]| {
+        let output = stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "80",
+                "6",
+                "--seed",
+                "1",
+                "--csv",
+                "--no-header",
+                "--no-archive",
+            ])
+            .args(extra)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let output = generate(&[]);
+    assert!(output.contains(",0:30,"));
+    for line in output.lines() {
+        let columns: Vec<_> = line.split(',').collect();
+        let [.., gross, pause, net] = columns[..] else {
+            panic!("`{}` has no breaks", line);
+        };
+        let expected = if gross > "4:00" { "0:30" } else { "0:00" };
+        assert_eq!(pause, expected, "{}", line);
+        assert_eq!(gross == net, pause == "0:00", "{}", line);
+    }
+    stundenzettel(home.path())
+        .args(["validate", "-", "80"])
+        .write_stdin(output)
+        .assert()
+        .success();
+
+    let output = generate(&["--no-breaks"]);
+    assert!(output.lines().all(|line| line.split(',').count() == 3));
+}
+
+#[test]
+fn test_earnings_cap() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |extra: &[&str]| {
+        stundenzettel(home.path())
+            .args([
+                "generate",
+                "5",
+                "2024",
+                "40",
+                "--seed",
+                "1",
+                "--earnings-cap",
+                "538",
+                "--no-archive",
+            ])
+            .args(extra)
+            .assert()
+    };
+    generate(&["--rate", "13.5"])
+        .success()
+        .stderr(predicates::str::contains("earnings cap of 538.00 €"))
+        .stderr(predicates::str::contains(r#""hours":39"#));
+    generate(&["--rate", "13,45"])
+        .success()
+        .stderr(predicates::str::contains("Reducing").not())
+        .stderr(predicates::str::contains(r#""hours":40"#));
+    generate(&["--rate", "13.5", "--strict"])
+        .code(2)
+        .stderr(predicates::str::contains("at most 39 hours fit"));
+    generate(&["--rate", "13.555"]).code(2);
+    generate(&[]).code(2);
+}
+
+#[test]
+fn test_pay() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "20",
+            "--seed",
+            "1",
+            "--rate",
+            "15.5",
+            "--pay-column",
+            "--locale",
+            "de-DE",
+            "--csv",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("310,00 €"))
+        .get_output()
+        .stdout
+        .clone();
+    let csv = String::from_utf8(output.clone()).unwrap();
+    assert!(csv
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .all(|line| line.ends_with(".50") || line.ends_with(".00")));
+    stundenzettel(home.path())
+        .args(["validate", "-", "20"])
+        .write_stdin(output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_invoice() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args(["generate", "5", "2024", "20", "--seed", "1"])
+        .assert()
+        .success();
+    let invoice = |arguments: &[&str]| {
+        stundenzettel(home.path())
+            .env("SOURCE_DATE_EPOCH", "1717200000")
+            .arg("invoice")
+            .args(arguments)
+            .assert()
+    };
+    invoice(&["5", "2024", "--rate", "50"])
+        .success()
+        .stdout(predicate::str::contains("<h1>Invoice 2024-001</h1>"))
+        .stdout(predicate::str::contains("Date: 2024-06-01"))
+        .stdout(predicate::str::contains("1190.00 €"));
+    invoice(&["5", "2024", "--rate", "50", "--weekly", "--vat", "0"])
+        .success()
+        .stdout(predicate::str::contains("<h1>Invoice 2024-002</h1>"))
+        .stdout(predicate::str::contains("Week 1 of May 2024"))
+        .stdout(predicate::str::contains("1000.00 €"));
+    invoice(&["5", "2024", "--rate", "50", "--number", "X-1"])
+        .success()
+        .stdout(predicate::str::contains("<h1>Invoice X-1</h1>"));
+    let state = fs::read_to_string(home.path().join(".local/share/stundenzettel/state.json"));
+    let state: serde_json::Value = serde_json::from_str(&state.unwrap()).unwrap();
+    assert_eq!(state["invoices"]["2024"], 2);
+    invoice(&["5", "2024"]).code(4);
+    invoice(&["6", "2024", "--rate", "50"])
+        .failure()
+        .stderr(predicate::str::contains("No timesheet of 06/2024"));
+
+    let csv = stundenzettel(home.path())
+        .args(["generate", "3", "2024", "10", "--csv", "--no-archive"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    stundenzettel(home.path())
+        .args(["invoice", "--file", "-", "--rate", "50", "--number", "1"])
+        .write_stdin(csv)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Period: March 2024"))
+        .stdout(predicate::str::contains("500.00 €"));
+}
+
+#[test]
+fn test_calendar() {
+    let home = tempfile::tempdir().unwrap();
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "3",
+            "2024",
+            "80",
+            "--seed",
+            "1",
+            "--timezone",
+            "Europe/Berlin",
+        ])
+        .assert()
+        .success();
+    let calendar = |extra: &[&str]| {
+        let output = stundenzettel(home.path())
+            .args(["calendar", "3", "2024"])
+            .args(extra)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let archived = calendar(&[]);
+    assert!(archived.starts_with("BEGIN:VCALENDAR\r\n"));
+    // the archived sheet keeps its zone
+    assert_eq!(archived, calendar(&["--timezone", "Europe/Berlin"]));
+    assert_ne!(archived, calendar(&["--timezone", "UTC"]));
+    assert!(archived
+        .lines()
+        .filter(|line| line.starts_with("DTSTART:"))
+        .all(|line| line.ends_with("00Z")));
+
+    // the transition weekends and the weekends before
+    let march = "24.3.2024,0:00,6:00\n31.3.2024,0:00,6:00\n";
+    let export = |input: &str, timezone: &str| {
+        let output = stundenzettel(home.path())
+            .args(["calendar", "--file", "-", "--timezone", timezone])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("DTEND:").map(str::to_owned))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        export(march, "Europe/Berlin"),
+        ["20240324T050000Z", "20240331T040000Z"]
+    );
+    let october = "20.10.2024,0:00,6:00\n27.10.2024,0:00,6:00\n";
+    assert_eq!(
+        export(october, "Europe/Berlin"),
+        ["20241020T040000Z", "20241027T050000Z"]
+    );
+    assert_eq!(
+        export(october, "+01:00"),
+        ["20241020T050000Z", "20241027T050000Z"]
+    );
+    stundenzettel(home.path())
+        .args(["calendar", "--file", "-", "--timezone", "Europe/Berlin"])
+        .write_stdin("26.3.1995,0:00,6:00\n")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("only known from 1996 on"));
+}
+
+#[test]
+fn test_timezone() {
+    let home = tempfile::tempdir().unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "3",
+            "2024",
+            "80",
+            "--seed",
+            "1",
+            "--timezone",
+            "Europe/Berlin",
+            "--format",
+            "json",
+            "--no-header",
+            "--no-archive",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let sheet: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(sheet["timezone"], "Europe/Berlin");
+    // summer time starts on 31.3.2024
+    for entry in sheet["entries"].as_array().unwrap() {
+        let start = entry["start"].as_str().unwrap();
+        assert!(start.starts_with(&format!("2024-03-{:02}T", entry["day"].as_u64().unwrap())));
+        assert!(start.ends_with("+01:00"));
+    }
+
+    stundenzettel(home.path())
+        .args(["generate", "3", "2024", "80", "--timezone", "Berlin"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("isn't a time zone"));
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "3",
+            "1995",
+            "80",
+            "--timezone",
+            "Europe/Berlin",
+            "--no-archive",
+        ])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("only known from 1996 on"))
+        // the zone is checked before anything is generated
+        .stderr(predicate::str::contains("Reproduce with").not());
+}
+
+#[test]
+fn test_warnings() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = || {
+        let output = stundenzettel(home.path())
+            .args([
+                "generate",
+                "12",
+                "2024",
+                "150",
+                "10",
+                "--seed",
+                "1",
+                "--no-archive",
+            ])
+            .assert()
+            .success()
+            .get_output()
+            .stderr
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let stderr = generate();
+    assert!(stderr.contains("warning: The 5. has 10:00 hours of work, more than 9 hours a day\n"));
+    assert!(stderr.contains("warning: The 24. is worked, it is usually a half-day\n"));
+    assert!(stderr.contains("warning: Week 4 has 42:00 hours of work, more than 40 hours a week\n"));
+
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[warnings]\nsilence = [\"long_days\", \"long_weeks\"]\n",
+    )
+    .unwrap();
+    let stderr = generate();
+    let warnings: Vec<_> = stderr
+        .lines()
+        .filter(|line| line.starts_with("warning:"))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            "warning: The 24. is worked, it is usually a half-day",
+            "warning: The 31. is worked, it is usually a half-day",
+        ]
+    );
+}
+
+#[test]
+fn test_schema() {
+    let home = tempfile::tempdir().unwrap();
+    for format in ["timesheet", "request", "constraints", "config"] {
+        let expected = fs::read_to_string(format!("schemas/{}.schema.json", format)).unwrap();
+        stundenzettel(home.path())
+            .args(["schema", format])
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+}
+
+#[test]
+fn test_notes() {
+    let home = tempfile::tempdir().unwrap();
+    let notes = home.path().join("notes.txt");
+    fs::write(
+        &notes,
+        "# March\n4.03=Code review\n5.3=\"Sprint planning, retro\"\n",
+    )
+    .unwrap();
+    let output = stundenzettel(home.path())
+        .args(["generate", "3", "2024", "40", "--seed", "1", "--no-header"])
+        .args(["--csv", "--notes", notes.to_str().unwrap()])
+        .args(["--note", "1.03=Server migration", "--note", "4.03=Deploy"])
+        .args(["--note", "2.03=Weekend"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: The 2. isn't worked, its note `Weekend` is left out\n",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines[0], "1.3.2024,10:00,17:00,\"Server migration\"");
+    assert_eq!(lines[1], "4.3.2024,14:00,16:00,\"Deploy\"");
+    assert_eq!(lines[2], "5.3.2024,11:00,12:00,\"Sprint planning, retro\"");
+    assert_eq!(lines[3], "8.3.2024,9:00,16:00,\"\"");
+
+    let archived =
+        fs::read_to_string(home.path().join(".local/share/stundenzettel/2024/03.json")).unwrap();
+    assert!(archived.contains(r#""note": "Sprint planning, retro""#));
+}
+
+#[test]
+fn test_activities() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[[activities]]\ntext = \"Development\"\nweight = 3\n\n[[activities]]\ntext = \"Code review\"\n\n[[activities]]\ntext = \"Support\"\n",
+    )
+    .unwrap();
+    let generate = |arguments: &[&str]| {
+        let output = stundenzettel(home.path())
+            .args(["generate", "--no-archive", "--no-header", "--csv"])
+            .args(arguments)
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        (
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    let (sheet, stderr) = generate(&["3", "2024", "40", "--seed", "1", "--activities", "balanced"]);
+    let count = |text: &str| sheet.matches(&format!(",\"{}\"\n", text)).count();
+    assert_eq!(
+        [count("Development"), count("Code review"), count("Support")],
+        [7, 3, 3]
+    );
+
+    let blob = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Reproduce with --reproduce '"))
+        .and_then(|blob| blob.strip_suffix('\''))
+        .unwrap();
+    fs::remove_file(config.join("config.toml")).unwrap();
+    assert_eq!(generate(&["--reproduce", blob]).0, sheet);
+}
+
+#[test]
+fn test_encoding() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| stundenzettel(home.path()).args(args).assert();
+    let utf8 = run(&["holidays", "export", "--year", "2024"]).success();
+    let utf8 = utf8.get_output().stdout.clone();
+    let latin1 = run(&[
+        "holidays",
+        "export",
+        "--year",
+        "2024",
+        "--encoding",
+        "latin1",
+    ])
+    .success();
+    let latin1 = &latin1.get_output().stdout;
+    assert!(latin1.windows(5).any(|w| w == b"Mari\xe4"));
+    let decoded: String = latin1.iter().map(|&byte| char::from(byte)).collect();
+    assert_eq!(decoded.as_bytes(), utf8);
+
+    let bom = run(&[
+        "holidays",
+        "export",
+        "--year",
+        "2024",
+        "--encoding",
+        "utf8-bom",
+    ])
+    .success();
+    assert_eq!(
+        bom.get_output().stdout,
+        [b"\xef\xbb\xbf".as_slice(), &utf8].concat()
+    );
+
+    let args = ["generate", "3", "2024", "40", "--seed", "1"];
+    let csv = run(&[&args[..], &["--csv", "--encoding", "UTF8-BOM"]].concat()).success();
+    assert!(csv.get_output().stdout.starts_with(b"\xef\xbb\xbf"));
+
+    run(&[&args[..], &["--format", "json", "--encoding", "latin1"]].concat())
+        .code(2)
+        .stderr(predicate::str::contains("JSON is always UTF-8"));
+}
+
+#[test]
+fn test_line_ending() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        let args = [
+            &["generate", "3", "2024", "40", "--seed", "1", "--csv"],
+            args,
+        ]
+        .concat();
+        let assert = stundenzettel(home.path()).args(args).assert().success();
+        String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+    };
+    let lf = run(&["--no-archive", "--no-header"]);
+    let crlf = run(&["--no-archive", "--no-header", "--line-ending", "crlf"]);
+    assert!(!lf.contains('\r'));
+    assert_eq!(crlf, lf.replace('\n', "\r\n"));
+}
+
+#[test]
+fn test_digest() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| stundenzettel(home.path()).args(args).assert();
+    let args = ["generate", "5", "2024", "40", "--seed", "7"];
+    run(&args).success();
+    let grid = run(&[&args[..], &["--layout", "calendar", "--no-archive"]].concat()).success();
+    let grid = String::from_utf8(grid.get_output().stdout.clone()).unwrap();
+    let digest = grid
+        .split("<figcaption>")
+        .nth(1)
+        .and_then(|caption| caption.split_once("</figcaption>"))
+        .map(|(digest, _)| digest.to_string())
+        .unwrap();
+    assert!(digest.starts_with("stundenzettel:2024-05:7:"));
+    run(&["digest", &digest])
+        .success()
+        .stdout(predicate::str::contains("The digest matches the timesheet"));
+
+    let csv = run(&[&args[..], &["--csv", "--no-archive"]].concat()).success();
+    let csv = String::from_utf8(csv.get_output().stdout.clone()).unwrap();
+    let file = home.path().join("2024-05.csv");
+    fs::write(&file, &csv).unwrap();
+    let file = file.to_str().unwrap();
+    run(&["digest", &digest, "--file", file]).success();
+    let entry = csv.lines().find(|line| !line.starts_with('#')).unwrap();
+    let (start, end) = entry.rsplit_once(',').unwrap();
+    let hour: u32 = end.trim_end_matches(":00").parse().unwrap();
+    let tampered = csv.replacen(entry, &format!("{},{}:00", start, hour + 1), 1);
+    fs::write(file, tampered).unwrap();
+    run(&["digest", &digest, "--file", file])
+        .code(2)
+        .stderr(predicate::str::contains("The digest doesn't match"));
+    run(&["digest", &digest.replace("2024-05", "2024-06")])
+        .failure()
+        .stderr(predicate::str::contains("No timesheet of 06/2024"));
+    run(&["digest", "stundenzettel:2024-05:7:00"]).code(2);
+}
+
+#[test]
+fn test_signing() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| stundenzettel(home.path()).args(args).assert();
+    run(&["key", "public"]).code(4);
+    let key = run(&["key", "generate"]).success();
+    let key = String::from_utf8(key.get_output().stdout.clone()).unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    let signing = format!("[signing]\nkey = \"{}\"\n", key.trim());
+    fs::write(config.join("config.toml"), signing).unwrap();
+    let public_key = run(&["key", "public"]).success();
+    let public_key = String::from_utf8(public_key.get_output().stdout.clone()).unwrap();
+
+    let args = ["generate", "3", "2024", "40", "--seed", "1", "--no-archive"];
+    let csv = run(&[&args[..], &["--csv"]].concat()).success();
+    let csv = String::from_utf8(csv.get_output().stdout.clone()).unwrap();
+    assert!(csv.contains("# Signature: "));
+    let file = home.path().join("2024-03.csv");
+    fs::write(&file, &csv).unwrap();
+    let file = file.to_str().unwrap();
+    run(&["verify", file])
+        .success()
+        .stdout(predicate::str::contains("The signature matches"));
+
+    let json = run(&[&args[..], &["--format", "json"]].concat()).success();
+    let json = String::from_utf8(json.get_output().stdout.clone()).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(value["header"]["signature"].is_string());
+    let json_file = home.path().join("2024-03.json");
+    fs::write(&json_file, &json).unwrap();
+    let json_file = json_file.to_str().unwrap();
+    run(&["verify", json_file])
+        .success()
+        .stdout(predicate::str::contains("The signature matches"));
+    // the header is signed as well
+    fs::write(
+        json_file,
+        json.replacen("\"hours\": 40", "\"hours\": 80", 1),
+    )
+    .unwrap();
+    run(&["verify", json_file])
+        .code(2)
+        .stderr(predicate::str::contains("The signature doesn't match"));
+    let tampered = csv.replacen("# Hours: 40", "# Hours: 80", 1);
+    assert_ne!(tampered, csv);
+    fs::write(file, tampered).unwrap();
+    run(&["verify", file])
+        .code(2)
+        .stderr(predicate::str::contains("The signature doesn't match"));
+    fs::write(file, &csv).unwrap();
+    let text = run(&args).success();
+    assert!(!String::from_utf8_lossy(&text.get_output().stdout).contains("Signature"));
+
+    // an employer without the private key checks with the public one
+    let employer = tempfile::tempdir().unwrap();
+    let verify = |args: &[&str]| {
+        stundenzettel(employer.path())
+            .arg("verify")
+            .args(args)
+            .assert()
+    };
+    verify(&[file]).code(4);
+    verify(&[file, "--public-key", public_key.trim()]).success();
+    let entry = csv.lines().find(|line| !line.starts_with('#')).unwrap();
+    let (start, end) = entry.rsplit_once(',').unwrap();
+    let hour: u32 = end.trim_end_matches(":00").parse().unwrap();
+    let tampered = csv.replacen(entry, &format!("{},{}:00", start, hour + 1), 1);
+    fs::write(file, tampered).unwrap();
+    verify(&[file, "--public-key", public_key.trim()])
+        .code(2)
+        .stderr(predicate::str::contains("The signature doesn't match"));
+    let unsigned = run(&[&args[..], &["--csv", "--no-header"]].concat()).success();
+    fs::write(file, &unsigned.get_output().stdout).unwrap();
+    verify(&[file, "--public-key", public_key.trim()])
+        .code(2)
+        .stderr(predicate::str::contains("The timesheet isn't signed"));
+}
+
+#[test]
+fn test_redact() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "employee = \"Erika Mustermann\"\n\n[contract]\nemployer = \"Muster GmbH\"\nweekly_hours = 10\n\n[[jobs]]\nemployer = \"Lehrstuhl\"\nweekly_hours = 5\n\n[[activities]]\ntext = \"Payroll of Muster GmbH\"\n",
+    )
+    .unwrap();
+    let generate = |arguments: &[&str]| {
+        let output = stundenzettel(home.path())
+            .args(["generate", "3", "2024", "--seed", "1"])
+            .args(arguments)
+            .assert()
+            .success()
+            .get_output()
+            .clone();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let noted = ["--activities", "independent"];
+    let plain = generate(&[&noted[..], &["--no-archive"]].concat());
+    assert!(plain.contains("Erika Mustermann") && plain.contains("Payroll of Muster GmbH"));
+    let redacted = generate(&[&noted[..], &["--redact"]].concat());
+    assert!(redacted.contains("note Activity 1"));
+    assert_eq!(
+        redacted.replace("Activity 1", "Payroll of Muster GmbH"),
+        plain.replace("Employee:     Erika Mustermann\n", "")
+    );
+    let archived = home.path().join(".local/share/stundenzettel/2024/03.json");
+    let archived = fs::read_to_string(archived).unwrap();
+    assert!(archived.contains("Payroll of Muster GmbH"));
+
+    for arguments in [
+        &[&noted[..], &["--format", "json"]].concat(),
+        &[&noted[..], &["--layout", "weekly"]].concat(),
+        &[&noted[..], &["--layout", "calendar"]].concat(),
+        &["--jobs"][..],
+    ] {
+        let redacted = generate(&[arguments, &["--redact", "--no-archive"]].concat());
+        for personal in ["Erika", "Muster", "Lehrstuhl"] {
+            assert!(
+                !redacted.contains(personal),
+                "{:?}: {}",
+                arguments,
+                redacted
+            );
+        }
+    }
+}
+
+#[test]
+fn test_possible_values() {
+    let home = tempfile::tempdir().unwrap();
+    let generate = |args: &[&str]| {
+        stundenzettel(home.path())
+            .args(["generate", "3", "2024", "40", "--seed", "1", "--no-archive"])
+            .args(args)
+            .assert()
+    };
+    generate(&["--state", "XX"])
+        .code(2)
+        .stderr(predicate::str::contains("[possible values: BW, BY, BE"));
+    generate(&["--format", "yaml"])
+        .code(2)
+        .stderr(predicate::str::contains(
+            "[possible values: text, csv, json, markdown, personio]",
+        ));
+    let upper = generate(&["--state", "NW", "--format", "csv", "--no-header"]).success();
+    let lower = generate(&["--state", "nw", "--format", "CSV", "--no-header"]).success();
+    assert_eq!(upper.get_output().stdout, lower.get_output().stdout);
+    generate(&["--rate", "15", "--locale", "de_DE"])
+        .success()
+        .stderr(predicate::str::contains(","));
+
+    let help = stundenzettel(home.path())
+        .args(["generate", "--help"])
+        .assert()
+        .success();
+    let help = String::from_utf8(help.get_output().stdout.clone()).unwrap();
+    for value in [
+        "NW: Nordrhein-Westfalen",
+        "utf8-bom:",
+        "crlf:",
+        "calendar:",
+        "de-DE:",
+    ] {
+        assert!(help.contains(value), "{} is missing from {}", value, help);
+    }
+}
+
+#[test]
+fn test_cross_validation() {
+    let home = tempfile::tempdir().unwrap();
+    let constraints = home.path().join("constraints.csv");
+    fs::write(&constraints, "10.5.2024,exclude\n3.6.2024,exclude\n").unwrap();
+    let generate = |arguments: &[&str]| {
+        stundenzettel(home.path())
+            .args(["generate", "5", "2024", "--seed", "1", "--no-archive"])
+            .args(arguments)
+            .assert()
+    };
+    generate(&["37:30", "--step", "30"]).success();
+    generate(&["37:30", "--step", "60"])
+        .code(2)
+        .stderr(predicate::str::contains(
+            "The 30 minutes above the full hours can't be rounded to steps of 60 minutes",
+        ));
+    generate(&["40", "--step", "30", "--break", "6h=45m"])
+        .code(2)
+        .stderr(predicate::str::contains(
+            "A break of 45 minutes can't be deducted from times rounded to 30 minutes",
+        ));
+    generate(&["40", "--constraints", constraints.to_str().unwrap()])
+        .code(2)
+        .stderr(predicate::str::contains(
+            "The constraint `3.6.2024,exclude` isn't in 5/2024",
+        ));
+    generate(&[
+        "40",
+        "--error-format",
+        "json",
+        "--step",
+        "60",
+        "--break",
+        "6h=30m",
+    ])
+    .code(2)
+    .stderr(predicate::str::contains(r#""code":"break_off_step""#));
+    assert!(!home.path().join(".local").exists());
+}
+
+#[test]
+fn test_show_plan() {
+    let home = tempfile::tempdir().unwrap();
+    let show = |arguments: &[&str]| {
+        let output = stundenzettel(home.path())
+            .args(["generate", "5", "2024", "40", "--show-plan"])
+            .args(["--constraints", "tests/golden/2024-05-constraints.csv"])
+            .args(arguments)
+            .assert()
+            .success();
+        String::from_utf8(output.get_output().stdout.clone()).unwrap()
+    };
+    let plan = show(&["--window", "fri=8-14"]);
+    assert!(
+        plan.starts_with("May 2024: 36 hours onto 18 days"),
+        "{}",
+        plan
+    );
+    assert_eq!(plan.lines().count(), 32);
+    for row in [
+        "Fri  3.  0-6 hours in 08:00-20:00 except 14:00-20:00",
+        "Sat  4.  1-8 hours in 08:00-20:00",
+        "Fri 10.  not worked",
+        "Mon 13.  0-3 hours in 08:00-20:00",
+        "Tue 14.  fill 09:00-13:00",
+    ] {
+        assert!(plan.contains(row), "{} is missing from {}", row, plan);
+    }
+    let json: serde_json::Value = serde_json::from_str(&show(&["--format", "json"])).unwrap();
+    assert_eq!(json["hours"], 36);
+    assert_eq!(json["filled"][0]["time"]["from"], "09:00");
+    assert!(!home.path().join(".local").exists());
+}
+
+#[test]
+fn test_trace() {
+    let home = tempfile::tempdir().unwrap();
+    let trace = home.path().join("trace.txt");
+    let generate = |arguments: &[&str]| {
+        stundenzettel(home.path())
+            .env("SOURCE_DATE_EPOCH", "1714521600")
+            .args(["generate", "2", "2024", "40", "--seed", "1", "--no-archive"])
+            .args(arguments)
+            .assert()
+            .success()
+    };
+    let expected = fs::read_to_string(golden_path("2024-02-by")).unwrap();
+    generate(&["--trace", trace.to_str().unwrap()])
+        .stdout(expected)
+        .stderr(predicate::str::contains("split").not());
+    let traced = fs::read_to_string(&trace).unwrap();
+    assert!(
+        traced.starts_with("split 40 hours onto 21 days within 0-8 "),
+        "{}",
+        traced
+    );
+    assert_eq!(
+        traced
+            .lines()
+            .filter(|line| line.starts_with("day "))
+            .count(),
+        21
+    );
+
+    stundenzettel(home.path())
+        .args(["generate", "5", "2024", "40", "--seed", "1", "--no-archive", "--trace"])
+        .args(["--blackout", "tue=12:00-13:00", "--avoid-round-weeks"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "round weeks, attempt 1: an hour from the 15. to the 6. rejected, same week or too short",
+        ))
+        .stderr(predicate::str::contains(
+            "round weeks, attempt 3: an hour from the 2. to the 28. taken",
+        ));
+}
+
+#[test]
+fn test_progress() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[contract]\nweekly_hours = 10\nstate = \"NW\"\nstart = 2024-10-01\n",
+    )
+    .unwrap();
+    let output = stundenzettel(home.path())
+        .args([
+            "generate",
+            "--period",
+            "Q4-2024",
+            "--seed",
+            "1",
+            "--no-archive",
+        ])
+        .args(["--progress", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let steps: Vec<_> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(str::to_string)
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            r#"{"done":1,"total":3,"item":"2024-10"}"#,
+            r#"{"done":2,"total":3,"item":"2024-11"}"#,
+            r#"{"done":3,"total":3,"item":"2024-12"}"#,
+        ]
+    );
+
+    stundenzettel(home.path())
+        .args(["simulate", "5", "2024", "40", "--runs", "2", "--seed", "7"])
+        .arg("--progress")
+        .assert()
+        .success()
+        .stderr("\r[##########----------] 1/2 7\x1b[K\r[####################] 2/2 8\x1b[K\n");
+}
+
+#[test]
+fn test_config_init() {
+    let home = tempfile::tempdir().unwrap();
+    let init = || {
+        let mut command = stundenzettel(home.path());
+        command.args(["config", "init"]);
+        command
+    };
+    // an invalid answer is asked again, an empty one takes the default
+    init()
+        .write_stdin("Erika Mustermann\nNRW\nnw\n10\n\n8\n14\ncsv\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote "))
+        .stderr("`NRW` isn't a state\nLatest has to be after earliest\n");
+    let config = home.path().join(".config/stundenzettel/config.toml");
+    let written = fs::read_to_string(&config).unwrap();
+    assert!(written.contains("employee = \"Erika Mustermann\"\n"));
+    assert!(written.contains("[contract]\n# The hours worked per week\nweekly_hours = 10\n"));
+    assert!(written.contains("state = \"NW\"\n"));
+    assert!(written.contains("earliest = 8\nlatest = 14\n"));
+
+    // the configured format is the default of generate
+    stundenzettel(home.path())
+        .args([
+            "generate",
+            "5",
+            "2024",
+            "--seed",
+            "1",
+            "--no-archive",
+            "--no-header",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"^\d+\.5\.2024,\d+:\d+,\d+:\d+\n").unwrap());
+
+    init()
+        .write_stdin("")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("pass --force to overwrite it"));
+    init().arg("--force").write_stdin("").assert().success();
+    let written = fs::read_to_string(&config).unwrap();
+    assert!(written.contains("# employee = "));
+    assert!(written.contains("state = \"BY\"\n"));
+}
+
+#[test]
+fn test_config_show_validate() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join(".config/stundenzettel");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "[contract]\nweekly_hours = 10\nstate = \"NW\"\n\n[webdav]\nusername = \"me\"\npassword = \"secret\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut command = stundenzettel(home.path());
+        command.arg("config").args(args);
+        command
+    };
+    run(&["show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[contract]\nweekly_hours = 10\nstate = \"NW\"\nmax_per_day = 8\n",
+        ))
+        .stdout(predicate::str::contains("password = \"...\""))
+        .stdout(predicate::str::contains("secret").not());
+    run(&["show", "--secrets"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("password = \"secret\""));
+    // the options of `generate` take precedence like there
+    run(&["show", "--state", "BY", "--city", "augsburg"])
+        .args(["--contract-from", "15.03.2024", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "# configuration: {}\n# state: {}\n",
+            config.join("config.toml").display(),
+            home.path().join(".local/share/stundenzettel").display()
+        )))
+        .stdout(predicate::str::contains("format = \"csv\"\n"))
+        .stdout(predicate::str::contains(
+            "state = \"BY\"\ncity = \"Augsburg\"\n",
+        ))
+        .stdout(predicate::str::contains("start = 2024-03-15\n"));
+    run(&["show", "--realism-profile", "house"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("No `[realism.house]` profile"));
+    run(&["validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("config.toml is valid\n"));
+
+    let invalid = home.path().join("invalid.toml");
+    fs::write(&invalid, "[contract]\nweekly_hours = 10\nearliest = 14\nlatest = 12\n\n[warnings]\nsilence = [\"everything\"]\n").unwrap();
+    // the schema is checked first
+    run(&["validate", invalid.to_str().unwrap()])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::starts_with(
+            "/warnings/silence/0: \"everything\" is not valid",
+        ));
+    fs::write(
+        &invalid,
+        "[contract]\nweekly_hours = 10\nearliest = 14\nlatest = 12\n",
+    )
+    .unwrap();
+    run(&["validate", "-"])
+        .write_stdin(fs::read_to_string(&invalid).unwrap())
+        .assert()
+        .code(4)
+        .stderr("[contract]: Earliest has to be before latest\n");
+    run(&["--error-format", "json", "validate", "-"])
+        .write_stdin("[contract\n")
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(r#""code":"invalid_config""#));
+}
+
+#[test]
+fn test_profile_inherits() {
+    let generate = |config: &str| {
+        let home = tempfile::tempdir().unwrap();
+        let directory = home.path().join(".config/stundenzettel");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("config.toml"), config).unwrap();
+        let output = stundenzettel(home.path())
+            .args(["generate", "5", "2024", "40", "--seed", "1", "--no-archive"])
+            .args(["--realism-profile", "summer", "--no-header"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+    let inherited = generate(
+        "[realism.house]\nweights = [3, 3, 2, 2, 1]\nwindows = [\"mon=9-18\"]\n\
+         cluster_starts = true\n\n[realism.summer]\ninherits = \"house\"\n\
+         windows = [\"fri=8-12\"]\n",
+    );
+    let written_out = generate(
+        "[realism.summer]\nweights = [3, 3, 2, 2, 1]\nwindows = [\"fri=8-12\"]\n\
+         cluster_starts = true\n",
+    );
+    assert_eq!(inherited, written_out);
+    let house = generate(
+        "[realism.summer]\nweights = [3, 3, 2, 2, 1]\nwindows = [\"mon=9-18\"]\n\
+         cluster_starts = true\n",
+    );
+    assert_ne!(inherited, house);
+}
+
+#[test]
+fn test_paths() {
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        let mut command = stundenzettel(home.path());
+        command
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .args(args);
+        command
+    };
+    run(&["generate", "5", "2024", "20", "--seed", "1"])
+        .assert()
+        .success();
+    assert!(home.path().join("data/stundenzettel/2024/05.json").exists());
+
+    let config = home.path().join("settings.toml");
+    fs::write(&config, "[contract]\nweekly_hours = 10\nstate = \"NW\"\n").unwrap();
+    let state = home.path().join("state");
+    let overrides = [
+        "--config",
+        config.to_str().unwrap(),
+        "--state-dir",
+        state.to_str().unwrap(),
+    ];
+    run(&["generate", "5", "2024", "--seed", "1"])
+        .args(overrides)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("NW"));
+    run(&["list"])
+        .args(overrides)
+        .assert()
+        .success()
+        .stdout(format!(
+            "2024-05  {}\n",
+            state.join("2024/05.json").display()
+        ));
+    run(&["config", "show"])
+        .args(overrides)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "# configuration: {}\n# state: {}\n",
+            config.display(),
+            state.display()
+        )))
+        .stdout(predicate::str::contains("state = \"NW\""));
+    run(&["config", "validate"])
+        .args(overrides)
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("settings.toml is valid\n"));
+
+    // a configuration given explicitly has to exist
+    run(&["config", "show", "--config", "missing.toml"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("missing.toml doesn't exist"));
+}